@@ -21,3 +21,9 @@ pub const SETPOINT_BASE_VALUE: i32 = 25;
 
 /// Default duration for market and stacks periods in seconds
 pub const DEFAULT_PERIOD_DURATION_SECONDS: u64 = 120;
+
+/// CO2 emitted by gas plants when producing energy (in kgCO2/MWh)
+pub const GAS_EMISSION_FACTOR: i32 = 400;
+
+/// CO2 emitted by nuclear plants when producing energy, on their whole life cycle (in kgCO2/MWh)
+pub const NUCLEAR_EMISSION_FACTOR: i32 = 12;
@@ -1,9 +1,14 @@
 use derive_more::Display;
-use rand::random_range;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 use crate::{constants, game::delivery_period::DeliveryPeriodId, utils::units::Power};
 
+pub mod profile;
+pub mod revision;
+pub mod shock;
+pub mod signal;
+
 pub type Forecasts = Vec<Forecast>;
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub struct Forecast {
@@ -18,8 +23,12 @@ pub struct ForecastValue {
 }
 
 impl ForecastValue {
-    pub fn forecast(&self) -> Power {
-        Power::from(forecast_in_range(self.lower_range(), self.upper_range()))
+    pub fn forecast(&self, rng: &mut impl Rng) -> Power {
+        Power::from(forecast_in_range(
+            self.lower_range(),
+            self.upper_range(),
+            rng,
+        ))
     }
 
     pub fn lower_range(&self) -> i32 {
@@ -41,13 +50,13 @@ pub struct Clip {
     pub max: i32,
 }
 
-pub fn forecast_in_range(min: i32, max: i32) -> i32 {
+pub fn forecast_in_range(min: i32, max: i32, rng: &mut impl Rng) -> i32 {
     if min == max {
         return round_to_nearest(min, constants::SETPOINT_BASE_VALUE);
     }
 
     round_to_nearest(
-        i64_to_i32_saturating(rand::random_range((min as i64)..(max as i64))),
+        i64_to_i32_saturating(rng.random_range((min as i64)..(max as i64))),
         constants::SETPOINT_BASE_VALUE,
     )
 }
@@ -62,7 +71,7 @@ fn i64_to_i32_saturating(value: i64) -> i32 {
     }
 }
 
-fn round_to_nearest(value: i32, constant: i32) -> i32 {
+pub fn round_to_nearest(value: i32, constant: i32) -> i32 {
     let rem = value % constant;
     let half = constant / 2;
 
@@ -124,22 +133,40 @@ impl NormalizedForecastValue {
                 (value * self.deviation) as i32,
                 constants::SETPOINT_BASE_VALUE,
             )
-            .abs() as u32,
+            .unsigned_abs(),
         }
     }
 }
 
-pub fn generate_random_forecasts_shape(len: usize) -> Vec<NormalizedForecastValue> {
+pub fn generate_random_forecasts_shape(
+    len: usize,
+    rng: &mut impl Rng,
+) -> Vec<NormalizedForecastValue> {
     (0..len)
         .map(|_| {
-            NormalizedForecastValue::try_new(random_range((0.)..=1.), random_range((0.)..=0.1))
-                .unwrap_or_default()
+            NormalizedForecastValue::try_new(
+                rng.random_range((0.)..=1.),
+                rng.random_range((0.)..=0.1),
+            )
+            .unwrap_or_default()
         })
         .collect()
 }
 
-pub fn generate_random_forecasts(len: usize, capacity: Power) -> Vec<ForecastValue> {
-    generate_random_forecasts_shape(len)
+/// Repeat a forecasts profile until it has `len` values, an empty profile stays empty.
+pub fn repeat_profile<T: Clone>(profile: Vec<T>, len: usize) -> Vec<T> {
+    if profile.len() >= len || profile.is_empty() {
+        return profile;
+    }
+    profile.into_iter().cycle().take(len).collect()
+}
+
+pub fn generate_random_forecasts(
+    len: usize,
+    capacity: Power,
+    rng: &mut impl Rng,
+) -> Vec<ForecastValue> {
+    generate_random_forecasts_shape(len, rng)
         .iter()
         .map(|f| f.as_forecast(capacity.into()))
         .collect()
@@ -153,7 +180,11 @@ mod tests {
     use super::forecast_in_range;
     use crate::{
         constants,
-        forecast::{CreateNormalizedForecastError, NormalizedForecastValue, round_to_nearest},
+        forecast::{
+            CreateNormalizedForecastError, NormalizedForecastValue, generate_random_forecasts,
+            repeat_profile, round_to_nearest,
+        },
+        utils::{rng::seeded_rng, units::Power},
     };
 
     #[test]
@@ -170,7 +201,7 @@ mod tests {
         let max = 1000;
 
         for _ in 0..0x1e4 {
-            let value = forecast_in_range(min, max);
+            let value = forecast_in_range(min, max, &mut rand::rng());
             assert!(value >= min);
             assert!(value <= max);
         }
@@ -182,7 +213,7 @@ mod tests {
         let max = 1000;
 
         for _ in 0..0x1e4 {
-            let value = forecast_in_range(min, max);
+            let value = forecast_in_range(min, max, &mut rand::rng());
             assert_eq!(value.rem(constants::SETPOINT_BASE_VALUE), 0);
         }
     }
@@ -192,7 +223,7 @@ mod tests {
         let min = 100;
         let max = 100;
 
-        assert_eq!(forecast_in_range(min, max), 100);
+        assert_eq!(forecast_in_range(min, max, &mut rand::rng()), 100);
     }
 
     #[test]
@@ -200,7 +231,17 @@ mod tests {
         let min = 105;
         let max = 105;
 
-        assert_eq!(forecast_in_range(min, max), 100);
+        assert_eq!(forecast_in_range(min, max, &mut rand::rng()), 100);
+    }
+
+    #[test]
+    fn test_same_seed_generates_same_forecasts() {
+        let capacity = Power::from(1000);
+
+        assert_eq!(
+            generate_random_forecasts(5, capacity, &mut seeded_rng(7)),
+            generate_random_forecasts(5, capacity, &mut seeded_rng(7))
+        );
     }
 
     #[test]
@@ -265,7 +306,7 @@ mod tests {
         };
 
         for _ in 0..0x1e4 {
-            let power: i32 = fv.forecast().into();
+            let power: i32 = fv.forecast(&mut rand::rng()).into();
             assert!(power >= fv.lower_range());
             assert!(power <= fv.upper_range());
             assert_eq!(power.rem(constants::SETPOINT_BASE_VALUE), 0);
@@ -279,7 +320,14 @@ mod tests {
             deviation: 0,
         };
 
-        let power: i32 = fv.forecast().into();
+        let power: i32 = fv.forecast(&mut rand::rng()).into();
         assert_eq!(power, 300);
     }
+
+    #[test]
+    fn test_repeat_profile() {
+        assert_eq!(repeat_profile(vec![1, 2], 5), vec![1, 2, 1, 2, 1]);
+        assert_eq!(repeat_profile(vec![1, 2, 3], 2), vec![1, 2, 3]);
+        assert!(repeat_profile(Vec::<i32>::new(), 3).is_empty());
+    }
 }
@@ -0,0 +1,315 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::utils::is_valid_file_stem;
+
+use super::{CreateNormalizedForecastError, ForecastValue, NormalizedForecastValue};
+
+/// Format of a profile file, CSV files have a `value,deviation` row per delivery period (the
+/// header being optional), JSON files an array of `{"value": .., "deviation": ..}` objects.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProfileFormat {
+    Csv,
+    Json,
+}
+
+impl ProfileFormat {
+    pub fn from_extension(path: &Path) -> Option<ProfileFormat> {
+        match path.extension()?.to_str()? {
+            "csv" => Some(ProfileFormat::Csv),
+            "json" => Some(ProfileFormat::Json),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ProfileFormat::Csv => "csv",
+            ProfileFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Debug, Clone, thiserror::Error, PartialEq)]
+pub enum LoadProfileError {
+    #[error("Invalid profile name {0:?}")]
+    InvalidName(String),
+    #[error("Profile {0:?} does not exist")]
+    NotFound(String),
+    #[error("Unable to read profile: {0}")]
+    Io(String),
+    #[error("Unable to parse profile at line {line}: {reason}")]
+    Parse { line: usize, reason: String },
+    #[error("Invalid value for period {period}: {error}")]
+    InvalidValue {
+        period: usize,
+        error: CreateNormalizedForecastError,
+    },
+    #[error("Profile is empty")]
+    Empty,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProfileRow {
+    value: f64,
+    deviation: f64,
+}
+
+fn parse_rows(content: &str, format: ProfileFormat) -> Result<Vec<ProfileRow>, LoadProfileError> {
+    let rows = match format {
+        ProfileFormat::Json => {
+            serde_json::from_str(content).map_err(|err| LoadProfileError::Parse {
+                line: err.line(),
+                reason: err.to_string(),
+            })?
+        }
+        ProfileFormat::Csv => parse_csv_rows(content)?,
+    };
+    if rows.is_empty() {
+        return Err(LoadProfileError::Empty);
+    }
+    Ok(rows)
+}
+
+fn parse_csv_rows(content: &str) -> Result<Vec<ProfileRow>, LoadProfileError> {
+    let mut rows = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (index == 0 && line.starts_with("value")) {
+            continue;
+        }
+        let parse_error = |reason: &str| LoadProfileError::Parse {
+            line: index + 1,
+            reason: reason.to_string(),
+        };
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [value, deviation] = fields[..] else {
+            return Err(parse_error("expected 2 columns: value,deviation"));
+        };
+        rows.push(ProfileRow {
+            value: value.parse().map_err(|_| parse_error("invalid value"))?,
+            deviation: deviation
+                .parse()
+                .map_err(|_| parse_error("invalid deviation"))?,
+        });
+    }
+    Ok(rows)
+}
+
+/// Parse a normalized profile, i.e. values and deviations relative to a plant's capacity, both
+/// within [0, 1].
+pub fn parse_normalized_profile(
+    content: &str,
+    format: ProfileFormat,
+) -> Result<Vec<NormalizedForecastValue>, LoadProfileError> {
+    parse_rows(content, format)?
+        .into_iter()
+        .enumerate()
+        .map(|(index, row)| {
+            NormalizedForecastValue::try_new(row.value, row.deviation).map_err(|error| {
+                LoadProfileError::InvalidValue {
+                    period: index + 1,
+                    error,
+                }
+            })
+        })
+        .collect()
+}
+
+/// Parse a profile of absolute values, in MW.
+pub fn parse_profile(
+    content: &str,
+    format: ProfileFormat,
+) -> Result<Vec<ForecastValue>, LoadProfileError> {
+    parse_rows(content, format)?
+        .into_iter()
+        .enumerate()
+        .map(|(index, row)| {
+            let period = index + 1;
+            if !row.value.is_finite() || row.value.abs() > f64::from(i32::MAX) {
+                return Err(LoadProfileError::InvalidValue {
+                    period,
+                    error: CreateNormalizedForecastError::ValueOutOfRange,
+                });
+            }
+            if !row.deviation.is_finite() || !(0. ..=f64::from(u32::MAX)).contains(&row.deviation) {
+                return Err(LoadProfileError::InvalidValue {
+                    period,
+                    error: CreateNormalizedForecastError::DeviationOutOfRange,
+                });
+            }
+            Ok(ForecastValue {
+                value: row.value.round() as i32,
+                deviation: row.deviation.round() as u32,
+            })
+        })
+        .collect()
+}
+
+/// Server-side directory of profile files, referenced by their name without extension.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileDirectory {
+    path: PathBuf,
+}
+
+impl ProfileDirectory {
+    pub fn new(path: impl Into<PathBuf>) -> ProfileDirectory {
+        ProfileDirectory { path: path.into() }
+    }
+
+    /// Load the normalized profile `name`, from either `<name>.csv` or `<name>.json`.
+    pub fn load(&self, name: &str) -> Result<Vec<NormalizedForecastValue>, LoadProfileError> {
+        if !is_valid_file_stem(name) {
+            return Err(LoadProfileError::InvalidName(name.to_string()));
+        }
+
+        let Some((path, format)) = [ProfileFormat::Csv, ProfileFormat::Json]
+            .into_iter()
+            .map(|format| {
+                let file = format!("{name}.{}", format.extension());
+                (self.path.join(file), format)
+            })
+            .find(|(path, _)| path.is_file())
+        else {
+            return Err(LoadProfileError::NotFound(name.to_string()));
+        };
+
+        let content =
+            fs::read_to_string(&path).map_err(|err| LoadProfileError::Io(err.to_string()))?;
+        parse_normalized_profile(&content, format)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::forecast::{CreateNormalizedForecastError, ForecastValue, NormalizedForecastValue};
+
+    use super::{
+        LoadProfileError, ProfileDirectory, ProfileFormat, parse_normalized_profile, parse_profile,
+    };
+
+    #[test]
+    fn test_format_from_extension() {
+        assert_eq!(
+            ProfileFormat::from_extension(Path::new("load.csv")),
+            Some(ProfileFormat::Csv)
+        );
+        assert_eq!(
+            ProfileFormat::from_extension(Path::new("load.json")),
+            Some(ProfileFormat::Json)
+        );
+        assert_eq!(ProfileFormat::from_extension(Path::new("load.txt")), None);
+    }
+
+    #[test]
+    fn test_parse_csv_normalized_profile() {
+        let profile = parse_normalized_profile(
+            "value,deviation\n0.5,0.1\n\n0.8, 0.05\n",
+            ProfileFormat::Csv,
+        )
+        .unwrap();
+
+        assert_eq!(
+            profile,
+            vec![
+                NormalizedForecastValue::try_new(0.5, 0.1).unwrap(),
+                NormalizedForecastValue::try_new(0.8, 0.05).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_json_normalized_profile() {
+        let profile = parse_normalized_profile(
+            r#"[{"value": 0.5, "deviation": 0.1}, {"value": 0.8, "deviation": 0.05}]"#,
+            ProfileFormat::Json,
+        )
+        .unwrap();
+
+        assert_eq!(profile.len(), 2);
+        assert_eq!(
+            profile[1],
+            NormalizedForecastValue::try_new(0.8, 0.05).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_profile_errors() {
+        assert_eq!(
+            parse_normalized_profile("value,deviation\n0.5\n", ProfileFormat::Csv),
+            Err(LoadProfileError::Parse {
+                line: 2,
+                reason: "expected 2 columns: value,deviation".to_string()
+            })
+        );
+        assert_eq!(
+            parse_normalized_profile("0.5,abc\n", ProfileFormat::Csv),
+            Err(LoadProfileError::Parse {
+                line: 1,
+                reason: "invalid deviation".to_string()
+            })
+        );
+        assert_eq!(
+            parse_normalized_profile("0.5,0.1\n1.5,0.1\n", ProfileFormat::Csv),
+            Err(LoadProfileError::InvalidValue {
+                period: 2,
+                error: CreateNormalizedForecastError::ValueOutOfRange
+            })
+        );
+        assert_eq!(
+            parse_normalized_profile("[]", ProfileFormat::Json),
+            Err(LoadProfileError::Empty)
+        );
+        assert!(matches!(
+            parse_normalized_profile("[{\"value\": 0.5}]", ProfileFormat::Json),
+            Err(LoadProfileError::Parse { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_absolute_profile() {
+        assert_eq!(
+            parse_profile("value,deviation\n-1200,100\n", ProfileFormat::Csv),
+            Ok(vec![ForecastValue {
+                value: -1200,
+                deviation: 100
+            }])
+        );
+        assert_eq!(
+            parse_profile("-1200,-100\n", ProfileFormat::Csv),
+            Err(LoadProfileError::InvalidValue {
+                period: 1,
+                error: CreateNormalizedForecastError::DeviationOutOfRange
+            })
+        );
+    }
+
+    #[test]
+    fn test_load_profile_from_directory() {
+        let dir = std::env::temp_dir().join(format!("parcelec-profiles-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("winter.csv"), "value,deviation\n0.9,0.05\n").unwrap();
+        let profiles = ProfileDirectory::new(&dir);
+
+        assert_eq!(
+            profiles.load("winter"),
+            Ok(vec![NormalizedForecastValue::try_new(0.9, 0.05).unwrap()])
+        );
+        assert_eq!(
+            profiles.load("summer"),
+            Err(LoadProfileError::NotFound("summer".to_string()))
+        );
+        assert_eq!(
+            profiles.load("../winter"),
+            Err(LoadProfileError::InvalidName("../winter".to_string()))
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,236 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{constants, utils::units::Power};
+
+use super::{ForecastValue, forecast_in_range, round_to_nearest};
+
+/// Deviation of the forecasts of a delivery period made `1..=horizon` periods ahead, index `h - 1`
+/// holding the deviation of the forecast made `h` periods ahead. Deviations linearly decrease
+/// with the distance to the target period, from `deviation` for the furthest forecast, and are
+/// multiples of the setpoint step so that narrowed ranges stay aligned.
+pub fn deviation_schedule(deviation: u32, horizon: usize) -> Vec<u32> {
+    let step = constants::SETPOINT_BASE_VALUE.unsigned_abs();
+    (1..=horizon)
+        .map(|h| {
+            if h == horizon {
+                return deviation;
+            }
+            let scaled = u64::from(deviation) * h as u64 / horizon as u64;
+            let scaled = u32::try_from(scaled).unwrap_or(deviation);
+            scaled - scaled % step
+        })
+        .collect()
+}
+
+impl ForecastValue {
+    /// Revise the forecast with a smaller deviation, the new value is randomly selected so that the
+    /// new range is included in the current one.
+    pub fn narrowed(&self, deviation: u32, rng: &mut impl Rng) -> ForecastValue {
+        let deviation = deviation.min(self.deviation);
+        let min = self.lower_range().saturating_add_unsigned(deviation);
+        let max = self.upper_range().saturating_sub_unsigned(deviation);
+        ForecastValue {
+            value: forecast_in_range(min, max, rng).clamp(min, max),
+            deviation,
+        }
+    }
+
+    /// Revise the forecast with a smaller deviation, the new range being included in the current
+    /// one and centered on `position`, from -1 (lower bound) to 1 (upper bound).
+    pub fn narrowed_towards(&self, deviation: u32, position: f64) -> ForecastValue {
+        let deviation = deviation.min(self.deviation);
+        let min = self.lower_range().saturating_add_unsigned(deviation);
+        let max = self.upper_range().saturating_sub_unsigned(deviation);
+        ForecastValue {
+            value: value_at_position(min, max, position),
+            deviation,
+        }
+    }
+}
+
+/// Value of `[min, max]` at `position`, from -1 (`min`) to 1 (`max`), rounded to the setpoint step.
+fn value_at_position(min: i32, max: i32, position: f64) -> i32 {
+    let ratio = (position.clamp(-1., 1.) + 1.) / 2.;
+    let value = f64::from(min) + ratio * (f64::from(max) - f64::from(min));
+    round_to_nearest(value.round() as i32, constants::SETPOINT_BASE_VALUE).clamp(min, max)
+}
+
+/// Successive forecasts of a single delivery period and its realised value. Each forecast range is
+/// included in the range of the forecast made one period earlier, and the realised value is
+/// within the range of the last forecast.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForecastRevisions {
+    revisions: Vec<ForecastValue>,
+    realised: Power,
+}
+
+impl ForecastRevisions {
+    /// Generate the forecasts of a delivery period from `horizon` periods ahead, the furthest
+    /// forecast being `base`.
+    pub fn generate(base: ForecastValue, horizon: usize, rng: &mut impl Rng) -> ForecastRevisions {
+        let schedule = deviation_schedule(base.deviation, horizon);
+        let mut revisions = vec![base; horizon];
+        for h in (1..horizon).rev() {
+            revisions[h - 1] = revisions[h].narrowed(schedule[h - 1], rng);
+        }
+        let last = revisions.first().copied().unwrap_or(base);
+        let realised = last.forecast(rng).clamp(
+            Power::from(last.lower_range()),
+            Power::from(last.upper_range()),
+        );
+
+        ForecastRevisions {
+            revisions,
+            realised,
+        }
+    }
+
+    /// Generate the forecasts of a delivery period from `horizon` periods ahead, converging towards
+    /// the realised value at `position` within the range of the furthest forecast `base`, e.g. a
+    /// position derived from a [`super::signal::GlobalSignal`].
+    pub fn generate_towards(
+        base: ForecastValue,
+        horizon: usize,
+        position: f64,
+    ) -> ForecastRevisions {
+        let schedule = deviation_schedule(base.deviation, horizon);
+        let mut revisions = vec![base; horizon];
+        for h in (1..horizon).rev() {
+            revisions[h - 1] = revisions[h].narrowed_towards(schedule[h - 1], position);
+        }
+        let last = revisions.first().copied().unwrap_or(base);
+        let realised = value_at_position(last.lower_range(), last.upper_range(), position);
+
+        ForecastRevisions {
+            revisions,
+            realised: Power::from(realised),
+        }
+    }
+
+    /// Forecast made `horizon` periods ahead of the delivery period, the furthest forecast is
+    /// returned if `horizon` is beyond the generated ones.
+    pub fn at(&self, horizon: usize) -> ForecastValue {
+        self.revisions
+            .get(horizon.saturating_sub(1))
+            .or(self.revisions.last())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn realised(&self) -> Power {
+        self.realised
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Rem;
+
+    use crate::{constants, forecast::ForecastValue, utils::units::Power};
+
+    use super::{ForecastRevisions, deviation_schedule};
+
+    fn base() -> ForecastValue {
+        ForecastValue {
+            value: 500,
+            deviation: 100,
+        }
+    }
+
+    #[test]
+    fn test_deviation_schedule_decreases_with_horizon() {
+        assert_eq!(deviation_schedule(100, 4), vec![25, 50, 75, 100]);
+        assert_eq!(deviation_schedule(100, 3), vec![25, 50, 100]);
+        assert_eq!(deviation_schedule(100, 1), vec![100]);
+        assert!(deviation_schedule(100, 0).is_empty());
+    }
+
+    #[test]
+    fn test_deviation_schedule_multiple_of_setpoint_step() {
+        for deviation in deviation_schedule(300, 7).iter().rev().skip(1) {
+            assert_eq!(
+                deviation.rem(constants::SETPOINT_BASE_VALUE.unsigned_abs()),
+                0
+            );
+        }
+    }
+
+    #[test]
+    fn test_narrowed_forecast_included_in_previous() {
+        for _ in 0..0x1e3 {
+            let narrowed = base().narrowed(75, &mut rand::rng());
+
+            assert_eq!(narrowed.deviation, 75);
+            assert!([475, 500, 525].contains(&narrowed.value));
+            assert!(narrowed.included_in(&base()));
+        }
+    }
+
+    #[test]
+    fn test_narrowed_forecast_cannot_widen_range() {
+        let narrowed = base().narrowed(200, &mut rand::rng());
+
+        assert_eq!(narrowed, base());
+    }
+
+    #[test]
+    fn test_forecast_revisions_converge() {
+        for _ in 0..0x1e3 {
+            let revisions = ForecastRevisions::generate(base(), 4, &mut rand::rng());
+
+            assert_eq!(revisions.at(4), base());
+            for horizon in 1..4 {
+                let forecast = revisions.at(horizon);
+                let previous = revisions.at(horizon + 1);
+                assert!(forecast.deviation <= previous.deviation);
+                assert!(forecast.included_in(&previous));
+            }
+            let last = revisions.at(1);
+            let realised: i32 = revisions.realised().into();
+            assert!((last.lower_range()..=last.upper_range()).contains(&realised));
+        }
+    }
+
+    #[test]
+    fn test_forecast_revisions_towards_position_converge() {
+        for position in [-1., -0.3, 0., 0.6, 1.] {
+            let revisions = ForecastRevisions::generate_towards(base(), 4, position);
+
+            for horizon in 1..4 {
+                assert!(
+                    revisions
+                        .at(horizon)
+                        .included_in(&revisions.at(horizon + 1))
+                );
+            }
+            let last = revisions.at(1);
+            let realised: i32 = revisions.realised().into();
+            assert!((last.lower_range()..=last.upper_range()).contains(&realised));
+        }
+    }
+
+    #[test]
+    fn test_forecast_revisions_towards_range_bounds() {
+        let lowest = ForecastRevisions::generate_towards(base(), 3, -1.);
+        let highest = ForecastRevisions::generate_towards(base(), 3, 1.);
+
+        assert_eq!(lowest.realised(), Power::from(400));
+        assert_eq!(highest.realised(), Power::from(600));
+    }
+
+    #[test]
+    fn test_forecast_revisions_without_horizon() {
+        let revisions = ForecastRevisions::generate(
+            ForecastValue {
+                value: 300,
+                deviation: 0,
+            },
+            0,
+            &mut rand::rng(),
+        );
+
+        assert_eq!(revisions.realised(), Power::from(300));
+        assert_eq!(revisions.at(1), ForecastValue::default());
+    }
+}
@@ -0,0 +1,113 @@
+use derive_more::Display;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{game::delivery_period::DeliveryPeriodId, plants::PlantCategory};
+
+/// Rare event shifting the realised value of a technology out of its forecasted range, e.g. a
+/// sudden cold snap for consumers or an unexpected storm for renewable plants.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShockModel {
+    /// Probability of a shock for each delivery period, in [0, 1]
+    pub probability: f64,
+    /// Relative change of the realised value, e.g. -0.5 halves the output of renewable plants
+    pub magnitude: f64,
+    pub technology: PlantCategory,
+}
+
+#[derive(Debug, Display, Clone, thiserror::Error, PartialEq)]
+pub enum CreateShockModelError {
+    ProbabilityOutOfRange,
+    MagnitudeOutOfRange,
+}
+
+impl ShockModel {
+    pub fn try_new(
+        probability: f64,
+        magnitude: f64,
+        technology: PlantCategory,
+    ) -> Result<Self, CreateShockModelError> {
+        let model = Self {
+            probability,
+            magnitude,
+            technology,
+        };
+        model.validate()?;
+        Ok(model)
+    }
+
+    /// Check the model parameters, e.g. after deserialising it from a game request.
+    pub fn validate(&self) -> Result<(), CreateShockModelError> {
+        if !(0. ..=1.).contains(&self.probability) {
+            return Err(CreateShockModelError::ProbabilityOutOfRange);
+        }
+        if !self.magnitude.is_finite() || self.magnitude < -1. {
+            return Err(CreateShockModelError::MagnitudeOutOfRange);
+        }
+        Ok(())
+    }
+
+    /// Randomly draw whether a shock happens during the given delivery period.
+    pub fn draw(&self, period: DeliveryPeriodId, rng: &mut impl Rng) -> Option<ForecastShock> {
+        if !rng.random_bool(self.probability) {
+            return None;
+        }
+        Some(ForecastShock {
+            period,
+            technology: self.technology,
+            magnitude: self.magnitude,
+        })
+    }
+}
+
+/// Shock that happened during a delivery period.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ForecastShock {
+    pub period: DeliveryPeriodId,
+    pub technology: PlantCategory,
+    pub magnitude: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{game::delivery_period::DeliveryPeriodId, plants::PlantCategory};
+
+    use super::{CreateShockModelError, ForecastShock, ShockModel};
+
+    #[test]
+    fn test_shock_model_validation() {
+        assert_eq!(
+            ShockModel::try_new(1.1, 0.5, PlantCategory::Consumers),
+            Err(CreateShockModelError::ProbabilityOutOfRange)
+        );
+        assert_eq!(
+            ShockModel::try_new(0.1, -1.5, PlantCategory::RenewablePlant),
+            Err(CreateShockModelError::MagnitudeOutOfRange)
+        );
+        assert!(ShockModel::try_new(0.1, -1., PlantCategory::RenewablePlant).is_ok());
+    }
+
+    #[test]
+    fn test_draw_certain_shock() {
+        let model = ShockModel::try_new(1., -0.5, PlantCategory::RenewablePlant).unwrap();
+
+        assert_eq!(
+            model.draw(DeliveryPeriodId::from(2), &mut rand::rng()),
+            Some(ForecastShock {
+                period: DeliveryPeriodId::from(2),
+                technology: PlantCategory::RenewablePlant,
+                magnitude: -0.5,
+            })
+        );
+    }
+
+    #[test]
+    fn test_draw_impossible_shock() {
+        let model = ShockModel::try_new(0., -0.5, PlantCategory::RenewablePlant).unwrap();
+
+        assert_eq!(
+            model.draw(DeliveryPeriodId::from(2), &mut rand::rng()),
+            None
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use derive_more::Display;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Signal shared by all the stacks of a game, so that the realisations of the same source are
+/// correlated between players. For each delivery period, the signal is a position within the
+/// forecasts range, from -1 (lower bound) to 1 (upper bound).
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct GlobalSignal {
+    values: Vec<f64>,
+    /// Weight of the global signal in the position of a stack's realisation, the remaining being
+    /// idiosyncratic noise: 0 means fully decorrelated stacks, 1 means identical positions
+    correlation: f64,
+}
+
+#[derive(Debug, Display, Clone, thiserror::Error, PartialEq)]
+pub enum CreateGlobalSignalError {
+    CorrelationOutOfRange,
+}
+
+impl GlobalSignal {
+    pub fn generate(
+        len: usize,
+        correlation: f64,
+        rng: &mut impl Rng,
+    ) -> Result<Self, CreateGlobalSignalError> {
+        if !(0. ..=1.).contains(&correlation) {
+            return Err(CreateGlobalSignalError::CorrelationOutOfRange);
+        }
+
+        Ok(Self {
+            values: (0..len).map(|_| rng.random_range(-1. ..=1.)).collect(),
+            correlation,
+        })
+    }
+
+    pub fn correlation(&self) -> f64 {
+        self.correlation
+    }
+
+    /// Position of a stack's realisation for the given delivery period, the signal is repeated if
+    /// the game is longer.
+    pub fn position(&self, period: usize, rng: &mut impl Rng) -> f64 {
+        let noise = rng.random_range(-1. ..=1.);
+        if self.values.is_empty() {
+            return noise;
+        }
+        let signal = self.values[period.saturating_sub(1) % self.values.len()];
+        self.correlation * signal + (1. - self.correlation) * noise
+    }
+}
+
+/// Global signals of a game: weather for renewable plants, and demand for consumers.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct WeatherSignal {
+    pub renewable: GlobalSignal,
+    pub consumers: GlobalSignal,
+}
+
+impl WeatherSignal {
+    pub fn generate(
+        len: usize,
+        correlation: f64,
+        rng: &mut impl Rng,
+    ) -> Result<Self, CreateGlobalSignalError> {
+        Ok(Self {
+            renewable: GlobalSignal::generate(len, correlation, rng)?,
+            consumers: GlobalSignal::generate(len, correlation, rng)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CreateGlobalSignalError, GlobalSignal};
+
+    #[test]
+    fn test_correlation_out_of_range() {
+        assert_eq!(
+            GlobalSignal::generate(3, -0.1, &mut rand::rng()),
+            Err(CreateGlobalSignalError::CorrelationOutOfRange)
+        );
+        assert_eq!(
+            GlobalSignal::generate(3, 1.1, &mut rand::rng()),
+            Err(CreateGlobalSignalError::CorrelationOutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_position_within_bounds() {
+        let signal = GlobalSignal::generate(4, 0.5, &mut rand::rng()).unwrap();
+
+        for period in 1..=8 {
+            let position = signal.position(period, &mut rand::rng());
+            assert!((-1. ..=1.).contains(&position));
+        }
+    }
+
+    #[test]
+    fn test_fully_correlated_positions_are_identical() {
+        let signal = GlobalSignal::generate(4, 1., &mut rand::rng()).unwrap();
+
+        for period in 1..=4 {
+            assert_eq!(
+                signal.position(period, &mut rand::rng()),
+                signal.position(period, &mut rand::rng())
+            );
+        }
+    }
+
+    #[test]
+    fn test_signal_repeated_when_game_is_longer() {
+        let signal = GlobalSignal::generate(3, 1., &mut rand::rng()).unwrap();
+
+        assert_eq!(
+            signal.position(1, &mut rand::rng()),
+            signal.position(4, &mut rand::rng())
+        );
+    }
+}
@@ -1,17 +1,22 @@
-use std::{collections::HashMap, ops::Sub, time::Duration};
+use std::{collections::HashMap, ops::Sub};
 
+use chrono::{DateTime, Utc};
 use derive_more::{Display, From, Into};
 use futures_util::future::join_all;
 use serde::{Deserialize, Serialize};
 use tokio::{
-    join,
-    sync::{mpsc, oneshot},
+    join, select,
+    sync::{mpsc, oneshot, watch},
     time::sleep,
 };
 use tokio_util::sync::CancellationToken;
 
 use crate::{
-    game::scores::{PlayerDetailedScore, compute_players_detailed_scores, compute_players_scores},
+    forecast::shock::ForecastShock,
+    game::scores::{
+        ImbalancePrices, PlayerDetailedScore, compute_players_detailed_scores,
+        compute_players_scores,
+    },
     market::{Market, order_book::Trade},
     plants::{Stack, StackDispatchResults},
     player::PlayerId,
@@ -62,21 +67,57 @@ pub struct DeliveryPeriodResults {
     pub period_id: DeliveryPeriodId,
     pub players_scores: HashMap<PlayerId, PlayerScore>,
     pub players_detailed_scores: HashMap<PlayerId, PlayerDetailedScore>,
+    /// Forecast shocks that hit each player's stack during the period
+    pub shocks: HashMap<PlayerId, Vec<ForecastShock>>,
 }
 
+/// End of the current phase of a game, which the host can push back, or unset while the game is
+/// paused.
+pub type Deadline = watch::Receiver<Option<DateTime<Utc>>>;
+
+/// Wait until the deadline is reached, following its updates. Without a deadline, wait until one
+/// is set.
+pub async fn wait_for_deadline(mut deadline: Deadline) {
+    loop {
+        let end_at = *deadline.borrow_and_update();
+        let Some(end_at) = end_at else {
+            if deadline.changed().await.is_err() {
+                std::future::pending::<()>().await;
+            }
+            continue;
+        };
+        let remaining = (end_at - Utc::now()).to_std().unwrap_or_default();
+        select! {
+            _ = sleep(remaining) => return,
+            changed = deadline.changed() => {
+                if changed.is_err() {
+                    sleep(remaining).await;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn start_delivery_period<StkS, MS>(
     period_id: DeliveryPeriodId,
     game_tx: mpsc::Sender<GameMessage>,
     market_service: MS,
     stack_services: HashMap<PlayerId, StkS>,
     players_ready_rx: oneshot::Receiver<()>,
-    period_duration: Option<Duration>,
+    deadline: Option<Deadline>,
+    shocks: Vec<ForecastShock>,
+    imbalance_prices: ImbalancePrices,
     cancellation_token: CancellationToken,
 ) where
     StkS: Stack,
     MS: Market,
 {
-    // First, open market and stacks
+    // Shocks hit stacks before they are opened, so that players can react during the period
+    let shocks = apply_shocks(&stack_services, &shocks).await;
+
+    // Then, open market and stacks
     let market_service_cloned = market_service.clone();
     let previous_period = period_id.previous();
     let open_market =
@@ -92,15 +133,15 @@ pub async fn start_delivery_period<StkS, MS>(
 
     let mut set = tokio::task::JoinSet::new();
 
-    if let Some(duration) = period_duration {
+    if let Some(deadline) = deadline {
         // Close market and stacks when time has elapsed
         let current_period = period_id;
         let market_service_cloned = market_service.clone();
         let stack_services_cloned = stack_services.clone();
         set.spawn(async move {
             Ok(join!(
-                close_market_future(market_service_cloned, current_period, duration),
-                close_stacks_future(stack_services_cloned, current_period, duration)
+                close_market_future(market_service_cloned, current_period, deadline.clone()),
+                close_stacks_future(stack_services_cloned, current_period, deadline)
             ))
         });
     }
@@ -146,27 +187,47 @@ pub async fn start_delivery_period<StkS, MS>(
         }
     };
 
-    let scores = compute_players_scores(&trades, &stacks_results);
-    let detailed_scores = compute_players_detailed_scores(&trades, &stacks_results);
+    let scores = compute_players_scores(&trades, &stacks_results, &imbalance_prices);
+    let detailed_scores =
+        compute_players_detailed_scores(&trades, &stacks_results, &imbalance_prices);
     tracing::info!("Delivery period ended: {scores:?}");
     let _ = game_tx
         .send(GameMessage::DeliveryPeriodResults(DeliveryPeriodResults {
             period_id,
             players_scores: scores,
             players_detailed_scores: detailed_scores,
+            shocks,
         }))
         .await;
 }
 
+async fn apply_shocks<StkS>(
+    stacks: &HashMap<PlayerId, StkS>,
+    shocks: &[ForecastShock],
+) -> HashMap<PlayerId, Vec<ForecastShock>>
+where
+    StkS: Stack,
+{
+    let mut affected: HashMap<PlayerId, Vec<ForecastShock>> = HashMap::new();
+    for shock in shocks {
+        for (player_id, stack) in stacks.iter() {
+            if stack.apply_shock(*shock).await {
+                affected.entry(player_id.clone()).or_default().push(*shock);
+            }
+        }
+    }
+    affected
+}
+
 async fn close_market_future<MS>(
     market: MS,
     period_id: DeliveryPeriodId,
-    duration: Duration,
+    deadline: Deadline,
 ) -> Vec<Trade>
 where
     MS: Market,
 {
-    sleep(duration).await;
+    wait_for_deadline(deadline).await;
     market.close_market(period_id).await
 }
 
@@ -187,12 +248,12 @@ where
 async fn close_stacks_future<StkS>(
     stacks: HashMap<PlayerId, StkS>,
     period_id: DeliveryPeriodId,
-    duration: Duration,
+    deadline: Deadline,
 ) -> HashMap<PlayerId, StackDispatchResults>
 where
     StkS: Stack,
 {
-    sleep(duration).await;
+    wait_for_deadline(deadline).await;
     close_stacks(stacks, period_id).await
 }
 
@@ -233,18 +294,24 @@ where
 mod tests {
     use std::{collections::HashMap, time::Duration};
 
+    use chrono::Utc;
     use futures::future;
     use mockall::{Sequence, predicate::eq};
-    use tokio::sync::{mpsc, oneshot};
+    use tokio::sync::{mpsc, oneshot, watch};
     use tokio_util::sync::CancellationToken;
 
     use crate::{
+        forecast::shock::ForecastShock,
         game::{
             GameMessage,
-            delivery_period::{DeliveryPeriodId, start_delivery_period},
+            delivery_period::{DeliveryPeriodId, start_delivery_period, wait_for_deadline},
+            scores::ImbalancePrices,
         },
         market::infra::service::MockMarketService,
-        plants::{StackAggregatedState, StackDispatchResults, infra::service::MockStackService},
+        plants::{
+            PlantCategory, StackAggregatedState, StackDispatchResults,
+            infra::service::MockStackService,
+        },
         player::PlayerId,
     };
 
@@ -326,7 +393,8 @@ mod tests {
 
         // Keep _players_ready_tx around to not drop the channel and trigger early closing
         let (_players_ready_tx, players_ready_rx) = oneshot::channel();
-        let duration = Some(Duration::from_micros(1));
+        let (_deadline_tx, deadline) = watch::channel(Some(Utc::now() + Duration::from_micros(1)));
+        let duration = Some(deadline);
         let token = CancellationToken::new();
 
         tokio::spawn(async move {
@@ -337,6 +405,8 @@ mod tests {
                 stacks_services,
                 players_ready_rx,
                 duration,
+                vec![],
+                ImbalancePrices::default(),
                 token,
             )
             .await;
@@ -419,6 +489,8 @@ mod tests {
                 stacks_services,
                 players_ready_rx,
                 timers,
+                vec![],
+                ImbalancePrices::default(),
                 token,
             )
             .await;
@@ -433,4 +505,106 @@ mod tests {
         };
         assert_eq!(results.period_id, period);
     }
+
+    #[tokio::test]
+    async fn test_shocks_are_recorded_in_period_results() {
+        let period = DeliveryPeriodId::from(1);
+        let (game_tx, mut game_rx) = mpsc::channel(16);
+        let (players_ready_tx, players_ready_rx) = oneshot::channel();
+
+        let mut market_service = MockMarketService::new();
+        market_service.expect_clone().once().returning(|| {
+            let mut mocked = MockMarketService::new();
+            mocked
+                .expect_open_market()
+                .returning(|_| Box::pin(future::ready(())));
+            mocked
+        });
+        market_service
+            .expect_close_market()
+            .returning(|_| Box::pin(future::ready(Vec::new())));
+
+        let shock = ForecastShock {
+            period,
+            technology: PlantCategory::RenewablePlant,
+            magnitude: -0.5,
+        };
+        let mut stack_service = MockStackService::new();
+        stack_service
+            .expect_apply_shock()
+            .with(eq(shock))
+            .once()
+            .returning(|_| Box::pin(future::ready(true)));
+        stack_service.expect_clone().once().returning(|| {
+            let mut mocked = MockStackService::new();
+            mocked
+                .expect_open_stack()
+                .returning(|_| Box::pin(future::ready(())));
+            mocked
+        });
+        stack_service.expect_close_stack().once().returning(|_| {
+            Box::pin(future::ready(Ok(StackDispatchResults::new(
+                HashMap::new(),
+                StackAggregatedState::empty(),
+            ))))
+        });
+        let stacks_services = HashMap::from([(PlayerId::from("toto"), stack_service)]);
+
+        tokio::spawn(async move {
+            start_delivery_period(
+                period,
+                game_tx,
+                market_service,
+                stacks_services,
+                players_ready_rx,
+                None,
+                vec![shock],
+                ImbalancePrices::default(),
+                CancellationToken::new(),
+            )
+            .await;
+        });
+        let _ = players_ready_tx.send(());
+
+        let Some(GameMessage::DeliveryPeriodResults(results)) = game_rx.recv().await else {
+            unreachable!("Should have received results for the delivery period")
+        };
+        assert_eq!(
+            results.shocks,
+            HashMap::from([(PlayerId::from("toto"), vec![shock])])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_deadline_follows_extensions() {
+        let (deadline_tx, deadline) = watch::channel(Some(Utc::now() + Duration::from_millis(20)));
+        let waiting = tokio::spawn(wait_for_deadline(deadline));
+
+        deadline_tx.send_modify(|end_at| {
+            *end_at = end_at.map(|end_at| end_at + Duration::from_millis(200));
+        });
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(!waiting.is_finished());
+
+        deadline_tx.send_replace(Some(Utc::now()));
+        tokio::time::timeout(Duration::from_millis(100), waiting)
+            .await
+            .expect("Should have reached the deadline")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_deadline_waits_for_one_to_be_set() {
+        let (deadline_tx, deadline) = watch::channel(None);
+        let waiting = tokio::spawn(wait_for_deadline(deadline));
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiting.is_finished());
+
+        deadline_tx.send_replace(Some(Utc::now()));
+        tokio::time::timeout(Duration::from_millis(100), waiting)
+            .await
+            .expect("Should have reached the deadline")
+            .unwrap();
+    }
 }
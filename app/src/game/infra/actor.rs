@@ -1,32 +1,62 @@
-use std::{collections::HashMap, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 
+use chrono::{DateTime, Utc};
 use futures_util::future::join_all;
-use tokio::{
-    sync::{
-        mpsc::{Receiver, Sender, channel},
-        oneshot, watch,
-    },
-    time::sleep,
+use serde::{Deserialize, Serialize};
+use tokio::sync::{
+    mpsc::{Receiver, Sender, channel},
+    oneshot, watch,
 };
+use tokio::task::AbortHandle;
 use tokio_util::sync::CancellationToken;
 
 use crate::{
+    forecast::{
+        Forecast,
+        shock::{ForecastShock, ShockModel},
+    },
     game::{
         Game, GameContext, GameEvent, GameId, GameMessage, GameName, GameState,
-        GetPreviousScoresResult, RegisterPlayerResponse, RegisterPlayerStackError,
-        delivery_period::{DeliveryPeriodId, DeliveryPeriodResults, start_delivery_period},
-        infra::stack_config::{GameStackConfig, GameStackPerPlayerPlayerConfig},
-        scores::{PlayerDetailedScore, PlayerResult, PlayerScore, compute_game_rankings},
+        GetPreviousScoresResult, HostCommandError, HostCommandOutcome, HostToken, JoinCode,
+        PhaseDurations, PlantInvestmentError, Player, PlayerLimits, RegisterPlayerError,
+        RegisterPlayerResponse, RegisterPlayerStackError, TeamSecret, deadline_after,
+        delivery_period::{
+            Deadline, DeliveryPeriodId, DeliveryPeriodResults, start_delivery_period,
+            wait_for_deadline,
+        },
+        infra::{
+            archive::{ArchivedGame, GameArchive, GameRecords},
+            event_log::{GameLog, GameLogEvent},
+            persistence::{
+                GameSnapshot, GameStore, PersistedGameState, PersistedPlayer, SNAPSHOT_VERSION,
+            },
+            results::GameResults,
+            stack_config::{
+                GameStackConfig, GameStackPerPlayerPlayerConfig, PlayerInvestmentConfig,
+            },
+        },
+        scores::{
+            ImbalancePrices, PlayerDetailedScore, PlayerEmissionsResult, PlayerResult, PlayerScore,
+            compute_game_emissions_rankings, compute_game_rankings,
+        },
     },
+    market::bots::BotKind,
     plants::{
-        Stack, StackPlants,
-        infra::{StackActor, StackState},
+        PlantId, Stack, StackPlants,
+        infra::{StackActor, StackState, StackTimeline},
     },
+    utils::rng::{GameRng, seeded_rng},
 };
 use crate::{
     market::{Market, MarketContext},
     plants::infra::{StackContext, StackService},
-    player::{PlayerConnections, PlayerId, PlayerMessage, PlayerName, PlayerResultView},
+    player::{
+        PlayerConnections, PlayerEmissionsResultView, PlayerId, PlayerMessage, PlayerName,
+        PlayerResultView,
+    },
 };
 
 /// Main entrypoint for a given game of parcelec. Responsible for:
@@ -41,10 +71,24 @@ pub struct GameActor<MS: Market, PC: PlayerConnections> {
     players_connections: PC,
     market_context: MarketContext<MS>,
     stacks_contexts: HashMap<PlayerId, StackContext<StackService>>,
+    /// Tokens terminating each player's stack, e.g. when the player is kicked
+    stacks_cancellation_tokens: HashMap<PlayerId, CancellationToken>,
     rx: Receiver<GameMessage>,
     tx: Sender<GameMessage>,
     delivery_period_all_players_ready_tx: Option<oneshot::Sender<()>>,
+    /// Task ending the current post delivery phase once its timer expires
+    post_delivery_waiter: Option<AbortHandle>,
+    /// End of the current phase, followed by the timers of games with timed periods
+    deadline: watch::Sender<Option<DateTime<Utc>>>,
+    /// Registered players, shared with the games listing and the players' connections
+    players_watch: watch::Sender<Vec<(PlayerId, PlayerName)>>,
     cancellation_token: CancellationToken,
+    /// Generator seeded from the game config, source of the randomness of stacks and shocks
+    rng: GameRng,
+    /// Where the game is snapshotted at each delivery period boundary, if persistence is enabled
+    store: Option<GameStore>,
+    /// Append-only log of the game's events, shared with its market and stacks
+    log: Option<GameLog>,
 }
 
 struct GameCache {
@@ -52,7 +96,11 @@ struct GameCache {
     players_readiness: HashMap<PlayerName, bool>,
     players_scores: HashMap<PlayerId, HashMap<DeliveryPeriodId, PlayerScore>>,
     players_detailed_scores: HashMap<PlayerId, HashMap<DeliveryPeriodId, PlayerDetailedScore>>,
+    players_shocks: HashMap<PlayerId, HashMap<DeliveryPeriodId, Vec<ForecastShock>>>,
+    /// Names of all the players who joined the game, kicked ones included so that their trades
+    /// are still attributed
     players_id_to_name: HashMap<PlayerId, PlayerName>,
+    kicked_players: HashSet<PlayerId>,
 }
 
 impl Default for GameCache {
@@ -62,17 +110,68 @@ impl Default for GameCache {
             players_readiness: HashMap::new(),
             players_scores: HashMap::new(),
             players_detailed_scores: HashMap::new(),
+            players_shocks: HashMap::new(),
             players_id_to_name: HashMap::new(),
+            kicked_players: HashSet::new(),
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameActorConfig {
     pub id: GameId,
     pub name: GameName,
     pub number_of_delivery_periods: usize,
+    /// Duration of the trading phase of the delivery periods
     pub delivery_period_duration: Option<Duration>,
+    /// Duration of the post delivery phase, same as the trading phase if not set
+    #[serde(default)]
+    pub post_delivery_duration: Option<Duration>,
+    /// Durations of the trading phase of the first delivery periods, overriding
+    /// `delivery_period_duration`
+    #[serde(default)]
+    pub delivery_periods_schedule: Vec<Duration>,
     pub stack_config: GameStackConfig,
+    /// Whether players should also be ranked on their CO2 emissions at the end of the game
+    pub rank_emissions: bool,
+    /// Rare events shifting realised values out of their forecasts
+    pub shocks: Vec<ShockModel>,
+    /// Prices at which players' imbalances are settled
+    pub imbalance_prices: ImbalancePrices,
+    /// Seed of the game's randomness, two games with the same config and seed generate the same
+    /// forecasts, realisations and shocks
+    pub seed: u64,
+    /// Bots taking part in the game, kept with the game so that they are restarted with it
+    #[serde(default)]
+    pub bots: Vec<BotKind>,
+    /// Secret of the game's creator, required to send host commands
+    pub host_token: HostToken,
+    /// Whether players can join once the game has started, they are scored from the next delivery
+    /// period to start
+    #[serde(default)]
+    pub allow_late_join: bool,
+    /// Whether players joining with the name of an existing player join their team, sharing its
+    /// portfolio, instead of being rejected
+    #[serde(default)]
+    pub team_play: bool,
+    /// Code required to join a private game, the game is public if not set
+    #[serde(default)]
+    pub join_code: Option<JoinCode>,
+    /// Number of players the game accepts, and needs to start
+    #[serde(default)]
+    pub player_limits: PlayerLimits,
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl GameActorConfig {
+    pub fn phase_durations(&self) -> PhaseDurations {
+        PhaseDurations {
+            running: self.delivery_period_duration,
+            post_delivery: self.post_delivery_duration,
+            schedule: self.delivery_periods_schedule.clone(),
+        }
+    }
 }
 
 impl<MS: Market, PC: PlayerConnections> GameActor<MS, PC> {
@@ -80,34 +179,159 @@ impl<MS: Market, PC: PlayerConnections> GameActor<MS, PC> {
         config: GameActorConfig,
         players_connections: PC,
         market_context: MarketContext<MS>,
+        store: Option<GameStore>,
+        log: Option<GameLog>,
         cancelation_token: CancellationToken,
     ) -> GameContext {
         let game = Game::init(
             DeliveryPeriodId::from(config.number_of_delivery_periods),
-            config.delivery_period_duration,
-        );
+            config.phase_durations(),
+        )
+        .with_late_join(config.allow_late_join)
+        .with_team_play(config.team_play)
+        .with_player_limits(config.player_limits);
         let (tx, rx) = channel::<GameMessage>(32);
         let (state_tx, _) = watch::channel(game.state.clone());
         let mut game = GameActor {
             cache: GameCache::default(),
             game,
+            rng: seeded_rng(config.seed),
             config,
             state_watch: state_tx,
             market_context,
             players_connections,
             stacks_contexts: HashMap::new(),
+            stacks_cancellation_tokens: HashMap::new(),
             rx,
             tx,
             delivery_period_all_players_ready_tx: None,
+            post_delivery_waiter: None,
+            deadline: watch::channel(None).0,
+            players_watch: watch::channel(Vec::new()).0,
             cancellation_token: cancelation_token,
+            store,
+            log,
         };
+        game.record(GameLogEvent::GameCreated {
+            name: game.config.name.clone(),
+            number_of_delivery_periods: game.config.number_of_delivery_periods,
+            durations: game.config.phase_durations(),
+        });
         let context = game.get_context();
 
-        tokio::spawn(async move { game.run().await });
+        tokio::spawn(async move {
+            game.save_snapshot().await;
+            game.run().await
+        });
 
         context
     }
 
+    /// Restart a game from its last snapshot, along with its players' stacks. A game snapshotted
+    /// after a delivery period resumes in its post delivery phase, with a fresh timer.
+    pub fn restore(
+        snapshot: GameSnapshot,
+        players_connections: PC,
+        market_context: MarketContext<MS>,
+        store: Option<GameStore>,
+        log: Option<GameLog>,
+        cancellation_token: CancellationToken,
+    ) -> (GameContext, HashMap<PlayerId, StackContext<StackService>>) {
+        let config = snapshot.config;
+        let (kicked_players, players): (Vec<_>, Vec<_>) = snapshot
+            .players
+            .into_iter()
+            .partition(|player| player.kicked);
+        let state = match snapshot.state {
+            PersistedGameState::Open => GameState::Open,
+            PersistedGameState::PostDelivery(period) => GameState::PostDelivery {
+                period,
+                end_at: config
+                    .phase_durations()
+                    .post_delivery()
+                    .and_then(deadline_after),
+            },
+            PersistedGameState::Ended(period) => GameState::Ended(period),
+        };
+        let game = Game::new(
+            state.clone(),
+            players
+                .into_iter()
+                .map(|player| Player {
+                    id: player.id,
+                    name: player.name,
+                    ready: false,
+                    team_secret: player.team_secret,
+                })
+                .collect(),
+            DeliveryPeriodId::from(config.number_of_delivery_periods),
+            config.phase_durations(),
+        )
+        .with_late_join(config.allow_late_join)
+        .with_team_play(config.team_play)
+        .with_player_limits(config.player_limits);
+        let cache = GameCache {
+            state: state.clone(),
+            players_readiness: game.players_readines(),
+            players_scores: snapshot.players_scores,
+            players_detailed_scores: snapshot.players_detailed_scores,
+            players_shocks: snapshot.players_shocks,
+            players_id_to_name: game
+                .players
+                .iter()
+                .map(|player| (player.id.clone(), player.name.clone()))
+                .chain(
+                    kicked_players
+                        .iter()
+                        .map(|player| (player.id.clone(), player.name.clone())),
+                )
+                .collect(),
+            kicked_players: kicked_players.into_iter().map(|player| player.id).collect(),
+        };
+        let (players_watch, _) = watch::channel(game.players_ids_and_names());
+        let (tx, rx) = channel::<GameMessage>(32);
+        let (state_tx, _) = watch::channel(state.clone());
+        let mut game = GameActor {
+            cache,
+            game,
+            rng: snapshot.rng,
+            config,
+            state_watch: state_tx,
+            market_context,
+            players_connections,
+            stacks_contexts: HashMap::new(),
+            stacks_cancellation_tokens: HashMap::new(),
+            rx,
+            tx,
+            delivery_period_all_players_ready_tx: None,
+            post_delivery_waiter: None,
+            deadline: watch::channel(state.end_at()).0,
+            players_watch,
+            cancellation_token,
+            store,
+            log,
+        };
+        for (player, stack) in snapshot.stacks {
+            game.spawn_player_stack(
+                &player,
+                StackPlants::restore(stack.plants),
+                stack.delivery_period,
+                stack.timeline,
+                stack.next_forecasts,
+            );
+        }
+        if let GameState::PostDelivery { period, .. } = state {
+            game.wait_for_post_delivery_end(period);
+        }
+        let context = game.get_context();
+        let stacks = game.stacks_contexts.clone();
+        tracing::info!("Game {:?} restored in state {state:?}", game.config.id);
+
+        tokio::spawn(async move { game.run().await });
+
+        (context, stacks)
+    }
+
     async fn run(&mut self) {
         loop {
             tokio::select! {
@@ -124,10 +348,14 @@ impl<MS: Market, PC: PlayerConnections> GameActor<MS, PC> {
 
     #[tracing::instrument(name = "GameActor::process_message", skip(self))]
     async fn process_message(&mut self, message: GameMessage) {
+        let persisted_state = self.persisted_state();
+        let players = self.game.players_ids_and_names();
         let events = match message {
-            GameMessage::RegisterPlayer { name, tx_back } => {
-                self.register_player(name, tx_back).await
-            }
+            GameMessage::RegisterPlayer {
+                name,
+                team_secret,
+                tx_back,
+            } => self.register_player(name, team_secret, tx_back).await,
             GameMessage::GetScores { player_id, tx_back } => {
                 self.send_scores(player_id, tx_back);
                 vec![]
@@ -136,6 +364,44 @@ impl<MS: Market, PC: PlayerConnections> GameActor<MS, PC> {
                 let _ = tx_back.send(self.cache.players_readiness.clone());
                 vec![]
             }
+            GameMessage::GetArchive { tx_back } => {
+                let _ = tx_back.send(self.build_archive().await);
+                vec![]
+            }
+            GameMessage::GetResults { tx_back } => {
+                let _ = tx_back.send(self.build_results());
+                vec![]
+            }
+            GameMessage::HostCommand {
+                token,
+                command,
+                tx_back,
+            } => {
+                if token != self.config.host_token {
+                    tracing::warn!("Host command rejected for game {:?}", self.config.id);
+                    let _ = tx_back.send(Err(HostCommandError::InvalidHostToken));
+                    return;
+                }
+                match self.game.apply_host_command(command) {
+                    Ok(events) => {
+                        let outcome = events
+                            .iter()
+                            .find_map(|event| match event {
+                                GameEvent::PlayerKicked { id } => {
+                                    Some(HostCommandOutcome::PlayerKicked(id.clone()))
+                                }
+                                _ => None,
+                            })
+                            .unwrap_or(HostCommandOutcome::Applied);
+                        let _ = tx_back.send(Ok(outcome));
+                        events
+                    }
+                    Err(err) => {
+                        let _ = tx_back.send(Err(err));
+                        vec![]
+                    }
+                }
+            }
             GameMessage::RegisterPlayerStackConfig {
                 player,
                 config,
@@ -144,6 +410,22 @@ impl<MS: Market, PC: PlayerConnections> GameActor<MS, PC> {
                 self.register_player_stack_config(player, config, tx_back)
                     .await
             }
+            GameMessage::InvestInPlant {
+                player,
+                investment,
+                tx_back,
+            } => {
+                let _ = tx_back.send(self.invest_in_plant(&player, &investment).await);
+                vec![]
+            }
+            GameMessage::RetirePlant {
+                player,
+                plant,
+                tx_back,
+            } => {
+                let _ = tx_back.send(self.retire_plant(&player, plant).await);
+                vec![]
+            }
             GameMessage::PlayerIsReady(player_id) => {
                 if !self.stacks_contexts.contains_key(&player_id) {
                     return;
@@ -151,6 +433,14 @@ impl<MS: Market, PC: PlayerConnections> GameActor<MS, PC> {
                 self.game.register_player_ready(&player_id)
             }
             GameMessage::DeliveryPeriodResults(results) => {
+                if let GameState::Ended(_) = self.cache.state {
+                    // The game was ended by its host while the period was running
+                    return;
+                }
+                self.record(GameLogEvent::ScoresComputed {
+                    period: results.period_id,
+                    scores: results.players_scores.clone(),
+                });
                 self.update_cached_scores(&results);
                 let events = self.game.process_delivery_period_results(&results);
                 self.send_scores_to_all_players(&results.period_id).await;
@@ -161,20 +451,188 @@ impl<MS: Market, PC: PlayerConnections> GameActor<MS, PC> {
             }
         };
 
+        self.deadline
+            .send_if_modified(|end_at| match self.game.state.end_at() {
+                new_end_at if new_end_at != *end_at => {
+                    *end_at = new_end_at;
+                    true
+                }
+                _ => false,
+            });
+        self.publish_players();
         self.process_game_events(events).await;
+        // Snapshots are only taken at phase boundaries, and when players join or leave the game
+        if self.persisted_state() != persisted_state || self.game.players_ids_and_names() != players
+        {
+            self.save_snapshot().await;
+        }
+    }
+
+    /// State of the game as it is snapshotted, if the game is between two delivery periods, i.e.
+    /// when the market and the stacks are closed.
+    fn persisted_state(&self) -> Option<PersistedGameState> {
+        match self.game.state {
+            GameState::Open => Some(PersistedGameState::Open),
+            GameState::PostDelivery { period, .. } => {
+                Some(PersistedGameState::PostDelivery(period))
+            }
+            GameState::Ended(period) => Some(PersistedGameState::Ended(period)),
+            // A paused game is restored from the snapshot taken before it was paused
+            GameState::Running { .. } | GameState::Paused { .. } => None,
+        }
+    }
+
+    /// Snapshot the game, its market and its stacks if the game is between two delivery periods.
+    async fn save_snapshot(&self) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        let Some(state) = self.persisted_state() else {
+            return;
+        };
+        let Some(market) = self.market_context.service.get_persisted_state().await else {
+            return;
+        };
+        let mut stacks = HashMap::new();
+        for (player, stack) in self.stacks_contexts.iter() {
+            let Some(persisted) = stack.service.get_persisted_state().await else {
+                return;
+            };
+            stacks.insert(player.clone(), persisted);
+        }
+
+        let snapshot = GameSnapshot {
+            version: SNAPSHOT_VERSION,
+            saved_at: Utc::now(),
+            config: self.config.clone(),
+            state,
+            players: self
+                .game
+                .players
+                .iter()
+                .map(|player| PersistedPlayer {
+                    id: player.id.clone(),
+                    name: player.name.clone(),
+                    team_secret: player.team_secret.clone(),
+                    kicked: false,
+                })
+                .chain(self.cache.kicked_players.iter().filter_map(|id| {
+                    Some(PersistedPlayer {
+                        id: id.clone(),
+                        name: self.cache.players_id_to_name.get(id)?.clone(),
+                        team_secret: None,
+                        kicked: true,
+                    })
+                }))
+                .collect(),
+            players_scores: self.cache.players_scores.clone(),
+            players_detailed_scores: self.cache.players_detailed_scores.clone(),
+            players_shocks: self.cache.players_shocks.clone(),
+            rng: self.rng.clone(),
+            market,
+            stacks,
+        };
+        if let Err(err) = store.save(&snapshot).await {
+            tracing::warn!("Unable to snapshot game {:?}: {err}", self.config.id);
+        }
+    }
+
+    /// Gather the timeline of an ended game from its market and stacks.
+    async fn build_archive(&self) -> Option<ArchivedGame> {
+        let GameState::Ended(last_period) = self.cache.state else {
+            return None;
+        };
+        let market = self.market_context.service.get_persisted_state().await?;
+        let mut stacks = HashMap::new();
+        for (player, stack) in self.stacks_contexts.iter() {
+            let persisted = stack.service.get_persisted_state().await?;
+            stacks.insert(player.clone(), persisted.timeline);
+        }
+        let records = GameRecords {
+            players: &self.cache.players_id_to_name,
+            last_period,
+            trades: market.past_trades,
+            closing_orders: market.closing_orders,
+            stacks,
+            scores: &self.cache.players_scores,
+            detailed_scores: &self.cache.players_detailed_scores,
+            shocks: &self.cache.players_shocks,
+        };
+
+        Some(ArchivedGame {
+            archive: GameArchive::build(
+                self.config.id.clone(),
+                self.config.name.clone(),
+                records,
+                map_rankings_to_player_name(
+                    compute_game_rankings(&self.cache.players_scores),
+                    &self.cache.players_id_to_name,
+                ),
+                self.emissions_rankings(),
+            ),
+            host_token: self.config.host_token.clone(),
+            join_code: self.config.join_code.clone(),
+        })
+    }
+
+    fn build_results(&self) -> GameResults {
+        GameResults::build(
+            &self.cache.players_id_to_name,
+            &self.cache.players_scores,
+            &self.cache.players_detailed_scores,
+            map_rankings_to_player_name(
+                compute_game_rankings(&self.cache.players_scores),
+                &self.cache.players_id_to_name,
+            ),
+            self.emissions_rankings(),
+        )
+    }
+
+    /// Deadline of the current phase, if it is timed.
+    fn phase_deadline(&self) -> Option<Deadline> {
+        self.game.state.end_at().map(|_| self.deadline.subscribe())
+    }
+
+    fn record(&self, event: GameLogEvent) {
+        if let Some(log) = &self.log {
+            log.record(event);
+        }
     }
 
     async fn process_game_events(&mut self, events: Vec<GameEvent>) {
         for event in events {
             match event {
                 GameEvent::PlayerJoined { id, name } => {
+                    self.record(GameLogEvent::PlayerJoined {
+                        player: id.clone(),
+                        name: name.clone(),
+                    });
                     self.cache.players_id_to_name.insert(id, name);
                 }
+                GameEvent::TeamMemberJoined { id } => {
+                    tracing::info!("New member joined team {id:?} in game {:?}", self.config.id);
+                }
+                GameEvent::PlayerKicked { id } => {
+                    self.record(GameLogEvent::PlayerKicked { player: id.clone() });
+                    self.stacks_contexts.remove(&id);
+                    if let Some(token) = self.stacks_cancellation_tokens.remove(&id) {
+                        token.cancel();
+                    }
+                    self.cache.kicked_players.insert(id.clone());
+                    self.cache.players_scores.remove(&id);
+                    self.cache.players_detailed_scores.remove(&id);
+                    self.cache.players_shocks.remove(&id);
+                    self.market_context.service.remove_player(id.clone()).await;
+                    self.players_connections
+                        .send_to_player(&self.config.id, &id, PlayerMessage::Kicked)
+                        .await;
+                }
                 GameEvent::StateUpdated(state) => {
                     self.cache.state = state;
                     let _ = self.state_watch.send(self.cache.state.clone());
 
-                    if let GameState::Ended(_) = self.cache.state {
+                    if let GameState::Ended(period) = self.cache.state {
+                        self.record(GameLogEvent::GameEnded { period });
                         self.send_final_scores().await;
                     }
                 }
@@ -190,53 +648,105 @@ impl<MS: Market, PC: PlayerConnections> GameActor<MS, PC> {
                         )
                         .await;
                 }
+                GameEvent::Paused | GameEvent::Resumed => {
+                    let paused = matches!(event, GameEvent::Paused);
+                    self.market_context.service.set_paused(paused).await;
+                    for stack in self.stacks_contexts.values() {
+                        stack.service.set_paused(paused).await;
+                    }
+                    // The post delivery timer may have expired while the game was paused
+                    if let GameState::PostDelivery {
+                        period,
+                        end_at: Some(_),
+                    } = self.game.state
+                    {
+                        self.wait_for_post_delivery_end(period);
+                    }
+                }
                 GameEvent::DeliveryPeriodStarted { id } => {
+                    self.record(GameLogEvent::PeriodOpened { period: id });
                     self.start_delivery_period_tasks(id);
                 }
                 GameEvent::DeliveryPeriodEnded { id } => {
+                    self.record(GameLogEvent::PeriodClosed { period: id });
                     if let Some(tx) = self.delivery_period_all_players_ready_tx.take() {
                         let _ = tx.send(());
                     }
-                    let timer = self.config.delivery_period_duration;
-                    let game_tx = self.tx.clone();
-                    tokio::spawn(async move {
-                        wait_for_post_delivery_period_end(id, timer, game_tx).await;
-                    });
+                    self.wait_for_post_delivery_end(id);
                 }
             }
         }
     }
 
+    /// End the post delivery phase of `period` once its timer expires, replacing the previous
+    /// waiter so that the phase is only ended once.
+    fn wait_for_post_delivery_end(&mut self, period: DeliveryPeriodId) {
+        if let Some(waiter) = self.post_delivery_waiter.take() {
+            waiter.abort();
+        }
+        let timer = self.phase_deadline();
+        let game_tx = self.tx.clone();
+        let waiter = tokio::spawn(async move {
+            wait_for_post_delivery_period_end(period, timer, game_tx).await;
+        });
+        self.post_delivery_waiter = Some(waiter.abort_handle());
+    }
+
+    /// Share the registered players, before answering a registration so that the new player can
+    /// connect right away.
+    fn publish_players(&self) {
+        self.players_watch.send_if_modified(|players| {
+            let current = self.game.players_ids_and_names();
+            let modified = current != *players;
+            *players = current;
+            modified
+        });
+    }
+
     async fn register_player(
         &mut self,
         name: PlayerName,
+        team_secret: Option<TeamSecret>,
         tx_back: tokio::sync::oneshot::Sender<RegisterPlayerResponse>,
     ) -> Vec<GameEvent> {
-        match self.game.try_register_player(name) {
+        match self.game.try_register_player(name, team_secret.as_ref()) {
             Ok(events) => {
+                self.publish_players();
                 if let Some(id) = events.iter().find_map(|e| match e {
                     GameEvent::PlayerJoined { id, .. } => Some(id.clone()),
                     _ => None,
                 }) {
                     let stack = match &self.config.stack_config {
-                        GameStackConfig::Fixed(config) => Some(
-                            self.create_player_stack(&id, config.generate_plants())
-                                .await,
-                        ),
+                        GameStackConfig::Fixed(config) => {
+                            let plants = config.generate_plants(
+                                self.game.current_delivery_period(),
+                                &mut self.rng,
+                            );
+                            Some(self.create_player_stack(&id, plants).await)
+                        }
                         GameStackConfig::PerPlayer(..) => None,
                     };
-                    let _ = tx_back.send(RegisterPlayerResponse::Success { id, stack });
+                    let team_secret = self.game.team_secret(&id);
+                    let _ = tx_back.send(RegisterPlayerResponse::Success {
+                        id,
+                        stack,
+                        team_secret,
+                    });
+                } else if let Some(GameEvent::TeamMemberJoined { id }) = events.first() {
+                    let _ = tx_back.send(RegisterPlayerResponse::JoinedTeam { id: id.clone() });
                 }
                 events
             }
             Err(err) => {
                 let _ = tx_back.send(match err {
-                    crate::game::RegisterPlayerError::GameStarted => {
-                        RegisterPlayerResponse::GameStarted
-                    }
-                    crate::game::RegisterPlayerError::NameAlreadyExists => {
+                    RegisterPlayerError::GameStarted => RegisterPlayerResponse::GameStarted,
+                    RegisterPlayerError::NameAlreadyExists => {
                         RegisterPlayerResponse::PlayerAlreadyExist
                     }
+                    RegisterPlayerError::GameFull => RegisterPlayerResponse::GameFull,
+                    RegisterPlayerError::InvalidTeamSecret => {
+                        RegisterPlayerResponse::InvalidTeamSecret
+                    }
                 });
                 vec![]
             }
@@ -259,7 +769,9 @@ impl<MS: Market, PC: PlayerConnections> GameActor<MS, PC> {
             ));
             return vec![];
         };
-        if !self.cache.players_id_to_name.contains_key(&player) {
+        if !self.cache.players_id_to_name.contains_key(&player)
+            || self.cache.kicked_players.contains(&player)
+        {
             tracing::warn!(
                 "Trying to register a stack for player {:} that does not exist",
                 &player
@@ -268,9 +780,29 @@ impl<MS: Market, PC: PlayerConnections> GameActor<MS, PC> {
             return vec![];
         }
 
-        let stack = self
-            .create_player_stack(&player, base_config.generate_plants(player_config))
-            .await;
+        let plants = match base_config.generate_plants(
+            player_config,
+            self.game.current_delivery_period(),
+            DeliveryPeriodId::from(self.config.number_of_delivery_periods),
+            &mut self.rng,
+        ) {
+            Ok(plants) => plants,
+            Err(err) => {
+                tracing::warn!("Stack config of player {player} rejected: {err}");
+                self.players_connections
+                    .send_to_player(
+                        &self.config.id,
+                        &player,
+                        PlayerMessage::StackConfigRejected {
+                            reason: err.to_string(),
+                        },
+                    )
+                    .await;
+                let _ = tx_back.send(Err(err));
+                return vec![];
+            }
+        };
+        let stack = self.create_player_stack(&player, plants).await;
         self.stacks_contexts.insert(player.clone(), stack.clone());
 
         self.send_stack_updates_to_player(&player, &stack).await;
@@ -280,6 +812,65 @@ impl<MS: Market, PC: PlayerConnections> GameActor<MS, PC> {
         vec![]
     }
 
+    /// Stack of a player, if plants can be built or retired, i.e. in a per player game between two
+    /// delivery periods.
+    fn stack_between_periods(
+        &self,
+        player: &PlayerId,
+    ) -> Result<(DeliveryPeriodId, &StackContext<StackService>), PlantInvestmentError> {
+        let GameState::PostDelivery { period, .. } = self.cache.state else {
+            return Err(PlantInvestmentError::NotBetweenDeliveryPeriods);
+        };
+        let stack = self
+            .stacks_contexts
+            .get(player)
+            .ok_or(PlantInvestmentError::PlayerHasNoStack)?;
+        Ok((period, stack))
+    }
+
+    async fn invest_in_plant(
+        &mut self,
+        player: &PlayerId,
+        player_investment: &PlayerInvestmentConfig,
+    ) -> Result<PlantId, PlantInvestmentError> {
+        let GameStackConfig::PerPlayer(base_config) = &self.config.stack_config else {
+            return Err(PlantInvestmentError::GameConfigDoesNotAllowInvestment);
+        };
+        let (period, stack) = self.stack_between_periods(player)?;
+        let service = stack.service.clone();
+        let investment = base_config.plant_investment(
+            player_investment,
+            period,
+            DeliveryPeriodId::from(self.config.number_of_delivery_periods),
+            &mut self.rng,
+        )?;
+
+        service
+            .invest(investment)
+            .await
+            .map_err(PlantInvestmentError::Stack)
+    }
+
+    async fn retire_plant(
+        &self,
+        player: &PlayerId,
+        plant: PlantId,
+    ) -> Result<(), PlantInvestmentError> {
+        let GameStackConfig::PerPlayer(base_config) = &self.config.stack_config else {
+            return Err(PlantInvestmentError::GameConfigDoesNotAllowInvestment);
+        };
+        if base_config.investment.is_none() {
+            return Err(PlantInvestmentError::GameConfigDoesNotAllowInvestment);
+        }
+        let (_, stack) = self.stack_between_periods(player)?;
+
+        stack
+            .service
+            .retire_plant(plant)
+            .await
+            .map_err(PlantInvestmentError::Stack)
+    }
+
     async fn send_stack_updates_to_player(
         &self,
         player: &PlayerId,
@@ -289,7 +880,7 @@ impl<MS: Market, PC: PlayerConnections> GameActor<MS, PC> {
             self.players_connections
                 .send_to_player(
                     &self.config.id,
-                    &player,
+                    player,
                     PlayerMessage::StackSnapshot {
                         plants: Some(snapshot),
                     },
@@ -300,7 +891,7 @@ impl<MS: Market, PC: PlayerConnections> GameActor<MS, PC> {
         self.players_connections
             .send_to_player(
                 &self.config.id,
-                &player,
+                player,
                 PlayerMessage::StackForecasts { forecasts },
             )
             .await;
@@ -315,7 +906,14 @@ impl<MS: Market, PC: PlayerConnections> GameActor<MS, PC> {
             .map(|(id, context)| (id.clone(), context.service.clone()))
             .collect();
         let (all_players_ready_tx, all_players_ready_rx) = oneshot::channel();
-        let timers = self.config.delivery_period_duration;
+        let timers = self.phase_deadline();
+        let imbalance_prices = self.config.imbalance_prices;
+        let shocks = self
+            .config
+            .shocks
+            .iter()
+            .filter_map(|model| model.draw(id, &mut self.rng))
+            .collect();
         let token = self.cancellation_token.clone();
         tokio::spawn(async move {
             start_delivery_period(
@@ -325,6 +923,8 @@ impl<MS: Market, PC: PlayerConnections> GameActor<MS, PC> {
                 stacks_tx,
                 all_players_ready_rx,
                 timers,
+                shocks,
+                imbalance_prices,
                 token,
             )
             .await;
@@ -347,6 +947,14 @@ impl<MS: Market, PC: PlayerConnections> GameActor<MS, PC> {
             }
         }
 
+        for (player, shocks) in results.shocks.iter() {
+            self.cache
+                .players_shocks
+                .entry(player.clone())
+                .or_default()
+                .insert(results.period_id, shocks.clone());
+        }
+
         for (player, scores) in results.players_detailed_scores.iter() {
             match self.cache.players_detailed_scores.get_mut(player) {
                 Some(cached_scores) => {
@@ -370,6 +978,7 @@ impl<MS: Market, PC: PlayerConnections> GameActor<MS, PC> {
                     compute_game_rankings(&self.cache.players_scores.clone()),
                     &self.cache.players_id_to_name,
                 ),
+                emissions: self.emissions_rankings(),
             },
             _ => PlayerScores {
                 scores: self
@@ -401,6 +1010,13 @@ impl<MS: Market, PC: PlayerConnections> GameActor<MS, PC> {
                 .players_detailed_scores
                 .get(player)
                 .and_then(|scores| scores.get(period));
+            let shocks = self
+                .cache
+                .players_shocks
+                .get(player)
+                .and_then(|shocks| shocks.get(period))
+                .cloned()
+                .unwrap_or_default();
 
             tasks.push(self.players_connections.send_to_player(
                 &self.config.id,
@@ -409,11 +1025,18 @@ impl<MS: Market, PC: PlayerConnections> GameActor<MS, PC> {
                     delivery_period: *period,
                     score: score.clone(),
                     detailed_score: detailed_score.cloned(),
+                    shocks,
                 },
             ))
         }
 
         join_all(tasks).await;
+        // Spectators see the scores of all players
+        let mut scores = self.build_results().periods;
+        scores.retain(|results| results.period == *period);
+        self.players_connections
+            .send_to_spectators(&self.config.id, PlayerMessage::PlayersScores { scores })
+            .await;
     }
 
     async fn send_final_scores(&self) {
@@ -426,19 +1049,36 @@ impl<MS: Market, PC: PlayerConnections> GameActor<MS, PC> {
                         compute_game_rankings(&self.cache.players_scores),
                         &self.cache.players_id_to_name,
                     ),
+                    emissions_rankings: self.emissions_rankings(),
                 },
             )
             .await;
     }
 
+    fn emissions_rankings(&self) -> Option<Vec<PlayerEmissionsResultView>> {
+        if !self.config.rank_emissions {
+            return None;
+        }
+        Some(map_emissions_rankings_to_player_name(
+            compute_game_emissions_rankings(&self.cache.players_detailed_scores),
+            &self.cache.players_id_to_name,
+        ))
+    }
+
     fn get_context(&self) -> GameContext {
         GameContext {
             id: self.config.id.clone(),
             name: self.config.name.clone(),
             stack: self.config.stack_config.clone(),
             last_delivery_period: DeliveryPeriodId::from(self.config.number_of_delivery_periods),
+            join_code: self.config.join_code.clone(),
+            host_token: self.config.host_token.clone(),
+            player_limits: self.config.player_limits,
+            durations: self.config.phase_durations(),
+            created_at: self.config.created_at,
             tx: self.tx.clone(),
             state_rx: self.state_watch.subscribe(),
+            players_rx: self.players_watch.subscribe(),
         }
     }
 
@@ -447,15 +1087,38 @@ impl<MS: Market, PC: PlayerConnections> GameActor<MS, PC> {
         player_id: &PlayerId,
         plants: StackPlants,
     ) -> StackContext<StackService> {
+        self.spawn_player_stack(
+            player_id,
+            plants,
+            self.game.current_delivery_period(),
+            StackTimeline::new(),
+            HashMap::new(),
+        )
+    }
+
+    fn spawn_player_stack(
+        &mut self,
+        player_id: &PlayerId,
+        plants: StackPlants,
+        delivery_period: DeliveryPeriodId,
+        timeline: StackTimeline,
+        next_forecasts: HashMap<PlantId, Forecast>,
+    ) -> StackContext<StackService> {
+        let cancellation_token = self.cancellation_token.child_token();
+        self.stacks_cancellation_tokens
+            .insert(player_id.clone(), cancellation_token.clone());
         let mut player_stack = StackActor::new(
             self.config.id.clone(),
             player_id.clone(),
             plants,
             StackState::Closed,
-            self.game.current_delivery_period(),
+            delivery_period,
             self.players_connections.clone(),
-            self.cancellation_token.clone(),
-        );
+            cancellation_token,
+        )
+        .with_log(self.log.clone())
+        .with_timeline(timeline, next_forecasts)
+        .with_paused(self.game.state.is_paused());
         let stack_context = player_stack.get_context();
         self.stacks_contexts
             .insert(player_id.clone(), stack_context.clone());
@@ -470,11 +1133,11 @@ impl<MS: Market, PC: PlayerConnections> GameActor<MS, PC> {
 
 async fn wait_for_post_delivery_period_end(
     period: DeliveryPeriodId,
-    timer: Option<Duration>,
+    timer: Option<Deadline>,
     game_tx: Sender<GameMessage>,
 ) {
-    if let Some(duration) = timer {
-        sleep(duration).await;
+    if let Some(deadline) = timer {
+        wait_for_deadline(deadline).await;
         let _ = game_tx
             .send(GameMessage::PostDeliveryPeriodEnded(period))
             .await;
@@ -499,14 +1162,33 @@ fn map_rankings_to_player_name(
         .collect()
 }
 
+fn map_emissions_rankings_to_player_name(
+    rankings: Vec<PlayerEmissionsResult>,
+    players_mapping: &HashMap<PlayerId, PlayerName>,
+) -> Vec<PlayerEmissionsResultView> {
+    rankings
+        .iter()
+        .filter_map(|rank| {
+            let name = players_mapping.get(&rank.player)?;
+
+            Some(PlayerEmissionsResultView {
+                player: name.clone(),
+                rank: rank.rank,
+                emissions: rank.emissions,
+            })
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod test_utils {
     use tokio::sync::mpsc;
 
     use crate::{
-        game::infra::stack_config::GameStackFixedConfig,
+        game::infra::stack_config::{GameStackFixedConfig, PlantConfig},
         market::{MarketState, OBS, order_book::TradeLeg},
-        utils::units::{Energy, EnergyCost, Power},
+        plants::PlantDefinition,
+        utils::units::{CarbonPrice, Energy, EnergyCost, Power},
     };
 
     use super::*;
@@ -533,10 +1215,26 @@ mod test_utils {
                 },
             )
         }
+        async fn get_public_order_book(&self) -> OBS {
+            OBS {
+                offers: Vec::new(),
+                bids: Vec::new(),
+            }
+        }
         async fn new_order(&self, _request: crate::market::order_book::OrderRequest) {}
         async fn open_market(&self, _delivery_period: DeliveryPeriodId) {
             let _ = self.state_tx.send(MarketState::Open);
         }
+        async fn set_paused(&self, _paused: bool) {}
+        async fn remove_player(&self, _player: PlayerId) {}
+        async fn get_persisted_state(&self) -> Option<crate::market::PersistedMarket> {
+            Some(crate::market::PersistedMarket {
+                delivery_period: DeliveryPeriodId::default(),
+                players: Vec::new(),
+                past_trades: HashMap::new(),
+                closing_orders: HashMap::new(),
+            })
+        }
     }
 
     #[derive(Debug, Clone)]
@@ -574,26 +1272,71 @@ mod test_utils {
         ) -> () {
             let _ = self.tx_send_to_player.send((player.clone(), message)).await;
         }
+        async fn send_to_spectators(&self, _game: &GameId, message: PlayerMessage) -> () {
+            let _ = self.tx_send_to_all_players.send(message).await;
+        }
     }
 
     pub fn default_game_config() -> GameActorConfig {
         GameActorConfig {
             delivery_period_duration: None,
+            post_delivery_duration: None,
+            delivery_periods_schedule: vec![],
             id: GameId::default(),
             name: GameName::default(),
             number_of_delivery_periods: 4,
             stack_config: GameStackConfig::Fixed(GameStackFixedConfig {
-                consumers_revenues: EnergyCost::from(60),
-                gas_cost: EnergyCost::from(70),
-                nuclear_cost: EnergyCost::from(35),
-                gas_capacity: Power::from(300),
-                nuclear_capacity: Power::from(1000),
-                battery_capacity: Energy::from(200),
-                renewable_forecasts: vec![],
-                renewable_forecasts_range: 3,
-                consumers_forecasts: vec![],
-                consumers_forecasts_range: 3,
+                plants: vec![
+                    PlantConfig::new(
+                        "battery",
+                        PlantDefinition::Battery {
+                            capacity: Energy::from(200),
+                        },
+                    ),
+                    PlantConfig::new(
+                        "gas",
+                        PlantDefinition::GasPlant {
+                            energy_cost: EnergyCost::from(70),
+                            capacity: Power::from(300),
+                        },
+                    ),
+                    PlantConfig::new(
+                        "nuclear",
+                        PlantDefinition::Nuclear {
+                            energy_cost: EnergyCost::from(35),
+                            capacity: Power::from(1000),
+                        },
+                    ),
+                    PlantConfig::new(
+                        "renewable",
+                        PlantDefinition::RenewablePlant {
+                            forecasts: vec![].into(),
+                            forecasts_range: 3,
+                        },
+                    ),
+                    PlantConfig::new(
+                        "consumers",
+                        PlantDefinition::Consumers {
+                            revenues: EnergyCost::from(60),
+                            forecasts: vec![].into(),
+                            forecasts_range: 3,
+                        },
+                    ),
+                ],
+                carbon_price: CarbonPrice::default(),
+                weather: None,
             }),
+            rank_emissions: false,
+            shocks: vec![],
+            imbalance_prices: ImbalancePrices::default(),
+            seed: 0,
+            bots: vec![],
+            host_token: HostToken::default(),
+            allow_late_join: false,
+            team_play: false,
+            join_code: None,
+            player_limits: PlayerLimits::default(),
+            created_at: Utc::now(),
         }
     }
 
@@ -611,8 +1354,11 @@ mod test_utils {
         let config = default_game_config();
         let game = Game::init(
             DeliveryPeriodId::from(config.number_of_delivery_periods),
-            config.delivery_period_duration,
-        );
+            config.phase_durations(),
+        )
+        .with_late_join(config.allow_late_join)
+        .with_team_play(config.team_play)
+        .with_player_limits(config.player_limits);
         let (tx, rx) = channel::<GameMessage>(32);
         let (state_watch, state_watch_rx) = watch::channel(game.state.clone());
         let cancellation_token = CancellationToken::new();
@@ -629,11 +1375,18 @@ mod test_utils {
             market_context,
             players_connections,
             stacks_contexts: HashMap::new(),
+            stacks_cancellation_tokens: HashMap::new(),
             rx,
             tx,
             delivery_period_all_players_ready_tx: None,
+            post_delivery_waiter: None,
+            deadline: watch::channel(None).0,
+            players_watch: watch::channel(Vec::new()).0,
             cancellation_token,
             cache: GameCache::default(),
+            rng: seeded_rng(0),
+            store: None,
+            log: None,
             game,
         };
         let comms = TestComms {
@@ -650,11 +1403,16 @@ mod test_utils {
 mod tests {
     use std::{collections::HashMap, time::Duration};
 
-    use crate::game::infra::stack_config::{GameStackConfig, GameStackFixedConfig};
-    use crate::utils::units::{Energy, EnergyCost, Power};
+    use chrono::Utc;
+
+    use crate::game::infra::stack_config::{GameStackConfig, GameStackFixedConfig, PlantConfig};
+    use crate::game::scores::ImbalancePrices;
+    use crate::plants::PlantDefinition;
+    use crate::utils::rng::seeded_rng;
+    use crate::utils::units::{CarbonPrice, Energy, EnergyCost, Power};
     use crate::{
         game::{
-            Game, GameId, GameName,
+            Game, GameId, GameName, HostToken, PhaseDurations, PlayerLimits,
             delivery_period::DeliveryPeriodId,
             infra::{
                 GameActorConfig,
@@ -679,7 +1437,7 @@ mod tests {
             service: MockMarket { state_tx },
             state_rx: rx,
         };
-        let game = Game::init(DeliveryPeriodId::from(3), None);
+        let game = Game::init(DeliveryPeriodId::from(3), PhaseDurations::default());
         let (state_tx, _) = watch::channel(game.state.clone());
         let cancellation_token = CancellationToken::new();
         let (tx, rx) = mpsc::channel(128);
@@ -687,19 +1445,61 @@ mod tests {
             id: GameId::default(),
             name: GameName::default(),
             stack_config: GameStackConfig::Fixed(GameStackFixedConfig {
-                consumers_revenues: EnergyCost::from(60),
-                gas_cost: EnergyCost::from(70),
-                nuclear_cost: EnergyCost::from(35),
-                gas_capacity: Power::from(300),
-                nuclear_capacity: Power::from(1000),
-                battery_capacity: Energy::from(200),
-                renewable_forecasts: vec![],
-                renewable_forecasts_range: 3,
-                consumers_forecasts: vec![],
-                consumers_forecasts_range: 3,
+                plants: vec![
+                    PlantConfig::new(
+                        "battery",
+                        PlantDefinition::Battery {
+                            capacity: Energy::from(200),
+                        },
+                    ),
+                    PlantConfig::new(
+                        "gas",
+                        PlantDefinition::GasPlant {
+                            energy_cost: EnergyCost::from(70),
+                            capacity: Power::from(300),
+                        },
+                    ),
+                    PlantConfig::new(
+                        "nuclear",
+                        PlantDefinition::Nuclear {
+                            energy_cost: EnergyCost::from(35),
+                            capacity: Power::from(1000),
+                        },
+                    ),
+                    PlantConfig::new(
+                        "renewable",
+                        PlantDefinition::RenewablePlant {
+                            forecasts: vec![].into(),
+                            forecasts_range: 3,
+                        },
+                    ),
+                    PlantConfig::new(
+                        "consumers",
+                        PlantDefinition::Consumers {
+                            revenues: EnergyCost::from(60),
+                            forecasts: vec![].into(),
+                            forecasts_range: 3,
+                        },
+                    ),
+                ],
+                carbon_price: CarbonPrice::default(),
+                weather: None,
             }),
             number_of_delivery_periods: 3,
             delivery_period_duration: None,
+            post_delivery_duration: None,
+            delivery_periods_schedule: vec![],
+            rank_emissions: false,
+            shocks: vec![],
+            imbalance_prices: ImbalancePrices::default(),
+            seed: 0,
+            bots: vec![],
+            host_token: HostToken::default(),
+            allow_late_join: false,
+            team_play: false,
+            join_code: None,
+            player_limits: PlayerLimits::default(),
+            created_at: Utc::now(),
         };
         let mut game = GameActor {
             config,
@@ -707,11 +1507,18 @@ mod tests {
             players_connections: connections,
             market_context,
             stacks_contexts: HashMap::new(),
+            stacks_cancellation_tokens: HashMap::new(),
             tx,
             rx,
             delivery_period_all_players_ready_tx: None,
+            post_delivery_waiter: None,
+            deadline: watch::channel(None).0,
+            players_watch: watch::channel(Vec::new()).0,
             cancellation_token: cancellation_token.clone(),
             cache: GameCache::default(),
+            rng: seeded_rng(0),
+            store: None,
+            log: None,
             game,
         };
         let handle = tokio::spawn(async move {
@@ -732,13 +1539,17 @@ mod tests {
 mod test_game_actor_process_game_messages {
     use crate::{
         game::{
-            RegisterPlayerStackError,
+            HostCommand, RegisterPlayerStackError,
             infra::{
                 actor::test_utils::{MockMarket, TestComms, build_game_actor},
-                stack_config::GameStackPerPlayerBaseConfig,
+                stack_config::{CapacityCosts, GameStackPerPlayerBaseConfig, InvestmentConfig},
             },
         },
-        utils::units::{Energy, EnergyCost, Power},
+        plants::{
+            PlantCategory,
+            investment::{PlantLifecycleError, PlantStatus},
+        },
+        utils::units::{CapacityCost, CarbonPrice, Emissions, Energy, EnergyCost, Money, Power},
     };
 
     use super::*;
@@ -752,6 +1563,7 @@ mod test_game_actor_process_game_messages {
         let (tx_back, rx) = oneshot::channel();
         let msg = GameMessage::RegisterPlayer {
             name: PlayerName::from("p1"),
+            team_secret: None,
             tx_back,
         };
 
@@ -765,6 +1577,100 @@ mod test_game_actor_process_game_messages {
         assert!(!game.stacks_contexts.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_register_team_member_shares_team_stack() {
+        let (mut game, _) = build_game_actor();
+        game.game = game.game.with_team_play(true);
+        let join_team = |team_secret: Option<TeamSecret>| {
+            let (tx_back, rx) = oneshot::channel();
+            let msg = GameMessage::RegisterPlayer {
+                name: PlayerName::from("team"),
+                team_secret,
+                tx_back,
+            };
+            (msg, rx)
+        };
+
+        let (msg, rx) = join_team(None);
+        game.process_message(msg).await;
+        let Ok(RegisterPlayerResponse::Success {
+            id: team_id,
+            team_secret: Some(secret),
+            ..
+        }) = rx.await
+        else {
+            unreachable!("The first member should have created the team")
+        };
+
+        let (msg, rx) = join_team(Some(TeamSecret::from("wrong")));
+        game.process_message(msg).await;
+        let Ok(RegisterPlayerResponse::InvalidTeamSecret) = rx.await else {
+            unreachable!("Should not join the team with a wrong secret")
+        };
+
+        let (msg, rx) = join_team(Some(secret));
+        game.process_message(msg).await;
+        let Ok(RegisterPlayerResponse::JoinedTeam { id }) = rx.await else {
+            unreachable!("Should have joined the existing team")
+        };
+        assert_eq!(id, team_id);
+        assert_eq!(game.stacks_contexts.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_late_joiner_stack_starts_at_the_next_period() {
+        let (mut game, _) = build_game_actor();
+        game.game = game.game.with_late_join(true);
+        game.game.state = GameState::Running {
+            period: DeliveryPeriodId::from(3),
+            end_at: None,
+        };
+
+        let (tx_back, rx) = oneshot::channel();
+        game.process_message(GameMessage::RegisterPlayer {
+            name: PlayerName::from("late"),
+            team_secret: None,
+            tx_back,
+        })
+        .await;
+
+        let Ok(RegisterPlayerResponse::Success {
+            stack: Some(stack), ..
+        }) = rx.await
+        else {
+            unreachable!("Late joiner should have been registered with a stack")
+        };
+        let first_period = stack
+            .service
+            .get_forecasts()
+            .await
+            .into_values()
+            .flatten()
+            .flatten()
+            .map(|forecast| forecast.period)
+            .min();
+        // The late joiner plays from period 4, its forecasts are for the following periods
+        assert_eq!(first_period, Some(DeliveryPeriodId::from(5)));
+    }
+
+    #[tokio::test]
+    async fn test_context_follows_registered_players() {
+        let (mut game, _) = build_game_actor();
+        let context = game.get_context();
+        assert!(context.players_rx.borrow().is_empty());
+
+        let toto = register_player(&mut game, "toto").await;
+        let tata = register_player(&mut game, "tata").await;
+
+        assert_eq!(
+            *context.players_rx.borrow(),
+            vec![
+                (toto, PlayerName::from("toto")),
+                (tata, PlayerName::from("tata"))
+            ]
+        );
+    }
+
     fn per_player_base_config() -> GameStackPerPlayerBaseConfig {
         GameStackPerPlayerBaseConfig {
             consumers_revenues: EnergyCost::from(60),
@@ -778,7 +1684,17 @@ mod test_game_actor_process_game_messages {
             nuclear_max_capacity: Power::from(1200),
             renewable_forecasts: vec![],
             renewable_forecasts_range: 3,
+            carbon_price: CarbonPrice::default(),
             renewable_max_capacity: Power::from(400),
+            capacity_costs: CapacityCosts {
+                gas: CapacityCost::from(100),
+                nuclear: CapacityCost::from(400),
+                renewable: CapacityCost::from(150),
+                battery: CapacityCost::from(50),
+            },
+            budget: None,
+            investment: Some(InvestmentConfig { build_delay: 1 }),
+            weather: None,
         }
     }
 
@@ -789,6 +1705,7 @@ mod test_game_actor_process_game_messages {
         let (tx_back, rx) = oneshot::channel();
         let msg = GameMessage::RegisterPlayer {
             name: PlayerName::from("p1"),
+            team_secret: None,
             tx_back,
         };
 
@@ -822,6 +1739,7 @@ mod test_game_actor_process_game_messages {
         let (tx_back, rx) = oneshot::channel();
         let msg = GameMessage::RegisterPlayer {
             name: PlayerName::from(name),
+            team_secret: None,
             tx_back,
         };
         let _ = game.process_message(msg).await;
@@ -880,6 +1798,36 @@ mod test_game_actor_process_game_messages {
         };
     }
 
+    #[tokio::test]
+    async fn test_register_player_stack_config_over_budget_is_rejected() {
+        let (mut game, mut comms) = build_game_actor();
+        game.config.stack_config = GameStackConfig::PerPlayer(GameStackPerPlayerBaseConfig {
+            budget: Some(Money::from(1000)),
+            ..per_player_base_config()
+        });
+        let id = register_player(&mut game, "p1").await;
+
+        let (tx_back, rx) = oneshot::channel();
+        let msg = GameMessage::RegisterPlayerStackConfig {
+            player: id.clone(),
+            config: per_player_player_config(),
+            tx_back,
+        };
+        game.process_message(msg).await;
+
+        let Ok(Err(RegisterPlayerStackError::BudgetExceeded { .. })) = rx.await else {
+            unreachable!("Should have rejected the stack config")
+        };
+        assert!(game.stacks_contexts.is_empty());
+        while let Ok((player_id, message)) = comms.rx_player.try_recv() {
+            if let PlayerMessage::StackConfigRejected { .. } = message {
+                assert_eq!(player_id, id);
+                return;
+            }
+        }
+        unreachable!("Player should have been told its stack config was rejected")
+    }
+
     #[tokio::test]
     async fn test_player_ready_but_with_no_stack_built() {
         let (mut game, _) = build_game_with_per_player_stack();
@@ -951,6 +1899,162 @@ mod test_game_actor_process_game_messages {
         assert_eq!(player_id, id);
     }
 
+    async fn register_player_with_stack(
+        game: &mut GameActor<MockMarket, test_utils::MockPlayerConnections>,
+        name: &'static str,
+    ) -> PlayerId {
+        let id = register_player(game, name).await;
+        let (tx_back, rx) = oneshot::channel();
+        game.process_message(GameMessage::RegisterPlayerStackConfig {
+            player: id.clone(),
+            config: per_player_player_config(),
+            tx_back,
+        })
+        .await;
+        let Ok(Ok(_)) = rx.await else {
+            unreachable!("Should have created the player's stack")
+        };
+        id
+    }
+
+    fn gas_investment() -> PlayerInvestmentConfig {
+        PlayerInvestmentConfig::GasPlant {
+            capacity: Power::from(200),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invest_in_plant_only_between_delivery_periods() {
+        let (mut game, _) = build_game_with_per_player_stack();
+        let id = register_player_with_stack(&mut game, "p1").await;
+
+        let (tx_back, rx) = oneshot::channel();
+        game.process_message(GameMessage::InvestInPlant {
+            player: id,
+            investment: gas_investment(),
+            tx_back,
+        })
+        .await;
+
+        assert_eq!(
+            rx.await.unwrap(),
+            Err(PlantInvestmentError::NotBetweenDeliveryPeriods)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invest_in_plant_not_allowed_in_fixed_stack_game() {
+        let (mut game, _) = build_game_actor();
+        let id = register_player(&mut game, "p1").await;
+        game.cache.state = GameState::PostDelivery {
+            period: DeliveryPeriodId::from(1),
+            end_at: None,
+        };
+
+        let (tx_back, rx) = oneshot::channel();
+        game.process_message(GameMessage::InvestInPlant {
+            player: id,
+            investment: gas_investment(),
+            tx_back,
+        })
+        .await;
+
+        assert_eq!(
+            rx.await.unwrap(),
+            Err(PlantInvestmentError::GameConfigDoesNotAllowInvestment)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invest_in_plant_adds_plant_under_construction_to_stack() {
+        let (mut game, mut comms) = build_game_with_per_player_stack();
+        game.config.number_of_delivery_periods = 4;
+        let id = register_player_with_stack(&mut game, "p1").await;
+        game.cache.state = GameState::PostDelivery {
+            period: DeliveryPeriodId::from(1),
+            end_at: None,
+        };
+        // Drain initial stack messages
+        let _ = comms.rx_player.recv().await;
+        let _ = comms.rx_player.recv().await;
+
+        let (tx_back, rx) = oneshot::channel();
+        game.process_message(GameMessage::InvestInPlant {
+            player: id.clone(),
+            investment: gas_investment(),
+            tx_back,
+        })
+        .await;
+        let Ok(Ok(plant_id)) = rx.await else {
+            unreachable!("Investment should have been accepted")
+        };
+
+        let Some((
+            player_id,
+            PlayerMessage::StackSnapshot {
+                plants: Some(plants),
+            },
+        )) = comms.rx_player.recv().await
+        else {
+            unreachable!("Should have received a StackSnapshot message")
+        };
+        assert_eq!(player_id, id);
+        let plant = plants.get(&plant_id).unwrap();
+        assert_eq!(
+            plant.status,
+            PlantStatus::UnderConstruction { periods_left: 1 }
+        );
+        let capex = plant.capex.unwrap();
+        assert_eq!(capex.remaining(), Money::from(200 * 100));
+        assert_eq!(capex.payments_left(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retire_plant_of_player_stack() {
+        let (mut game, _) = build_game_with_per_player_stack();
+        let id = register_player_with_stack(&mut game, "p1").await;
+        game.cache.state = GameState::PostDelivery {
+            period: DeliveryPeriodId::from(1),
+            end_at: None,
+        };
+        let snapshot = game
+            .stacks_contexts
+            .get(&id)
+            .unwrap()
+            .service
+            .get_snapshot()
+            .await
+            .unwrap();
+        let plant = snapshot
+            .iter()
+            .find(|(_, plant)| plant.name == "gas")
+            .map(|(id, _)| id.clone())
+            .unwrap();
+
+        let (tx_back, rx) = oneshot::channel();
+        game.process_message(GameMessage::RetirePlant {
+            player: id.clone(),
+            plant: plant.clone(),
+            tx_back,
+        })
+        .await;
+        assert_eq!(rx.await.unwrap(), Ok(()));
+
+        let (tx_back, rx) = oneshot::channel();
+        game.process_message(GameMessage::RetirePlant {
+            player: id,
+            plant,
+            tx_back,
+        })
+        .await;
+        assert_eq!(
+            rx.await.unwrap(),
+            Err(PlantInvestmentError::Stack(
+                PlantLifecycleError::PlantAlreadyRetired
+            ))
+        );
+    }
+
     #[tokio::test]
     async fn test_register_player_player_wiht_stack_config_ok() {
         let (mut game, _) = build_game_with_per_player_stack();
@@ -1028,6 +2132,7 @@ mod test_game_actor_process_game_messages {
             period_id: DeliveryPeriodId::from(1),
             players_scores: HashMap::new(),
             players_detailed_scores: HashMap::new(),
+            shocks: HashMap::new(),
         };
         let msg = GameMessage::DeliveryPeriodResults(results);
 
@@ -1039,6 +2144,7 @@ mod test_game_actor_process_game_messages {
                 delivery_period,
                 score,
                 detailed_score,
+                shocks,
             },
         )) = comms.rx_player.recv().await
         else {
@@ -1047,6 +2153,289 @@ mod test_game_actor_process_game_messages {
         assert_eq!(delivery_period, DeliveryPeriodId::from(1));
         assert_eq!(score, PlayerScore::default());
         assert!(detailed_score.is_none());
+        assert!(shocks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_delivery_period_results_include_player_shocks() {
+        let (mut game, mut comms) = build_game_actor();
+        let player = PlayerId::from("p1");
+        let shock = ForecastShock {
+            period: DeliveryPeriodId::from(1),
+            technology: PlantCategory::Consumers,
+            magnitude: 0.3,
+        };
+
+        let results = DeliveryPeriodResults {
+            period_id: DeliveryPeriodId::from(1),
+            players_scores: HashMap::from_iter([(player.clone(), PlayerScore::default())]),
+            players_detailed_scores: HashMap::new(),
+            shocks: HashMap::from_iter([(player.clone(), vec![shock])]),
+        };
+        game.process_message(GameMessage::DeliveryPeriodResults(results))
+            .await;
+
+        let Some((_, PlayerMessage::DeliveryPeriodResults { shocks, .. })) =
+            comms.rx_player.recv().await
+        else {
+            unreachable!("Should have received a PlayerMessage::DeliveryPeriodResults")
+        };
+        assert_eq!(shocks, vec![shock]);
+        assert_eq!(
+            game.cache.players_shocks.get(&player),
+            Some(&HashMap::from_iter([(
+                DeliveryPeriodId::from(1),
+                vec![shock]
+            )]))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_scores_game_ended_no_emissions_ranking_by_default() {
+        let (mut game, _) = build_game_actor();
+        game.cache.state = GameState::Ended(DeliveryPeriodId::from(4));
+        let (tx_back, rx) = oneshot::channel();
+
+        game.process_message(GameMessage::GetScores {
+            player_id: PlayerId::from("p1"),
+            tx_back,
+        })
+        .await;
+
+        let Ok(GetPreviousScoresResult::PlayersRanking { emissions, .. }) = rx.await else {
+            unreachable!("Should have received the players ranking")
+        };
+        assert!(emissions.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_scores_game_ended_with_emissions_ranking() {
+        let (mut game, _) = build_game_actor();
+        game.config.rank_emissions = true;
+        game.cache.state = GameState::Ended(DeliveryPeriodId::from(4));
+        game.cache
+            .players_id_to_name
+            .insert(PlayerId::from("p1"), PlayerName::from("p1"));
+        game.cache.players_detailed_scores.insert(
+            PlayerId::from("p1"),
+            HashMap::from_iter([(
+                DeliveryPeriodId::from(1),
+                PlayerDetailedScore {
+                    emissions: Emissions::from(400),
+                    ..PlayerDetailedScore::default()
+                },
+            )]),
+        );
+        let (tx_back, rx) = oneshot::channel();
+
+        game.process_message(GameMessage::GetScores {
+            player_id: PlayerId::from("p1"),
+            tx_back,
+        })
+        .await;
+
+        let Ok(GetPreviousScoresResult::PlayersRanking { emissions, .. }) = rx.await else {
+            unreachable!("Should have received the players ranking")
+        };
+        assert_eq!(
+            emissions,
+            Some(vec![PlayerEmissionsResultView {
+                player: PlayerName::from("p1"),
+                rank: 1,
+                emissions: Emissions::from(400),
+            }])
+        );
+    }
+
+    async fn send_host_command(
+        game: &mut GameActor<MockMarket, test_utils::MockPlayerConnections>,
+        token: HostToken,
+        command: HostCommand,
+    ) -> Result<HostCommandOutcome, HostCommandError> {
+        let (tx_back, rx) = oneshot::channel();
+        game.process_message(GameMessage::HostCommand {
+            token,
+            command,
+            tx_back,
+        })
+        .await;
+        rx.await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_host_command_rejected_with_invalid_token() {
+        let (mut game, _) = build_game_actor();
+        let _ = register_player(&mut game, "p1").await;
+
+        let result = send_host_command(
+            &mut game,
+            HostToken::from("not the token"),
+            HostCommand::ForceStartPeriod,
+        )
+        .await;
+
+        assert_eq!(result, Err(HostCommandError::InvalidHostToken));
+        assert_eq!(game.game.state, GameState::Open);
+    }
+
+    #[tokio::test]
+    async fn test_host_can_force_start_the_game() {
+        let (mut game, comms) = build_game_actor();
+        let _ = register_player(&mut game, "p1").await;
+        let token = game.config.host_token.clone();
+
+        let result = send_host_command(&mut game, token, HostCommand::ForceStartPeriod).await;
+
+        assert_eq!(result, Ok(HostCommandOutcome::Applied));
+        assert!(matches!(
+            *comms.state_watch_rx.borrow(),
+            GameState::Running { period, .. } if period == DeliveryPeriodId::from(1)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_post_delivery_timer_expiring_before_a_pause_ends_the_phase_on_resume() {
+        let (mut game, _comms) = build_game_actor();
+        let _ = register_player(&mut game, "p1").await;
+        let token = game.config.host_token.clone();
+        game.game.state = GameState::PostDelivery {
+            period: DeliveryPeriodId::from(1),
+            end_at: Some(Utc::now()),
+        };
+
+        // The timer fires, but the pause is processed before its expiry
+        let _ = send_host_command(&mut game, token.clone(), HostCommand::Pause).await;
+        game.process_message(GameMessage::PostDeliveryPeriodEnded(
+            DeliveryPeriodId::from(1),
+        ))
+        .await;
+        assert!(game.game.state.is_paused());
+
+        let _ = send_host_command(&mut game, token, HostCommand::Resume).await;
+
+        let Ok(Some(message)) = tokio::time::timeout(Duration::from_secs(1), game.rx.recv()).await
+        else {
+            unreachable!("The post delivery timer should have been re-armed on resume")
+        };
+        game.process_message(message).await;
+        assert!(matches!(
+            game.game.state,
+            GameState::Running { period, .. } if period == DeliveryPeriodId::from(2)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_kicked_player_is_notified_and_loses_their_stack() {
+        let (mut game, mut comms) = build_game_actor();
+        let player = register_player(&mut game, "p1").await;
+        let _ = register_player(&mut game, "p2").await;
+        let token = game.config.host_token.clone();
+        let stack_token = game.stacks_cancellation_tokens[&player].clone();
+
+        let result = send_host_command(
+            &mut game,
+            token,
+            HostCommand::KickPlayer(PlayerName::from("p1")),
+        )
+        .await;
+
+        assert_eq!(result, Ok(HostCommandOutcome::PlayerKicked(player.clone())));
+        assert!(!game.stacks_contexts.contains_key(&player));
+        assert!(!game.stacks_cancellation_tokens.contains_key(&player));
+        assert!(stack_token.is_cancelled());
+        assert!(
+            !game
+                .cache
+                .players_readiness
+                .contains_key(&PlayerName::from("p1"))
+        );
+        loop {
+            let (to, message) = comms.rx_player.recv().await.unwrap();
+            if let PlayerMessage::Kicked = message {
+                assert_eq!(to, player);
+                break;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_kicked_player_is_purged_from_the_game() {
+        let (mut game, _comms) = build_game_actor();
+        let context = game.get_context();
+        let player = register_player(&mut game, "p1").await;
+        let other = register_player(&mut game, "p2").await;
+        for id in [&player, &other] {
+            game.cache.players_scores.insert(id.clone(), HashMap::new());
+            game.cache
+                .players_detailed_scores
+                .insert(id.clone(), HashMap::new());
+            game.cache.players_shocks.insert(id.clone(), HashMap::new());
+        }
+        let token = game.config.host_token.clone();
+
+        let _ = send_host_command(
+            &mut game,
+            token,
+            HostCommand::KickPlayer(PlayerName::from("p1")),
+        )
+        .await;
+
+        assert_eq!(
+            game.cache.players_id_to_name.get(&player),
+            Some(&PlayerName::from("p1"))
+        );
+        assert!(game.cache.kicked_players.contains(&player));
+        assert!(!game.cache.players_scores.contains_key(&player));
+        assert!(!game.cache.players_detailed_scores.contains_key(&player));
+        assert!(!game.cache.players_shocks.contains_key(&player));
+        assert!(game.cache.players_scores.contains_key(&other));
+        assert_eq!(
+            *context.players_rx.borrow(),
+            vec![(other, PlayerName::from("p2"))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_host_can_pause_and_resume_a_running_game() {
+        let (mut game, comms) = build_game_actor();
+        let _ = register_player(&mut game, "p1").await;
+        let token = game.config.host_token.clone();
+        let _ = send_host_command(&mut game, token.clone(), HostCommand::ForceStartPeriod).await;
+
+        let result = send_host_command(&mut game, token.clone(), HostCommand::Pause).await;
+
+        assert_eq!(result, Ok(HostCommandOutcome::Applied));
+        assert!(comms.state_watch_rx.borrow().is_paused());
+        assert_eq!(*game.deadline.borrow(), None);
+
+        let result = send_host_command(&mut game, token, HostCommand::Resume).await;
+
+        assert_eq!(result, Ok(HostCommandOutcome::Applied));
+        assert!(matches!(
+            *comms.state_watch_rx.borrow(),
+            GameState::Running { period, .. } if period == DeliveryPeriodId::from(1)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_resume_replaces_the_post_delivery_waiter() {
+        let (mut game, _) = build_game_actor();
+        let _ = register_player(&mut game, "p1").await;
+        let token = game.config.host_token.clone();
+        let period = DeliveryPeriodId::from(1);
+        game.game.state = GameState::PostDelivery {
+            period,
+            end_at: Some(Utc::now() + Duration::from_secs(60)),
+        };
+        game.wait_for_post_delivery_end(period);
+        let first_waiter = game.post_delivery_waiter.clone().unwrap();
+
+        let _ = send_host_command(&mut game, token.clone(), HostCommand::Pause).await;
+        let _ = send_host_command(&mut game, token, HostCommand::Resume).await;
+        tokio::task::yield_now().await;
+
+        assert!(first_waiter.is_finished());
+        assert!(!game.post_delivery_waiter.unwrap().is_finished());
     }
 }
 
@@ -1170,3 +2559,304 @@ mod test_rankings_mapping {
         assert!(map_rankings_to_player_name(rankings, &players_mapping).is_empty());
     }
 }
+
+#[cfg(test)]
+mod test_game_actor_persistence {
+    use std::{collections::HashMap, path::PathBuf};
+
+    use tokio::sync::{oneshot, watch};
+    use tokio_util::sync::CancellationToken;
+
+    use crate::{
+        game::{
+            GameMessage, GameState, GetPreviousScoresResult, HostCommand, JoinCode,
+            RegisterPlayerResponse,
+            delivery_period::DeliveryPeriodId,
+            infra::{
+                archive::ArchivedGame,
+                event_log::{GameLog, GameLogEvent},
+                persistence::{GameStore, PersistedGameState, SNAPSHOT_VERSION},
+            },
+            scores::PlayerScore,
+        },
+        market::{MarketContext, MarketState},
+        player::{PlayerId, PlayerName},
+        utils::units::Money,
+    };
+
+    use super::{
+        GameActor,
+        test_utils::{MockMarket, MockPlayerConnections, build_game_actor},
+    };
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("parcelec-games-{}", uuid::Uuid::new_v4()))
+    }
+
+    async fn register_player(
+        game: &mut GameActor<MockMarket, MockPlayerConnections>,
+        name: &str,
+    ) -> PlayerId {
+        let (tx_back, rx) = oneshot::channel();
+        game.process_message(GameMessage::RegisterPlayer {
+            name: PlayerName::from(name),
+            team_secret: None,
+            tx_back,
+        })
+        .await;
+        let Ok(RegisterPlayerResponse::Success { id, .. }) = rx.await else {
+            unreachable!("Should have registered player")
+        };
+        id
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_saved_when_player_joins() {
+        let dir = temp_dir();
+        let (mut game, _) = build_game_actor();
+        game.store = Some(GameStore::new(&dir));
+
+        let player = register_player(&mut game, "p1").await;
+
+        let snapshots = GameStore::new(&dir).load_all();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].config.id, game.config.id);
+        assert_eq!(snapshots[0].state, PersistedGameState::Open);
+        assert_eq!(snapshots[0].players[0].id, player);
+        assert_eq!(snapshots[0].players[0].name, PlayerName::from("p1"));
+        assert!(snapshots[0].stacks.contains_key(&player));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_no_snapshot_when_a_player_is_ready() {
+        let dir = temp_dir();
+        let (mut game, _) = build_game_actor();
+        game.store = Some(GameStore::new(&dir));
+        let player = register_player(&mut game, "p1").await;
+        let _ = register_player(&mut game, "p2").await;
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        game.process_message(GameMessage::PlayerIsReady(player))
+            .await;
+
+        assert!(GameStore::new(&dir).load_all().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_keeps_kicked_players_names() {
+        let dir = temp_dir();
+        let (mut game, _) = build_game_actor();
+        game.store = Some(GameStore::new(&dir));
+        let player = register_player(&mut game, "p1").await;
+        let other = register_player(&mut game, "p2").await;
+        let (tx_back, _rx) = oneshot::channel();
+        game.process_message(GameMessage::HostCommand {
+            token: game.config.host_token.clone(),
+            command: HostCommand::KickPlayer(PlayerName::from("p1")),
+            tx_back,
+        })
+        .await;
+
+        let snapshot = GameStore::new(&dir).load_all().pop().unwrap();
+        let kicked = snapshot
+            .players
+            .iter()
+            .map(|player| (player.id.clone(), player.kicked))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(
+            kicked,
+            HashMap::from([(player.clone(), true), (other, false)])
+        );
+
+        let (connections, ..) = MockPlayerConnections::new();
+        let (state_tx, state_rx) = watch::channel(MarketState::Closed);
+        let (context, stacks) = GameActor::restore(
+            snapshot,
+            connections,
+            MarketContext {
+                service: MockMarket { state_tx },
+                state_rx,
+            },
+            None,
+            None,
+            CancellationToken::new(),
+        );
+        assert!(!stacks.contains_key(&player));
+        assert_eq!(context.players_rx.borrow().len(), 1);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_version_1_snapshot_restored_with_an_unknown_host_token() {
+        let dir = temp_dir();
+        let (mut game, _) = build_game_actor();
+        game.store = Some(GameStore::new(&dir));
+        let _ = register_player(&mut game, "p1").await;
+        let path = dir.join(format!("{}.json", game.config.id));
+        let mut snapshot: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        snapshot["version"] = serde_json::json!(1);
+        snapshot["config"]
+            .as_object_mut()
+            .unwrap()
+            .remove("host_token");
+        std::fs::write(&path, snapshot.to_string()).unwrap();
+
+        let snapshot = GameStore::load(&path).unwrap();
+
+        assert_eq!(snapshot.version, SNAPSHOT_VERSION);
+        assert_eq!(snapshot.config.id, game.config.id);
+        assert_ne!(snapshot.config.host_token, game.config.host_token);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    async fn get_archive(
+        game: &mut GameActor<MockMarket, MockPlayerConnections>,
+    ) -> Option<ArchivedGame> {
+        let (tx_back, rx) = oneshot::channel();
+        game.process_message(GameMessage::GetArchive { tx_back })
+            .await;
+        rx.await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_no_archive_before_game_ended() {
+        let (mut game, _) = build_game_actor();
+        let _ = register_player(&mut game, "p1").await;
+
+        assert_eq!(get_archive(&mut game).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_archive_of_ended_game() {
+        let (mut game, _) = build_game_actor();
+        game.config.join_code = Some(JoinCode::default());
+        let player = register_player(&mut game, "p1").await;
+        let score = PlayerScore {
+            pnl: Money::from(100),
+            ..PlayerScore::default()
+        };
+        game.cache.players_scores.insert(
+            player,
+            HashMap::from_iter([(DeliveryPeriodId::from(1), score.clone())]),
+        );
+        game.cache.state = GameState::Ended(DeliveryPeriodId::from(1));
+
+        let archived = get_archive(&mut game).await.unwrap();
+
+        assert_eq!(archived.host_token, game.config.host_token);
+        assert_eq!(archived.join_code, game.config.join_code);
+        assert_eq!(archived.archive.id, game.config.id);
+        assert_eq!(archived.archive.players, vec![PlayerName::from("p1")]);
+        assert_eq!(archived.archive.periods.len(), 1);
+        assert_eq!(
+            archived.archive.periods[0].players[&PlayerName::from("p1")].score,
+            Some(score)
+        );
+        assert_eq!(archived.archive.rankings.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_player_and_stack_logged_when_player_joins() {
+        let (mut game, _) = build_game_actor();
+        let (log, mut log_rx) = GameLog::channel();
+        game.log = Some(log);
+
+        let player = register_player(&mut game, "p1").await;
+
+        let mut events = [
+            log_rx.recv().await.unwrap().event,
+            log_rx.recv().await.unwrap().event,
+        ];
+        events.sort_by_key(|event| matches!(event, GameLogEvent::StackCreated { .. }));
+        assert!(matches!(
+            &events[0],
+            GameLogEvent::PlayerJoined { player: id, name }
+                if *id == player && *name == PlayerName::from("p1")
+        ));
+        assert!(matches!(
+            &events[1],
+            GameLogEvent::StackCreated { player: id, .. } if *id == player
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_no_snapshot_while_delivery_period_is_running() {
+        let dir = temp_dir();
+        let (mut game, _) = build_game_actor();
+        game.store = Some(GameStore::new(&dir));
+        game.game.state = GameState::Running {
+            period: DeliveryPeriodId::from(1),
+            end_at: None,
+        };
+
+        game.save_snapshot().await;
+
+        assert!(GameStore::new(&dir).load_all().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_restore_game_after_delivery_period() {
+        let dir = temp_dir();
+        let (mut game, _) = build_game_actor();
+        game.store = Some(GameStore::new(&dir));
+        let player = register_player(&mut game, "p1").await;
+        game.game.state = GameState::PostDelivery {
+            period: DeliveryPeriodId::from(1),
+            end_at: None,
+        };
+        let score = PlayerScore {
+            pnl: Money::from(100),
+            ..PlayerScore::default()
+        };
+        game.cache.players_scores.insert(
+            player.clone(),
+            HashMap::from_iter([(DeliveryPeriodId::from(1), score.clone())]),
+        );
+        game.save_snapshot().await;
+        let snapshot = GameStore::new(&dir).load_all().pop().unwrap();
+
+        let (connections, ..) = MockPlayerConnections::new();
+        let (state_tx, state_rx) = watch::channel(MarketState::Closed);
+        let (context, stacks) = GameActor::restore(
+            snapshot,
+            connections,
+            MarketContext {
+                service: MockMarket { state_tx },
+                state_rx,
+            },
+            None,
+            None,
+            CancellationToken::new(),
+        );
+
+        assert_eq!(context.id, game.config.id);
+        assert_eq!(
+            *context.state_rx.borrow(),
+            GameState::PostDelivery {
+                period: DeliveryPeriodId::from(1),
+                end_at: None
+            }
+        );
+        assert!(stacks.contains_key(&player));
+        let (tx_back, rx) = oneshot::channel();
+        context
+            .tx
+            .send(GameMessage::GetScores {
+                player_id: player,
+                tx_back,
+            })
+            .await
+            .unwrap();
+        let Ok(GetPreviousScoresResult::PlayerScores { scores, .. }) = rx.await else {
+            unreachable!("Should have returned the player's scores")
+        };
+        assert_eq!(scores.get(&DeliveryPeriodId::from(1)), Some(&score));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
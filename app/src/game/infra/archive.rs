@@ -0,0 +1,358 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    path::PathBuf,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    forecast::shock::ForecastShock,
+    game::{
+        GameId, GameName, HostToken, JoinCode,
+        delivery_period::DeliveryPeriodId,
+        scores::{PlayerDetailedScore, PlayerScore},
+    },
+    market::{
+        Direction,
+        order_book::{Order, Trade},
+    },
+    plants::{
+        PlantId,
+        infra::{PlantPeriod, StackTimeline},
+    },
+    player::{PlayerEmissionsResultView, PlayerId, PlayerName, PlayerResultView},
+    utils::units::{Energy, EnergyCost},
+};
+
+/// Complete timeline of a finished game, kept once the game's actors are terminated so that the
+/// game can be replayed and debriefed. Players are only identified by their names, as their ids are
+/// used to authenticate them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameArchive {
+    pub id: GameId,
+    pub name: GameName,
+    pub ended_at: DateTime<Utc>,
+    pub players: Vec<PlayerName>,
+    pub periods: Vec<ArchivedPeriod>,
+    pub rankings: Vec<PlayerResultView>,
+    pub emissions_rankings: Option<Vec<PlayerEmissionsResultView>>,
+}
+
+/// Archive of a game along with the secrets guarding access to it, as kept by the server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedGame {
+    #[serde(flatten)]
+    pub archive: GameArchive,
+    /// Secret of the game's host, archives saved before it was kept get one nobody knows
+    #[serde(default)]
+    pub host_token: HostToken,
+    /// Code of the game if it was private
+    #[serde(default)]
+    pub join_code: Option<JoinCode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedPeriod {
+    pub period: DeliveryPeriodId,
+    /// Orders left unmatched when the market closed
+    pub order_book: Vec<ArchivedOrder>,
+    pub trades: Vec<ArchivedTrade>,
+    pub players: HashMap<PlayerName, ArchivedPlayerPeriod>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedOrder {
+    pub owner: PlayerName,
+    pub direction: Direction,
+    pub price: EnergyCost,
+    pub volume: Energy,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedTrade {
+    pub buyer: PlayerName,
+    pub seller: PlayerName,
+    pub price: EnergyCost,
+    pub volume: Energy,
+    pub execution_time: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct ArchivedPlayerPeriod {
+    pub plants: HashMap<PlantId, PlantPeriod>,
+    pub score: Option<PlayerScore>,
+    pub detailed_score: Option<PlayerDetailedScore>,
+    pub shocks: Vec<ForecastShock>,
+}
+
+/// Data of a finished game, gathered from its actors, from which its archive is built.
+pub struct GameRecords<'a> {
+    pub players: &'a HashMap<PlayerId, PlayerName>,
+    pub last_period: DeliveryPeriodId,
+    pub trades: HashMap<DeliveryPeriodId, Vec<Trade>>,
+    pub closing_orders: HashMap<DeliveryPeriodId, Vec<Order>>,
+    pub stacks: HashMap<PlayerId, StackTimeline>,
+    pub scores: &'a HashMap<PlayerId, HashMap<DeliveryPeriodId, PlayerScore>>,
+    pub detailed_scores: &'a HashMap<PlayerId, HashMap<DeliveryPeriodId, PlayerDetailedScore>>,
+    pub shocks: &'a HashMap<PlayerId, HashMap<DeliveryPeriodId, Vec<ForecastShock>>>,
+}
+
+impl GameRecords<'_> {
+    fn name(&self, player: &PlayerId) -> Option<PlayerName> {
+        self.players.get(player).cloned()
+    }
+
+    fn period(&mut self, period: DeliveryPeriodId) -> ArchivedPeriod {
+        let order_book = self
+            .closing_orders
+            .remove(&period)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|order| {
+                Some(ArchivedOrder {
+                    owner: self.name(&order.owner)?,
+                    direction: order.direction,
+                    price: order.price,
+                    volume: order.volume,
+                    timestamp: order.timestamp,
+                })
+            })
+            .collect();
+        let trades = self
+            .trades
+            .remove(&period)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|trade| {
+                Some(ArchivedTrade {
+                    buyer: self.name(&trade.buyer)?,
+                    seller: self.name(&trade.seller)?,
+                    price: trade.price,
+                    volume: trade.volume,
+                    execution_time: trade.execution_time,
+                })
+            })
+            .collect();
+        let players = self
+            .players
+            .iter()
+            .map(|(player, name)| {
+                let archived = ArchivedPlayerPeriod {
+                    plants: self
+                        .stacks
+                        .get_mut(player)
+                        .and_then(|timeline| timeline.remove(&period))
+                        .unwrap_or_default(),
+                    score: self
+                        .scores
+                        .get(player)
+                        .and_then(|scores| scores.get(&period))
+                        .cloned(),
+                    detailed_score: self
+                        .detailed_scores
+                        .get(player)
+                        .and_then(|scores| scores.get(&period))
+                        .cloned(),
+                    shocks: self
+                        .shocks
+                        .get(player)
+                        .and_then(|shocks| shocks.get(&period))
+                        .cloned()
+                        .unwrap_or_default(),
+                };
+                (name.clone(), archived)
+            })
+            .collect();
+
+        ArchivedPeriod {
+            period,
+            order_book,
+            trades,
+            players,
+        }
+    }
+}
+
+impl GameArchive {
+    pub fn build(
+        id: GameId,
+        name: GameName,
+        mut records: GameRecords,
+        rankings: Vec<PlayerResultView>,
+        emissions_rankings: Option<Vec<PlayerEmissionsResultView>>,
+    ) -> GameArchive {
+        let periods = (1..=usize::from(records.last_period))
+            .map(|period| records.period(DeliveryPeriodId::from(period)))
+            .collect();
+        let mut players = records.players.values().cloned().collect::<Vec<_>>();
+        players.sort_by_key(|player| player.to_string());
+
+        GameArchive {
+            id,
+            name,
+            ended_at: Utc::now(),
+            players,
+            periods,
+            rankings,
+            emissions_rankings,
+        }
+    }
+}
+
+#[derive(Debug, Clone, thiserror::Error, PartialEq)]
+pub enum ArchiveError {
+    #[error("Unable to access game archive: {0}")]
+    Io(String),
+    #[error("Unable to parse game archive: {0}")]
+    Parse(String),
+}
+
+/// Local directory holding the archive of each finished game, named after the game id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchiveStore {
+    path: PathBuf,
+}
+
+impl ArchiveStore {
+    pub fn new(path: impl Into<PathBuf>) -> ArchiveStore {
+        ArchiveStore { path: path.into() }
+    }
+
+    fn archive_path(&self, game: &GameId) -> PathBuf {
+        self.path.join(format!("{game}.json"))
+    }
+
+    pub async fn save(&self, archived: &ArchivedGame) -> Result<(), ArchiveError> {
+        let content =
+            serde_json::to_vec(archived).map_err(|err| ArchiveError::Parse(err.to_string()))?;
+        tokio::fs::create_dir_all(&self.path)
+            .await
+            .map_err(|err| ArchiveError::Io(err.to_string()))?;
+        tokio::fs::write(self.archive_path(&archived.archive.id), content)
+            .await
+            .map_err(|err| ArchiveError::Io(err.to_string()))
+    }
+
+    pub async fn load(&self, game: &GameId) -> Result<ArchivedGame, ArchiveError> {
+        let content = tokio::fs::read_to_string(self.archive_path(game))
+            .await
+            .map_err(|err| ArchiveError::Io(err.to_string()))?;
+        serde_json::from_str(&content).map_err(|err| ArchiveError::Parse(err.to_string()))
+    }
+}
+
+/// Number of archives kept in memory
+const CACHED_ARCHIVES: usize = 32;
+
+/// Archives of the finished games. Only the most recent ones are kept in memory, the others are
+/// read from the store when requested.
+#[derive(Debug)]
+pub struct Archives {
+    store: Option<ArchiveStore>,
+    recent: VecDeque<ArchivedGame>,
+}
+
+impl Archives {
+    pub fn new(store: Option<ArchiveStore>) -> Archives {
+        Archives {
+            store,
+            recent: VecDeque::new(),
+        }
+    }
+
+    pub fn store(&self) -> Option<&ArchiveStore> {
+        self.store.as_ref()
+    }
+
+    /// Keep an archive in memory, dropping the oldest one if there are too many.
+    pub fn insert(&mut self, archived: ArchivedGame) {
+        self.recent
+            .retain(|recent| recent.archive.id != archived.archive.id);
+        self.recent.push_front(archived);
+        self.recent.truncate(CACHED_ARCHIVES);
+    }
+
+    pub fn cached(&self, game: &GameId) -> Option<&ArchivedGame> {
+        self.recent
+            .iter()
+            .find(|archived| archived.archive.id == *game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use crate::{
+        game::{GameId, GameName, HostToken},
+        player::PlayerName,
+    };
+
+    use super::{ArchiveStore, ArchivedGame, Archives, CACHED_ARCHIVES, GameArchive};
+
+    fn archived_game() -> ArchivedGame {
+        ArchivedGame {
+            archive: GameArchive {
+                id: GameId::default(),
+                name: GameName::from("game".to_string()),
+                ended_at: Utc::now(),
+                players: vec![PlayerName::from("toto")],
+                periods: Vec::new(),
+                rankings: Vec::new(),
+                emissions_rankings: None,
+            },
+            host_token: HostToken::default(),
+            join_code: None,
+        }
+    }
+
+    #[test]
+    fn test_only_recent_archives_are_cached() {
+        let mut archives = Archives::new(None);
+        let oldest = archived_game();
+        archives.insert(oldest.clone());
+        for _ in 0..CACHED_ARCHIVES {
+            archives.insert(archived_game());
+        }
+
+        assert_eq!(archives.cached(&oldest.archive.id), None);
+        archives.insert(oldest.clone());
+        assert_eq!(archives.cached(&oldest.archive.id), Some(&oldest));
+    }
+
+    #[tokio::test]
+    async fn test_load_saved_archive() {
+        let dir = std::env::temp_dir().join(format!("parcelec-archives-{}", uuid::Uuid::new_v4()));
+        let store = ArchiveStore::new(&dir);
+        let archived = archived_game();
+
+        store.save(&archived).await.unwrap();
+
+        assert_eq!(store.load(&archived.archive.id).await, Ok(archived));
+        assert!(store.load(&GameId::default()).await.is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_archives_saved_without_access_secrets_are_loaded() {
+        let dir = std::env::temp_dir().join(format!("parcelec-archives-{}", uuid::Uuid::new_v4()));
+        let store = ArchiveStore::new(&dir);
+        let archived = archived_game();
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join(format!("{}.json", archived.archive.id)),
+            serde_json::to_vec(&archived.archive).unwrap(),
+        )
+        .unwrap();
+
+        let loaded = store.load(&archived.archive.id).await.unwrap();
+
+        assert_eq!(loaded.archive, archived.archive);
+        assert_eq!(loaded.join_code, None);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
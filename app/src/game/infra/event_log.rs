@@ -0,0 +1,571 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use tokio::{
+    io::AsyncWriteExt,
+    sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel},
+};
+
+use crate::{
+    forecast::shock::ForecastShock,
+    game::{
+        Game, GameId, GameName, GameState, PhaseDurations, Player,
+        delivery_period::DeliveryPeriodId, scores::PlayerScore,
+    },
+    market::order_book::{Order, OrderBook, Trade},
+    plants::{
+        PlantId, StackPlants, investment::PlantInvestment, persistence::PersistedStackPlants,
+    },
+    player::{PlayerId, PlayerName},
+    utils::units::Power,
+};
+
+/// Phase durations of a created game, logs written before the phases had their own durations
+/// hold a single duration for all of them.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LoggedDurations {
+    Phases(PhaseDurations),
+    Uniform(Option<Duration>),
+}
+
+fn logged_durations<'de, D>(deserializer: D) -> Result<PhaseDurations, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match LoggedDurations::deserialize(deserializer)? {
+        LoggedDurations::Phases(durations) => durations,
+        LoggedDurations::Uniform(duration) => PhaseDurations::uniform(duration),
+    })
+}
+
+/// Domain event of a game, recorded by the game, market and stack actors as it happens.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum GameLogEvent {
+    GameCreated {
+        name: GameName,
+        number_of_delivery_periods: usize,
+        #[serde(
+            alias = "delivery_period_duration",
+            deserialize_with = "logged_durations"
+        )]
+        durations: PhaseDurations,
+    },
+    PlayerJoined {
+        player: PlayerId,
+        name: PlayerName,
+    },
+    PlayerKicked {
+        player: PlayerId,
+    },
+    PeriodOpened {
+        period: DeliveryPeriodId,
+    },
+    PeriodClosed {
+        period: DeliveryPeriodId,
+    },
+    GameEnded {
+        period: DeliveryPeriodId,
+    },
+    ScoresComputed {
+        period: DeliveryPeriodId,
+        scores: HashMap<PlayerId, PlayerScore>,
+    },
+    OrderPlaced {
+        order: Order,
+    },
+    OrderDeleted {
+        order_id: String,
+    },
+    TradeExecuted {
+        trade: Trade,
+    },
+    MarketClosed {
+        period: DeliveryPeriodId,
+    },
+    /// Complete state of a stack's plants, recorded each time a stack actor starts, including
+    /// after a game is restored from a snapshot
+    StackCreated {
+        player: PlayerId,
+        plants: PersistedStackPlants,
+    },
+    SetpointProgrammed {
+        player: PlayerId,
+        plant: PlantId,
+        setpoint: Power,
+    },
+    StackClosed {
+        player: PlayerId,
+        period: DeliveryPeriodId,
+    },
+    PlantInvested {
+        player: PlayerId,
+        plant: PlantId,
+        investment: PlantInvestment,
+        last_period: DeliveryPeriodId,
+    },
+    PlantRetired {
+        player: PlayerId,
+        plant: PlantId,
+    },
+    ShockApplied {
+        player: PlayerId,
+        shock: ForecastShock,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameLogEntry {
+    pub at: DateTime<Utc>,
+    pub event: GameLogEvent,
+}
+
+/// Handle to the append-only log of a game. Recording an event never blocks the calling actor,
+/// entries are written in the order they are recorded by a dedicated task.
+#[derive(Debug, Clone)]
+pub struct GameLog {
+    tx: UnboundedSender<GameLogEntry>,
+}
+
+impl GameLog {
+    /// Open the log of a game as a JSON lines file in `dir`, appending to it if it already exists,
+    /// e.g. when a game is restored from a snapshot.
+    pub fn open(dir: &Path, game: &GameId) -> GameLog {
+        let (tx, rx) = unbounded_channel();
+        let path = log_path(dir, game);
+        tokio::spawn(async move {
+            write_entries(&path, rx).await;
+        });
+        GameLog { tx }
+    }
+
+    #[cfg(test)]
+    pub fn channel() -> (GameLog, UnboundedReceiver<GameLogEntry>) {
+        let (tx, rx) = unbounded_channel();
+        (GameLog { tx }, rx)
+    }
+
+    pub fn record(&self, event: GameLogEvent) {
+        let _ = self.tx.send(GameLogEntry {
+            at: Utc::now(),
+            event,
+        });
+    }
+}
+
+pub fn log_path(dir: &Path, game: &GameId) -> PathBuf {
+    dir.join(format!("{game}.jsonl"))
+}
+
+async fn write_entries(path: &Path, mut rx: UnboundedReceiver<GameLogEntry>) {
+    if let Some(dir) = path.parent()
+        && let Err(err) = tokio::fs::create_dir_all(dir).await
+    {
+        tracing::warn!("Unable to create game log directory {dir:?}: {err}");
+        return;
+    }
+    let mut file = match tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await
+    {
+        Ok(file) => file,
+        Err(err) => {
+            tracing::warn!("Unable to open game log {path:?}: {err}");
+            return;
+        }
+    };
+
+    while let Some(entry) = rx.recv().await {
+        let Ok(mut line) = serde_json::to_vec(&entry) else {
+            tracing::warn!("Unable to serialise game log entry {entry:?}");
+            continue;
+        };
+        line.push(b'\n');
+        if let Err(err) = file.write_all(&line).await {
+            tracing::warn!("Unable to append to game log {path:?}: {err}");
+        }
+    }
+    let _ = file.flush().await;
+}
+
+#[derive(Debug, Clone, thiserror::Error, PartialEq)]
+pub enum GameLogError {
+    #[error("Unable to read game log: {0}")]
+    Io(String),
+    #[error("Invalid game log entry at line {line}: {reason}")]
+    Parse { line: usize, reason: String },
+    #[error("Game log does not start with the game creation")]
+    MissingGameCreated,
+    #[error("Game log references unknown player {0}")]
+    UnknownPlayer(PlayerId),
+}
+
+/// Read all the entries of a game log file.
+pub fn read_log(path: &Path) -> Result<Vec<GameLogEntry>, GameLogError> {
+    let content = fs::read_to_string(path).map_err(|err| GameLogError::Io(err.to_string()))?;
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|err| GameLogError::Parse {
+                line: index + 1,
+                reason: err.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// State of a game rebuilt from its log.
+pub struct ReplayedGame {
+    pub game: Game,
+    /// Orders of the delivery period being played when the log ends, if any
+    pub order_book: OrderBook,
+    pub trades: HashMap<DeliveryPeriodId, Vec<Trade>>,
+    pub stacks: HashMap<PlayerId, StackPlants>,
+    pub scores: HashMap<PlayerId, HashMap<DeliveryPeriodId, PlayerScore>>,
+}
+
+/// Rebuild the state of a game by applying the events of its log in order. Trades are matched
+/// again from the orders, and stacks are dispatched again from their setpoints, so that the
+/// replayed state can be compared with the recorded trades and scores.
+pub fn replay(
+    entries: impl IntoIterator<Item = GameLogEntry>,
+) -> Result<ReplayedGame, GameLogError> {
+    let mut entries = entries.into_iter();
+    let Some(GameLogEntry {
+        event:
+            GameLogEvent::GameCreated {
+                number_of_delivery_periods,
+                durations,
+                ..
+            },
+        ..
+    }) = entries.next()
+    else {
+        return Err(GameLogError::MissingGameCreated);
+    };
+    let mut replayed = ReplayedGame {
+        game: Game::init(
+            DeliveryPeriodId::from(number_of_delivery_periods),
+            durations,
+        ),
+        order_book: OrderBook::new(),
+        trades: HashMap::new(),
+        stacks: HashMap::new(),
+        scores: HashMap::new(),
+    };
+
+    for GameLogEntry { event, .. } in entries {
+        replayed.apply(event)?;
+    }
+    Ok(replayed)
+}
+
+impl ReplayedGame {
+    fn stack(&mut self, player: &PlayerId) -> Result<&mut StackPlants, GameLogError> {
+        self.stacks
+            .get_mut(player)
+            .ok_or_else(|| GameLogError::UnknownPlayer(player.clone()))
+    }
+
+    fn apply(&mut self, event: GameLogEvent) -> Result<(), GameLogError> {
+        match event {
+            GameLogEvent::GameCreated { .. } | GameLogEvent::TradeExecuted { .. } => {}
+            GameLogEvent::PlayerJoined { player, name } => {
+                self.game.players.push(Player {
+                    id: player,
+                    name,
+                    ready: false,
+                    team_secret: None,
+                });
+            }
+            GameLogEvent::PlayerKicked { player } => {
+                self.game.players.retain(|p| p.id != player);
+            }
+            GameLogEvent::PeriodOpened { period } => {
+                self.game.reset_players_readiness();
+                self.game.state = GameState::Running {
+                    period,
+                    end_at: None,
+                };
+            }
+            GameLogEvent::PeriodClosed { period } => {
+                self.game.reset_players_readiness();
+                self.game.state = GameState::PostDelivery {
+                    period,
+                    end_at: None,
+                };
+            }
+            GameLogEvent::GameEnded { period } => {
+                self.game.state = GameState::Ended(period);
+            }
+            GameLogEvent::ScoresComputed { period, scores } => {
+                for (player, score) in scores {
+                    self.scores.entry(player).or_default().insert(period, score);
+                }
+            }
+            GameLogEvent::OrderPlaced { order } => {
+                self.order_book.register_order(order);
+            }
+            GameLogEvent::OrderDeleted { order_id } => {
+                self.order_book.remove_offer(order_id);
+            }
+            GameLogEvent::MarketClosed { period } => {
+                self.trades.insert(period, self.order_book.drain());
+            }
+            GameLogEvent::StackCreated { player, plants } => {
+                self.stacks.insert(player, StackPlants::restore(plants));
+            }
+            GameLogEvent::SetpointProgrammed {
+                player,
+                plant,
+                setpoint,
+            } => {
+                self.stack(&player)?.program_setpoint(&plant, setpoint);
+            }
+            GameLogEvent::StackClosed { player, .. } => {
+                self.stack(&player)?.dispatch_plants();
+            }
+            GameLogEvent::PlantInvested {
+                player,
+                plant,
+                investment,
+                last_period,
+            } => {
+                self.stack(&player)?
+                    .invest_as(plant, investment, last_period);
+            }
+            GameLogEvent::PlantRetired { player, plant } => {
+                let _ = self.stack(&player)?.retire_plant(&plant);
+            }
+            GameLogEvent::ShockApplied { player, shock } => {
+                self.stack(&player)?.apply_shock(&shock);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::Utc;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    use crate::{
+        game::{GameId, GameName, GameState, PhaseDurations, delivery_period::DeliveryPeriodId},
+        market::{Direction, order_book::Order},
+        plants::{PlantOutput, StackPlants, technologies::gas_plant::GasPlant},
+        player::{PlayerId, PlayerName},
+        utils::units::{Energy, EnergyCost, Money, Power},
+    };
+
+    use super::{
+        GameLogEntry, GameLogError, GameLogEvent, log_path, read_log, replay, write_entries,
+    };
+
+    fn entry(event: GameLogEvent) -> GameLogEntry {
+        GameLogEntry {
+            at: Utc::now(),
+            event,
+        }
+    }
+
+    fn game_created() -> GameLogEvent {
+        GameLogEvent::GameCreated {
+            name: GameName::from("game".to_string()),
+            number_of_delivery_periods: 3,
+            durations: PhaseDurations::default(),
+        }
+    }
+
+    fn order(id: &str, direction: Direction, price: i32, owner: &PlayerId) -> GameLogEvent {
+        GameLogEvent::OrderPlaced {
+            order: Order {
+                id: id.to_string(),
+                direction,
+                price: EnergyCost::from(price),
+                volume: Energy::from(10),
+                timestamp: Utc::now(),
+                owner: owner.clone(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_log_appended_as_json_lines() {
+        let dir = std::env::temp_dir().join(format!("parcelec-logs-{}", uuid::Uuid::new_v4()));
+        let path = log_path(&dir, &GameId::default());
+
+        for event in [
+            game_created(),
+            GameLogEvent::PeriodOpened {
+                period: DeliveryPeriodId::from(1),
+            },
+        ] {
+            let (tx, rx) = unbounded_channel();
+            tx.send(entry(event)).unwrap();
+            drop(tx);
+            write_entries(&path, rx).await;
+        }
+
+        let entries = read_log(&path).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(matches!(entries[0].event, GameLogEvent::GameCreated { .. }));
+        assert!(matches!(
+            entries[1].event,
+            GameLogEvent::PeriodOpened { period } if period == DeliveryPeriodId::from(1)
+        ));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_replay_requires_game_creation() {
+        let result = replay([entry(GameLogEvent::PeriodOpened {
+            period: DeliveryPeriodId::from(1),
+        })]);
+
+        assert_eq!(result.err(), Some(GameLogError::MissingGameCreated));
+    }
+
+    #[test]
+    fn test_replay_restores_phase_durations() {
+        let durations = PhaseDurations {
+            running: Some(Duration::from_secs(180)),
+            post_delivery: Some(Duration::from_secs(30)),
+            schedule: vec![Duration::from_secs(300)],
+        };
+
+        let replayed = replay([entry(GameLogEvent::GameCreated {
+            name: GameName::from("game".to_string()),
+            number_of_delivery_periods: 3,
+            durations: durations.clone(),
+        })])
+        .unwrap();
+
+        assert_eq!(replayed.game.durations, durations);
+    }
+
+    #[test]
+    fn test_read_game_created_with_a_single_period_duration() {
+        let mut value = serde_json::to_value(entry(game_created())).unwrap();
+        let created = value["event"]["GameCreated"].as_object_mut().unwrap();
+        created.remove("durations");
+        created.insert(
+            "delivery_period_duration".to_string(),
+            serde_json::json!({"secs": 60, "nanos": 0}),
+        );
+
+        let entry: GameLogEntry = serde_json::from_value(value).unwrap();
+        let GameLogEvent::GameCreated { durations, .. } = entry.event else {
+            unreachable!("Should have read the game creation")
+        };
+        assert_eq!(
+            durations,
+            PhaseDurations::uniform(Some(Duration::from_secs(60)))
+        );
+    }
+
+    #[test]
+    fn test_replay_rejects_unknown_player() {
+        let player = PlayerId::from("toto");
+        let result = replay([
+            entry(game_created()),
+            entry(GameLogEvent::StackClosed {
+                player: player.clone(),
+                period: DeliveryPeriodId::from(1),
+            }),
+        ]);
+
+        assert_eq!(result.err(), Some(GameLogError::UnknownPlayer(player)));
+    }
+
+    #[test]
+    fn test_replay_rebuilds_game_market_and_stacks() {
+        let (buyer, seller) = (PlayerId::from("buyer"), PlayerId::from("seller"));
+        let mut stack = StackPlants::default();
+        let plant = stack.add_plant(
+            "gas".to_string(),
+            Box::new(GasPlant::new(EnergyCost::from(50), Power::from(500))),
+            &mut rand::rng(),
+        );
+        let events = vec![
+            game_created(),
+            GameLogEvent::PlayerJoined {
+                player: buyer.clone(),
+                name: PlayerName::from("buyer"),
+            },
+            GameLogEvent::PlayerJoined {
+                player: seller.clone(),
+                name: PlayerName::from("seller"),
+            },
+            GameLogEvent::StackCreated {
+                player: buyer.clone(),
+                plants: stack.persist(),
+            },
+            GameLogEvent::PeriodOpened {
+                period: DeliveryPeriodId::from(1),
+            },
+            order("bid", Direction::Buy, 60, &buyer),
+            order("offer", Direction::Sell, 55, &seller),
+            order("other bid", Direction::Buy, 40, &buyer),
+            GameLogEvent::OrderDeleted {
+                order_id: "other bid".to_string(),
+            },
+            GameLogEvent::SetpointProgrammed {
+                player: buyer.clone(),
+                plant: plant.clone(),
+                setpoint: Power::from(200),
+            },
+            GameLogEvent::MarketClosed {
+                period: DeliveryPeriodId::from(1),
+            },
+            GameLogEvent::StackClosed {
+                player: buyer.clone(),
+                period: DeliveryPeriodId::from(1),
+            },
+            GameLogEvent::PeriodClosed {
+                period: DeliveryPeriodId::from(1),
+            },
+            GameLogEvent::PeriodOpened {
+                period: DeliveryPeriodId::from(2),
+            },
+            order("late bid", Direction::Buy, 45, &buyer),
+        ];
+
+        let replayed = replay(events.into_iter().map(entry)).unwrap();
+
+        assert_eq!(
+            replayed.game.state,
+            GameState::Running {
+                period: DeliveryPeriodId::from(2),
+                end_at: None
+            }
+        );
+        assert_eq!(replayed.game.players.len(), 2);
+        let trades = &replayed.trades[&DeliveryPeriodId::from(1)];
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].buyer, buyer);
+        assert_eq!(trades[0].seller, seller);
+        assert_eq!(trades[0].price, EnergyCost::from(60));
+        assert_eq!(replayed.order_book.snapshot().bids.len(), 1);
+        assert_eq!(
+            replayed.stacks[&buyer].history()[&plant],
+            vec![PlantOutput {
+                setpoint: Power::from(200),
+                cost: Money::from(-10_000),
+            }]
+        );
+    }
+}
@@ -1,4 +1,8 @@
 pub mod actor;
+pub mod archive;
+pub mod event_log;
+pub mod persistence;
+pub mod results;
 pub mod stack_config;
 
 pub use actor::{GameActor, GameActorConfig};
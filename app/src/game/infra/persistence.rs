@@ -0,0 +1,219 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::{
+    forecast::shock::ForecastShock,
+    game::{
+        GameId, HostToken, TeamSecret,
+        delivery_period::DeliveryPeriodId,
+        scores::{PlayerDetailedScore, PlayerScore},
+    },
+    market::PersistedMarket,
+    plants::infra::PersistedStack,
+    player::{PlayerId, PlayerName},
+    utils::rng::GameRng,
+};
+
+use super::GameActorConfig;
+
+/// Current version of the snapshot format, older snapshots are migrated when loaded and snapshots
+/// of other versions are not restored.
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+/// State of a game between two delivery periods, the only states a game is snapshotted in.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PersistedGameState {
+    Open,
+    PostDelivery(DeliveryPeriodId),
+    Ended(DeliveryPeriodId),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PersistedPlayer {
+    pub id: PlayerId,
+    pub name: PlayerName,
+    #[serde(default)]
+    pub team_secret: Option<TeamSecret>,
+    /// Kicked players are only kept to name them in the game's archive
+    #[serde(default)]
+    pub kicked: bool,
+}
+
+/// Snapshot of a game and of its market and stacks, taken at a delivery period boundary so that
+/// the game can be restored after a server restart. Players keep their ids, so that their cookies
+/// are still valid once the game is restored.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameSnapshot {
+    pub version: u32,
+    pub saved_at: DateTime<Utc>,
+    pub config: GameActorConfig,
+    pub state: PersistedGameState,
+    pub players: Vec<PersistedPlayer>,
+    pub players_scores: HashMap<PlayerId, HashMap<DeliveryPeriodId, PlayerScore>>,
+    pub players_detailed_scores: HashMap<PlayerId, HashMap<DeliveryPeriodId, PlayerDetailedScore>>,
+    pub players_shocks: HashMap<PlayerId, HashMap<DeliveryPeriodId, Vec<ForecastShock>>>,
+    pub rng: GameRng,
+    pub market: PersistedMarket,
+    pub stacks: HashMap<PlayerId, PersistedStack>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SnapshotVersion {
+    version: u32,
+}
+
+#[derive(Debug, Clone, thiserror::Error, PartialEq)]
+pub enum PersistenceError {
+    #[error("Unable to access game snapshot: {0}")]
+    Io(String),
+    #[error("Unable to parse game snapshot: {0}")]
+    Parse(String),
+    #[error("Unsupported snapshot version {0}, expected version {SNAPSHOT_VERSION}")]
+    UnsupportedVersion(u32),
+}
+
+/// Local directory holding a JSON snapshot per game, named after the game id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameStore {
+    path: PathBuf,
+}
+
+impl GameStore {
+    pub fn new(path: impl Into<PathBuf>) -> GameStore {
+        GameStore { path: path.into() }
+    }
+
+    fn snapshot_path(&self, game: &GameId) -> PathBuf {
+        self.path.join(format!("{game}.json"))
+    }
+
+    /// Save the snapshot of a game, replacing the previous one. The snapshot is written to a
+    /// temporary file first so that a crash while saving never leaves a truncated snapshot.
+    pub async fn save(&self, snapshot: &GameSnapshot) -> Result<(), PersistenceError> {
+        let content =
+            serde_json::to_vec(snapshot).map_err(|err| PersistenceError::Parse(err.to_string()))?;
+        let path = self.snapshot_path(&snapshot.config.id);
+        let tmp_path = path.with_extension("json.tmp");
+
+        tokio::fs::create_dir_all(&self.path)
+            .await
+            .map_err(|err| PersistenceError::Io(err.to_string()))?;
+        tokio::fs::write(&tmp_path, content)
+            .await
+            .map_err(|err| PersistenceError::Io(err.to_string()))?;
+        tokio::fs::rename(&tmp_path, &path)
+            .await
+            .map_err(|err| PersistenceError::Io(err.to_string()))
+    }
+
+    /// Load a snapshot, checking its version before its content so that snapshots written by
+    /// another version of the server get a meaningful error. Snapshots of previous versions are
+    /// migrated to the current one.
+    pub fn load(path: &Path) -> Result<GameSnapshot, PersistenceError> {
+        let content =
+            fs::read_to_string(path).map_err(|err| PersistenceError::Io(err.to_string()))?;
+        let mut snapshot: Value = serde_json::from_str(&content)
+            .map_err(|err| PersistenceError::Parse(err.to_string()))?;
+        let SnapshotVersion { version } = SnapshotVersion::deserialize(&snapshot)
+            .map_err(|err| PersistenceError::Parse(err.to_string()))?;
+        match version {
+            1 => migrate_v1(&mut snapshot),
+            SNAPSHOT_VERSION => {}
+            version => return Err(PersistenceError::UnsupportedVersion(version)),
+        }
+        serde_json::from_value(snapshot).map_err(|err| PersistenceError::Parse(err.to_string()))
+    }
+
+    /// Load the snapshots of all the games of the store, invalid snapshots are skipped.
+    pub fn load_all(&self) -> Vec<GameSnapshot> {
+        let Ok(entries) = fs::read_dir(&self.path) else {
+            return Vec::new();
+        };
+        entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| match GameStore::load(&path) {
+                Ok(snapshot) => Some(snapshot),
+                Err(err) => {
+                    tracing::warn!("Game snapshot {path:?} not restored: {err}");
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Delete the snapshot of a game, e.g. once the game has been cleaned up.
+    pub async fn remove(&self, game: &GameId) {
+        if let Err(err) = tokio::fs::remove_file(self.snapshot_path(game)).await
+            && err.kind() != std::io::ErrorKind::NotFound
+        {
+            tracing::warn!("Unable to remove snapshot of game {game}: {err}");
+        }
+    }
+}
+
+/// Version 1 snapshots predate host commands, their games get a host token nobody knows so that
+/// they cannot be hosted.
+fn migrate_v1(snapshot: &mut Value) {
+    if let Some(config) = snapshot.get_mut("config").and_then(Value::as_object_mut) {
+        config
+            .entry("host_token")
+            .or_insert_with(|| json!(HostToken::default()));
+    }
+    snapshot["version"] = json!(SNAPSHOT_VERSION);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{GameStore, PersistenceError};
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("parcelec-games-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_load_snapshot_errors() {
+        let dir = temp_dir();
+        std::fs::write(dir.join("old.json"), r#"{"version": 0}"#).unwrap();
+        std::fs::write(dir.join("broken.json"), "{").unwrap();
+
+        assert_eq!(
+            GameStore::load(&dir.join("old.json")).unwrap_err(),
+            PersistenceError::UnsupportedVersion(0)
+        );
+        assert!(matches!(
+            GameStore::load(&dir.join("broken.json")),
+            Err(PersistenceError::Parse(_))
+        ));
+        assert!(matches!(
+            GameStore::load(&dir.join("missing.json")),
+            Err(PersistenceError::Io(_))
+        ));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_all_skips_invalid_snapshots() {
+        let dir = temp_dir();
+        std::fs::write(dir.join("broken.json"), "{").unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a snapshot").unwrap();
+
+        assert!(GameStore::new(&dir).load_all().is_empty());
+        assert!(GameStore::new(dir.join("missing")).load_all().is_empty());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{
+        delivery_period::DeliveryPeriodId,
+        scores::{PlayerDetailedScore, PlayerScore, ScoreDetails},
+    },
+    plants::registry::STACK_LINES,
+    player::{PlayerEmissionsResultView, PlayerId, PlayerName, PlayerResultView},
+};
+
+use super::archive::GameArchive;
+
+/// Scores of every player for each delivery period, along with their final ranks, to be exported
+/// for grading.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameResults {
+    pub periods: Vec<PlayerPeriodResults>,
+    pub rankings: Vec<PlayerResultView>,
+    pub emissions_rankings: Option<Vec<PlayerEmissionsResultView>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerPeriodResults {
+    pub player: PlayerName,
+    pub period: DeliveryPeriodId,
+    pub score: PlayerScore,
+    pub detailed_score: Option<PlayerDetailedScore>,
+}
+
+impl GameResults {
+    pub fn build(
+        players: &HashMap<PlayerId, PlayerName>,
+        scores: &HashMap<PlayerId, HashMap<DeliveryPeriodId, PlayerScore>>,
+        detailed_scores: &HashMap<PlayerId, HashMap<DeliveryPeriodId, PlayerDetailedScore>>,
+        rankings: Vec<PlayerResultView>,
+        emissions_rankings: Option<Vec<PlayerEmissionsResultView>>,
+    ) -> GameResults {
+        let periods = scores
+            .iter()
+            .filter_map(|(player_id, scores)| Some((players.get(player_id)?, player_id, scores)))
+            .flat_map(|(player, player_id, scores)| {
+                scores
+                    .iter()
+                    .map(move |(period, score)| PlayerPeriodResults {
+                        player: player.clone(),
+                        period: *period,
+                        score: score.clone(),
+                        detailed_score: detailed_scores
+                            .get(player_id)
+                            .and_then(|scores| scores.get(period))
+                            .cloned(),
+                    })
+            })
+            .collect();
+
+        GameResults::sorted(periods, rankings, emissions_rankings)
+    }
+
+    fn sorted(
+        mut periods: Vec<PlayerPeriodResults>,
+        rankings: Vec<PlayerResultView>,
+        emissions_rankings: Option<Vec<PlayerEmissionsResultView>>,
+    ) -> GameResults {
+        periods.sort_by_key(|results| (results.player.to_string(), results.period));
+        GameResults {
+            periods,
+            rankings,
+            emissions_rankings,
+        }
+    }
+
+    /// One line per player and delivery period, with the player's final ranks repeated on each of
+    /// their lines so that the export can be filtered and pivoted in a spreadsheet.
+    pub fn to_csv(&self) -> String {
+        let mut header = vec![
+            "player".to_string(),
+            "period".to_string(),
+            "balance".to_string(),
+            "pnl".to_string(),
+            "imbalance_cost".to_string(),
+        ];
+        for line in detailed_lines() {
+            header.push(format!("{line}_volume"));
+            header.push(format!("{line}_pnl"));
+        }
+        header.extend(["emissions", "rank", "emissions_rank"].map(String::from));
+
+        let mut csv = header.join(",");
+        csv.push('\n');
+        for results in self.periods.iter() {
+            let mut row = vec![
+                csv_field(&results.player.to_string()),
+                results.period.to_string(),
+                i32::from(results.score.balance).to_string(),
+                i32::from(results.score.pnl).to_string(),
+                i32::from(results.score.imbalance_cost).to_string(),
+            ];
+            for line in detailed_lines() {
+                match results.detailed_score.as_ref() {
+                    Some(detailed_score) => {
+                        let details = detailed_line(detailed_score, line);
+                        row.push(i32::from(details.volume).to_string());
+                        row.push(i32::from(details.pnl).to_string());
+                    }
+                    None => row.extend([String::new(), String::new()]),
+                }
+            }
+            row.push(
+                results
+                    .detailed_score
+                    .as_ref()
+                    .map(|score| i32::from(score.emissions).to_string())
+                    .unwrap_or_default(),
+            );
+            row.push(
+                self.rankings
+                    .iter()
+                    .find(|ranking| ranking.player == results.player)
+                    .map(|ranking| ranking.rank.to_string())
+                    .unwrap_or_default(),
+            );
+            row.push(
+                self.emissions_rankings
+                    .iter()
+                    .flatten()
+                    .find(|ranking| ranking.player == results.player)
+                    .map(|ranking| ranking.rank.to_string())
+                    .unwrap_or_default(),
+            );
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+impl From<&GameArchive> for GameResults {
+    fn from(archive: &GameArchive) -> Self {
+        let periods = archive
+            .periods
+            .iter()
+            .flat_map(|period| {
+                period.players.iter().filter_map(|(player, archived)| {
+                    Some(PlayerPeriodResults {
+                        player: player.clone(),
+                        period: period.period,
+                        score: archived.score.clone()?,
+                        detailed_score: archived.detailed_score.clone(),
+                    })
+                })
+            })
+            .collect();
+
+        GameResults::sorted(
+            periods,
+            archive.rankings.clone(),
+            archive.emissions_rankings.clone(),
+        )
+    }
+}
+
+/// Lines of a detailed score, the stack ones followed by the market and imbalance ones.
+fn detailed_lines() -> impl Iterator<Item = &'static str> {
+    STACK_LINES
+        .into_iter()
+        .chain(["market_bought", "market_sold", "imbalance"])
+}
+
+fn detailed_line(score: &PlayerDetailedScore, line: &str) -> ScoreDetails {
+    match line {
+        "market_bought" => score.market_bought.clone(),
+        "market_sold" => score.market_sold.clone(),
+        "imbalance" => score.imbalance.clone(),
+        line => score.line(line),
+    }
+}
+
+/// Quote a field if it contains characters that would break the CSV structure. Fields that a
+/// spreadsheet would evaluate as a formula are prefixed with `'` so that they are read as text.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{value}")
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        game::{
+            delivery_period::DeliveryPeriodId,
+            scores::{PlayerDetailedScore, PlayerScore, ScoreDetails},
+        },
+        player::{PlayerId, PlayerName, PlayerResultView},
+        utils::units::{Emissions, Energy, Money, Power},
+    };
+
+    use super::{GameResults, csv_field};
+
+    fn score(pnl: i32) -> PlayerScore {
+        PlayerScore {
+            balance: Power::from(0),
+            pnl: Money::from(pnl),
+            imbalance_cost: Money::from(0),
+        }
+    }
+
+    fn results() -> GameResults {
+        let alice = PlayerId::default();
+        let bob = PlayerId::default();
+        let players = HashMap::from([
+            (alice.clone(), PlayerName::from("alice")),
+            (bob.clone(), PlayerName::from("bob, jr")),
+        ]);
+        let scores = HashMap::from([
+            (
+                alice.clone(),
+                HashMap::from([
+                    (DeliveryPeriodId::from(2), score(200)),
+                    (DeliveryPeriodId::from(1), score(100)),
+                ]),
+            ),
+            (
+                bob.clone(),
+                HashMap::from([(DeliveryPeriodId::from(1), score(-50))]),
+            ),
+        ]);
+        let mut detailed_score = PlayerDetailedScore {
+            market_sold: ScoreDetails {
+                volume: Energy::from(-10),
+                pnl: Money::from(100),
+            },
+            emissions: Emissions::from(12),
+            ..Default::default()
+        };
+        detailed_score.stack.insert(
+            "gas".to_string(),
+            ScoreDetails {
+                volume: Energy::from(10),
+                pnl: Money::from(-40),
+            },
+        );
+        let detailed_scores = HashMap::from([(
+            alice,
+            HashMap::from([(DeliveryPeriodId::from(1), detailed_score)]),
+        )]);
+        let rankings = vec![
+            PlayerResultView {
+                player: PlayerName::from("alice"),
+                rank: 1,
+                score: Money::from(300),
+            },
+            PlayerResultView {
+                player: PlayerName::from("bob, jr"),
+                rank: 2,
+                score: Money::from(-50),
+            },
+        ];
+
+        GameResults::build(&players, &scores, &detailed_scores, rankings, None)
+    }
+
+    #[test]
+    fn test_results_sorted_by_player_and_period() {
+        let results = results();
+
+        assert_eq!(
+            results
+                .periods
+                .iter()
+                .map(|results| (results.player.to_string(), usize::from(results.period)))
+                .collect::<Vec<_>>(),
+            vec![
+                ("alice".to_string(), 1),
+                ("alice".to_string(), 2),
+                ("bob, jr".to_string(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_results_to_csv() {
+        let csv = results().to_csv();
+        let lines = csv.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[0],
+            "player,period,balance,pnl,imbalance_cost,\
+            consumers_volume,consumers_pnl,renewables_volume,renewables_pnl,gas_volume,gas_pnl,\
+            nuclear_volume,nuclear_pnl,battery_discharge_volume,battery_discharge_pnl,\
+            battery_charge_volume,battery_charge_pnl,capex_volume,capex_pnl,\
+            market_bought_volume,market_bought_pnl,market_sold_volume,market_sold_pnl,\
+            imbalance_volume,imbalance_pnl,emissions,rank,emissions_rank"
+        );
+        assert_eq!(
+            lines[1],
+            "alice,1,0,100,0,0,0,0,0,10,-40,0,0,0,0,0,0,0,0,0,0,-10,100,0,0,12,1,"
+        );
+        assert_eq!(lines[2], "alice,2,0,200,0,,,,,,,,,,,,,,,,,,,,,,1,");
+        assert_eq!(lines[3], "\"bob, jr\",1,0,-50,0,,,,,,,,,,,,,,,,,,,,,,2,");
+    }
+
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("alice"), "alice");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("=1+1"), "'=1+1");
+        assert_eq!(csv_field("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(csv_field("-2,3"), "\"'-2,3\"");
+    }
+}
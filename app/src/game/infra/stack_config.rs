@@ -1,17 +1,19 @@
-use std::{cmp::min, collections::HashMap};
+use std::cmp::min;
 
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    forecast::{ForecastValue, NormalizedForecastValue},
+    forecast::{NormalizedForecastValue, repeat_profile, signal::WeatherSignal},
+    game::{PlantInvestmentError, RegisterPlayerStackError, delivery_period::DeliveryPeriodId},
     plants::{
-        PlantId, PowerPlant, StackPlants,
-        technologies::{
-            battery::Battery, consumers::Consumers, gas_plant::GasPlant, nuclear::NuclearPlant,
-            renewable::RenewablePlant,
-        },
+        PlantDefinition, PlantEnvironment, StackPlants,
+        investment::{CapexSchedule, PlantInvestment},
+    },
+    utils::{
+        rng::GameRng,
+        units::{CapacityCost, CarbonPrice, Energy, EnergyCost, Money, NO_POWER, Power},
     },
-    utils::units::{Energy, EnergyCost, Power},
 };
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, PartialOrd)]
@@ -20,53 +22,120 @@ pub enum GameStackConfig {
     PerPlayer(GameStackPerPlayerBaseConfig),
 }
 
+/// A plant of a stack: a display name and the plant's definition.
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, PartialOrd)]
-pub struct GameStackFixedConfig {
-    pub gas_cost: EnergyCost,
-    pub nuclear_cost: EnergyCost,
-    pub consumers_revenues: EnergyCost,
-    pub gas_capacity: Power,
-    pub nuclear_capacity: Power,
-    pub battery_capacity: Energy,
-    pub consumers_forecasts: Vec<ForecastValue>,
-    pub consumers_forecasts_range: usize,
-    pub renewable_forecasts: Vec<ForecastValue>,
-    pub renewable_forecasts_range: usize,
+pub struct PlantConfig {
+    pub name: String,
+    #[serde(flatten)]
+    pub definition: PlantDefinition,
 }
 
-impl GameStackFixedConfig {
-    pub fn generate_plants(&self) -> StackPlants {
-        let mut stack: HashMap<PlantId, Box<dyn PowerPlant + Send + Sync>> = HashMap::new();
+impl PlantConfig {
+    pub fn new(name: &str, definition: PlantDefinition) -> PlantConfig {
+        PlantConfig {
+            name: name.to_string(),
+            definition,
+        }
+    }
+}
 
-        stack.insert(
-            PlantId::default(),
-            Box::new(Battery::new(self.battery_capacity, Energy::from(0))),
-        );
-        stack.insert(
-            PlantId::default(),
-            Box::new(GasPlant::new(self.gas_cost, self.gas_capacity)),
-        );
-        stack.insert(
-            PlantId::default(),
-            Box::new(NuclearPlant::new(self.nuclear_capacity, self.nuclear_cost)),
-        );
-        stack.insert(
-            PlantId::default(),
-            Box::new(RenewablePlant::new(
-                self.renewable_forecasts.clone(),
-                self.renewable_forecasts_range,
-            )),
-        );
-        stack.insert(
-            PlantId::default(),
-            Box::new(Consumers::new(
-                self.consumers_revenues,
-                self.consumers_forecasts.clone(),
-                self.consumers_forecasts_range,
-            )),
+impl GameStackConfig {
+    /// Correlate renewable plants and consumers of all the stacks of the game with a global signal.
+    pub fn with_weather(self, weather: WeatherSignal) -> GameStackConfig {
+        match self {
+            GameStackConfig::Fixed(config) => GameStackConfig::Fixed(GameStackFixedConfig {
+                weather: Some(weather),
+                ..config
+            }),
+            GameStackConfig::PerPlayer(config) => {
+                GameStackConfig::PerPlayer(GameStackPerPlayerBaseConfig {
+                    weather: Some(weather),
+                    ..config
+                })
+            }
+        }
+    }
+
+    /// Repeat the forecasts profiles explicitly so that they cover the `number_of_periods`
+    /// delivery periods of the game, and the forecasts made during the last one.
+    pub fn with_forecasts_covering(self, number_of_periods: usize) -> GameStackConfig {
+        match self {
+            GameStackConfig::Fixed(config) => GameStackConfig::Fixed(GameStackFixedConfig {
+                plants: config
+                    .plants
+                    .into_iter()
+                    .map(|plant| PlantConfig {
+                        name: plant.name,
+                        definition: plant.definition.map_forecasts(|forecasts, range| {
+                            repeat_profile(forecasts.forecasts, number_of_periods + range).into()
+                        }),
+                    })
+                    .collect(),
+                ..config
+            }),
+            GameStackConfig::PerPlayer(config) => {
+                GameStackConfig::PerPlayer(GameStackPerPlayerBaseConfig {
+                    consumers_forecasts: repeat_profile(
+                        config.consumers_forecasts,
+                        number_of_periods + config.consumers_forecasts_range,
+                    ),
+                    renewable_forecasts: repeat_profile(
+                        config.renewable_forecasts,
+                        number_of_periods + config.renewable_forecasts_range,
+                    ),
+                    ..config
+                })
+            }
+        }
+    }
+}
+
+fn generate_stack_plants(
+    plants: &[PlantConfig],
+    environment: &PlantEnvironment,
+    first_period: DeliveryPeriodId,
+    rng: &mut GameRng,
+) -> StackPlants {
+    let mut stack = StackPlants::default();
+    for plant in plants {
+        stack.add_plant(
+            plant.name.clone(),
+            plant
+                .definition
+                .build_starting_at(environment, first_period, rng),
+            rng,
         );
+    }
+    stack
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, PartialOrd)]
+pub struct GameStackFixedConfig {
+    pub plants: Vec<PlantConfig>,
+    #[serde(default)]
+    pub carbon_price: CarbonPrice,
+    /// Signal shared by all the stacks, renewables and consumers are decorrelated if not set
+    #[serde(default)]
+    pub weather: Option<WeatherSignal>,
+}
 
-        StackPlants::new(stack)
+impl GameStackFixedConfig {
+    /// Generate the plants of a player joining the game after `current_period` was delivered, i.e.
+    /// playing from the next delivery period.
+    pub fn generate_plants(
+        &self,
+        current_period: DeliveryPeriodId,
+        rng: &mut GameRng,
+    ) -> StackPlants {
+        generate_stack_plants(
+            &self.plants,
+            &PlantEnvironment {
+                carbon_price: self.carbon_price,
+                weather: self.weather.clone(),
+            },
+            current_period.next(),
+            rng,
+        )
     }
 }
 
@@ -84,6 +153,47 @@ pub struct GameStackPerPlayerBaseConfig {
     pub renewable_max_capacity: Power,
     pub renewable_forecasts: Vec<NormalizedForecastValue>,
     pub renewable_forecasts_range: usize,
+    #[serde(default)]
+    pub carbon_price: CarbonPrice,
+    /// Signal shared by all the stacks, renewables and consumers are decorrelated if not set
+    #[serde(default)]
+    pub weather: Option<WeatherSignal>,
+    /// Cost of building each technology's capacity
+    #[serde(default)]
+    pub capacity_costs: CapacityCosts,
+    /// Maximum capex of a player's initial portfolio, unbounded if not set
+    #[serde(default)]
+    pub budget: Option<Money>,
+    /// Allow players to build new plants between delivery periods
+    #[serde(default)]
+    pub investment: Option<InvestmentConfig>,
+}
+
+/// Capital expenditure per MW of power capacity, or per MWh of storage capacity.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, PartialOrd)]
+pub struct CapacityCosts {
+    pub gas: CapacityCost,
+    pub nuclear: CapacityCost,
+    pub renewable: CapacityCost,
+    pub battery: CapacityCost,
+}
+
+/// Delay of building new plants between delivery periods, their costs are the game's capacity
+/// costs.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, PartialOrd)]
+pub struct InvestmentConfig {
+    /// Number of delivery periods between the investment decision and the plant operating
+    pub build_delay: usize,
+}
+
+/// New plant a player wants to build, its other parameters are those of the game's base config.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(tag = "technology")]
+pub enum PlayerInvestmentConfig {
+    Battery { capacity: Energy },
+    GasPlant { capacity: Power },
+    Nuclear { capacity: Power },
+    RenewablePlant { capacity: Power },
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -95,113 +205,444 @@ pub struct GameStackPerPlayerPlayerConfig {
 }
 
 impl GameStackPerPlayerBaseConfig {
-    pub fn generate_plants(&self, player_config: GameStackPerPlayerPlayerConfig) -> StackPlants {
-        let mut stack: HashMap<PlantId, Box<dyn PowerPlant + Send + Sync>> = HashMap::new();
-
-        stack.insert(
-            PlantId::default(),
-            Box::new(Battery::new(
-                min(self.battery_max_capacity, player_config.battery_capacity),
-                Energy::from(0),
-            )),
-        );
-        stack.insert(
-            PlantId::default(),
-            Box::new(GasPlant::new(
-                self.gas_cost,
-                min(self.gas_max_capacity, player_config.gas_capacity),
-            )),
-        );
-        stack.insert(
-            PlantId::default(),
-            Box::new(NuclearPlant::new(
-                min(self.nuclear_max_capacity, player_config.nuclear_capacity),
-                self.nuclear_cost,
-            )),
-        );
-        let capacity = min(
-            self.renewable_max_capacity,
-            player_config.renewable_capacity,
-        );
-        stack.insert(
-            PlantId::default(),
-            Box::new(RenewablePlant::new(
-                self.renewable_forecasts
-                    .iter()
-                    .map(|f| f.as_forecast(capacity.into()))
-                    .collect(),
-                self.consumers_forecasts_range,
-            )),
-        );
+    /// Generate the plants of a player's portfolio for a player joining the game after
+    /// `current_period` was delivered, their capex being paid over the remaining delivery periods
+    /// of the game. The portfolio is rejected if its capex exceeds the game's budget.
+    pub fn generate_plants(
+        &self,
+        player_config: GameStackPerPlayerPlayerConfig,
+        current_period: DeliveryPeriodId,
+        last_period: DeliveryPeriodId,
+        rng: &mut GameRng,
+    ) -> Result<StackPlants, RegisterPlayerStackError> {
+        let plants = self
+            .plants_definitions(self.clamp_capacities(player_config))
+            .ok_or(RegisterPlayerStackError::CapexOverflow)?;
+        let cost = total_capex(&plants).ok_or(RegisterPlayerStackError::CapexOverflow)?;
+        if let Some(budget) = self.budget
+            && cost > budget
+        {
+            return Err(RegisterPlayerStackError::BudgetExceeded { cost, budget });
+        }
 
-        stack.insert(
-            PlantId::default(),
-            Box::new(Consumers::new(
-                self.consumers_revenues,
-                self.consumers_forecasts
-                    .iter()
-                    .map(|f| f.as_forecast(self.consumers_capacity.into()))
-                    .collect(),
-                self.consumers_forecasts_range,
-            )),
-        );
+        let environment = self.environment();
+        let remaining_periods = usize::from(last_period).saturating_sub(current_period.into());
+        let mut stack = StackPlants::default();
+        for (plant, capex) in plants {
+            let built =
+                plant
+                    .definition
+                    .build_starting_at(&environment, current_period.next(), rng);
+            if capex > Money::from(0) {
+                stack.add_plant_with_capex(
+                    plant.name,
+                    built,
+                    CapexSchedule::new(capex, remaining_periods),
+                    rng,
+                );
+            } else {
+                stack.add_plant(plant.name, built, rng);
+            }
+        }
+        Ok(stack)
+    }
+
+    /// Total capex of a player's portfolio, once its capacities are limited to the game's maximums.
+    /// `None` if it overflows.
+    pub fn portfolio_cost(&self, player_config: &GameStackPerPlayerPlayerConfig) -> Option<Money> {
+        total_capex(&self.plants_definitions(self.clamp_capacities(player_config.clone()))?)
+    }
+
+    fn clamp_capacities(
+        &self,
+        player_config: GameStackPerPlayerPlayerConfig,
+    ) -> GameStackPerPlayerPlayerConfig {
+        GameStackPerPlayerPlayerConfig {
+            gas_capacity: min(self.gas_max_capacity, player_config.gas_capacity).max(NO_POWER),
+            nuclear_capacity: min(self.nuclear_max_capacity, player_config.nuclear_capacity)
+                .max(NO_POWER),
+            battery_capacity: min(self.battery_max_capacity, player_config.battery_capacity)
+                .max(Energy::from(0)),
+            renewable_capacity: min(
+                self.renewable_max_capacity,
+                player_config.renewable_capacity,
+            )
+            .max(NO_POWER),
+        }
+    }
+
+    /// Investment in a new plant decided by a player after `current_period` was delivered. Its
+    /// capex is paid over the remaining delivery periods of the game.
+    pub fn plant_investment(
+        &self,
+        player_investment: &PlayerInvestmentConfig,
+        current_period: DeliveryPeriodId,
+        last_period: DeliveryPeriodId,
+        rng: &mut GameRng,
+    ) -> Result<PlantInvestment, PlantInvestmentError> {
+        let Some(config) = &self.investment else {
+            return Err(PlantInvestmentError::GameConfigDoesNotAllowInvestment);
+        };
+        let remaining_periods = usize::from(last_period).saturating_sub(current_period.into());
+        if config.build_delay >= remaining_periods {
+            return Err(PlantInvestmentError::PlantWouldNotOperateBeforeGameEnd);
+        }
 
-        StackPlants::new(stack)
+        let costs = &self.capacity_costs;
+        let (name, definition, capex) = match *player_investment {
+            PlayerInvestmentConfig::Battery { capacity }
+                if capacity > Energy::from(0) && capacity <= self.battery_max_capacity =>
+            {
+                (
+                    "battery",
+                    PlantDefinition::Battery { capacity },
+                    capacity.checked_mul(costs.battery),
+                )
+            }
+            PlayerInvestmentConfig::GasPlant { capacity }
+                if capacity > NO_POWER && capacity <= self.gas_max_capacity =>
+            {
+                (
+                    "gas",
+                    PlantDefinition::GasPlant {
+                        energy_cost: self.gas_cost,
+                        capacity,
+                    },
+                    capacity.checked_mul(costs.gas),
+                )
+            }
+            PlayerInvestmentConfig::Nuclear { capacity }
+                if capacity > NO_POWER && capacity <= self.nuclear_max_capacity =>
+            {
+                (
+                    "nuclear",
+                    PlantDefinition::Nuclear {
+                        energy_cost: self.nuclear_cost,
+                        capacity,
+                    },
+                    capacity.checked_mul(costs.nuclear),
+                )
+            }
+            PlayerInvestmentConfig::RenewablePlant { capacity }
+                if capacity > NO_POWER && capacity <= self.renewable_max_capacity =>
+            {
+                (
+                    "renewable",
+                    self.renewable_definition(capacity),
+                    capacity.checked_mul(costs.renewable),
+                )
+            }
+            _ => return Err(PlantInvestmentError::InvalidCapacity),
+        };
+        let capex = capex.ok_or(PlantInvestmentError::CapexOverflow)?;
+        if let Some(budget) = self.budget
+            && capex > budget
+        {
+            return Err(PlantInvestmentError::BudgetExceeded {
+                cost: capex,
+                budget,
+            });
+        }
+
+        Ok(PlantInvestment {
+            name: name.to_string(),
+            definition,
+            environment: self.environment(),
+            seed: rng.random(),
+            build_delay: config.build_delay,
+            capex: CapexSchedule::new(capex, remaining_periods),
+        })
+    }
+
+    fn environment(&self) -> PlantEnvironment {
+        PlantEnvironment {
+            carbon_price: self.carbon_price,
+            weather: self.weather.clone(),
+        }
+    }
+
+    fn renewable_definition(&self, capacity: Power) -> PlantDefinition {
+        PlantDefinition::RenewablePlant {
+            forecasts: self
+                .renewable_forecasts
+                .iter()
+                .map(|f| f.as_forecast(capacity.into()))
+                .collect(),
+            forecasts_range: self.renewable_forecasts_range,
+        }
+    }
+
+    /// Plants of a player's portfolio with their capex, capacities being already clamped. `None`
+    /// if a capex overflows.
+    fn plants_definitions(
+        &self,
+        player_config: GameStackPerPlayerPlayerConfig,
+    ) -> Option<Vec<(PlantConfig, Money)>> {
+        let costs = &self.capacity_costs;
+        Some(vec![
+            (
+                PlantConfig::new(
+                    "battery",
+                    PlantDefinition::Battery {
+                        capacity: player_config.battery_capacity,
+                    },
+                ),
+                player_config.battery_capacity.checked_mul(costs.battery)?,
+            ),
+            (
+                PlantConfig::new(
+                    "gas",
+                    PlantDefinition::GasPlant {
+                        energy_cost: self.gas_cost,
+                        capacity: player_config.gas_capacity,
+                    },
+                ),
+                player_config.gas_capacity.checked_mul(costs.gas)?,
+            ),
+            (
+                PlantConfig::new(
+                    "nuclear",
+                    PlantDefinition::Nuclear {
+                        energy_cost: self.nuclear_cost,
+                        capacity: player_config.nuclear_capacity,
+                    },
+                ),
+                player_config.nuclear_capacity.checked_mul(costs.nuclear)?,
+            ),
+            (
+                PlantConfig::new(
+                    "renewable",
+                    self.renewable_definition(player_config.renewable_capacity),
+                ),
+                player_config
+                    .renewable_capacity
+                    .checked_mul(costs.renewable)?,
+            ),
+            (
+                PlantConfig::new(
+                    "consumers",
+                    PlantDefinition::Consumers {
+                        revenues: self.consumers_revenues,
+                        forecasts: self
+                            .consumers_forecasts
+                            .iter()
+                            .map(|f| f.as_forecast(self.consumers_capacity.into()))
+                            .collect(),
+                        forecasts_range: self.consumers_forecasts_range,
+                    },
+                ),
+                Money::from(0),
+            ),
+        ])
     }
 }
 
+fn total_capex(plants: &[(PlantConfig, Money)]) -> Option<Money> {
+    plants.iter().try_fold(Money::from(0), |total, (_, capex)| {
+        total.checked_add(*capex)
+    })
+}
+
 #[cfg(test)]
 mod test_fixed_config_generate_stack {
 
-    use crate::plants::PowerPlantPublicRepr;
+    use crate::{forecast::ForecastValue, plants::PowerPlantPublicRepr, utils::rng::seeded_rng};
 
     use super::*;
 
+    #[test]
+    fn test_forecasts_profiles_repeated_to_cover_the_game() {
+        let forecasts = vec![
+            ForecastValue {
+                value: 100,
+                deviation: 0,
+            },
+            ForecastValue {
+                value: 200,
+                deviation: 0,
+            },
+        ];
+        let config = GameStackConfig::Fixed(GameStackFixedConfig {
+            plants: vec![PlantConfig::new(
+                "renewable",
+                PlantDefinition::RenewablePlant {
+                    forecasts: forecasts.clone().into(),
+                    forecasts_range: 1,
+                },
+            )],
+            carbon_price: CarbonPrice::default(),
+            weather: None,
+        });
+
+        let GameStackConfig::Fixed(config) = config.with_forecasts_covering(4) else {
+            unreachable!("Should be a fixed config")
+        };
+
+        let PlantDefinition::RenewablePlant {
+            forecasts: extended,
+            ..
+        } = &config.plants[0].definition
+        else {
+            unreachable!("Should be a renewable plant")
+        };
+        assert_eq!(
+            extended.forecasts,
+            [
+                forecasts.clone(),
+                forecasts.clone(),
+                forecasts[..1].to_vec()
+            ]
+            .concat()
+        );
+    }
+
     #[test]
     fn test_generate_stack() {
         let config = GameStackFixedConfig {
-            gas_cost: EnergyCost::from(70),
-            gas_capacity: Power::from(300),
-            nuclear_cost: EnergyCost::from(35),
-            nuclear_capacity: Power::from(1000),
-            battery_capacity: Energy::from(200),
-            consumers_revenues: EnergyCost::from(60),
-            consumers_forecasts: vec![],
-            consumers_forecasts_range: 3,
-            renewable_forecasts: vec![],
-            renewable_forecasts_range: 3,
+            plants: vec![
+                PlantConfig::new(
+                    "battery",
+                    PlantDefinition::Battery {
+                        capacity: Energy::from(200),
+                    },
+                ),
+                PlantConfig::new(
+                    "gas",
+                    PlantDefinition::GasPlant {
+                        energy_cost: EnergyCost::from(70),
+                        capacity: Power::from(300),
+                    },
+                ),
+                PlantConfig::new(
+                    "nuclear",
+                    PlantDefinition::Nuclear {
+                        energy_cost: EnergyCost::from(35),
+                        capacity: Power::from(1000),
+                    },
+                ),
+                PlantConfig::new(
+                    "renewable",
+                    PlantDefinition::RenewablePlant {
+                        forecasts: vec![].into(),
+                        forecasts_range: 3,
+                    },
+                ),
+                PlantConfig::new(
+                    "consumers",
+                    PlantDefinition::Consumers {
+                        revenues: EnergyCost::from(60),
+                        forecasts: vec![].into(),
+                        forecasts_range: 3,
+                    },
+                ),
+            ],
+            carbon_price: CarbonPrice::from(50),
+            weather: None,
         };
 
-        let stack = config.generate_plants();
+        let stack = config.generate_plants(DeliveryPeriodId::from(0), &mut seeded_rng(0));
         let snapshot = stack.snapshot();
 
         assert_eq!(snapshot.len(), 5);
 
         for (_id, plant) in snapshot.iter() {
-            match plant {
+            match plant.state {
                 PowerPlantPublicRepr::Battery(battery) => {
+                    assert_eq!(plant.name, "battery");
                     assert_eq!(battery.max_charge, Energy::from(200))
                 }
                 PowerPlantPublicRepr::Consumers(consumers) => {
+                    assert_eq!(plant.name, "consumers");
                     assert_eq!(consumers.revenue, EnergyCost::from(60))
                 }
-                PowerPlantPublicRepr::GasPlant(plant) => {
-                    assert_eq!(plant.settings.energy_cost(), EnergyCost::from(70));
-                    assert_eq!(plant.settings.max_setpoint(), Power::from(300));
+                PowerPlantPublicRepr::GasPlant(gas) => {
+                    assert_eq!(plant.name, "gas");
+                    assert_eq!(gas.settings.energy_cost(), EnergyCost::from(70));
+                    assert_eq!(gas.settings.max_setpoint(), Power::from(300));
+                    assert_eq!(gas.settings.carbon_price(), CarbonPrice::from(50));
                 }
-                PowerPlantPublicRepr::Nuclear(plant) => {
-                    assert_eq!(plant.energy_cost, EnergyCost::from(35));
-                    assert_eq!(plant.max_setpoint, Power::from(1000));
+                PowerPlantPublicRepr::Nuclear(nuclear) => {
+                    assert_eq!(plant.name, "nuclear");
+                    assert_eq!(nuclear.energy_cost, EnergyCost::from(35));
+                    assert_eq!(nuclear.max_setpoint, Power::from(1000));
+                    assert_eq!(nuclear.carbon_price, CarbonPrice::from(50));
+                }
+                PowerPlantPublicRepr::RenewablePlant(_) => {
+                    assert_eq!(plant.name, "renewable");
                 }
-                _ => {}
             }
         }
     }
+
+    #[test]
+    fn test_generate_stack_with_multiple_plants_of_same_technology() {
+        let config = GameStackFixedConfig {
+            plants: [("gas-1", 50), ("gas-2", 70), ("gas-3", 110)]
+                .into_iter()
+                .map(|(name, cost)| {
+                    PlantConfig::new(
+                        name,
+                        PlantDefinition::GasPlant {
+                            energy_cost: EnergyCost::from(cost),
+                            capacity: Power::from(100),
+                        },
+                    )
+                })
+                .collect(),
+            carbon_price: CarbonPrice::default(),
+            weather: None,
+        };
+
+        let snapshot = config
+            .generate_plants(DeliveryPeriodId::from(0), &mut seeded_rng(0))
+            .snapshot();
+
+        let mut gas_plants = snapshot
+            .values()
+            .map(|plant| {
+                let PowerPlantPublicRepr::GasPlant(gas) = plant.state else {
+                    unreachable!("Should only contain gas plants")
+                };
+                (plant.name.clone(), gas.settings.energy_cost())
+            })
+            .collect::<Vec<_>>();
+        gas_plants.sort();
+        assert_eq!(
+            gas_plants,
+            vec![
+                ("gas-1".to_string(), EnergyCost::from(50)),
+                ("gas-2".to_string(), EnergyCost::from(70)),
+                ("gas-3".to_string(), EnergyCost::from(110)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deserialize_plant_config() {
+        let config: PlantConfig = serde_json::from_str(
+            r#"{"name": "peaker", "technology": "GasPlant", "energy_cost": 120, "capacity": 200}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config,
+            PlantConfig::new(
+                "peaker",
+                PlantDefinition::GasPlant {
+                    energy_cost: EnergyCost::from(120),
+                    capacity: Power::from(200),
+                }
+            )
+        );
+    }
 }
 
 #[cfg(test)]
 mod test_per_player_config_generate_stack {
-    use crate::plants::PowerPlantPublicRepr;
+    use crate::{
+        plants::PowerPlantPublicRepr,
+        utils::{rng::seeded_rng, units::Money},
+    };
 
     use super::*;
 
@@ -219,9 +660,65 @@ mod test_per_player_config_generate_stack {
             renewable_max_capacity: Power::from(600),
             renewable_forecasts: vec![NormalizedForecastValue::try_new(1., 0.).unwrap()],
             renewable_forecasts_range: 3,
+            carbon_price: CarbonPrice::from(50),
+            capacity_costs: CapacityCosts {
+                gas: CapacityCost::from(100),
+                nuclear: CapacityCost::from(400),
+                renewable: CapacityCost::from(150),
+                battery: CapacityCost::from(50),
+            },
+            budget: None,
+            investment: Some(InvestmentConfig { build_delay: 1 }),
+            weather: None,
         }
     }
 
+    #[test]
+    fn test_forecasts_profiles_repeated_to_cover_the_game() {
+        let GameStackConfig::PerPlayer(config) =
+            GameStackConfig::PerPlayer(base_config()).with_forecasts_covering(4)
+        else {
+            unreachable!("Should be a per player config")
+        };
+
+        assert_eq!(config.consumers_forecasts.len(), 4 + 3);
+        assert_eq!(config.renewable_forecasts.len(), 4 + 3);
+        assert!(
+            config
+                .renewable_forecasts
+                .iter()
+                .all(|f| *f == base_config().renewable_forecasts[0])
+        );
+    }
+
+    #[test]
+    fn test_same_seed_generates_same_plant_ids() {
+        let player_config = || GameStackPerPlayerPlayerConfig {
+            gas_capacity: Power::from(300),
+            nuclear_capacity: Power::from(800),
+            battery_capacity: Energy::from(200),
+            renewable_capacity: Power::from(400),
+        };
+        let plant_ids = || {
+            let mut ids = base_config()
+                .generate_plants(
+                    player_config(),
+                    DeliveryPeriodId::from(0),
+                    DeliveryPeriodId::from(4),
+                    &mut seeded_rng(7),
+                )
+                .unwrap()
+                .snapshot()
+                .into_keys()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>();
+            ids.sort();
+            ids
+        };
+
+        assert_eq!(plant_ids(), plant_ids());
+    }
+
     #[test]
     fn test_generate_stack_player_below_max() {
         let base = base_config();
@@ -232,13 +729,20 @@ mod test_per_player_config_generate_stack {
             renewable_capacity: Power::from(400),
         };
 
-        let stack = base.generate_plants(player_config);
+        let stack = base
+            .generate_plants(
+                player_config,
+                DeliveryPeriodId::from(0),
+                DeliveryPeriodId::from(4),
+                &mut seeded_rng(0),
+            )
+            .unwrap();
         let snapshot = stack.snapshot();
 
         assert_eq!(snapshot.len(), 5);
 
         for (_id, plant) in snapshot.iter() {
-            match plant {
+            match plant.state {
                 PowerPlantPublicRepr::Battery(battery) => {
                     assert_eq!(battery.max_charge, Energy::from(200))
                 }
@@ -268,13 +772,20 @@ mod test_per_player_config_generate_stack {
             renewable_capacity: Power::from(1200),
         };
 
-        let stack = base.generate_plants(player_config);
+        let stack = base
+            .generate_plants(
+                player_config,
+                DeliveryPeriodId::from(0),
+                DeliveryPeriodId::from(4),
+                &mut seeded_rng(0),
+            )
+            .unwrap();
         let snapshot = stack.snapshot();
 
         assert_eq!(snapshot.len(), 5);
 
         for (_id, plant) in snapshot.iter() {
-            match plant {
+            match plant.state {
                 PowerPlantPublicRepr::Battery(battery) => {
                     assert_eq!(battery.max_charge, Energy::from(400))
                 }
@@ -293,4 +804,291 @@ mod test_per_player_config_generate_stack {
             }
         }
     }
+
+    #[test]
+    fn test_portfolio_cost_uses_clamped_capacities() {
+        let base = base_config();
+        let player_config = GameStackPerPlayerPlayerConfig {
+            gas_capacity: Power::from(900),
+            nuclear_capacity: Power::from(100),
+            battery_capacity: Energy::from(-100),
+            renewable_capacity: Power::from(200),
+        };
+
+        assert_eq!(
+            base.portfolio_cost(&player_config),
+            Some(Money::from(500 * 100 + 100 * 400 + 200 * 150))
+        );
+    }
+
+    #[test]
+    fn test_generate_stack_rejected_when_capex_overflows() {
+        let base = GameStackPerPlayerBaseConfig {
+            gas_max_capacity: Power::from(i32::MAX),
+            nuclear_max_capacity: Power::from(i32::MAX),
+            ..base_config()
+        };
+        let player_config = GameStackPerPlayerPlayerConfig {
+            gas_capacity: Power::from(10_000_000),
+            nuclear_capacity: Power::from(5_000_000),
+            battery_capacity: Energy::from(0),
+            renewable_capacity: Power::from(0),
+        };
+
+        assert_eq!(base.portfolio_cost(&player_config), None);
+        let result = base.generate_plants(
+            player_config,
+            DeliveryPeriodId::from(0),
+            DeliveryPeriodId::from(4),
+            &mut seeded_rng(0),
+        );
+        assert!(matches!(
+            result,
+            Err(RegisterPlayerStackError::CapexOverflow)
+        ));
+    }
+
+    #[test]
+    fn test_generate_stack_rejected_when_budget_exceeded() {
+        let base = GameStackPerPlayerBaseConfig {
+            budget: Some(Money::from(100_000)),
+            ..base_config()
+        };
+        let player_config = GameStackPerPlayerPlayerConfig {
+            gas_capacity: Power::from(500),
+            nuclear_capacity: Power::from(200),
+            battery_capacity: Energy::from(0),
+            renewable_capacity: Power::from(0),
+        };
+
+        let result = base.generate_plants(
+            player_config,
+            DeliveryPeriodId::from(0),
+            DeliveryPeriodId::from(4),
+            &mut seeded_rng(0),
+        );
+
+        let Err(RegisterPlayerStackError::BudgetExceeded { cost, budget }) = result else {
+            unreachable!("Portfolio should exceed the budget");
+        };
+        assert_eq!(cost, Money::from(500 * 100 + 200 * 400));
+        assert_eq!(budget, Money::from(100_000));
+    }
+
+    #[test]
+    fn test_generate_stack_within_budget_schedules_capex_over_the_game() {
+        let base = GameStackPerPlayerBaseConfig {
+            budget: Some(Money::from(100_000)),
+            ..base_config()
+        };
+        let player_config = GameStackPerPlayerPlayerConfig {
+            gas_capacity: Power::from(200),
+            nuclear_capacity: Power::from(100),
+            battery_capacity: Energy::from(0),
+            renewable_capacity: Power::from(0),
+        };
+
+        let snapshot = base
+            .generate_plants(
+                player_config,
+                DeliveryPeriodId::from(0),
+                DeliveryPeriodId::from(4),
+                &mut seeded_rng(0),
+            )
+            .unwrap()
+            .snapshot();
+
+        for plant in snapshot.values() {
+            match plant.name.as_str() {
+                "gas" => assert_eq!(
+                    plant.capex,
+                    Some(CapexSchedule::new(Money::from(200 * 100), 4))
+                ),
+                "nuclear" => assert_eq!(
+                    plant.capex,
+                    Some(CapexSchedule::new(Money::from(100 * 400), 4))
+                ),
+                _ => assert_eq!(plant.capex, None),
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate_stack_of_late_joiner_schedules_capex_over_the_remaining_periods() {
+        let base = base_config();
+        let player_config = GameStackPerPlayerPlayerConfig {
+            gas_capacity: Power::from(200),
+            nuclear_capacity: Power::from(0),
+            battery_capacity: Energy::from(0),
+            renewable_capacity: Power::from(0),
+        };
+
+        let snapshot = base
+            .generate_plants(
+                player_config,
+                DeliveryPeriodId::from(3),
+                DeliveryPeriodId::from(4),
+                &mut seeded_rng(0),
+            )
+            .unwrap()
+            .snapshot();
+
+        let gas = snapshot.values().find(|plant| plant.name == "gas").unwrap();
+        assert_eq!(
+            gas.capex,
+            Some(CapexSchedule::new(Money::from(200 * 100), 1))
+        );
+    }
+
+    #[test]
+    fn test_plant_investment_capex_paid_over_remaining_periods() {
+        let base = base_config();
+
+        let investment = base
+            .plant_investment(
+                &PlayerInvestmentConfig::GasPlant {
+                    capacity: Power::from(200),
+                },
+                DeliveryPeriodId::from(2),
+                DeliveryPeriodId::from(6),
+                &mut seeded_rng(0),
+            )
+            .unwrap();
+
+        assert_eq!(investment.name, "gas");
+        assert_eq!(
+            investment.definition,
+            PlantDefinition::GasPlant {
+                energy_cost: EnergyCost::from(70),
+                capacity: Power::from(200),
+            }
+        );
+        assert_eq!(investment.environment.carbon_price, CarbonPrice::from(50));
+        assert_eq!(investment.build_delay, 1);
+        assert_eq!(
+            investment.capex,
+            CapexSchedule::new(Money::from(200 * 100), 4)
+        );
+    }
+
+    #[test]
+    fn test_plant_investment_not_allowed_without_investment_config() {
+        let base = GameStackPerPlayerBaseConfig {
+            investment: None,
+            ..base_config()
+        };
+
+        let result = base.plant_investment(
+            &PlayerInvestmentConfig::Nuclear {
+                capacity: Power::from(200),
+            },
+            DeliveryPeriodId::from(1),
+            DeliveryPeriodId::from(6),
+            &mut seeded_rng(0),
+        );
+
+        assert_eq!(
+            result,
+            Err(PlantInvestmentError::GameConfigDoesNotAllowInvestment)
+        );
+    }
+
+    #[test]
+    fn test_plant_investment_rejected_when_built_after_game_end() {
+        let base = base_config();
+
+        let result = base.plant_investment(
+            &PlayerInvestmentConfig::RenewablePlant {
+                capacity: Power::from(200),
+            },
+            DeliveryPeriodId::from(5),
+            DeliveryPeriodId::from(6),
+            &mut seeded_rng(0),
+        );
+
+        assert_eq!(
+            result,
+            Err(PlantInvestmentError::PlantWouldNotOperateBeforeGameEnd)
+        );
+    }
+
+    #[test]
+    fn test_plant_investment_rejects_non_positive_capacity() {
+        let base = base_config();
+
+        let result = base.plant_investment(
+            &PlayerInvestmentConfig::Battery {
+                capacity: Energy::from(-100),
+            },
+            DeliveryPeriodId::from(1),
+            DeliveryPeriodId::from(6),
+            &mut seeded_rng(0),
+        );
+
+        assert_eq!(result, Err(PlantInvestmentError::InvalidCapacity));
+    }
+
+    #[test]
+    fn test_plant_investment_rejects_capacity_above_maximum() {
+        let base = base_config();
+
+        let result = base.plant_investment(
+            &PlayerInvestmentConfig::GasPlant {
+                capacity: base.gas_max_capacity + Power::from(1),
+            },
+            DeliveryPeriodId::from(1),
+            DeliveryPeriodId::from(6),
+            &mut seeded_rng(0),
+        );
+
+        assert_eq!(result, Err(PlantInvestmentError::InvalidCapacity));
+    }
+
+    #[test]
+    fn test_plant_investment_rejected_when_budget_exceeded() {
+        let base = GameStackPerPlayerBaseConfig {
+            budget: Some(Money::from(10_000)),
+            ..base_config()
+        };
+
+        let result = base.plant_investment(
+            &PlayerInvestmentConfig::GasPlant {
+                capacity: Power::from(200),
+            },
+            DeliveryPeriodId::from(1),
+            DeliveryPeriodId::from(6),
+            &mut seeded_rng(0),
+        );
+
+        assert_eq!(
+            result,
+            Err(PlantInvestmentError::BudgetExceeded {
+                cost: Money::from(200 * 100),
+                budget: Money::from(10_000),
+            })
+        );
+    }
+
+    #[test]
+    fn test_plant_investment_rejects_overflowing_capex() {
+        let base = GameStackPerPlayerBaseConfig {
+            nuclear_max_capacity: Power::from(i32::MAX),
+            capacity_costs: CapacityCosts {
+                nuclear: CapacityCost::from(2),
+                ..base_config().capacity_costs
+            },
+            ..base_config()
+        };
+
+        let result = base.plant_investment(
+            &PlayerInvestmentConfig::Nuclear {
+                capacity: Power::from(i32::MAX),
+            },
+            DeliveryPeriodId::from(1),
+            DeliveryPeriodId::from(6),
+            &mut seeded_rng(0),
+        );
+
+        assert_eq!(result, Err(PlantInvestmentError::CapexOverflow));
+    }
 }
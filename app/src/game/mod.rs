@@ -1,11 +1,11 @@
-use std::{collections::HashMap, time::Duration};
+use std::{collections::HashMap, mem, time::Duration};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeDelta, Utc};
 use delivery_period::{DeliveryPeriodId, DeliveryPeriodResults};
 use derive_more::{AsRef, Display, From};
 use petname::petname;
 use scores::PlayerScore;
-use serde::{Serialize, ser::SerializeStruct};
+use serde::{Deserialize, Serialize, ser::SerializeStruct};
 use tokio::sync::{
     mpsc::{self},
     oneshot, watch,
@@ -13,11 +13,19 @@ use tokio::sync::{
 
 use crate::{
     game::{
-        infra::stack_config::{GameStackConfig, GameStackPerPlayerPlayerConfig},
+        infra::stack_config::{
+            GameStackConfig, GameStackPerPlayerPlayerConfig, PlayerInvestmentConfig,
+        },
+        infra::{archive::ArchivedGame, results::GameResults},
         scores::PlayerDetailedScore,
     },
-    plants::infra::{StackContext, StackService},
-    player::{PlayerId, PlayerName, PlayerResultView},
+    plants::{
+        PlantId,
+        infra::{StackContext, StackService},
+        investment::PlantLifecycleError,
+    },
+    player::{PlayerEmissionsResultView, PlayerId, PlayerName, PlayerResultView},
+    utils::units::Money,
 };
 
 pub mod delivery_period;
@@ -31,6 +39,8 @@ pub struct Player {
     id: PlayerId,
     name: PlayerName,
     ready: bool,
+    /// Secret required to join the player's team, in team games
+    team_secret: Option<TeamSecret>,
 }
 
 #[derive(Debug)]
@@ -41,6 +51,7 @@ pub enum GetPreviousScoresResult {
     },
     PlayersRanking {
         scores: Vec<PlayerResultView>,
+        emissions: Option<Vec<PlayerEmissionsResultView>>,
     },
 }
 
@@ -48,6 +59,8 @@ pub enum GetPreviousScoresResult {
 pub enum GameMessage {
     RegisterPlayer {
         name: PlayerName,
+        /// Secret of the team to join, in team games
+        team_secret: Option<TeamSecret>,
         tx_back: oneshot::Sender<RegisterPlayerResponse>,
     },
     RegisterPlayerStackConfig {
@@ -55,6 +68,18 @@ pub enum GameMessage {
         config: GameStackPerPlayerPlayerConfig,
         tx_back: oneshot::Sender<Result<StackContext<StackService>, RegisterPlayerStackError>>,
     },
+    /// Build a new plant in the player's stack, only between delivery periods
+    InvestInPlant {
+        player: PlayerId,
+        investment: PlayerInvestmentConfig,
+        tx_back: oneshot::Sender<Result<PlantId, PlantInvestmentError>>,
+    },
+    /// Decommission a plant of the player's stack, only between delivery periods
+    RetirePlant {
+        player: PlayerId,
+        plant: PlantId,
+        tx_back: oneshot::Sender<Result<(), PlantInvestmentError>>,
+    },
     PlayerIsReady(PlayerId),
     DeliveryPeriodResults(DeliveryPeriodResults),
     PostDeliveryPeriodEnded(DeliveryPeriodId),
@@ -65,12 +90,84 @@ pub enum GameMessage {
     GetReadiness {
         tx_back: oneshot::Sender<HashMap<PlayerName, bool>>,
     },
+    /// Timeline of the game, only once the game has ended
+    GetArchive {
+        tx_back: oneshot::Sender<Option<ArchivedGame>>,
+    },
+    /// Scores of all players for each delivery period so far, and their current ranks
+    GetResults {
+        tx_back: oneshot::Sender<GameResults>,
+    },
+    /// Command from the host of the game, rejected if `token` is not the game's host token
+    HostCommand {
+        token: HostToken,
+        command: HostCommand,
+        tx_back: oneshot::Sender<Result<HostCommandOutcome, HostCommandError>>,
+    },
+}
+
+/// Commands reserved to the host of a game, to keep it on schedule when players go idle.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HostCommand {
+    /// Start the next delivery period without waiting for all players to be ready, or end the
+    /// game if the last period has been played
+    ForceStartPeriod,
+    /// Close the running delivery period without waiting for all players to be ready
+    ForceClosePeriod,
+    /// Push back the end of the current phase, for games with timers
+    ExtendTimer(Duration),
+    /// End the game, a running delivery period is closed but not scored
+    EndGame,
+    KickPlayer(PlayerName),
+    /// Freeze the current phase, its timer and the players' market and stack actions
+    Pause,
+    /// Resume a paused phase with the time that was remaining when it was paused
+    Resume,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum HostCommandOutcome {
+    Applied,
+    PlayerKicked(PlayerId),
+}
+
+#[derive(Debug, Display, thiserror::Error, PartialEq)]
+pub enum HostCommandError {
+    InvalidHostToken,
+    NotAllowedInCurrentState,
+    GameHasNoTimer,
+    /// The extended timer would end beyond the representable dates
+    TimerOverflow,
+    PlayerNotFound,
 }
 
 #[derive(Debug, Display, thiserror::Error)]
 pub enum RegisterPlayerStackError {
     PlayerDoesNotExist,
     GameConfigDoesNotAllowPerPlayerStack,
+    #[display("Portfolio capex {cost} exceeds the budget {budget}")]
+    BudgetExceeded {
+        cost: Money,
+        budget: Money,
+    },
+    CapexOverflow,
+}
+
+#[derive(Debug, Display, thiserror::Error, PartialEq)]
+pub enum PlantInvestmentError {
+    GameConfigDoesNotAllowInvestment,
+    NotBetweenDeliveryPeriods,
+    PlayerHasNoStack,
+    InvalidCapacity,
+    CapexOverflow,
+    #[display("Plant capex {cost} exceeds the budget {budget}")]
+    BudgetExceeded {
+        cost: Money,
+        budget: Money,
+    },
+    PlantWouldNotOperateBeforeGameEnd,
+    #[display("{_0}")]
+    Stack(PlantLifecycleError),
 }
 
 #[derive(Debug)]
@@ -78,9 +175,18 @@ pub enum RegisterPlayerResponse {
     Success {
         id: PlayerId,
         stack: Option<StackContext<StackService>>,
+        /// Secret to share with the other members of the team, in team games
+        team_secret: Option<TeamSecret>,
     },
     PlayerAlreadyExist,
     GameStarted,
+    GameFull,
+    /// In team games, a new session joining an existing team shares its id and stack
+    JoinedTeam {
+        id: PlayerId,
+    },
+    /// In team games, a new session gave a wrong secret to join an existing team
+    InvalidTeamSecret,
 }
 
 #[derive(Debug, PartialEq, Clone, Display)]
@@ -101,6 +207,37 @@ pub enum GameState {
     },
     /// Game has ended.
     Ended(DeliveryPeriodId),
+    /// Game is paused by its host during a [GameState::Running] or [GameState::PostDelivery]
+    /// phase, with the time that was remaining in the phase for timed games.
+    #[display("Paused")]
+    Paused {
+        phase: Box<GameState>,
+        remaining: Option<Duration>,
+    },
+}
+
+impl GameState {
+    /// End of the current phase of the game, if it is timed.
+    pub fn end_at(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Running { end_at, .. } | Self::PostDelivery { end_at, .. } => *end_at,
+            Self::Open | Self::Ended(_) | Self::Paused { .. } => None,
+        }
+    }
+
+    pub fn delivery_period(&self) -> DeliveryPeriodId {
+        match self {
+            Self::Open => DeliveryPeriodId::from(0),
+            Self::Running { period, .. }
+            | Self::PostDelivery { period, .. }
+            | Self::Ended(period) => *period,
+            Self::Paused { phase, .. } => phase.delivery_period(),
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        matches!(self, Self::Paused { .. })
+    }
 }
 
 impl Serialize for GameState {
@@ -108,26 +245,26 @@ impl Serialize for GameState {
     where
         S: serde::Serializer,
     {
-        let mut state = serializer.serialize_struct("GameState", 4)?;
+        // A paused game is seen as its paused phase, without an end, flagged as paused
+        let (phase, paused) = match self {
+            Self::Paused { phase, remaining } => (phase.as_ref(), Some(remaining)),
+            state => (state, None),
+        };
+        let mut state = serializer.serialize_struct("GameState", 6)?;
         state.serialize_field("type", "GameState")?;
         state.serialize_field(
             "state",
-            match self {
+            match phase {
                 Self::Running { .. } => "Running",
                 Self::Open => "Open",
                 Self::PostDelivery { .. } => "PostDelivery",
                 Self::Ended(_) => "Ended",
+                Self::Paused { .. } => "Paused",
             },
         )?;
-        let period = match self {
-            Self::Running { period, .. }
-            | Self::PostDelivery { period, .. }
-            | Self::Ended(period) => *period,
-            Self::Open => DeliveryPeriodId::from(0),
-        };
-        state.serialize_field("delivery_period", &period)?;
+        state.serialize_field("delivery_period", &phase.delivery_period())?;
 
-        match self {
+        match phase {
             Self::Running {
                 end_at: Some(end_at),
                 ..
@@ -143,13 +280,22 @@ impl Serialize for GameState {
             }
         }
 
+        if let Some(remaining) = paused {
+            state.serialize_field("paused", &true)?;
+            state.serialize_field(
+                "remaining_seconds",
+                &remaining.map(|remaining| remaining.as_secs()),
+            )?;
+        }
+
         state.end()
     }
 }
 
+use rand::Rng;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Display, From, AsRef, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Display, From, AsRef, Serialize, Deserialize)]
 #[serde(transparent)]
 #[from(String, &str)]
 #[as_ref(str)]
@@ -173,12 +319,74 @@ impl Default for GameId {
     }
 }
 
+/// Secret handed to the creator of a game, required to send [HostCommand]s.
+#[derive(Debug, Clone, PartialEq, Eq, Display, From, Serialize, Deserialize)]
+#[serde(transparent)]
+#[from(String, &str)]
+pub struct HostToken(String);
+
+impl Default for HostToken {
+    fn default() -> Self {
+        HostToken(Uuid::new_v4().to_string())
+    }
+}
+
+/// Characters of the join codes, without the ones easily mistaken for each other (0/O, 1/I)
+const JOIN_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const JOIN_CODE_LENGTH: usize = 6;
+
+/// Short code required to join a private game, meant to be read aloud or written on a board.
+#[derive(Debug, Clone, PartialEq, Eq, Display, From, Serialize, Deserialize)]
+#[serde(transparent)]
+#[from(String, &str)]
+pub struct JoinCode(String);
+
+impl JoinCode {
+    /// Whether `code` matches, ignoring case and surrounding whitespaces.
+    pub fn matches(&self, code: &str) -> bool {
+        self.0.eq_ignore_ascii_case(code.trim())
+    }
+}
+
+impl Default for JoinCode {
+    fn default() -> Self {
+        JoinCode(random_code())
+    }
+}
+
+fn random_code() -> String {
+    let mut rng = rand::rng();
+    (0..JOIN_CODE_LENGTH)
+        .map(|_| char::from(JOIN_CODE_ALPHABET[rng.random_range(0..JOIN_CODE_ALPHABET.len())]))
+        .collect()
+}
+
+/// Secret handed to the first member of a team, required for other sessions to join the team.
+/// Like [JoinCode]s, it is meant to be read aloud to the other members.
+#[derive(Debug, Clone, PartialEq, Eq, Display, From, Serialize, Deserialize)]
+#[serde(transparent)]
+#[from(String, &str)]
+pub struct TeamSecret(String);
+
+impl TeamSecret {
+    /// Whether `secret` matches, ignoring case and surrounding whitespaces.
+    pub fn matches(&self, secret: &TeamSecret) -> bool {
+        self.0.eq_ignore_ascii_case(secret.0.trim())
+    }
+}
+
+impl Default for TeamSecret {
+    fn default() -> Self {
+        TeamSecret(random_code())
+    }
+}
+
 #[derive(Debug, Display, thiserror::Error)]
 pub enum NewGameNameError {
     EmptyName,
 }
 
-#[derive(Debug, Clone, PartialEq, Hash, Display, From, Serialize)]
+#[derive(Debug, Clone, PartialEq, Hash, Display, From, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct GameName(String);
 impl GameName {
@@ -202,7 +410,15 @@ pub enum GameEvent {
         id: PlayerId,
         name: PlayerName,
     },
+    PlayerKicked {
+        id: PlayerId,
+    },
+    TeamMemberJoined {
+        id: PlayerId,
+    },
     StateUpdated(GameState),
+    Paused,
+    Resumed,
     DeliveryPeriodStarted {
         id: DeliveryPeriodId,
     },
@@ -214,23 +430,78 @@ pub enum GameEvent {
     },
 }
 
+/// Durations of the phases of the delivery periods, a phase without duration lasts until all
+/// players are ready.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PhaseDurations {
+    /// Duration of the trading phase
+    pub running: Option<Duration>,
+    /// Duration of the post delivery phase, same as the trading phase if not set
+    pub post_delivery: Option<Duration>,
+    /// Durations of the trading phase of the first delivery periods, e.g. for a longer first
+    /// period, the following periods last `running`
+    pub schedule: Vec<Duration>,
+}
+
+impl PhaseDurations {
+    /// Same duration for all the phases of all the delivery periods.
+    pub fn uniform(duration: Option<Duration>) -> Self {
+        PhaseDurations {
+            running: duration,
+            ..PhaseDurations::default()
+        }
+    }
+
+    pub fn running(&self, period: DeliveryPeriodId) -> Option<Duration> {
+        usize::from(period)
+            .checked_sub(1)
+            .and_then(|index| self.schedule.get(index))
+            .copied()
+            .or(self.running)
+    }
+
+    pub fn post_delivery(&self) -> Option<Duration> {
+        self.post_delivery.or(self.running)
+    }
+}
+
+/// End of a phase lasting `duration` from now, none if it is too far away to be represented.
+pub fn deadline_after(duration: Duration) -> Option<DateTime<Utc>> {
+    TimeDelta::from_std(duration)
+        .ok()
+        .and_then(|duration| Utc::now().checked_add_signed(duration))
+}
+
+/// Number of players a game accepts, and needs before its first delivery period can start.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayerLimits {
+    pub min: Option<usize>,
+    pub max: Option<usize>,
+}
+
 pub struct Game {
     state: GameState,
     players: Vec<Player>,
     last_delivery_period: DeliveryPeriodId,
-    delivery_period_duration: Option<Duration>,
+    durations: PhaseDurations,
+    /// Whether players can still join once the game has started
+    allow_late_join: bool,
+    /// Whether sessions joining with the name of an existing player join its team, sharing its
+    /// stack, market identity and readiness
+    team_play: bool,
+    player_limits: PlayerLimits,
 }
 
 impl Game {
-    pub fn init(
-        last_delivery_period: DeliveryPeriodId,
-        delivery_period_duration: Option<Duration>,
-    ) -> Self {
+    pub fn init(last_delivery_period: DeliveryPeriodId, durations: PhaseDurations) -> Self {
         Self {
             state: GameState::Open,
             players: Vec::new(),
             last_delivery_period,
-            delivery_period_duration,
+            durations,
+            allow_late_join: false,
+            team_play: false,
+            player_limits: PlayerLimits::default(),
         }
     }
 
@@ -238,33 +509,98 @@ impl Game {
         state: GameState,
         players: Vec<Player>,
         last_delivery_period: DeliveryPeriodId,
-        delivery_period_duration: Option<Duration>,
+        durations: PhaseDurations,
     ) -> Self {
         Self {
             state,
             players,
             last_delivery_period,
-            delivery_period_duration,
+            durations,
+            allow_late_join: false,
+            team_play: false,
+            player_limits: PlayerLimits::default(),
         }
     }
 
+    /// Let players join the game while it is running, they play from the next delivery period to
+    /// start.
+    pub fn with_late_join(mut self, allow_late_join: bool) -> Self {
+        self.allow_late_join = allow_late_join;
+        self
+    }
+
+    /// Let several sessions play as the same player, a team, by joining with its name.
+    pub fn with_team_play(mut self, team_play: bool) -> Self {
+        self.team_play = team_play;
+        self
+    }
+
+    /// Cap the number of players, and keep the game open until enough players joined, even if
+    /// they are all ready. The host can still force the game to start.
+    pub fn with_player_limits(mut self, player_limits: PlayerLimits) -> Self {
+        self.player_limits = player_limits;
+        self
+    }
+
+    /// Register a player, or in team games, a new member of an existing team if `team_secret`
+    /// matches the team's secret.
     pub fn try_register_player(
         &mut self,
         name: PlayerName,
+        team_secret: Option<&TeamSecret>,
     ) -> Result<Vec<GameEvent>, RegisterPlayerError> {
-        if self.state != GameState::Open {
-            return Err(RegisterPlayerError::GameStarted);
+        let team = self.players.iter().find(|player| player.name == name);
+        match (&self.state, team) {
+            (GameState::Ended(_), _) => return Err(RegisterPlayerError::GameStarted),
+            // Members can join their team until the game ends
+            (_, Some(team)) if self.team_play => {
+                let secret_matches = team
+                    .team_secret
+                    .as_ref()
+                    .zip(team_secret)
+                    .is_some_and(|(expected, given)| expected.matches(given));
+                if !secret_matches {
+                    return Err(RegisterPlayerError::InvalidTeamSecret);
+                }
+                return Ok(vec![GameEvent::TeamMemberJoined {
+                    id: team.id.clone(),
+                }]);
+            }
+            (GameState::Open, _) => {}
+            (
+                GameState::Running { .. }
+                | GameState::PostDelivery { .. }
+                | GameState::Paused { .. },
+                _,
+            ) if self.allow_late_join => {}
+            _ => return Err(RegisterPlayerError::GameStarted),
         }
 
-        if self.players.iter().any(|player| player.name == name) {
+        if team.is_some() {
             return Err(RegisterPlayerError::NameAlreadyExists);
         }
+        if self
+            .player_limits
+            .max
+            .is_some_and(|max| self.players.len() >= max)
+        {
+            return Err(RegisterPlayerError::GameFull);
+        }
+
+        // A player joining a running period has no stack dispatched for it, they are left out of
+        // its readiness check and only wait for the next phase like the others
+        let phase = match &self.state {
+            GameState::Paused { phase, .. } => phase.as_ref(),
+            state => state,
+        };
+        let joins_running_period = matches!(phase, GameState::Running { .. });
 
         let id = PlayerId::default();
         let player = Player {
             id: id.clone(),
             name: name.clone(),
-            ready: false,
+            ready: joins_running_period,
+            team_secret: self.team_play.then(TeamSecret::default),
         };
 
         self.players.push(player);
@@ -283,12 +619,18 @@ impl Game {
         };
         player.ready = true;
 
-        if !self.all_players_ready() {
+        // A paused game waits to be resumed before moving to its next phase
+        if !self.all_players_ready() || !self.has_enough_players() || self.state.is_paused() {
             return vec![GameEvent::PlayersReadinessChanged {
                 readiness: self.players_readines(),
             }];
         }
 
+        self.advance()
+    }
+
+    /// Move the game to its next phase, as when all players are ready.
+    fn advance(&mut self) -> Vec<GameEvent> {
         let mut events = vec![];
         match &self.state {
             GameState::Open | GameState::PostDelivery { .. } => {
@@ -298,9 +640,10 @@ impl Game {
 
                 self.reset_players_readiness();
 
+                let period = self.next_delivery_period();
                 self.state = GameState::Running {
-                    period: self.next_delivery_period(),
-                    end_at: self.period_end_at(),
+                    period,
+                    end_at: self.running_end_at(period),
                 };
                 events.push(GameEvent::DeliveryPeriodStarted {
                     id: self.current_delivery_period(),
@@ -310,7 +653,7 @@ impl Game {
                 self.reset_players_readiness();
                 self.state = GameState::PostDelivery {
                     period: self.current_delivery_period(),
-                    end_at: self.period_end_at(),
+                    end_at: self.post_delivery_end_at(),
                 };
                 events.push(GameEvent::DeliveryPeriodEnded {
                     id: self.current_delivery_period(),
@@ -328,20 +671,158 @@ impl Game {
         events
     }
 
-    pub fn current_delivery_period(&self) -> DeliveryPeriodId {
-        match &self.state {
-            GameState::Open => DeliveryPeriodId::from(0),
-            GameState::Ended(period) => *period,
-            GameState::Running { period, .. } | GameState::PostDelivery { period, .. } => *period,
+    pub fn apply_host_command(
+        &mut self,
+        command: HostCommand,
+    ) -> Result<Vec<GameEvent>, HostCommandError> {
+        match command {
+            HostCommand::ForceStartPeriod => match self.state {
+                GameState::Open | GameState::PostDelivery { .. } => Ok(self.advance()),
+                _ => Err(HostCommandError::NotAllowedInCurrentState),
+            },
+            HostCommand::ForceClosePeriod => match self.state {
+                GameState::Running { .. } => Ok(self.advance()),
+                _ => Err(HostCommandError::NotAllowedInCurrentState),
+            },
+            HostCommand::ExtendTimer(duration) => self.extend_timer(duration),
+            HostCommand::EndGame => self.end_game_early(),
+            HostCommand::KickPlayer(name) => self.kick_player(&name),
+            HostCommand::Pause => self.pause(),
+            HostCommand::Resume => self.resume(),
+        }
+    }
+
+    fn pause(&mut self) -> Result<Vec<GameEvent>, HostCommandError> {
+        let (GameState::Running { end_at, .. } | GameState::PostDelivery { end_at, .. }) =
+            &mut self.state
+        else {
+            return Err(HostCommandError::NotAllowedInCurrentState);
+        };
+        let remaining = end_at
+            .take()
+            .map(|end_at| (end_at - Utc::now()).to_std().unwrap_or_default());
+        let phase = mem::replace(&mut self.state, GameState::Open);
+        self.state = GameState::Paused {
+            phase: Box::new(phase),
+            remaining,
+        };
+        Ok(vec![
+            GameEvent::Paused,
+            GameEvent::StateUpdated(self.state.clone()),
+        ])
+    }
+
+    fn resume(&mut self) -> Result<Vec<GameEvent>, HostCommandError> {
+        let GameState::Paused { phase, remaining } = &self.state else {
+            return Err(HostCommandError::NotAllowedInCurrentState);
+        };
+        let mut phase = phase.as_ref().clone();
+        if let GameState::Running { end_at, .. } | GameState::PostDelivery { end_at, .. } =
+            &mut phase
+        {
+            *end_at = remaining.and_then(deadline_after);
+        }
+        self.state = phase;
+
+        let mut events = vec![GameEvent::Resumed];
+        // Players may have all become ready while the game was paused
+        if !self.players.is_empty() && self.all_players_ready() && self.has_enough_players() {
+            events.extend(self.advance());
+        } else {
+            events.push(GameEvent::StateUpdated(self.state.clone()));
         }
+        Ok(events)
+    }
+
+    fn extend_timer(&mut self, duration: Duration) -> Result<Vec<GameEvent>, HostCommandError> {
+        match &mut self.state {
+            GameState::Running {
+                end_at: Some(end_at),
+                ..
+            }
+            | GameState::PostDelivery {
+                end_at: Some(end_at),
+                ..
+            } => {
+                *end_at = TimeDelta::from_std(duration)
+                    .ok()
+                    .and_then(|duration| end_at.checked_add_signed(duration))
+                    .ok_or(HostCommandError::TimerOverflow)?;
+            }
+            GameState::Paused {
+                remaining: Some(remaining),
+                ..
+            } => {
+                *remaining = remaining
+                    .checked_add(duration)
+                    .ok_or(HostCommandError::TimerOverflow)?;
+            }
+            GameState::Running { .. }
+            | GameState::PostDelivery { .. }
+            | GameState::Paused { .. } => {
+                return Err(HostCommandError::GameHasNoTimer);
+            }
+            GameState::Open | GameState::Ended(_) => {
+                return Err(HostCommandError::NotAllowedInCurrentState);
+            }
+        }
+        Ok(vec![GameEvent::StateUpdated(self.state.clone())])
+    }
+
+    fn end_game_early(&mut self) -> Result<Vec<GameEvent>, HostCommandError> {
+        if let GameState::Paused { phase, .. } = &self.state {
+            self.state = phase.as_ref().clone();
+        }
+        match self.state {
+            GameState::Ended(_) => Err(HostCommandError::NotAllowedInCurrentState),
+            GameState::Running { period, .. } => {
+                // Close the market and stacks, the game ends on the last scored period
+                self.state = GameState::Ended(period.previous());
+                Ok(vec![
+                    GameEvent::DeliveryPeriodEnded { id: period },
+                    GameEvent::StateUpdated(self.state.clone()),
+                ])
+            }
+            GameState::Open | GameState::PostDelivery { .. } => Ok(self.end_game()),
+            GameState::Paused { .. } => Err(HostCommandError::NotAllowedInCurrentState),
+        }
+    }
+
+    fn kick_player(&mut self, name: &PlayerName) -> Result<Vec<GameEvent>, HostCommandError> {
+        if let GameState::Ended(_) = self.state {
+            return Err(HostCommandError::NotAllowedInCurrentState);
+        }
+        let Some(position) = self.players.iter().position(|p| p.name == *name) else {
+            return Err(HostCommandError::PlayerNotFound);
+        };
+        let player = self.players.remove(position);
+
+        let mut events = vec![GameEvent::PlayerKicked { id: player.id }];
+        // The kicked player may have been the last one the others were waiting for
+        if !self.players.is_empty()
+            && self.all_players_ready()
+            && self.has_enough_players()
+            && !self.state.is_paused()
+        {
+            events.extend(self.advance());
+        } else {
+            events.push(GameEvent::PlayersReadinessChanged {
+                readiness: self.players_readines(),
+            });
+        }
+        Ok(events)
+    }
+
+    pub fn current_delivery_period(&self) -> DeliveryPeriodId {
+        self.state.delivery_period()
     }
     fn next_delivery_period(&self) -> DeliveryPeriodId {
         match &self.state {
             GameState::Open => DeliveryPeriodId::from(1),
             GameState::Ended(period) => *period,
-            GameState::Running { period, .. } | GameState::PostDelivery { period, .. } => {
-                period.next()
-            }
+            GameState::Running { .. }
+            | GameState::PostDelivery { .. }
+            | GameState::Paused { .. } => self.current_delivery_period().next(),
         }
     }
 
@@ -349,6 +830,16 @@ impl Game {
         self.players.iter().all(|player| player.ready)
     }
 
+    /// Whether the game has the players it needs to start, once started it goes on whatever its
+    /// number of players.
+    fn has_enough_players(&self) -> bool {
+        self.state != GameState::Open
+            || self
+                .player_limits
+                .min
+                .is_none_or(|min| self.players.len() >= min)
+    }
+
     fn reset_players_readiness(&mut self) {
         for player in self.players.iter_mut() {
             player.ready = false;
@@ -359,9 +850,28 @@ impl Game {
         HashMap::from_iter(self.players.iter().map(|p| (p.name.clone(), p.ready)))
     }
 
-    fn period_end_at(&self) -> Option<DateTime<Utc>> {
-        self.delivery_period_duration
-            .map(|period| Utc::now() + period)
+    /// Secret required to join a player's team, in team games.
+    fn team_secret(&self, id: &PlayerId) -> Option<TeamSecret> {
+        self.players
+            .iter()
+            .find(|player| player.id == *id)
+            .and_then(|player| player.team_secret.clone())
+    }
+
+    /// Ids and names of the players, in registration order.
+    fn players_ids_and_names(&self) -> Vec<(PlayerId, PlayerName)> {
+        self.players
+            .iter()
+            .map(|p| (p.id.clone(), p.name.clone()))
+            .collect()
+    }
+
+    fn running_end_at(&self, period: DeliveryPeriodId) -> Option<DateTime<Utc>> {
+        self.durations.running(period).and_then(deadline_after)
+    }
+
+    fn post_delivery_end_at(&self) -> Option<DateTime<Utc>> {
+        self.durations.post_delivery().and_then(deadline_after)
     }
 
     fn end_game(&mut self) -> Vec<GameEvent> {
@@ -374,7 +884,10 @@ impl Game {
         results: &DeliveryPeriodResults,
     ) -> Vec<GameEvent> {
         match &self.state {
-            GameState::Open | GameState::PostDelivery { .. } | GameState::Ended(..) => {
+            GameState::Open
+            | GameState::PostDelivery { .. }
+            | GameState::Ended(..)
+            | GameState::Paused { .. } => {
                 vec![]
             }
             GameState::Running { period, .. } => {
@@ -384,7 +897,7 @@ impl Game {
                 self.reset_players_readiness();
                 self.state = GameState::PostDelivery {
                     period: self.current_delivery_period(),
-                    end_at: self.period_end_at(),
+                    end_at: self.post_delivery_end_at(),
                 };
                 vec![
                     GameEvent::DeliveryPeriodEnded {
@@ -415,9 +928,10 @@ impl Game {
         }
 
         self.reset_players_readiness();
+        let period = self.next_delivery_period();
         self.state = GameState::Running {
-            period: self.next_delivery_period(),
-            end_at: self.period_end_at(),
+            period,
+            end_at: self.running_end_at(period),
         };
         vec![
             GameEvent::DeliveryPeriodStarted {
@@ -435,6 +949,8 @@ impl Game {
 pub enum RegisterPlayerError {
     NameAlreadyExists,
     GameStarted,
+    GameFull,
+    InvalidTeamSecret,
 }
 
 #[derive(Debug, Clone)]
@@ -443,12 +959,23 @@ pub struct GameContext {
     pub name: GameName,
     pub stack: GameStackConfig,
     pub last_delivery_period: DeliveryPeriodId,
+    /// Code required to join the game, which is hidden from the games listing, if private
+    pub join_code: Option<JoinCode>,
+    /// Secret of the game's host, also required to watch the game or read its live results
+    pub host_token: HostToken,
+    pub player_limits: PlayerLimits,
+    pub durations: PhaseDurations,
+    pub created_at: DateTime<Utc>,
     pub tx: mpsc::Sender<GameMessage>,
     pub state_rx: watch::Receiver<GameState>,
+    /// Ids and names of the players registered to the game, teams counting as a single player
+    pub players_rx: watch::Receiver<Vec<(PlayerId, PlayerName)>>,
 }
 
 #[cfg(test)]
 mod test_game_state {
+    use std::time::Duration;
+
     use chrono::Utc;
 
     use crate::game::GameState;
@@ -490,6 +1017,21 @@ mod test_game_state {
             "{\"type\":\"GameState\",\"state\":\"Ended\",\"delivery_period\":3,\"end_at\":\"None\"}".to_string()
         );
     }
+    #[test]
+    fn test_paused_game_state_serialize() {
+        assert_eq!(
+            serde_json::to_string(&GameState::Paused {
+                phase: Box::new(GameState::Running {
+                    period: DeliveryPeriodId::from(2),
+                    end_at: None
+                }),
+                remaining: Some(Duration::from_secs(42))
+            })
+            .unwrap(),
+            "{\"type\":\"GameState\",\"state\":\"Running\",\"delivery_period\":2,\"end_at\":\"None\",\"paused\":true,\"remaining_seconds\":42}"
+                .to_string()
+        );
+    }
 }
 
 #[cfg(test)]
@@ -550,7 +1092,10 @@ mod test_game {
             state: GameState::Open,
             players: Vec::new(),
             last_delivery_period: DeliveryPeriodId::from(2),
-            delivery_period_duration: None,
+            durations: PhaseDurations::default(),
+            allow_late_join: false,
+            team_play: false,
+            player_limits: PlayerLimits::default(),
         }
     }
 
@@ -561,7 +1106,7 @@ mod test_game {
 
         let player = PlayerName::from("test-player");
 
-        let Ok(events) = game.try_register_player(player.clone()) else {
+        let Ok(events) = game.try_register_player(player.clone(), None) else {
             unreachable!("Should have register the player")
         };
 
@@ -578,7 +1123,7 @@ mod test_game {
 
         let player = PlayerName::from("test-player");
 
-        let Ok(events) = game.try_register_player(player.clone()) else {
+        let Ok(events) = game.try_register_player(player.clone(), None) else {
             unreachable!("Should have register the player")
         };
 
@@ -594,9 +1139,10 @@ mod test_game {
         assert_eq!(game.state, GameState::Open);
 
         let player = PlayerName::from("test-player");
-        let _ = game.try_register_player(player.clone());
+        let _ = game.try_register_player(player.clone(), None);
 
-        let Err(RegisterPlayerError::NameAlreadyExists) = game.try_register_player(player.clone())
+        let Err(RegisterPlayerError::NameAlreadyExists) =
+            game.try_register_player(player.clone(), None)
         else {
             unreachable!("Should have failed to register the player")
         };
@@ -620,13 +1166,208 @@ mod test_game {
             game.state = state;
             let player = PlayerName::from("test-player");
 
-            let Err(RegisterPlayerError::GameStarted) = game.try_register_player(player.clone())
+            let Err(RegisterPlayerError::GameStarted) =
+                game.try_register_player(player.clone(), None)
             else {
                 unreachable!("Should have failed to register the player")
             };
         }
     }
 
+    #[test]
+    fn test_register_late_player_when_allowed() {
+        let mut game = build_empty_game().with_late_join(true);
+
+        for (idx, state) in [
+            GameState::Running {
+                period: DeliveryPeriodId::from(1),
+                end_at: None,
+            },
+            GameState::PostDelivery {
+                period: DeliveryPeriodId::from(1),
+                end_at: None,
+            },
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            game.state = state;
+            let player = PlayerName::from(format!("late-player-{idx}"));
+
+            let Ok(events) = game.try_register_player(player.clone(), None) else {
+                unreachable!("Should have registered the late player")
+            };
+            assert!(matches!(
+                events.first(),
+                Some(GameEvent::PlayerJoined { name, .. }) if *name == player
+            ));
+        }
+
+        game.state = GameState::Ended(DeliveryPeriodId::from(2));
+        let Err(RegisterPlayerError::GameStarted) =
+            game.try_register_player(PlayerName::from("too-late"), None)
+        else {
+            unreachable!("Should not register a player once the game has ended")
+        };
+    }
+
+    #[test]
+    fn test_late_player_left_out_of_running_period_readiness() {
+        let mut game = build_game_with_players().with_late_join(true);
+        game.state = GameState::Running {
+            period: DeliveryPeriodId::from(1),
+            end_at: None,
+        };
+
+        let Ok(events) = game.try_register_player(PlayerName::from("late"), None) else {
+            unreachable!("Should have registered the late player")
+        };
+        assert!(matches!(
+            events.last(),
+            Some(GameEvent::PlayersReadinessChanged { readiness })
+                if readiness.get(&PlayerName::from("late")) == Some(&true)
+        ));
+
+        let _ = game.register_player_ready(&PlayerId::from("p1"));
+        let events = game.register_player_ready(&PlayerId::from("p2"));
+        assert!(matches!(
+            events.first(),
+            Some(GameEvent::DeliveryPeriodEnded { .. })
+        ));
+        assert!(matches!(game.state, GameState::PostDelivery { .. }));
+        assert!(game.players.iter().all(|player| !player.ready));
+    }
+
+    #[test]
+    fn test_late_player_in_post_delivery_not_ready() {
+        let mut game = build_game_with_players().with_late_join(true);
+        game.state = GameState::PostDelivery {
+            period: DeliveryPeriodId::from(1),
+            end_at: None,
+        };
+
+        let Ok(_) = game.try_register_player(PlayerName::from("late"), None) else {
+            unreachable!("Should have registered the late player")
+        };
+        assert!(
+            game.players
+                .iter()
+                .any(|p| p.name == PlayerName::from("late") && !p.ready)
+        );
+    }
+
+    #[test]
+    fn test_join_code() {
+        let code = JoinCode::default();
+        assert_eq!(code.to_string().len(), JOIN_CODE_LENGTH);
+        assert!(
+            code.to_string()
+                .bytes()
+                .all(|c| JOIN_CODE_ALPHABET.contains(&c))
+        );
+        assert!(code.matches(&format!(" {} ", code.to_string().to_lowercase())));
+        assert!(!code.matches(""));
+    }
+
+    #[test]
+    fn test_register_player_game_full() {
+        let mut game = build_empty_game()
+            .with_team_play(true)
+            .with_player_limits(PlayerLimits {
+                min: None,
+                max: Some(1),
+            });
+        game.try_register_player(PlayerName::from("p1"), None)
+            .unwrap();
+
+        let Err(RegisterPlayerError::GameFull) =
+            game.try_register_player(PlayerName::from("p2"), None)
+        else {
+            unreachable!("Should not register more players than the maximum")
+        };
+        // Joining an existing team does not add a player
+        let secret = game.players[0].team_secret.clone();
+        let Ok(_) = game.try_register_player(PlayerName::from("p1"), secret.as_ref()) else {
+            unreachable!("Should have joined the existing team")
+        };
+    }
+
+    #[test]
+    fn test_game_does_not_start_without_enough_players() {
+        let mut game = build_game_with_players().with_player_limits(PlayerLimits {
+            min: Some(3),
+            max: None,
+        });
+
+        game.register_player_ready(&PlayerId::from("p1"));
+        game.register_player_ready(&PlayerId::from("p2"));
+        assert_eq!(game.state, GameState::Open);
+
+        game.try_register_player(PlayerName::from("p3"), None)
+            .unwrap();
+        let id = game.players[2].id.clone();
+        let events = game.register_player_ready(&id);
+        assert!(matches!(
+            get_game_state(&events),
+            Some(GameState::Running { .. })
+        ));
+    }
+
+    #[test]
+    fn test_register_team_member() {
+        let mut game = build_empty_game().with_team_play(true);
+        let team = PlayerName::from("team");
+        let Ok(events) = game.try_register_player(team.clone(), None) else {
+            unreachable!("Should have registered the team")
+        };
+        let Some(GameEvent::PlayerJoined { id, .. }) = events.into_iter().next() else {
+            unreachable!("Should have created the team")
+        };
+        let Some(secret) = game.team_secret(&id) else {
+            unreachable!("The team should have a secret")
+        };
+
+        for state in [
+            GameState::Open,
+            GameState::Running {
+                period: DeliveryPeriodId::from(1),
+                end_at: None,
+            },
+        ] {
+            game.state = state;
+            let Ok(events) = game.try_register_player(team.clone(), Some(&secret)) else {
+                unreachable!("Should have joined the existing team")
+            };
+            assert!(matches!(
+                events.as_slice(),
+                [GameEvent::TeamMemberJoined { id: member_of }] if *member_of == id
+            ));
+            assert_eq!(game.players.len(), 1);
+        }
+
+        game.state = GameState::Ended(DeliveryPeriodId::from(1));
+        let Err(RegisterPlayerError::GameStarted) = game.try_register_player(team, Some(&secret))
+        else {
+            unreachable!("Should not join a team once the game has ended")
+        };
+    }
+
+    #[test]
+    fn test_join_team_requires_its_secret() {
+        let mut game = build_empty_game().with_team_play(true);
+        let team = PlayerName::from("team");
+        game.try_register_player(team.clone(), None).unwrap();
+
+        for secret in [None, Some(TeamSecret::from("wrong"))] {
+            let Err(RegisterPlayerError::InvalidTeamSecret) =
+                game.try_register_player(team.clone(), secret.as_ref())
+            else {
+                unreachable!("Should not join a team without its secret")
+            };
+        }
+        assert_eq!(game.players.len(), 1);
+    }
+
     fn build_game_with_players() -> Game {
         Game {
             state: GameState::Open,
@@ -635,15 +1376,20 @@ mod test_game {
                     id: PlayerId::from("p1"),
                     name: PlayerName::from("p1"),
                     ready: false,
+                    team_secret: None,
                 },
                 Player {
                     id: PlayerId::from("p2"),
                     name: PlayerName::from("p2"),
                     ready: false,
+                    team_secret: None,
                 },
             ],
             last_delivery_period: DeliveryPeriodId::from(2),
-            delivery_period_duration: None,
+            durations: PhaseDurations::default(),
+            allow_late_join: false,
+            team_play: false,
+            player_limits: PlayerLimits::default(),
         }
     }
 
@@ -911,6 +1657,7 @@ mod test_game {
                 PlayerId::from("p1"),
                 PlayerDetailedScore::default(),
             )]),
+            shocks: HashMap::new(),
         }
     }
 
@@ -1048,15 +1795,20 @@ mod test_game {
                     id: PlayerId::from("p1"),
                     name: PlayerName::from("p1"),
                     ready: false,
+                    team_secret: None,
                 },
                 Player {
                     id: PlayerId::from("p2"),
                     name: PlayerName::from("p2"),
                     ready: true,
+                    team_secret: None,
                 },
             ],
             last_delivery_period: DeliveryPeriodId::from(2),
-            delivery_period_duration: None,
+            durations: PhaseDurations::default(),
+            allow_late_join: false,
+            team_play: false,
+            player_limits: PlayerLimits::default(),
         }
     }
 
@@ -1097,6 +1849,48 @@ mod test_game {
         )
     }
 
+    #[test]
+    fn test_phase_durations() {
+        let durations = PhaseDurations {
+            running: Some(Duration::from_secs(60)),
+            post_delivery: None,
+            schedule: vec![Duration::from_secs(120)],
+        };
+        assert_eq!(
+            durations.running(DeliveryPeriodId::from(1)),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            durations.running(DeliveryPeriodId::from(2)),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(durations.post_delivery(), Some(Duration::from_secs(60)));
+        assert_eq!(PhaseDurations::uniform(None).post_delivery(), None);
+    }
+
+    #[test]
+    fn test_phases_end_at_follow_their_own_durations() {
+        let mut game = build_game_with_players();
+        game.durations = PhaseDurations {
+            running: Some(Duration::from_secs(60)),
+            post_delivery: Some(Duration::from_secs(10)),
+            schedule: vec![Duration::from_secs(120)],
+        };
+        let remaining = |game: &Game| {
+            let end_at = game.state.end_at().expect("Phase should be timed");
+            (end_at - Utc::now()).num_seconds()
+        };
+
+        game.advance();
+        assert!((119..=120).contains(&remaining(&game)));
+
+        game.advance();
+        assert!((9..=10).contains(&remaining(&game)));
+
+        game.process_post_delivery_period_ends(&DeliveryPeriodId::from(1));
+        assert!((59..=60).contains(&remaining(&game)));
+    }
+
     #[test]
     fn test_process_post_delivery_periods_ends_wrong_period() {
         let mut game = build_game_post_delivery();
@@ -1123,4 +1917,261 @@ mod test_game {
             assert!(events.is_empty());
         }
     }
+
+    #[test]
+    fn test_host_force_start_period() {
+        let mut game = build_game_with_players();
+
+        let Ok(events) = game.apply_host_command(HostCommand::ForceStartPeriod) else {
+            unreachable!("Should have started the game")
+        };
+
+        assert_eq!(
+            get_period_started_id(&events),
+            Some(DeliveryPeriodId::from(1))
+        );
+        assert_eq!(
+            game.apply_host_command(HostCommand::ForceStartPeriod)
+                .unwrap_err(),
+            HostCommandError::NotAllowedInCurrentState
+        );
+    }
+
+    #[test]
+    fn test_host_force_close_period() {
+        let mut game = build_game_with_players();
+        assert_eq!(
+            game.apply_host_command(HostCommand::ForceClosePeriod)
+                .unwrap_err(),
+            HostCommandError::NotAllowedInCurrentState
+        );
+        game.state = GameState::Running {
+            period: DeliveryPeriodId::from(1),
+            end_at: None,
+        };
+
+        let Ok(events) = game.apply_host_command(HostCommand::ForceClosePeriod) else {
+            unreachable!("Should have closed the period")
+        };
+
+        assert_eq!(
+            get_period_ended_id(&events),
+            Some(DeliveryPeriodId::from(1))
+        );
+        assert_eq!(
+            get_game_state(&events),
+            Some(GameState::PostDelivery {
+                period: DeliveryPeriodId::from(1),
+                end_at: None
+            })
+        );
+    }
+
+    #[test]
+    fn test_host_extend_timer() {
+        let mut game = build_game_with_players();
+        let end_at = Utc::now();
+        game.state = GameState::Running {
+            period: DeliveryPeriodId::from(1),
+            end_at: Some(end_at),
+        };
+
+        let Ok(events) = game.apply_host_command(HostCommand::ExtendTimer(Duration::from_secs(60)))
+        else {
+            unreachable!("Should have extended the timer")
+        };
+
+        assert_eq!(
+            get_game_state(&events),
+            Some(GameState::Running {
+                period: DeliveryPeriodId::from(1),
+                end_at: Some(end_at + Duration::from_secs(60))
+            })
+        );
+    }
+
+    #[test]
+    fn test_host_extend_timer_without_timer() {
+        let mut game = build_game_with_players();
+        game.state = GameState::PostDelivery {
+            period: DeliveryPeriodId::from(1),
+            end_at: None,
+        };
+
+        assert_eq!(
+            game.apply_host_command(HostCommand::ExtendTimer(Duration::from_secs(60)))
+                .unwrap_err(),
+            HostCommandError::GameHasNoTimer
+        );
+    }
+
+    #[test]
+    fn test_host_extend_timer_overflow() {
+        let mut game = build_game_with_players();
+        let end_at = Utc::now();
+        game.state = GameState::Running {
+            period: DeliveryPeriodId::from(1),
+            end_at: Some(end_at),
+        };
+
+        assert_eq!(
+            game.apply_host_command(HostCommand::ExtendTimer(Duration::MAX))
+                .unwrap_err(),
+            HostCommandError::TimerOverflow
+        );
+        assert_eq!(
+            game.state,
+            GameState::Running {
+                period: DeliveryPeriodId::from(1),
+                end_at: Some(end_at),
+            }
+        );
+    }
+
+    #[test]
+    fn test_host_end_game_during_a_period() {
+        let mut game = build_game_with_players();
+        game.state = GameState::Running {
+            period: DeliveryPeriodId::from(2),
+            end_at: None,
+        };
+
+        let Ok(events) = game.apply_host_command(HostCommand::EndGame) else {
+            unreachable!("Should have ended the game")
+        };
+
+        // The running period is closed, but the game ends on the last complete one
+        assert_eq!(
+            get_period_ended_id(&events),
+            Some(DeliveryPeriodId::from(2))
+        );
+        assert_eq!(
+            get_game_state(&events),
+            Some(GameState::Ended(DeliveryPeriodId::from(1)))
+        );
+        assert_eq!(
+            game.apply_host_command(HostCommand::EndGame).unwrap_err(),
+            HostCommandError::NotAllowedInCurrentState
+        );
+    }
+
+    #[test]
+    fn test_host_kick_last_player_not_ready() {
+        let mut game = build_game_with_players();
+        let _ = game.register_player_ready(&PlayerId::from("p1"));
+
+        let Ok(events) = game.apply_host_command(HostCommand::KickPlayer(PlayerName::from("p2")))
+        else {
+            unreachable!("Should have kicked the player")
+        };
+
+        assert!(matches!(
+            events.first(),
+            Some(GameEvent::PlayerKicked { id }) if *id == PlayerId::from("p2")
+        ));
+        // The remaining players were all ready, the game starts
+        assert_eq!(
+            get_period_started_id(&events),
+            Some(DeliveryPeriodId::from(1))
+        );
+        assert_eq!(
+            game.apply_host_command(HostCommand::KickPlayer(PlayerName::from("p2")))
+                .unwrap_err(),
+            HostCommandError::PlayerNotFound
+        );
+    }
+    #[test]
+    fn test_host_pause_freezes_remaining_time() {
+        let mut game = build_game_with_players();
+        assert_eq!(
+            game.apply_host_command(HostCommand::Pause).unwrap_err(),
+            HostCommandError::NotAllowedInCurrentState
+        );
+        game.state = GameState::Running {
+            period: DeliveryPeriodId::from(1),
+            end_at: Some(Utc::now() + Duration::from_secs(120)),
+        };
+
+        let Ok(events) = game.apply_host_command(HostCommand::Pause) else {
+            unreachable!("Should have paused the game")
+        };
+
+        assert!(matches!(events.first(), Some(GameEvent::Paused)));
+        let Some(GameState::Paused { phase, remaining }) = get_game_state(&events) else {
+            unreachable!("Game should be paused")
+        };
+        assert_eq!(
+            *phase,
+            GameState::Running {
+                period: DeliveryPeriodId::from(1),
+                end_at: None
+            }
+        );
+        assert!(remaining.is_some_and(|remaining| remaining > Duration::from_secs(110)));
+        assert_eq!(game.current_delivery_period(), DeliveryPeriodId::from(1));
+        assert_eq!(
+            game.apply_host_command(HostCommand::Pause).unwrap_err(),
+            HostCommandError::NotAllowedInCurrentState
+        );
+    }
+
+    #[test]
+    fn test_host_resume_recomputes_end_of_phase() {
+        let mut game = build_game_with_players();
+        assert_eq!(
+            game.apply_host_command(HostCommand::Resume).unwrap_err(),
+            HostCommandError::NotAllowedInCurrentState
+        );
+        game.state = GameState::Paused {
+            phase: Box::new(GameState::PostDelivery {
+                period: DeliveryPeriodId::from(1),
+                end_at: None,
+            }),
+            remaining: Some(Duration::from_secs(60)),
+        };
+        let _ = game.apply_host_command(HostCommand::ExtendTimer(Duration::from_secs(30)));
+
+        let before = Utc::now();
+        let Ok(events) = game.apply_host_command(HostCommand::Resume) else {
+            unreachable!("Should have resumed the game")
+        };
+
+        assert!(matches!(events.first(), Some(GameEvent::Resumed)));
+        let Some(GameState::PostDelivery {
+            period,
+            end_at: Some(end_at),
+        }) = get_game_state(&events)
+        else {
+            unreachable!("Game should be back to its post delivery phase")
+        };
+        assert_eq!(period, DeliveryPeriodId::from(1));
+        assert!(end_at >= before + Duration::from_secs(90));
+        assert!(end_at <= Utc::now() + Duration::from_secs(90));
+    }
+
+    #[test]
+    fn test_players_ready_while_paused_advance_on_resume() {
+        let mut game = build_game_with_players();
+        game.state = GameState::Paused {
+            phase: Box::new(GameState::Running {
+                period: DeliveryPeriodId::from(1),
+                end_at: None,
+            }),
+            remaining: None,
+        };
+
+        let _ = game.register_player_ready(&PlayerId::from("p1"));
+        let events = game.register_player_ready(&PlayerId::from("p2"));
+
+        assert_eq!(get_period_ended_id(&events), None);
+        assert!(game.state.is_paused());
+
+        let Ok(events) = game.apply_host_command(HostCommand::Resume) else {
+            unreachable!("Should have resumed the game")
+        };
+        assert_eq!(
+            get_period_ended_id(&events),
+            Some(DeliveryPeriodId::from(1))
+        );
+    }
 }
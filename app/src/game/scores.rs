@@ -1,6 +1,10 @@
-use std::{collections::HashMap, fmt::Debug, ops::Add};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    ops::Add,
+};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     constants::{NEGATIVE_IMBALANCE_COST, POSITIVE_IMBALANCE_COST},
@@ -8,14 +12,33 @@ use crate::{
         Direction,
         order_book::{Trade, TradeLeg},
     },
-    plants::{Output, PlantId, PlantOutput, StackDispatchResults},
+    plants::{Output, PlantId, PlantOutput, StackDispatchResults, registry::STACK_LINES},
     player::PlayerId,
-    utils::units::{Energy, EnergyCost, Money, NO_POWER, Power, TIMESTEP, ZERO_ENERGY},
+    utils::units::{
+        Emissions, Energy, EnergyCost, Money, NO_EMISSIONS, NO_POWER, Power, TIMESTEP, ZERO_ENERGY,
+    },
 };
 
 use super::delivery_period::DeliveryPeriodId;
 
-#[derive(Debug, PartialEq, Default, Clone, Serialize)]
+/// Prices at which players' imbalances are settled (in €/MWh): excess production is bought at
+/// `positive`, and production deficit is paid at `negative`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ImbalancePrices {
+    pub positive: EnergyCost,
+    pub negative: EnergyCost,
+}
+
+impl Default for ImbalancePrices {
+    fn default() -> Self {
+        ImbalancePrices {
+            positive: EnergyCost::from(POSITIVE_IMBALANCE_COST),
+            negative: EnergyCost::from(NEGATIVE_IMBALANCE_COST),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize)]
 pub struct PlayerScore {
     pub balance: Power,
     pub pnl: Money,
@@ -65,7 +88,7 @@ impl Add<TradeLeg> for PlayerScore {
     }
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ScoreDetails {
     pub volume: Energy,
     pub pnl: Money,
@@ -89,54 +112,70 @@ impl Default for ScoreDetails {
     }
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PlayerDetailedScore {
-    pub consumers: ScoreDetails,
-    pub renewables: ScoreDetails,
-    pub gas: ScoreDetails,
-    pub nuclear: ScoreDetails,
-    pub battery_discharge: ScoreDetails,
-    pub battery_charge: ScoreDetails,
+    /// Score of each line of the stack, see [`crate::plants::registry::STACK_LINES`].
+    #[serde(flatten)]
+    pub stack: BTreeMap<String, ScoreDetails>,
     pub market_bought: ScoreDetails,
     pub market_sold: ScoreDetails,
     pub imbalance: ScoreDetails,
+    pub emissions: Emissions,
+}
+
+impl Default for PlayerDetailedScore {
+    fn default() -> Self {
+        Self {
+            stack: STACK_LINES
+                .iter()
+                .map(|line| (line.to_string(), ScoreDetails::default()))
+                .collect(),
+            market_bought: ScoreDetails::default(),
+            market_sold: ScoreDetails::default(),
+            imbalance: ScoreDetails::default(),
+            emissions: Emissions::default(),
+        }
+    }
 }
 
 impl PlayerDetailedScore {
+    /// Score of a line of the stack, empty if the player has no plant on this line.
+    pub fn line(&self, line: &str) -> ScoreDetails {
+        self.stack.get(line).cloned().unwrap_or_default()
+    }
+
+    fn all_lines(&self) -> impl Iterator<Item = &ScoreDetails> {
+        self.stack
+            .values()
+            .chain([&self.market_bought, &self.market_sold, &self.imbalance])
+    }
+
     pub fn position(&self) -> Energy {
-        self.consumers.volume
-            + self.renewables.volume
-            + self.gas.volume
-            + self.nuclear.volume
-            + self.battery_discharge.volume
-            + self.battery_charge.volume
-            + self.market_bought.volume
-            + self.market_sold.volume
-            + self.imbalance.volume
+        self.all_lines()
+            .fold(Energy::from(0), |total, line| total + line.volume)
     }
     pub fn pnl(&self) -> Money {
-        self.consumers.pnl
-            + self.renewables.pnl
-            + self.gas.pnl
-            + self.nuclear.pnl
-            + self.battery_discharge.pnl
-            + self.battery_charge.pnl
-            + self.market_bought.pnl
-            + self.market_sold.pnl
-            + self.imbalance.pnl
+        self.all_lines()
+            .fold(Money::from(0), |total, line| total + line.pnl)
     }
 }
 
 pub fn compute_players_scores(
     trades: &[Trade],
     stacks_results: &HashMap<PlayerId, StackDispatchResults>,
+    imbalance_prices: &ImbalancePrices,
 ) -> HashMap<PlayerId, PlayerScore> {
     stacks_results
         .iter()
         .map(|(player_id, results)| {
             (
                 player_id.clone(),
-                compute_player_score(player_id, results.plants_outputs(), trades),
+                compute_player_score(
+                    player_id,
+                    results.plants_outputs(),
+                    trades,
+                    imbalance_prices,
+                ),
             )
         })
         .collect()
@@ -146,6 +185,7 @@ fn compute_player_score(
     player_id: &PlayerId,
     outputs: &HashMap<PlantId, PlantOutput>,
     trades: &[Trade],
+    imbalance_prices: &ImbalancePrices,
 ) -> PlayerScore {
     let market_position = trades
         .iter()
@@ -157,12 +197,8 @@ fn compute_player_score(
 
     let mut player_position = plant_position + market_position;
     player_position.imbalance_cost = match player_position.balance {
-        balance if balance > NO_POWER => {
-            balance * TIMESTEP * EnergyCost::from(POSITIVE_IMBALANCE_COST)
-        }
-        balance if balance < NO_POWER => {
-            balance * TIMESTEP * EnergyCost::from(NEGATIVE_IMBALANCE_COST)
-        }
+        balance if balance > NO_POWER => balance * TIMESTEP * imbalance_prices.positive,
+        balance if balance < NO_POWER => balance * TIMESTEP * imbalance_prices.negative,
         _ => 0.into(),
     };
     player_position
@@ -171,13 +207,14 @@ fn compute_player_score(
 pub fn compute_players_detailed_scores(
     trades: &[Trade],
     stacks_results: &HashMap<PlayerId, StackDispatchResults>,
+    imbalance_prices: &ImbalancePrices,
 ) -> HashMap<PlayerId, PlayerDetailedScore> {
     stacks_results
         .iter()
         .map(|(player_id, results)| {
             (
                 player_id.clone(),
-                compute_player_detailed_score(player_id, results, trades),
+                compute_player_detailed_score(player_id, results, trades, imbalance_prices),
             )
         })
         .collect()
@@ -187,21 +224,24 @@ fn compute_player_detailed_score(
     player_id: &PlayerId,
     outputs: &StackDispatchResults,
     trades: &[Trade],
+    imbalance_prices: &ImbalancePrices,
 ) -> PlayerDetailedScore {
     let market_scores = process_player_trades(player_id, trades);
-    let imbalance_score =
-        compute_imbalance_score(market_scores.position() + outputs.aggregated_state().position());
+    let imbalance_score = compute_imbalance_score(
+        market_scores.position() + outputs.aggregated_state().position(),
+        imbalance_prices,
+    );
 
     PlayerDetailedScore {
-        consumers: outputs.aggregated_state().consumers().into(),
-        renewables: outputs.aggregated_state().renewables().into(),
-        gas: outputs.aggregated_state().gas().into(),
-        nuclear: outputs.aggregated_state().nuclear().into(),
-        battery_discharge: outputs.aggregated_state().battery_discharge().into(),
-        battery_charge: outputs.aggregated_state().battery_charge().into(),
+        stack: outputs
+            .aggregated_state()
+            .lines()
+            .map(|(line, output)| (line.clone(), output.into()))
+            .collect(),
         market_bought: market_scores.bought,
         market_sold: market_scores.sold,
         imbalance: imbalance_score,
+        emissions: outputs.emissions(),
     }
 }
 
@@ -251,10 +291,10 @@ fn process_player_trades(player_id: &PlayerId, trades: &[Trade]) -> MarketScore
     }
 }
 
-fn compute_imbalance_score(position: Energy) -> ScoreDetails {
+fn compute_imbalance_score(position: Energy, imbalance_prices: &ImbalancePrices) -> ScoreDetails {
     let imbalance_cost = match position {
-        balance if balance > ZERO_ENERGY => balance * EnergyCost::from(POSITIVE_IMBALANCE_COST),
-        balance if balance < ZERO_ENERGY => balance * EnergyCost::from(NEGATIVE_IMBALANCE_COST),
+        balance if balance > ZERO_ENERGY => balance * imbalance_prices.positive,
+        balance if balance < ZERO_ENERGY => balance * imbalance_prices.negative,
         _ => 0.into(),
     };
     ScoreDetails {
@@ -270,28 +310,109 @@ pub struct PlayerResult {
     pub score: Money,
 }
 
+/// Number of delivery periods played by the players who played the most of them, i.e. by the
+/// players who joined the game from its start.
+fn periods_played<T>(players_scores: &HashMap<PlayerId, HashMap<DeliveryPeriodId, T>>) -> usize {
+    players_scores.values().map(HashMap::len).max().unwrap_or(0)
+}
+
+/// Extrapolate the total of a player who joined the game late to the periods played by the other
+/// players, so that late joiners are ranked on their average over the periods they played.
+fn normalise_total(total: i32, played: usize, periods: usize) -> i32 {
+    if played == 0 || played >= periods {
+        return total;
+    }
+    let (Ok(periods), Ok(played)) = (i64::try_from(periods), i64::try_from(played)) else {
+        return total;
+    };
+    let scaled = i64::from(total).saturating_mul(periods) / played;
+    i32::try_from(scaled).unwrap_or(if scaled < 0 { i32::MIN } else { i32::MAX })
+}
+
+/// Rank players based on their total PnL over the game, the totals of players who joined the game
+/// late being extrapolated to all the periods played. In team play, a team is a single player and
+/// is ranked as such.
 pub fn compute_game_rankings(
     players_scores: &HashMap<PlayerId, HashMap<DeliveryPeriodId, PlayerScore>>,
 ) -> Vec<PlayerResult> {
+    let periods = periods_played(players_scores);
     let mut scores: Vec<(PlayerId, Money)> = players_scores
         .iter()
         .map(|(player, score)| {
+            let total = score
+                .iter()
+                .fold(Money::from(0), |acc, (_, s)| acc + s.pnl + s.imbalance_cost);
             (
                 player.clone(),
-                score
-                    .iter()
-                    .fold(Money::from(0), |acc, (_, s)| acc + s.pnl + s.imbalance_cost),
+                Money::from(normalise_total(total.into(), score.len(), periods)),
             )
         })
         .collect();
-    scores.sort_by(|(_, a), (_, b)| b.cmp(a));
-    scores
+    scores.sort_by(|(a_player, a), (b_player, b)| {
+        b.cmp(a)
+            .then_with(|| a_player.as_ref().cmp(b_player.as_ref()))
+    });
+    competition_ranks(&scores)
+        .into_iter()
+        .zip(scores)
+        .map(|(rank, (player, score))| PlayerResult {
+            player,
+            rank,
+            score,
+        })
+        .collect()
+}
+
+/// Ranks of sorted totals, players with equal totals sharing the same rank and the next player
+/// being ranked after all of them, e.g. 1, 2, 2, 4.
+fn competition_ranks<T: PartialEq>(sorted: &[(PlayerId, T)]) -> Vec<usize> {
+    let mut ranks: Vec<usize> = Vec::with_capacity(sorted.len());
+    for (idx, (_, total)) in sorted.iter().enumerate() {
+        let rank = match idx.checked_sub(1) {
+            Some(previous) if sorted[previous].1 == *total => ranks[previous],
+            _ => idx + 1,
+        };
+        ranks.push(rank);
+    }
+    ranks
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct PlayerEmissionsResult {
+    pub player: PlayerId,
+    pub rank: usize,
+    pub emissions: Emissions,
+}
+
+/// Rank players based on their total CO2 emissions over the game, the lowest emitter first. As
+/// for PnL rankings, the totals of late joiners are extrapolated to all the periods played.
+pub fn compute_game_emissions_rankings(
+    players_detailed_scores: &HashMap<PlayerId, HashMap<DeliveryPeriodId, PlayerDetailedScore>>,
+) -> Vec<PlayerEmissionsResult> {
+    let periods = periods_played(players_detailed_scores);
+    let mut emissions: Vec<(PlayerId, Emissions)> = players_detailed_scores
         .iter()
-        .enumerate()
-        .map(|(idx, (player_id, score))| PlayerResult {
-            player: player_id.clone(),
-            rank: idx + 1,
-            score: *score,
+        .map(|(player, scores)| {
+            let total = scores
+                .values()
+                .fold(NO_EMISSIONS, |acc, score| acc + score.emissions);
+            (
+                player.clone(),
+                Emissions::from(normalise_total(total.into(), scores.len(), periods)),
+            )
+        })
+        .collect();
+    emissions.sort_by(|(a_player, a), (b_player, b)| {
+        a.cmp(b)
+            .then_with(|| a_player.as_ref().cmp(b_player.as_ref()))
+    });
+    competition_ranks(&emissions)
+        .into_iter()
+        .zip(emissions)
+        .map(|(rank, (player, emissions))| PlayerEmissionsResult {
+            player,
+            rank,
+            emissions,
         })
         .collect()
 }
@@ -300,7 +421,8 @@ pub fn compute_game_rankings(
 mod test_player_score_details {
     use crate::{
         game::scores::{PlayerDetailedScore, ScoreDetails},
-        utils::units::{Energy, Money},
+        plants::registry::STACK_LINES,
+        utils::units::{Emissions, Energy, Money},
     };
 
     fn make_score(volume: i32, pnl: i32) -> ScoreDetails {
@@ -310,29 +432,33 @@ mod test_player_score_details {
         }
     }
 
-    fn make_details(volumes: [i32; 9], pnls: [i32; 9]) -> PlayerDetailedScore {
+    /// Stack lines, then market bought, market sold and imbalance
+    const LINES: usize = STACK_LINES.len() + 3;
+
+    fn make_details(volumes: [i32; LINES], pnls: [i32; LINES]) -> PlayerDetailedScore {
+        let market = STACK_LINES.len();
         PlayerDetailedScore {
-            consumers: make_score(volumes[0], pnls[0]),
-            renewables: make_score(volumes[1], pnls[1]),
-            gas: make_score(volumes[2], pnls[2]),
-            nuclear: make_score(volumes[3], pnls[3]),
-            battery_discharge: make_score(volumes[4], pnls[4]),
-            battery_charge: make_score(volumes[5], pnls[5]),
-            market_bought: make_score(volumes[6], pnls[6]),
-            market_sold: make_score(volumes[7], pnls[7]),
-            imbalance: make_score(volumes[8], pnls[8]),
+            stack: STACK_LINES
+                .iter()
+                .enumerate()
+                .map(|(i, line)| (line.to_string(), make_score(volumes[i], pnls[i])))
+                .collect(),
+            market_bought: make_score(volumes[market], pnls[market]),
+            market_sold: make_score(volumes[market + 1], pnls[market + 1]),
+            imbalance: make_score(volumes[market + 2], pnls[market + 2]),
+            emissions: Emissions::from(0),
         }
     }
 
     #[test]
     fn test_position_all_zeros() {
-        let details = make_details([0; 9], [0; 9]);
+        let details = make_details([0; LINES], [0; LINES]);
         assert_eq!(details.position(), Energy::from(0));
     }
 
     #[test]
     fn test_position_sums_all_volumes() {
-        let details = make_details([-100, 50, 30, 80, 20, -10, 40, -30, 5], [0; 9]);
+        let details = make_details([-100, 50, 30, 80, 20, -10, 0, 40, -30, 5], [0; LINES]);
         assert_eq!(
             details.position(),
             Energy::from(-100 + 50 + 30 + 80 + 20 - 10 + 40 - 30 + 5)
@@ -341,16 +467,19 @@ mod test_player_score_details {
 
     #[test]
     fn test_pnl_all_zeros() {
-        let details = make_details([0; 9], [0; 9]);
+        let details = make_details([0; LINES], [0; LINES]);
         assert_eq!(details.pnl(), Money::from(0));
     }
 
     #[test]
     fn test_pnl_sums_all_pnls() {
-        let details = make_details([0; 9], [-500, 200, -300, 400, 100, -50, 800, -600, -150]);
+        let details = make_details(
+            [0; LINES],
+            [-500, 200, -300, 400, 100, -50, -70, 800, -600, -150],
+        );
         assert_eq!(
             details.pnl(),
-            Money::from(-500 + 200 - 300 + 400 + 100 - 50 + 800 - 600 - 150)
+            Money::from(-500 + 200 - 300 + 400 + 100 - 50 - 70 + 800 - 600 - 150)
         );
     }
 }
@@ -363,18 +492,22 @@ mod tests {
 
     use crate::{
         game::scores::{
-            NEGATIVE_IMBALANCE_COST, POSITIVE_IMBALANCE_COST, PlayerScore, compute_players_scores,
+            ImbalancePrices, NEGATIVE_IMBALANCE_COST, POSITIVE_IMBALANCE_COST, PlayerScore,
+            compute_players_scores,
         },
         market::order_book::Trade,
-        plants::{Output, PlantId, PlantOutput, StackAggregatedState, StackDispatchResults},
+        plants::{
+            Output, PlantId, PlantOutput, StackAggregatedState, StackDispatchResults,
+            registry::lines,
+        },
         player::PlayerId,
-        utils::units::{Energy, EnergyCost, Money, Power},
+        utils::units::{Emissions, Energy, EnergyCost, Money, Power},
     };
 
     #[test]
     fn test_scores_no_players() {
         assert_eq!(
-            compute_players_scores(&Vec::new(), &HashMap::new()),
+            compute_players_scores(&Vec::new(), &HashMap::new(), &ImbalancePrices::default()),
             HashMap::new()
         );
     }
@@ -406,7 +539,7 @@ mod tests {
         )]);
 
         assert_eq!(
-            compute_players_scores(&trades, &plants_outputs),
+            compute_players_scores(&trades, &plants_outputs, &ImbalancePrices::default()),
             HashMap::from([(
                 PlayerId::from("player_1"),
                 PlayerScore {
@@ -451,7 +584,7 @@ mod tests {
         )]);
 
         assert_eq!(
-            compute_players_scores(&trades, &plants_outputs),
+            compute_players_scores(&trades, &plants_outputs, &ImbalancePrices::default()),
             HashMap::from([(
                 PlayerId::from("player_1"),
                 PlayerScore {
@@ -511,7 +644,7 @@ mod tests {
         ]);
 
         assert_eq!(
-            compute_players_scores(&trades, &plants_outputs),
+            compute_players_scores(&trades, &plants_outputs, &ImbalancePrices::default()),
             HashMap::from([
                 (
                     PlayerId::from("player_1"),
@@ -646,45 +779,54 @@ mod tests {
 
     #[test]
     fn test_compute_imbalance_zero_position() {
-        let result = super::compute_imbalance_score(Energy::from(0));
+        let result = super::compute_imbalance_score(Energy::from(0), &ImbalancePrices::default());
         assert_eq!(result.volume, Energy::from(0));
         assert_eq!(result.pnl, Money::from(0));
     }
 
     #[test]
     fn test_compute_imbalance_positive_position_uses_positive_cost() {
-        let result = super::compute_imbalance_score(Energy::from(100));
+        let result = super::compute_imbalance_score(Energy::from(100), &ImbalancePrices::default());
         assert_eq!(result.volume, Energy::from(100));
         assert_eq!(result.pnl, Money::from(100 * POSITIVE_IMBALANCE_COST));
     }
 
     #[test]
     fn test_compute_imbalance_negative_position_uses_negative_cost() {
-        let result = super::compute_imbalance_score(Energy::from(-200));
+        let result =
+            super::compute_imbalance_score(Energy::from(-200), &ImbalancePrices::default());
         assert_eq!(result.volume, Energy::from(-200));
         assert_eq!(result.pnl, Money::from(-200 * NEGATIVE_IMBALANCE_COST));
     }
 
+    #[test]
+    fn test_compute_imbalance_with_custom_prices() {
+        let prices = ImbalancePrices {
+            positive: EnergyCost::from(5),
+            negative: EnergyCost::from(200),
+        };
+
+        let result = super::compute_imbalance_score(Energy::from(100), &prices);
+        assert_eq!(result.pnl, Money::from(500));
+        let result = super::compute_imbalance_score(Energy::from(-100), &prices);
+        assert_eq!(result.pnl, Money::from(-20000));
+    }
+
     #[test]
     fn test_compute_player_score_details_gas_only_no_trades() {
         let player = PlayerId::from("player");
         let stack = StackDispatchResults::new(
             HashMap::new(),
-            StackAggregatedState::new(
-                Output::empty(),
-                Output::empty(),
-                Output::new(Energy::from(100), Money::from(500)),
-                Output::empty(),
-                Output::empty(),
-                Output::empty(),
-            ),
+            StackAggregatedState::empty()
+                .with_line(lines::GAS, Output::new(Energy::from(100), Money::from(500))),
         );
 
-        let result = super::compute_player_detailed_score(&player, &stack, &[]);
+        let result =
+            super::compute_player_detailed_score(&player, &stack, &[], &ImbalancePrices::default());
 
-        assert_eq!(result.gas.volume, Energy::from(100));
-        assert_eq!(result.gas.pnl, Money::from(500));
-        assert_eq!(result.consumers.volume, Energy::from(0));
+        assert_eq!(result.line(lines::GAS).volume, Energy::from(100));
+        assert_eq!(result.line(lines::GAS).pnl, Money::from(500));
+        assert_eq!(result.line(lines::CONSUMERS).volume, Energy::from(0));
         assert_eq!(result.market_bought.volume, Energy::from(0));
         assert_eq!(result.market_sold.volume, Energy::from(0));
         assert_eq!(result.imbalance.volume, Energy::from(100));
@@ -693,6 +835,20 @@ mod tests {
             Money::from(100 * POSITIVE_IMBALANCE_COST)
         );
     }
+
+    #[test]
+    fn test_compute_player_score_details_reports_stack_emissions() {
+        let player = PlayerId::from("player");
+        let stack = StackDispatchResults::new(
+            HashMap::new(),
+            StackAggregatedState::empty().with_emissions(Emissions::from(4000)),
+        );
+
+        let result =
+            super::compute_player_detailed_score(&player, &stack, &[], &ImbalancePrices::default());
+
+        assert_eq!(result.emissions, Emissions::from(4000));
+    }
 }
 
 #[cfg(test)]
@@ -702,7 +858,7 @@ mod test_pnl_ranking {
     use crate::{
         game::{
             delivery_period::DeliveryPeriodId,
-            scores::{PlayerResult, PlayerScore, compute_game_rankings},
+            scores::{PlayerResult, PlayerScore, compute_game_rankings, normalise_total},
         },
         player::PlayerId,
         utils::units::{Money, Power},
@@ -772,4 +928,217 @@ mod test_pnl_ranking {
             ]
         )
     }
+    fn score(pnl: i32) -> PlayerScore {
+        PlayerScore {
+            balance: Power::from(0),
+            imbalance_cost: Money::from(0),
+            pnl: Money::from(pnl),
+        }
+    }
+
+    #[test]
+    fn test_late_joiner_ranked_on_periods_played() {
+        let scores = HashMap::from([
+            (
+                PlayerId::from("toto"),
+                HashMap::from([
+                    (DeliveryPeriodId::from(1), score(100)),
+                    (DeliveryPeriodId::from(2), score(100)),
+                    (DeliveryPeriodId::from(3), score(100)),
+                ]),
+            ),
+            (
+                PlayerId::from("late_player"),
+                HashMap::from([(DeliveryPeriodId::from(3), score(150))]),
+            ),
+        ]);
+
+        let rankings = compute_game_rankings(&scores);
+        assert_eq!(
+            rankings,
+            vec![
+                PlayerResult {
+                    player: PlayerId::from("late_player"),
+                    rank: 1,
+                    score: Money::from(450),
+                },
+                PlayerResult {
+                    player: PlayerId::from("toto"),
+                    rank: 2,
+                    score: Money::from(300),
+                }
+            ]
+        )
+    }
+
+    #[test]
+    fn test_normalised_total_saturates_instead_of_overflowing() {
+        assert_eq!(normalise_total(i32::MAX, 1, 4), i32::MAX);
+        assert_eq!(normalise_total(i32::MIN, 1, 4), i32::MIN);
+        assert_eq!(normalise_total(-300, 3, 4), -400);
+    }
+
+    #[test]
+    fn test_tied_players_share_their_rank() {
+        let scores = HashMap::from([
+            (
+                PlayerId::from("toto"),
+                HashMap::from([(DeliveryPeriodId::from(1), score(100))]),
+            ),
+            (
+                PlayerId::from("tata"),
+                HashMap::from([(DeliveryPeriodId::from(1), score(100))]),
+            ),
+            (
+                PlayerId::from("titi"),
+                HashMap::from([(DeliveryPeriodId::from(1), score(50))]),
+            ),
+        ]);
+
+        let rankings = compute_game_rankings(&scores);
+        assert_eq!(
+            rankings
+                .iter()
+                .map(|result| (result.player.to_string(), result.rank))
+                .collect::<Vec<_>>(),
+            vec![
+                ("tata".to_string(), 1),
+                ("toto".to_string(), 1),
+                ("titi".to_string(), 3)
+            ]
+        )
+    }
+}
+
+#[cfg(test)]
+mod test_emissions_ranking {
+    use std::collections::HashMap;
+
+    use crate::{
+        game::{
+            delivery_period::DeliveryPeriodId,
+            scores::{PlayerDetailedScore, PlayerEmissionsResult, compute_game_emissions_rankings},
+        },
+        player::PlayerId,
+        utils::units::Emissions,
+    };
+
+    fn score_with_emissions(emissions: i32) -> PlayerDetailedScore {
+        PlayerDetailedScore {
+            emissions: Emissions::from(emissions),
+            ..PlayerDetailedScore::default()
+        }
+    }
+
+    #[test]
+    fn test_emissions_ranking_lowest_emitter_first() {
+        let scores = HashMap::from([
+            (
+                PlayerId::from("toto"),
+                HashMap::from([
+                    (DeliveryPeriodId::from(1), score_with_emissions(4000)),
+                    (DeliveryPeriodId::from(2), score_with_emissions(2000)),
+                ]),
+            ),
+            (
+                PlayerId::from("other_player"),
+                HashMap::from([
+                    (DeliveryPeriodId::from(1), score_with_emissions(1000)),
+                    (DeliveryPeriodId::from(2), score_with_emissions(0)),
+                ]),
+            ),
+        ]);
+
+        let rankings = compute_game_emissions_rankings(&scores);
+        assert_eq!(
+            rankings,
+            vec![
+                PlayerEmissionsResult {
+                    player: PlayerId::from("other_player"),
+                    rank: 1,
+                    emissions: Emissions::from(1000),
+                },
+                PlayerEmissionsResult {
+                    player: PlayerId::from("toto"),
+                    rank: 2,
+                    emissions: Emissions::from(6000),
+                }
+            ]
+        )
+    }
+
+    #[test]
+    fn test_late_joiner_emissions_extrapolated_to_periods_played() {
+        let scores = HashMap::from([
+            (
+                PlayerId::from("toto"),
+                HashMap::from([
+                    (DeliveryPeriodId::from(1), score_with_emissions(1000)),
+                    (DeliveryPeriodId::from(2), score_with_emissions(1000)),
+                    (DeliveryPeriodId::from(3), score_with_emissions(1000)),
+                ]),
+            ),
+            (
+                PlayerId::from("late_player"),
+                HashMap::from([(DeliveryPeriodId::from(3), score_with_emissions(1500))]),
+            ),
+        ]);
+
+        let rankings = compute_game_emissions_rankings(&scores);
+        assert_eq!(
+            rankings,
+            vec![
+                PlayerEmissionsResult {
+                    player: PlayerId::from("toto"),
+                    rank: 1,
+                    emissions: Emissions::from(3000),
+                },
+                PlayerEmissionsResult {
+                    player: PlayerId::from("late_player"),
+                    rank: 2,
+                    emissions: Emissions::from(4500),
+                }
+            ]
+        )
+    }
+
+    #[test]
+    fn test_equal_emitters_share_their_rank() {
+        let scores = HashMap::from([
+            (
+                PlayerId::from("toto"),
+                HashMap::from([(DeliveryPeriodId::from(1), score_with_emissions(1000))]),
+            ),
+            (
+                PlayerId::from("tata"),
+                HashMap::from([(DeliveryPeriodId::from(1), score_with_emissions(1000))]),
+            ),
+            (
+                PlayerId::from("titi"),
+                HashMap::from([(DeliveryPeriodId::from(1), score_with_emissions(3000))]),
+            ),
+        ]);
+
+        let rankings = compute_game_emissions_rankings(&scores);
+        assert_eq!(
+            rankings,
+            vec![
+                PlayerEmissionsResult {
+                    player: PlayerId::from("tata"),
+                    rank: 1,
+                    emissions: Emissions::from(1000),
+                },
+                PlayerEmissionsResult {
+                    player: PlayerId::from("toto"),
+                    rank: 1,
+                    emissions: Emissions::from(1000),
+                },
+                PlayerEmissionsResult {
+                    player: PlayerId::from("titi"),
+                    rank: 3,
+                    emissions: Emissions::from(3000),
+                }
+            ]
+        )
+    }
 }
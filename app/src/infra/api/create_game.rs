@@ -1,24 +1,46 @@
-use std::time::Duration;
+use std::{path::PathBuf, time::Duration};
 
 use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use chrono::Utc;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     constants::DEFAULT_PERIOD_DURATION_SECONDS,
-    forecast::{generate_random_forecasts, generate_random_forecasts_shape},
+    forecast::{
+        ForecastValue, NormalizedForecastValue, generate_random_forecasts,
+        generate_random_forecasts_shape,
+        profile::{LoadProfileError, ProfileDirectory},
+        shock::ShockModel,
+        signal::WeatherSignal,
+    },
     game::{
-        GameActor, GameId, GameName,
+        GameActor, GameContext, GameId, GameName, HostToken, JoinCode, PlayerLimits,
         infra::{
             GameActorConfig,
-            stack_config::{GameStackConfig, GameStackFixedConfig, GameStackPerPlayerBaseConfig},
+            event_log::GameLog,
+            persistence::{GameSnapshot, GameStore},
+            stack_config::{
+                CapacityCosts, GameStackConfig, GameStackFixedConfig, GameStackPerPlayerBaseConfig,
+                InvestmentConfig, PlantConfig,
+            },
         },
+        scores::ImbalancePrices,
+    },
+    infra::api::{
+        scenario::{SCENARIO_VERSION, Scenario, ScenarioDirectory, ScenarioError},
+        state::{AppState, archive_game_on_end, cleanup_state},
+    },
+    market::{
+        MarketActor,
+        bots::{BotKind, start_bots},
     },
-    infra::api::state::cleanup_state,
-    market::{MarketActor, bots::start_bots},
+    plants::{PlantForecasts, PlantTechnology},
     player::infra::PlayerConnectionsService,
     utils::{
         program_actors_termination,
-        units::{Energy, EnergyCost, Power},
+        rng::{GameRng, derived_rng, seeded_rng},
+        units::{CarbonPrice, Energy, EnergyCost, Money, Power},
     },
 };
 
@@ -35,6 +57,12 @@ pub struct GameStackFixedConfigRequest {
     pub consumers_forecasts_range: usize,
     pub renewable_capacity: Power,
     pub renewable_forecasts_range: usize,
+    #[serde(default)]
+    pub carbon_price: CarbonPrice,
+    #[serde(default)]
+    pub consumers_profile: Option<String>,
+    #[serde(default)]
+    pub renewable_profile: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -49,35 +77,206 @@ pub struct GameStackPerPlayerBaseConfigRequest {
     pub consumers_forecasts_range: usize,
     pub renewable_max_capacity: Power,
     pub renewable_forecasts_range: usize,
+    #[serde(default)]
+    pub carbon_price: CarbonPrice,
+    #[serde(default)]
+    pub capacity_costs: CapacityCosts,
+    #[serde(default)]
+    pub budget: Option<Money>,
+    #[serde(default)]
+    pub investment: Option<InvestmentConfig>,
+    /// Name of the profile of consumers forecasts, randomly generated if not set
+    #[serde(default)]
+    pub consumers_profile: Option<String>,
+    /// Name of the profile of renewable forecasts, randomly generated if not set
+    #[serde(default)]
+    pub renewable_profile: Option<String>,
+}
+
+/// Forecasts of renewable plants and consumers in a request, either explicit, or generated from a
+/// named profile, or randomly if neither is set, based on their capacity.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PlantForecastsRequest {
+    pub capacity: Power,
+    #[serde(default)]
+    pub profile: Option<String>,
+    #[serde(default)]
+    pub forecasts: Option<Vec<ForecastValue>>,
+}
+
+/// Parameters of a plant, see [PlantForecastsRequest] for the forecasts.
+pub type PlantTechnologyRequest = PlantTechnology<PlantForecastsRequest>;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PlantConfigRequest {
+    pub name: String,
+    #[serde(flatten)]
+    pub technology: PlantTechnologyRequest,
+}
+
+/// Forecasts of a plant, the explicit ones if any, or from the named profile if any, random
+/// otherwise.
+fn plant_forecasts(
+    forecasts: Option<Vec<ForecastValue>>,
+    profile: Option<&str>,
+    forecasts_range: usize,
+    capacity: Power,
+    profiles: &ProfileDirectory,
+    rng: &mut GameRng,
+) -> Result<Vec<ForecastValue>, LoadProfileError> {
+    if let Some(forecasts) = forecasts {
+        return Ok(forecasts);
+    }
+    let Some(profile) = profile else {
+        return Ok(generate_random_forecasts(forecasts_range, capacity, rng));
+    };
+    Ok(profiles
+        .load(profile)?
+        .iter()
+        .map(|f| f.as_forecast(capacity.into()))
+        .collect())
+}
+
+/// Forecasts shape of a technology, from the named profile if any, random otherwise.
+fn forecasts_shape(
+    profile: Option<&str>,
+    forecasts_range: usize,
+    profiles: &ProfileDirectory,
+    rng: &mut GameRng,
+) -> Result<Vec<NormalizedForecastValue>, LoadProfileError> {
+    match profile {
+        Some(profile) => profiles.load(profile),
+        None => Ok(generate_random_forecasts_shape(forecasts_range, rng)),
+    }
+}
+
+impl PlantConfigRequest {
+    /// Plant configuration, forecasts being loaded from `profiles` or drawn from `rng`.
+    pub fn into_plant_config(
+        self,
+        profiles: &ProfileDirectory,
+        rng: &mut GameRng,
+    ) -> Result<PlantConfig, LoadProfileError> {
+        let definition = self
+            .technology
+            .try_map_forecasts(|request, forecasts_range| {
+                plant_forecasts(
+                    request.forecasts,
+                    request.profile.as_deref(),
+                    forecasts_range,
+                    request.capacity,
+                    profiles,
+                    rng,
+                )
+                .map(PlantForecasts::from)
+            })?;
+        Ok(PlantConfig {
+            name: self.name,
+            definition,
+        })
+    }
+}
+
+/// Fixed stack made of an arbitrary list of plants, e.g. several gas plants with different costs.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GameStackPlantsConfigRequest {
+    pub plants: Vec<PlantConfigRequest>,
+    #[serde(default)]
+    pub carbon_price: CarbonPrice,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub enum GameStackConfigRequest {
     Fixed(GameStackFixedConfigRequest),
+    Plants(GameStackPlantsConfigRequest),
     PerPlayer(GameStackPerPlayerBaseConfigRequest),
 }
 
-impl From<GameStackConfigRequest> for GameStackConfig {
-    fn from(value: GameStackConfigRequest) -> Self {
-        match value {
-            GameStackConfigRequest::Fixed(config) => GameStackConfig::Fixed(GameStackFixedConfig {
-                gas_cost: config.gas_cost,
-                nuclear_cost: config.nuclear_cost,
-                consumers_revenues: config.consumers_revenues,
-                gas_capacity: config.gas_capacity,
-                nuclear_capacity: config.nuclear_capacity,
-                battery_capacity: config.battery_capacity,
-                consumers_forecasts: generate_random_forecasts(
-                    config.consumers_forecasts_range,
-                    config.consumers_capacity,
-                ),
-                consumers_forecasts_range: config.consumers_forecasts_range,
-                renewable_forecasts: generate_random_forecasts(
-                    config.renewable_forecasts_range,
-                    config.renewable_capacity,
-                ),
-                renewable_forecasts_range: config.renewable_forecasts_range,
-            }),
+impl From<GameStackFixedConfigRequest> for GameStackPlantsConfigRequest {
+    fn from(config: GameStackFixedConfigRequest) -> Self {
+        use PlantTechnology::*;
+        let plants = [
+            (
+                "battery",
+                Battery {
+                    capacity: config.battery_capacity,
+                },
+            ),
+            (
+                "gas",
+                GasPlant {
+                    energy_cost: config.gas_cost,
+                    capacity: config.gas_capacity,
+                },
+            ),
+            (
+                "nuclear",
+                Nuclear {
+                    energy_cost: config.nuclear_cost,
+                    capacity: config.nuclear_capacity,
+                },
+            ),
+            (
+                "renewable",
+                RenewablePlant {
+                    forecasts: PlantForecastsRequest {
+                        capacity: config.renewable_capacity,
+                        profile: config.renewable_profile,
+                        forecasts: None,
+                    },
+                    forecasts_range: config.renewable_forecasts_range,
+                },
+            ),
+            (
+                "consumers",
+                Consumers {
+                    revenues: config.consumers_revenues,
+                    forecasts: PlantForecastsRequest {
+                        capacity: config.consumers_capacity,
+                        profile: config.consumers_profile,
+                        forecasts: None,
+                    },
+                    forecasts_range: config.consumers_forecasts_range,
+                },
+            ),
+        ];
+        GameStackPlantsConfigRequest {
+            plants: plants
+                .into_iter()
+                .map(|(name, technology)| PlantConfigRequest {
+                    name: name.to_string(),
+                    technology,
+                })
+                .collect(),
+            carbon_price: config.carbon_price,
+        }
+    }
+}
+
+impl GameStackConfigRequest {
+    /// Stack configuration of the game, forecasts being loaded from `profiles` or drawn from
+    /// `rng`.
+    pub fn into_stack_config(
+        self,
+        profiles: &ProfileDirectory,
+        rng: &mut GameRng,
+    ) -> Result<GameStackConfig, LoadProfileError> {
+        let config = match self {
+            GameStackConfigRequest::Fixed(config) => {
+                return GameStackConfigRequest::Plants(config.into())
+                    .into_stack_config(profiles, rng);
+            }
+            GameStackConfigRequest::Plants(config) => {
+                GameStackConfig::Fixed(GameStackFixedConfig {
+                    plants: config
+                        .plants
+                        .into_iter()
+                        .map(|plant| plant.into_plant_config(profiles, rng))
+                        .collect::<Result<_, _>>()?,
+                    carbon_price: config.carbon_price,
+                    weather: None,
+                })
+            }
             GameStackConfigRequest::PerPlayer(config) => {
                 GameStackConfig::PerPlayer(GameStackPerPlayerBaseConfig {
                     gas_cost: config.gas_cost,
@@ -88,17 +287,29 @@ impl From<GameStackConfigRequest> for GameStackConfig {
                     battery_max_capacity: config.battery_max_capacity,
                     consumers_capacity: config.consumers_capacity,
                     renewable_max_capacity: config.renewable_max_capacity,
-                    consumers_forecasts: generate_random_forecasts_shape(
+                    consumers_forecasts: forecasts_shape(
+                        config.consumers_profile.as_deref(),
                         config.consumers_forecasts_range,
-                    ),
+                        profiles,
+                        rng,
+                    )?,
                     consumers_forecasts_range: config.consumers_forecasts_range,
-                    renewable_forecasts: generate_random_forecasts_shape(
+                    renewable_forecasts: forecasts_shape(
+                        config.renewable_profile.as_deref(),
                         config.renewable_forecasts_range,
-                    ),
+                        profiles,
+                        rng,
+                    )?,
                     renewable_forecasts_range: config.renewable_forecasts_range,
+                    carbon_price: config.carbon_price,
+                    capacity_costs: config.capacity_costs,
+                    budget: config.budget,
+                    investment: config.investment,
+                    weather: None,
                 })
             }
-        }
+        };
+        Ok(config)
     }
 }
 
@@ -106,27 +317,220 @@ impl From<GameStackConfigRequest> for GameStackConfig {
 pub struct NewGameRequest {
     game_name: String,
     period_duration_seconds: Option<u64>,
+    #[serde(default)]
+    post_delivery_duration_seconds: Option<u64>,
+    #[serde(default)]
+    period_durations_schedule_seconds: Vec<u64>,
     number_of_periods: usize,
     stack: GameStackConfigRequest,
+    #[serde(default)]
+    rank_emissions: bool,
+    #[serde(default)]
+    allow_late_join: bool,
+    #[serde(default)]
+    team_play: bool,
+    #[serde(default)]
+    private: bool,
+    #[serde(default)]
+    min_players: Option<usize>,
+    #[serde(default)]
+    max_players: Option<usize>,
+    /// Correlation of renewables and consumers between players, in [0, 1], fully decorrelated
+    /// if not set
+    #[serde(default)]
+    weather_correlation: Option<f64>,
+    #[serde(default)]
+    shocks: Vec<ShockModel>,
+    #[serde(default)]
+    imbalance_prices: ImbalancePrices,
+    /// Seed of the game's randomness, to replay a game identically, random if not set
+    #[serde(default)]
+    seed: Option<u64>,
+}
+
+impl From<NewGameRequest> for Scenario {
+    fn from(request: NewGameRequest) -> Self {
+        Scenario {
+            version: SCENARIO_VERSION,
+            description: String::new(),
+            number_of_periods: request.number_of_periods,
+            period_duration_seconds: Some(
+                request
+                    .period_duration_seconds
+                    .unwrap_or(DEFAULT_PERIOD_DURATION_SECONDS),
+            ),
+            post_delivery_duration_seconds: request.post_delivery_duration_seconds,
+            period_durations_schedule_seconds: request.period_durations_schedule_seconds,
+            stack: request.stack,
+            bots: vec![BotKind::ExtremeOrders],
+            imbalance_prices: request.imbalance_prices,
+            rank_emissions: request.rank_emissions,
+            allow_late_join: request.allow_late_join,
+            team_play: request.team_play,
+            private: request.private,
+            min_players: request.min_players,
+            max_players: request.max_players,
+            weather_correlation: request.weather_correlation,
+            shocks: request.shocks,
+            seed: request.seed,
+        }
+    }
+}
+
+/// Game created from a scenario of the server's scenarios directory.
+#[derive(Debug, Deserialize)]
+pub struct NewGameFromScenarioRequest {
+    game_name: String,
+    scenario: String,
+    /// Overrides the seed of the scenario, if any
+    #[serde(default)]
+    seed: Option<u64>,
+    /// Makes the game private, even if its scenario is not
+    #[serde(default)]
+    private: bool,
+}
+
+/// A game created from a scenario of the server, or described by the request itself.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+pub enum CreateGameRequest {
+    FromScenario(NewGameFromScenarioRequest),
+    Custom(Box<NewGameRequest>),
 }
 
 #[derive(Debug, Serialize)]
 struct NewGameSuccess {
     game_id: GameId,
     game_name: GameName,
+    /// Secret to send host commands for the game, only given to its creator
+    host_token: HostToken,
+    /// Code to share with the players of a private game
+    #[serde(skip_serializing_if = "Option::is_none")]
+    join_code: Option<JoinCode>,
 }
 
 pub async fn create_game(
     State(state): State<ApiState>,
-    Json(request): Json<NewGameRequest>,
+    Json(request): Json<CreateGameRequest>,
 ) -> Result<impl IntoResponse, StatusCode> {
-    let Ok(game_name) = GameName::new(request.game_name) else {
+    let (game_name, scenario) = match request {
+        CreateGameRequest::Custom(request) => {
+            let game_name = request.game_name.clone();
+            let scenario = Scenario::from(*request);
+            if let Err(err) = scenario.validate() {
+                tracing::warn!("Invalid game request: {err}");
+                return Err(StatusCode::BAD_REQUEST);
+            }
+            (game_name, scenario)
+        }
+        CreateGameRequest::FromScenario(request) => {
+            let scenarios = ScenarioDirectory::new(state.read().await.config.scenarios_dir.clone());
+            let mut scenario = match scenarios.load(&request.scenario) {
+                Ok(scenario) => scenario,
+                Err(ScenarioError::NotFound(_)) => return Err(StatusCode::NOT_FOUND),
+                Err(err) => {
+                    tracing::warn!("Invalid scenario {:?}: {err}", request.scenario);
+                    return Err(StatusCode::BAD_REQUEST);
+                }
+            };
+            scenario.seed = request.seed.or(scenario.seed);
+            scenario.private |= request.private;
+            (request.game_name, scenario)
+        }
+    };
+    let Ok(game_name) = GameName::new(game_name) else {
         return Err(StatusCode::BAD_REQUEST);
     };
 
-    let mut state = state.write().await;
+    let profiles_dir = state.read().await.config.profiles_dir.clone();
+    let setup = match prepare_game(scenario, profiles_dir) {
+        Ok(setup) => setup,
+        Err(err) => {
+            tracing::warn!("Unable to create game {game_name:?}: {err}");
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    };
+    let host_token = HostToken::default();
+    let (game_id, game) = start_game(
+        &mut *state.write().await,
+        game_name.clone(),
+        setup,
+        host_token.clone(),
+    );
+    Ok((
+        StatusCode::CREATED,
+        Json(NewGameSuccess {
+            game_id,
+            game_name,
+            host_token,
+            join_code: game.join_code,
+        }),
+    ))
+}
+
+/// Duration after which the actors of a game are terminated and the game is removed.
+const GAME_LIFETIME: Duration = Duration::from_secs(3600 * 24);
+
+/// A new game whose stack has been generated from its scenario, ready to be started.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameSetup {
+    scenario: Scenario,
+    stack_config: GameStackConfig,
+    seed: u64,
+    rng: GameRng,
+}
+
+impl GameSetup {
+    /// Require a join code to join the game.
+    pub fn private(mut self) -> GameSetup {
+        self.scenario.private = true;
+        self
+    }
+}
+
+/// Validate `scenario` and generate the stack of its game. Load profiles are read from
+/// `profiles_dir`, games are therefore prepared before locking the app state.
+pub fn prepare_game(
+    scenario: Scenario,
+    profiles_dir: impl Into<PathBuf>,
+) -> Result<GameSetup, ScenarioError> {
+    scenario.validate()?;
+    let seed = scenario.seed.unwrap_or_else(rand::random);
+    let mut rng = seeded_rng(seed);
+    let profiles = ProfileDirectory::new(profiles_dir);
+    let mut stack_config = scenario
+        .stack
+        .clone()
+        .into_stack_config(&profiles, &mut rng)?
+        .with_forecasts_covering(scenario.number_of_periods);
+    if let Some(correlation) = scenario.weather_correlation {
+        let weather = WeatherSignal::generate(scenario.number_of_periods, correlation, &mut rng)?;
+        stack_config = stack_config.with_weather(weather);
+    }
+    Ok(GameSetup {
+        scenario,
+        stack_config,
+        seed,
+        rng,
+    })
+}
+
+/// Start the actors and the bots of a prepared game, hosted with `host_token`.
+pub fn start_game(
+    state: &mut AppState,
+    game_name: GameName,
+    setup: GameSetup,
+    host_token: HostToken,
+) -> (GameId, GameContext) {
+    let GameSetup {
+        scenario,
+        stack_config,
+        seed,
+        mut rng,
+    } = setup;
+
     let game_id = GameId::default();
-    let cancellation_token = program_actors_termination(Duration::from_secs(3600 * 24));
+    let cancellation_token = program_actors_termination(GAME_LIFETIME);
     cleanup_state(
         game_id.clone(),
         cancellation_token.clone(),
@@ -134,26 +538,52 @@ pub async fn create_game(
     );
     let connections_service =
         PlayerConnectionsService::new(state.player_connections_repository.clone());
+    let log = game_log(state, &game_id);
     let market_context = MarketActor::start(
         &game_id,
         connections_service.clone(),
+        log.clone(),
         cancellation_token.clone(),
     );
-    let period_duration = request
-        .period_duration_seconds
-        .unwrap_or(DEFAULT_PERIOD_DURATION_SECONDS);
 
     let game_config = GameActorConfig {
         id: game_id.clone(),
         name: game_name.clone(),
-        delivery_period_duration: Some(Duration::from_secs(period_duration)),
-        number_of_delivery_periods: request.number_of_periods,
-        stack_config: request.stack.into(),
+        delivery_period_duration: scenario.period_duration_seconds.map(Duration::from_secs),
+        post_delivery_duration: scenario
+            .post_delivery_duration_seconds
+            .map(Duration::from_secs),
+        delivery_periods_schedule: scenario
+            .period_durations_schedule_seconds
+            .iter()
+            .copied()
+            .map(Duration::from_secs)
+            .collect(),
+        number_of_delivery_periods: scenario.number_of_periods,
+        stack_config,
+        rank_emissions: scenario.rank_emissions,
+        shocks: scenario.shocks,
+        imbalance_prices: scenario.imbalance_prices,
+        seed: rng.random(),
+        bots: scenario.bots,
+        host_token,
+        allow_late_join: scenario.allow_late_join,
+        team_play: scenario.team_play,
+        join_code: scenario.private.then(JoinCode::default),
+        player_limits: PlayerLimits {
+            min: scenario.min_players,
+            max: scenario.max_players,
+        },
+        created_at: Utc::now(),
     };
+    let bots = game_config.bots.clone();
+    let store = state.config.persistence_dir.clone().map(GameStore::new);
     let game_context = GameActor::start(
         game_config,
         connections_service.clone(),
         market_context.clone(),
+        store,
+        log,
         cancellation_token.clone(),
     );
 
@@ -163,17 +593,290 @@ pub async fn create_game(
     state
         .game_services
         .insert(game_id.clone(), game_context.clone());
+    archive_game_on_end(game_context.clone(), state.archive_tx.clone());
 
     // Start the bots
-    let cloned_market_context = market_context.clone();
-    let cloned_cancellation_token = cancellation_token.clone();
+    let cloned_game_context = game_context.clone();
+    let bots_rng = derived_rng(&mut rng);
     tokio::spawn(async move {
-        start_bots(cloned_market_context, cloned_cancellation_token).await;
+        start_bots(
+            &bots,
+            cloned_game_context,
+            market_context,
+            cancellation_token,
+            bots_rng,
+        )
+        .await;
     });
 
-    tracing::info!("Game {game_name:?} created");
-    Ok((
-        StatusCode::CREATED,
-        Json(NewGameSuccess { game_id, game_name }),
-    ))
+    tracing::info!("Game {game_name:?} created with seed {seed}");
+    (game_id, game_context)
+}
+
+/// Log of the game's events, if event logging is enabled.
+fn game_log(state: &AppState, game_id: &GameId) -> Option<GameLog> {
+    state
+        .config
+        .event_log_dir
+        .as_deref()
+        .map(|dir| GameLog::open(dir, game_id))
+}
+
+/// Restart a game from its snapshot, e.g. after a server restart. The game and its players keep
+/// their ids, so that players can reconnect with their existing cookies.
+pub fn restore_game(state: &mut AppState, snapshot: GameSnapshot, store: GameStore) {
+    let game_id = snapshot.config.id.clone();
+    let bots = snapshot.config.bots.clone();
+    let bots_rng = seeded_rng(snapshot.config.seed);
+    let cancellation_token = program_actors_termination(GAME_LIFETIME);
+    cleanup_state(
+        game_id.clone(),
+        cancellation_token.clone(),
+        state.cleanup_tx.clone(),
+    );
+    let connections_service =
+        PlayerConnectionsService::new(state.player_connections_repository.clone());
+    let log = game_log(state, &game_id);
+    let market_context = MarketActor::restore(
+        &game_id,
+        snapshot.market.clone(),
+        connections_service.clone(),
+        log.clone(),
+        cancellation_token.clone(),
+    );
+    let (game_context, stacks) = GameActor::restore(
+        snapshot,
+        connections_service,
+        market_context.clone(),
+        Some(store),
+        log,
+        cancellation_token.clone(),
+    );
+
+    state
+        .market_services
+        .insert(game_id.clone(), market_context.clone());
+    state
+        .game_services
+        .insert(game_id.clone(), game_context.clone());
+    state.stack_services.insert(game_id, stacks);
+    archive_game_on_end(game_context.clone(), state.archive_tx.clone());
+
+    tokio::spawn(async move {
+        start_bots(
+            &bots,
+            game_context,
+            market_context,
+            cancellation_token,
+            bots_rng,
+        )
+        .await;
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        forecast::{
+            NormalizedForecastValue,
+            profile::{LoadProfileError, ProfileDirectory},
+        },
+        game::{
+            delivery_period::DeliveryPeriodId,
+            infra::stack_config::{GameStackConfig, PlantConfig},
+        },
+        plants::PlantDefinition,
+        utils::{
+            rng::seeded_rng,
+            units::{CarbonPrice, Energy, EnergyCost, Money, Power},
+        },
+    };
+
+    use super::{
+        CreateGameRequest, GameStackConfigRequest, GameStackFixedConfigRequest,
+        GameStackPerPlayerBaseConfigRequest, ImbalancePrices, NewGameFromScenarioRequest, Scenario,
+    };
+
+    fn fixed_request() -> GameStackConfigRequest {
+        GameStackConfigRequest::Fixed(GameStackFixedConfigRequest {
+            gas_cost: EnergyCost::from(80),
+            nuclear_cost: EnergyCost::from(35),
+            consumers_revenues: EnergyCost::from(50),
+            gas_capacity: Power::from(500),
+            nuclear_capacity: Power::from(1000),
+            battery_capacity: Energy::from(300),
+            consumers_capacity: Power::from(-1500),
+            consumers_forecasts_range: 3,
+            renewable_capacity: Power::from(300),
+            renewable_forecasts_range: 3,
+            carbon_price: CarbonPrice::default(),
+            consumers_profile: None,
+            renewable_profile: None,
+        })
+    }
+
+    fn per_player_request() -> GameStackConfigRequest {
+        GameStackConfigRequest::PerPlayer(GameStackPerPlayerBaseConfigRequest {
+            gas_cost: EnergyCost::from(80),
+            nuclear_cost: EnergyCost::from(35),
+            consumers_revenues: EnergyCost::from(50),
+            gas_max_capacity: Power::from(500),
+            nuclear_max_capacity: Power::from(1000),
+            battery_max_capacity: Energy::from(300),
+            consumers_capacity: Power::from(-1500),
+            consumers_forecasts_range: 3,
+            renewable_max_capacity: Power::from(300),
+            renewable_forecasts_range: 3,
+            carbon_price: CarbonPrice::default(),
+            capacity_costs: Default::default(),
+            budget: Some(Money::from(1000)),
+            investment: None,
+            consumers_profile: None,
+            renewable_profile: None,
+        })
+    }
+
+    fn profiles() -> ProfileDirectory {
+        ProfileDirectory::new(concat!(env!("CARGO_MANIFEST_DIR"), "/profiles"))
+    }
+
+    #[test]
+    fn test_same_seed_generates_same_stack_config() {
+        for request in [fixed_request(), per_player_request()] {
+            assert_eq!(
+                request
+                    .clone()
+                    .into_stack_config(&profiles(), &mut seeded_rng(12)),
+                request.into_stack_config(&profiles(), &mut seeded_rng(12))
+            );
+        }
+    }
+
+    #[test]
+    fn test_same_seed_generates_same_realisations() {
+        let Ok(GameStackConfig::Fixed(first)) =
+            fixed_request().into_stack_config(&profiles(), &mut seeded_rng(5))
+        else {
+            unreachable!("Should be a fixed stack")
+        };
+        let second = first.clone();
+
+        let mut first = first.generate_plants(DeliveryPeriodId::from(0), &mut seeded_rng(9));
+        let mut second = second.generate_plants(DeliveryPeriodId::from(0), &mut seeded_rng(9));
+        for _ in 0..4 {
+            let (first, second) = (first.dispatch_plants(), second.dispatch_plants());
+            assert_eq!(first.position(), second.position());
+            assert_eq!(first.pnl(), second.pnl());
+        }
+    }
+
+    #[test]
+    fn test_per_player_stack_config_from_profiles() {
+        let GameStackConfigRequest::PerPlayer(request) = per_player_request() else {
+            unreachable!("Should be a per player request")
+        };
+        let request = GameStackConfigRequest::PerPlayer(GameStackPerPlayerBaseConfigRequest {
+            consumers_profile: Some("winter_load".to_string()),
+            renewable_profile: Some("sunny_day".to_string()),
+            ..request
+        });
+
+        let Ok(GameStackConfig::PerPlayer(config)) =
+            request.into_stack_config(&profiles(), &mut seeded_rng(0))
+        else {
+            unreachable!("Should be a per player config")
+        };
+        assert_eq!(config.consumers_forecasts.len(), 6);
+        assert_eq!(
+            config.consumers_forecasts[2],
+            NormalizedForecastValue::try_new(1., 0.08).unwrap()
+        );
+        assert_eq!(
+            config.renewable_forecasts[0],
+            NormalizedForecastValue::try_new(0.05, 0.02).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_fixed_stack_config_from_profile() {
+        let GameStackConfigRequest::Fixed(request) = fixed_request() else {
+            unreachable!("Should be a fixed request")
+        };
+        let request = GameStackConfigRequest::Fixed(GameStackFixedConfigRequest {
+            consumers_profile: Some("winter_load".to_string()),
+            ..request
+        });
+
+        let Ok(GameStackConfig::Fixed(config)) =
+            request.into_stack_config(&profiles(), &mut seeded_rng(0))
+        else {
+            unreachable!("Should be a fixed config")
+        };
+        let Some(PlantConfig {
+            definition: PlantDefinition::Consumers { forecasts, .. },
+            ..
+        }) = config.plants.iter().find(|plant| plant.name == "consumers")
+        else {
+            unreachable!("Should have consumers")
+        };
+        assert_eq!(forecasts.forecasts.len(), 6);
+        assert_eq!(forecasts.forecasts[2].value, -1500);
+    }
+
+    #[test]
+    fn test_stack_config_with_unknown_profile() {
+        let GameStackConfigRequest::Fixed(request) = fixed_request() else {
+            unreachable!("Should be a fixed request")
+        };
+        let request = GameStackConfigRequest::Fixed(GameStackFixedConfigRequest {
+            renewable_profile: Some("missing".to_string()),
+            ..request
+        });
+
+        assert_eq!(
+            request.into_stack_config(&profiles(), &mut seeded_rng(0)),
+            Err(LoadProfileError::NotFound("missing".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_create_game_requests() {
+        let request: CreateGameRequest = serde_json::from_str(
+            r#"{"type": "FromScenario", "game_name": "game", "scenario": "tutorial", "seed": 3}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            request,
+            CreateGameRequest::FromScenario(NewGameFromScenarioRequest { seed: Some(3), .. })
+        ));
+
+        let request: CreateGameRequest = serde_json::from_str(
+            r#"{
+                "type": "Custom",
+                "game_name": "game",
+                "period_duration_seconds": 60,
+                "post_delivery_duration_seconds": 15,
+                "period_durations_schedule_seconds": [120],
+                "number_of_periods": 3,
+                "stack": {"Plants": {"plants": []}}
+            }"#,
+        )
+        .unwrap();
+        let CreateGameRequest::Custom(request) = request else {
+            unreachable!("Should be a custom game")
+        };
+        let scenario = Scenario::from(*request);
+        assert_eq!(scenario.period_duration_seconds, Some(60));
+        assert_eq!(scenario.post_delivery_duration_seconds, Some(15));
+        assert_eq!(scenario.period_durations_schedule_seconds, vec![120]);
+        assert_eq!(scenario.imbalance_prices, ImbalancePrices::default());
+        assert!(scenario.validate().is_ok());
+
+        assert!(
+            serde_json::from_str::<CreateGameRequest>(
+                r#"{"game_name": "game", "scenario": "tutorial"}"#
+            )
+            .is_err()
+        );
+    }
 }
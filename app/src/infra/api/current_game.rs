@@ -16,11 +16,12 @@ pub struct ActiveGame {
     state: String,
 }
 
-fn state_name(state: &GameState) -> String {
+pub(super) fn state_name(state: &GameState) -> String {
     match state {
         GameState::Open => "Open".to_string(),
         GameState::Running { .. } => "Running".to_string(),
         GameState::PostDelivery { .. } => "Running".to_string(),
+        GameState::Paused { .. } => "Running".to_string(),
         GameState::Ended(_) => "Ended".to_string(),
     }
 }
@@ -53,6 +54,7 @@ pub async fn current_game(State(state): State<ApiState>, cookies: Cookies) -> im
 #[cfg(test)]
 mod tests {
     use axum::http::{Request, StatusCode};
+    use chrono::Utc;
     use http_body_util::BodyExt;
     use tokio::sync::{mpsc, watch};
     use tower::ServiceExt;
@@ -60,26 +62,41 @@ mod tests {
     use crate::{
         AppConfig,
         game::{
-            GameContext, GameId, GameName, GameState,
+            GameContext, GameId, GameName, GameState, HostToken, PhaseDurations, PlayerLimits,
             delivery_period::DeliveryPeriodId,
-            infra::stack_config::{GameStackConfig, GameStackFixedConfig},
+            infra::stack_config::{GameStackConfig, GameStackFixedConfig, PlantConfig},
         },
         infra::api::{build_router, state::new_api_state},
-        utils::units::{Energy, EnergyCost, Power},
+        plants::PlantDefinition,
+        utils::units::{CarbonPrice, Energy, EnergyCost, Power},
     };
 
     fn stack_config() -> GameStackConfig {
         GameStackConfig::Fixed(GameStackFixedConfig {
-            battery_capacity: Energy::from(300),
-            consumers_forecasts: vec![],
-            consumers_forecasts_range: 2,
-            consumers_revenues: EnergyCost::from(50),
-            gas_capacity: Power::from(500),
-            gas_cost: EnergyCost::from(80),
-            nuclear_capacity: Power::from(1200),
-            nuclear_cost: EnergyCost::from(35),
-            renewable_forecasts: vec![],
-            renewable_forecasts_range: 2,
+            plants: vec![
+                PlantConfig::new(
+                    "battery",
+                    PlantDefinition::Battery {
+                        capacity: Energy::from(300),
+                    },
+                ),
+                PlantConfig::new(
+                    "gas",
+                    PlantDefinition::GasPlant {
+                        energy_cost: EnergyCost::from(80),
+                        capacity: Power::from(500),
+                    },
+                ),
+                PlantConfig::new(
+                    "nuclear",
+                    PlantDefinition::Nuclear {
+                        energy_cost: EnergyCost::from(35),
+                        capacity: Power::from(1200),
+                    },
+                ),
+            ],
+            carbon_price: CarbonPrice::default(),
+            weather: None,
         })
     }
 
@@ -92,8 +109,14 @@ mod tests {
             name: GameName::new("test-game".to_string()).unwrap(),
             stack: stack_config(),
             last_delivery_period: DeliveryPeriodId::from(4),
+            join_code: None,
+            host_token: HostToken::default(),
+            player_limits: PlayerLimits::default(),
+            durations: PhaseDurations::default(),
+            created_at: Utc::now(),
             tx,
             state_rx,
+            players_rx: watch::channel(Vec::new()).1,
         };
         (game_id, ctx)
     }
@@ -102,6 +125,12 @@ mod tests {
         AppConfig {
             port: 0,
             allow_origin: "http://localhost:5173".to_string(),
+            profiles_dir: "profiles".into(),
+            scenarios_dir: "scenarios".into(),
+            persistence_dir: None,
+            event_log_dir: None,
+            archive_dir: None,
+            trusted_proxies: Vec::new(),
         }
     }
 
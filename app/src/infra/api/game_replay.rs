@@ -0,0 +1,249 @@
+use std::net::IpAddr;
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use serde::Deserialize;
+
+use crate::game::{GameId, HostToken, infra::archive::ArchivedGame};
+
+use super::{
+    ApiState,
+    host_game::host_token_header,
+    join_game::{ClientAddress, check_code_attempt},
+    state::get_archive,
+};
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ArchiveQuery {
+    /// Code of the game, for private games
+    join_code: Option<String>,
+}
+
+/// Check that a client can read the archive of a game, as for the details of a live game: private
+/// games require their host token or their join code, wrong codes counting towards the lockout.
+pub(super) async fn check_archive_access(
+    state: &ApiState,
+    archived: &ArchivedGame,
+    client: IpAddr,
+    host_token: Option<HostToken>,
+    code: Option<&str>,
+) -> Result<(), StatusCode> {
+    let Some(join_code) = &archived.join_code else {
+        return Ok(());
+    };
+    if host_token.as_ref() == Some(&archived.host_token) {
+        return Ok(());
+    }
+    check_code_attempt(state, &archived.archive.id, join_code, client, code).await
+}
+
+/// Timeline of a finished game, served from its archive so that it is still available once the
+/// game's actors are terminated.
+pub async fn game_replay(
+    ClientAddress(client): ClientAddress,
+    State(state): State<ApiState>,
+    Path(game_id): Path<String>,
+    Query(query): Query<ArchiveQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let Some(game_id) = GameId::parse(&game_id) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let Some(archived) = get_archive(&state, &game_id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if let Err(status) = check_archive_access(
+        &state,
+        &archived,
+        client,
+        host_token_header(&headers),
+        query.join_code.as_deref(),
+    )
+    .await
+    {
+        return status.into_response();
+    }
+    Json(archived.archive).into_response()
+}
+
+#[cfg(test)]
+mod test_api_game_replay {
+    use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+
+    use axum::{
+        Router,
+        body::Body,
+        extract::connect_info::MockConnectInfo,
+        http::{Request, StatusCode},
+        routing::get,
+    };
+    use chrono::Utc;
+    use http_body_util::BodyExt;
+    use tokio::sync::{RwLock, mpsc};
+    use tower::ServiceExt;
+
+    use crate::{
+        game::{
+            GameId, GameName, HostToken, JoinCode,
+            infra::archive::{ArchiveStore, ArchivedGame, Archives, GameArchive},
+        },
+        infra::api::{ApiState, host_game::HOST_TOKEN_HEADER, state::AppState},
+        player::PlayerName,
+        utils::config::AppConfig,
+    };
+
+    use super::game_replay;
+
+    fn init_state(archives: Archives) -> ApiState {
+        let (tx, _) = mpsc::channel(16);
+        let (cleanup_tx, _) = mpsc::channel(16);
+        let (archive_tx, _) = mpsc::channel(16);
+        Arc::new(RwLock::new(AppState {
+            game_services: HashMap::new(),
+            market_services: HashMap::new(),
+            stack_services: HashMap::new(),
+            player_connections_repository: tx,
+            cleanup_tx,
+            archives,
+            archive_tx,
+            tournaments: HashMap::new(),
+            join_code_attempts: HashMap::new(),
+            config: AppConfig::default(),
+        }))
+    }
+
+    fn build_app(state: ApiState) -> Router {
+        Router::new()
+            .route("/game/{game_id}/replay", get(game_replay))
+            .with_state(state)
+            .layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 8080))))
+    }
+
+    fn archived_game(join_code: Option<JoinCode>) -> ArchivedGame {
+        ArchivedGame {
+            archive: GameArchive {
+                id: GameId::default(),
+                name: GameName::from("game".to_string()),
+                ended_at: Utc::now(),
+                players: vec![PlayerName::from("toto")],
+                periods: Vec::new(),
+                rankings: Vec::new(),
+                emissions_rankings: None,
+            },
+            host_token: HostToken::default(),
+            join_code,
+        }
+    }
+
+    async fn get_replay(
+        app: Router,
+        uri: String,
+        host_token: Option<&HostToken>,
+    ) -> (StatusCode, Vec<u8>) {
+        let mut request = Request::builder().uri(uri);
+        if let Some(token) = host_token {
+            request = request.header(HOST_TOKEN_HEADER, token.to_string());
+        }
+        let response = app
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, body.to_vec())
+    }
+
+    #[tokio::test]
+    async fn test_unknown_game_has_no_replay() {
+        let (status, _) = get_replay(
+            build_app(init_state(Archives::new(None))),
+            format!("/game/{}/replay", GameId::default()),
+            None,
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_replay_served_from_archive() {
+        let archived = archived_game(None);
+        let mut archives = Archives::new(None);
+        archives.insert(archived.clone());
+
+        let (status, body) = get_replay(
+            build_app(init_state(archives)),
+            format!("/game/{}/replay", archived.archive.id),
+            None,
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            serde_json::from_slice::<GameArchive>(&body).unwrap(),
+            archived.archive
+        );
+    }
+
+    #[tokio::test]
+    async fn test_replay_loaded_from_store_when_not_cached() {
+        let dir = std::env::temp_dir().join(format!("parcelec-archives-{}", uuid::Uuid::new_v4()));
+        let store = ArchiveStore::new(&dir);
+        let archived = archived_game(None);
+        store.save(&archived).await.unwrap();
+
+        let (status, body) = get_replay(
+            build_app(init_state(Archives::new(Some(store)))),
+            format!("/game/{}/replay", archived.archive.id),
+            None,
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            serde_json::from_slice::<GameArchive>(&body).unwrap(),
+            archived.archive
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_replay_of_private_game_requires_host_token_or_join_code() {
+        let join_code = JoinCode::default();
+        let archived = archived_game(Some(join_code.clone()));
+        let mut archives = Archives::new(None);
+        archives.insert(archived.clone());
+        let state = init_state(archives);
+        let uri = format!("/game/{}/replay", archived.archive.id);
+
+        let (status, _) = get_replay(build_app(state.clone()), uri.clone(), None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _) = get_replay(
+            build_app(state.clone()),
+            uri.clone(),
+            Some(&HostToken::default()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (status, _) = get_replay(
+            build_app(state.clone()),
+            uri.clone(),
+            Some(&archived.host_token),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = get_replay(
+            build_app(state),
+            format!("{uri}?join_code={join_code}"),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
@@ -0,0 +1,348 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use tokio::sync::oneshot;
+
+use crate::game::{GameId, GameMessage, infra::results::GameResults};
+
+use super::{
+    ApiState, game_replay::check_archive_access, host_game::host_token_header,
+    join_game::ClientAddress, state::get_archive,
+};
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ResultsFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResultsQuery {
+    #[serde(default)]
+    format: ResultsFormat,
+    /// Code of the game, for private games once they are archived
+    join_code: Option<String>,
+}
+
+/// Scores of each player for every delivery period and their final ranks, as JSON or CSV. Results
+/// are taken from the running game if any, only for its host whose token is sent in a header, or
+/// from its archive once the game is terminated, with the same access as its replay.
+pub async fn game_results(
+    ClientAddress(client): ClientAddress,
+    State(state): State<ApiState>,
+    Path(game_id): Path<String>,
+    Query(query): Query<ResultsQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let Some(game_id) = GameId::parse(&game_id) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let game = state.read().await.game_services.get(&game_id).cloned();
+    let host_token = host_token_header(&headers);
+
+    let results = match game {
+        Some(game) => {
+            if host_token.as_ref() != Some(&game.host_token) {
+                return StatusCode::FORBIDDEN.into_response();
+            }
+            let (tx_back, rx) = oneshot::channel();
+            let _ = game.tx.send(GameMessage::GetResults { tx_back }).await;
+            match rx.await {
+                Ok(results) => results,
+                Err(err) => {
+                    tracing::error!("Error while sending message to game instance: {err:?}");
+                    return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+                }
+            }
+        }
+        None => {
+            let Some(archived) = get_archive(&state, &game_id).await else {
+                return StatusCode::NOT_FOUND.into_response();
+            };
+            if let Err(status) = check_archive_access(
+                &state,
+                &archived,
+                client,
+                host_token,
+                query.join_code.as_deref(),
+            )
+            .await
+            {
+                return status.into_response();
+            }
+            GameResults::from(&archived.archive)
+        }
+    };
+
+    match query.format {
+        ResultsFormat::Json => Json(results).into_response(),
+        ResultsFormat::Csv => (
+            [
+                (header::CONTENT_TYPE, "text/csv".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"parcelec-{game_id}.csv\""),
+                ),
+            ],
+            results.to_csv(),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod test_api_game_results {
+    use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+
+    use axum::{
+        Router,
+        body::Body,
+        extract::connect_info::MockConnectInfo,
+        http::{Request, StatusCode, header},
+        routing::get,
+    };
+    use chrono::Utc;
+    use http_body_util::BodyExt;
+    use tokio::sync::{RwLock, mpsc, watch};
+    use tower::ServiceExt;
+
+    use crate::{
+        game::{
+            GameContext, GameId, GameMessage, GameName, GameState, HostToken, JoinCode,
+            PhaseDurations, PlayerLimits,
+            delivery_period::DeliveryPeriodId,
+            infra::{
+                archive::{ArchivedGame, Archives, GameArchive},
+                results::GameResults,
+                stack_config::{GameStackConfig, GameStackFixedConfig},
+            },
+        },
+        infra::api::{ApiState, host_game::HOST_TOKEN_HEADER, state::AppState},
+        player::{PlayerName, PlayerResultView},
+        utils::{
+            config::AppConfig,
+            units::{CarbonPrice, Money},
+        },
+    };
+
+    use super::game_results;
+
+    fn init_state() -> ApiState {
+        let (tx, _) = mpsc::channel(16);
+        let (cleanup_tx, _) = mpsc::channel(16);
+        let (archive_tx, _) = mpsc::channel(16);
+        Arc::new(RwLock::new(AppState {
+            game_services: HashMap::new(),
+            market_services: HashMap::new(),
+            stack_services: HashMap::new(),
+            player_connections_repository: tx,
+            cleanup_tx,
+            archives: Archives::new(None),
+            archive_tx,
+            tournaments: HashMap::new(),
+            join_code_attempts: HashMap::new(),
+            config: AppConfig::default(),
+        }))
+    }
+
+    fn build_app(state: ApiState) -> Router {
+        Router::new()
+            .route("/game/{game_id}/results", get(game_results))
+            .with_state(state)
+            .layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 8080))))
+    }
+
+    fn rankings() -> Vec<PlayerResultView> {
+        vec![PlayerResultView {
+            player: PlayerName::from("toto"),
+            rank: 1,
+            score: Money::from(100),
+        }]
+    }
+
+    async fn get_results(
+        app: Router,
+        uri: String,
+        host_token: Option<&str>,
+    ) -> (StatusCode, Option<String>, Vec<u8>) {
+        let mut request = Request::builder().uri(uri);
+        if let Some(token) = host_token {
+            request = request.header(HOST_TOKEN_HEADER, token);
+        }
+        let response = app
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .map(|value| value.to_str().unwrap().to_string());
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, content_type, body.to_vec())
+    }
+
+    #[tokio::test]
+    async fn test_unknown_game_has_no_results() {
+        let (status, _, _) = get_results(
+            build_app(init_state()),
+            format!("/game/{}/results", GameId::default()),
+            None,
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_results_of_running_game_as_json() {
+        let state = init_state();
+        let game_id = GameId::default();
+        let (tx, mut rx) = mpsc::channel(16);
+        let (_, state_rx) = watch::channel(GameState::Running {
+            period: DeliveryPeriodId::from(2),
+            end_at: None,
+        });
+        state.write().await.game_services.insert(
+            game_id.clone(),
+            GameContext {
+                id: game_id.clone(),
+                name: GameName::default(),
+                stack: GameStackConfig::Fixed(GameStackFixedConfig {
+                    plants: Vec::new(),
+                    carbon_price: CarbonPrice::default(),
+                    weather: None,
+                }),
+                last_delivery_period: DeliveryPeriodId::from(3),
+                join_code: None,
+                host_token: HostToken::from("token"),
+                player_limits: PlayerLimits::default(),
+                durations: PhaseDurations::default(),
+                created_at: Utc::now(),
+                tx,
+                state_rx,
+                players_rx: watch::channel(Vec::new()).1,
+            },
+        );
+        let expected = GameResults {
+            periods: Vec::new(),
+            rankings: rankings(),
+            emissions_rankings: None,
+        };
+        let results = expected.clone();
+        tokio::spawn(async move {
+            if let Some(GameMessage::GetResults { tx_back }) = rx.recv().await {
+                let _ = tx_back.send(results);
+            }
+        });
+
+        let (status, _, _) = get_results(
+            build_app(state.clone()),
+            format!("/game/{game_id}/results"),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _, _) = get_results(
+            build_app(state.clone()),
+            format!("/game/{game_id}/results"),
+            Some("not-the-token"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _, _) = get_results(
+            build_app(state.clone()),
+            format!("/game/{game_id}/results?host_token=token"),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (status, _, body) = get_results(
+            build_app(state),
+            format!("/game/{game_id}/results"),
+            Some("token"),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            serde_json::from_slice::<GameResults>(&body).unwrap(),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn test_results_of_archived_game_as_csv() {
+        let state = init_state();
+        let archive = GameArchive {
+            id: GameId::default(),
+            name: GameName::from("game".to_string()),
+            ended_at: Utc::now(),
+            players: vec![PlayerName::from("toto")],
+            periods: Vec::new(),
+            rankings: rankings(),
+            emissions_rankings: None,
+        };
+        state.write().await.archives.insert(ArchivedGame {
+            archive: archive.clone(),
+            host_token: HostToken::default(),
+            join_code: None,
+        });
+
+        let (status, content_type, body) = get_results(
+            build_app(state),
+            format!("/game/{}/results?format=csv", archive.id),
+            None,
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, Some("text/csv".to_string()));
+        assert!(
+            String::from_utf8(body)
+                .unwrap()
+                .starts_with("player,period,")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_results_of_archived_private_game_require_host_token_or_join_code() {
+        let state = init_state();
+        let join_code = JoinCode::default();
+        let archive = GameArchive {
+            id: GameId::default(),
+            name: GameName::from("game".to_string()),
+            ended_at: Utc::now(),
+            players: vec![PlayerName::from("toto")],
+            periods: Vec::new(),
+            rankings: rankings(),
+            emissions_rankings: None,
+        };
+        state.write().await.archives.insert(ArchivedGame {
+            archive: archive.clone(),
+            host_token: HostToken::from("token"),
+            join_code: Some(join_code.clone()),
+        });
+        let uri = format!("/game/{}/results", archive.id);
+
+        let (status, _, _) = get_results(build_app(state.clone()), uri.clone(), None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _, _) =
+            get_results(build_app(state.clone()), uri.clone(), Some("token")).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _, _) = get_results(
+            build_app(state),
+            format!("{uri}?join_code={join_code}"),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
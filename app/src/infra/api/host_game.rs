@@ -0,0 +1,365 @@
+use std::time::Duration;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+use crate::{
+    game::{GameId, GameMessage, HostCommand, HostCommandError, HostCommandOutcome, HostToken},
+    player::PlayerName,
+};
+
+use super::ApiState;
+
+/// Longest extension of a timer in a single host command
+const MAX_TIMER_EXTENSION_SECONDS: u64 = 3600;
+
+/// Header carrying the host token on read requests, so that the token is kept out of URLs
+pub const HOST_TOKEN_HEADER: &str = "x-host-token";
+
+/// Host token sent along a request, if any.
+pub(super) fn host_token_header(headers: &HeaderMap) -> Option<HostToken> {
+    headers
+        .get(HOST_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(HostToken::from)
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum HostCommandRequest {
+    ForceStartPeriod,
+    ForceClosePeriod,
+    ExtendTimer { seconds: u64 },
+    EndGame,
+    KickPlayer { player_name: String },
+    Pause,
+    Resume,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct HostRequest {
+    host_token: String,
+    #[serde(flatten)]
+    command: HostCommandRequest,
+}
+
+impl HostCommandRequest {
+    fn into_command(self) -> Option<HostCommand> {
+        Some(match self {
+            HostCommandRequest::ForceStartPeriod => HostCommand::ForceStartPeriod,
+            HostCommandRequest::ForceClosePeriod => HostCommand::ForceClosePeriod,
+            HostCommandRequest::ExtendTimer { seconds } => {
+                if seconds > MAX_TIMER_EXTENSION_SECONDS {
+                    return None;
+                }
+                HostCommand::ExtendTimer(Duration::from_secs(seconds))
+            }
+            HostCommandRequest::EndGame => HostCommand::EndGame,
+            HostCommandRequest::KickPlayer { player_name } => {
+                HostCommand::KickPlayer(PlayerName::parse(&player_name)?)
+            }
+            HostCommandRequest::Pause => HostCommand::Pause,
+            HostCommandRequest::Resume => HostCommand::Resume,
+        })
+    }
+}
+
+/// Commands reserved to the creator of a game, authenticated by the host token returned when the
+/// game was created.
+pub async fn host_command(
+    State(state): State<ApiState>,
+    Path(game_id): Path<String>,
+    Json(request): Json<HostRequest>,
+) -> StatusCode {
+    let (Some(game_id), Some(command)) = (GameId::parse(&game_id), request.command.into_command())
+    else {
+        return StatusCode::BAD_REQUEST;
+    };
+    let Some(game) = state.read().await.game_services.get(&game_id).cloned() else {
+        return StatusCode::NOT_FOUND;
+    };
+
+    let (tx_back, rx) = oneshot::channel();
+    let _ = game
+        .tx
+        .send(GameMessage::HostCommand {
+            token: HostToken::from(request.host_token),
+            command,
+            tx_back,
+        })
+        .await;
+
+    match rx.await {
+        Ok(Ok(HostCommandOutcome::Applied)) => StatusCode::OK,
+        Ok(Ok(HostCommandOutcome::PlayerKicked(player_id))) => {
+            if let Some(stacks) = state.write().await.stack_services.get_mut(&game_id) {
+                stacks.remove(&player_id);
+            }
+            tracing::info!("Player {player_id} kicked from game {game_id:?}");
+            StatusCode::OK
+        }
+        Ok(Err(HostCommandError::InvalidHostToken)) => StatusCode::FORBIDDEN,
+        Ok(Err(HostCommandError::TimerOverflow)) => StatusCode::BAD_REQUEST,
+        Ok(Err(HostCommandError::PlayerNotFound)) => StatusCode::NOT_FOUND,
+        Ok(Err(HostCommandError::NotAllowedInCurrentState | HostCommandError::GameHasNoTimer)) => {
+            StatusCode::CONFLICT
+        }
+        Err(err) => {
+            tracing::error!("Error while sending message to game instance: {err:?}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_api_host_game {
+    use std::{collections::HashMap, sync::Arc, time::Duration};
+
+    use axum::{
+        Router,
+        body::Body,
+        http::{self, Request, StatusCode},
+        routing::post,
+    };
+    use chrono::Utc;
+    use tokio::sync::{RwLock, mpsc, watch};
+    use tower::ServiceExt;
+
+    use crate::{
+        game::{
+            GameContext, GameId, GameMessage, GameName, GameState, HostCommand, HostCommandError,
+            HostCommandOutcome, HostToken, PhaseDurations, PlayerLimits,
+            delivery_period::DeliveryPeriodId,
+            infra::{
+                archive::Archives,
+                stack_config::{GameStackConfig, GameStackFixedConfig},
+            },
+        },
+        infra::api::{ApiState, state::AppState},
+        plants::{
+            StackService,
+            infra::{StackContext, StackState},
+        },
+        player::{PlayerId, PlayerName},
+        utils::{config::AppConfig, units::CarbonPrice},
+    };
+
+    use super::{HostCommandRequest, HostRequest, host_command};
+
+    fn init_state() -> ApiState {
+        let (tx, _) = mpsc::channel(16);
+        let (cleanup_tx, _) = mpsc::channel(16);
+        let (archive_tx, _) = mpsc::channel(16);
+        Arc::new(RwLock::new(AppState {
+            game_services: HashMap::new(),
+            market_services: HashMap::new(),
+            stack_services: HashMap::new(),
+            player_connections_repository: tx,
+            cleanup_tx,
+            archives: Archives::new(None),
+            archive_tx,
+            tournaments: HashMap::new(),
+            join_code_attempts: HashMap::new(),
+            config: AppConfig::default(),
+        }))
+    }
+
+    fn start_game(id: GameId) -> (GameContext, mpsc::Receiver<GameMessage>) {
+        let (tx, rx) = mpsc::channel(16);
+        let (_, state_rx) = watch::channel(GameState::Open);
+        (
+            GameContext {
+                id,
+                name: GameName::default(),
+                stack: GameStackConfig::Fixed(GameStackFixedConfig {
+                    plants: Vec::new(),
+                    carbon_price: CarbonPrice::default(),
+                    weather: None,
+                }),
+                last_delivery_period: DeliveryPeriodId::from(3),
+                join_code: None,
+                host_token: HostToken::default(),
+                player_limits: PlayerLimits::default(),
+                durations: PhaseDurations::default(),
+                created_at: Utc::now(),
+                tx,
+                state_rx,
+                players_rx: watch::channel(Vec::new()).1,
+            },
+            rx,
+        )
+    }
+
+    async fn send_command(state: ApiState, game_id: &GameId, request: HostRequest) -> StatusCode {
+        let app = Router::new()
+            .route("/game/{game_id}/host", post(host_command))
+            .with_state(state);
+        app.oneshot(
+            Request::builder()
+                .method(http::Method::POST)
+                .uri(format!("/game/{game_id}/host"))
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(serde_json::to_string(&request).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+        .status()
+    }
+
+    #[tokio::test]
+    async fn test_host_command_unknown_game() {
+        let status = send_command(
+            init_state(),
+            &GameId::default(),
+            HostRequest {
+                host_token: "token".to_string(),
+                command: HostCommandRequest::EndGame,
+            },
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_host_command_forwarded_to_game() {
+        let state = init_state();
+        let game_id = GameId::default();
+        let (game, mut rx) = start_game(game_id.clone());
+        state
+            .write()
+            .await
+            .game_services
+            .insert(game_id.clone(), game);
+        tokio::spawn(async move {
+            if let Some(GameMessage::HostCommand {
+                token,
+                command,
+                tx_back,
+            }) = rx.recv().await
+            {
+                assert_eq!(token.to_string(), "token");
+                assert_eq!(command, HostCommand::ExtendTimer(Duration::from_secs(60)));
+                let _ = tx_back.send(Ok(HostCommandOutcome::Applied));
+            }
+        });
+
+        let status = send_command(
+            state,
+            &game_id,
+            HostRequest {
+                host_token: "token".to_string(),
+                command: HostCommandRequest::ExtendTimer { seconds: 60 },
+            },
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_host_command_rejects_too_long_timer_extension() {
+        let state = init_state();
+        let game_id = GameId::default();
+        let (game, mut rx) = start_game(game_id.clone());
+        state
+            .write()
+            .await
+            .game_services
+            .insert(game_id.clone(), game);
+
+        let status = send_command(
+            state,
+            &game_id,
+            HostRequest {
+                host_token: "token".to_string(),
+                command: HostCommandRequest::ExtendTimer { seconds: u64::MAX },
+            },
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_host_command_invalid_token() {
+        let state = init_state();
+        let game_id = GameId::default();
+        let (game, mut rx) = start_game(game_id.clone());
+        state
+            .write()
+            .await
+            .game_services
+            .insert(game_id.clone(), game);
+        tokio::spawn(async move {
+            if let Some(GameMessage::HostCommand { tx_back, .. }) = rx.recv().await {
+                let _ = tx_back.send(Err(HostCommandError::InvalidHostToken));
+            }
+        });
+
+        let status = send_command(
+            state,
+            &game_id,
+            HostRequest {
+                host_token: "not the token".to_string(),
+                command: HostCommandRequest::ForceStartPeriod,
+            },
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_kicked_player_stack_is_removed() {
+        let state = init_state();
+        let game_id = GameId::default();
+        let player_id = PlayerId::default();
+        let (game, mut rx) = start_game(game_id.clone());
+        {
+            let mut state = state.write().await;
+            state.game_services.insert(game_id.clone(), game);
+            let (_, state_rx) = watch::channel(StackState::Closed);
+            let (tx, _) = mpsc::channel(16);
+            let stack = StackContext {
+                service: StackService::new(tx),
+                state_rx,
+            };
+            state
+                .stack_services
+                .insert(game_id.clone(), HashMap::from([(player_id.clone(), stack)]));
+        }
+        let kicked = player_id.clone();
+        tokio::spawn(async move {
+            if let Some(GameMessage::HostCommand {
+                command, tx_back, ..
+            }) = rx.recv().await
+            {
+                assert_eq!(command, HostCommand::KickPlayer(PlayerName::from("idle")));
+                let _ = tx_back.send(Ok(HostCommandOutcome::PlayerKicked(kicked)));
+            }
+        });
+
+        let status = send_command(
+            state.clone(),
+            &game_id,
+            HostRequest {
+                host_token: "token".to_string(),
+                command: HostCommandRequest::KickPlayer {
+                    player_name: "idle".to_string(),
+                },
+            },
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert!(state.read().await.stack_services[&game_id].is_empty());
+    }
+}
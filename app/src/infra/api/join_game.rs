@@ -1,78 +1,322 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
 
-use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use axum::{
+    Json,
+    extract::{ConnectInfo, FromRequestParts, State},
+    http::{HeaderMap, StatusCode, request::Parts},
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use tokio::sync::oneshot;
 use tower_cookies::Cookies;
 
 use crate::{
-    game::{GameId, GameMessage, GameState, RegisterPlayerResponse},
+    game::{GameId, GameMessage, JoinCode, RegisterPlayerResponse, TeamSecret},
     infra::api::cookies::add_game_cookies,
-    player::PlayerName,
+    player::{PlayerId, PlayerName},
+    utils::config::IpNetwork,
 };
 
-use super::ApiState;
+use super::{ApiState, state::AppState};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct JoinGame {
     game_id: String,
     player_name: String,
+    /// Code of the game, required to join private games
+    #[serde(default)]
+    join_code: Option<String>,
+    /// Secret of the team to join, required to join an existing team in team games
+    #[serde(default)]
+    team_secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JoinGameResponse {
+    /// Secret to share with the other members of the team, for the creator of a team
+    #[serde(skip_serializing_if = "Option::is_none")]
+    team_secret: Option<TeamSecret>,
+}
+
+/// Number of wrong codes after which joining a private game is locked for a while
+const MAX_WRONG_JOIN_CODES: u32 = 5;
+const JOIN_CODE_LOCKOUT: Duration = Duration::from_secs(60);
+/// Clients whose wrong codes are tracked for each game, other clients cannot try a code until
+/// tracked attempts expire, so that attempts from many addresses neither grow the app state nor
+/// escape the lockout
+const MAX_TRACKED_CLIENTS_PER_GAME: usize = 256;
+
+/// Address of the client of a request, taken from the `X-Forwarded-For` header only when the
+/// request comes from a trusted proxy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClientAddress(pub IpAddr);
+
+impl FromRequestParts<ApiState> for ClientAddress {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &ApiState,
+    ) -> Result<Self, Self::Rejection> {
+        let ConnectInfo(peer) = ConnectInfo::<SocketAddr>::from_request_parts(parts, state)
+            .await
+            .map_err(|err| {
+                tracing::error!("Missing connection info of the request: {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+        let trusted_proxies = state.read().await.config.trusted_proxies.clone();
+        Ok(ClientAddress(client_address(
+            &trusted_proxies,
+            peer.ip(),
+            &parts.headers,
+        )))
+    }
+}
+
+/// Address of the client behind `peer`. Each trusted proxy appends the address it got the request
+/// from to `X-Forwarded-For`, the client is therefore the last address not of a trusted proxy.
+fn client_address(trusted_proxies: &[IpNetwork], peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+    let is_trusted = |ip: IpAddr| trusted_proxies.iter().any(|proxy| proxy.contains(ip));
+    if !is_trusted(peer) {
+        return peer;
+    }
+    let Some(forwarded) = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .map(|value| value.to_str().ok())
+        .collect::<Option<Vec<&str>>>()
+    else {
+        return peer;
+    };
+    let mut address = peer;
+    for value in forwarded.iter().flat_map(|value| value.split(',')).rev() {
+        let Ok(ip) = value.trim().parse::<IpAddr>() else {
+            break;
+        };
+        address = ip;
+        if !is_trusted(ip) {
+            break;
+        }
+    }
+    address
+}
+
+/// Wrong join codes and team secrets submitted by a client to join a game. Joining the game is
+/// locked for a while for this client after too many wrong attempts, so that its code and team
+/// secrets cannot be brute-forced without locking out the other players.
+#[derive(Debug, Default)]
+pub struct JoinCodeAttempts {
+    wrong_codes: u32,
+    last_wrong_code: DateTime<Utc>,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+impl JoinCodeAttempts {
+    pub fn is_locked(&self, now: DateTime<Utc>) -> bool {
+        self.locked_until.is_some_and(|until| now < until)
+    }
+
+    /// Attempts are forgotten once the client has not tried a wrong code for as long as a lockout.
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        !self.is_locked(now) && now >= self.last_wrong_code + JOIN_CODE_LOCKOUT
+    }
+
+    pub fn record_wrong_code(&mut self, now: DateTime<Utc>) {
+        self.last_wrong_code = now;
+        self.wrong_codes += 1;
+        if self.wrong_codes >= MAX_WRONG_JOIN_CODES {
+            self.wrong_codes = 0;
+            self.locked_until = Some(now + JOIN_CODE_LOCKOUT);
+        }
+    }
 }
 
 pub async fn join_game(
     cookies: Cookies,
+    ClientAddress(client): ClientAddress,
     State(state): State<ApiState>,
     Json(input): Json<JoinGame>,
 ) -> impl IntoResponse {
-    let mut state = state.write().await;
     let (Some(game_id), Some(player_name)) = (
         GameId::parse(&input.game_id),
         PlayerName::parse(&input.player_name),
     ) else {
-        return StatusCode::BAD_REQUEST;
+        return StatusCode::BAD_REQUEST.into_response();
     };
+    if let Err(status) = check_join_code(&state, &game_id, client, input.join_code.as_deref()).await
+    {
+        return status.into_response();
+    }
+
+    let mut state = state.write().await;
+    let now = Utc::now();
+    let team_secret = input.team_secret.map(TeamSecret::from);
+    // Team secrets share the lockout of join codes, so that they cannot be brute-forced either
+    let joins_team = team_secret.is_some();
+    if joins_team && is_locked_out(&state, &game_id, client, now) {
+        tracing::warn!("Too many wrong secrets from {client} to join a team in game {game_id:?}");
+        return StatusCode::TOO_MANY_REQUESTS.into_response();
+    }
+    let (player_id, team_secret) =
+        match register_player(&mut state, &game_id, &player_name, team_secret).await {
+            Ok(registration) => registration,
+            Err(StatusCode::FORBIDDEN) if joins_team => {
+                record_wrong_attempt(&mut state, &game_id, client, now);
+                return StatusCode::FORBIDDEN.into_response();
+            }
+            Err(status) => return status.into_response(),
+        };
+
+    add_game_cookies(&cookies, &player_id, &player_name, &game_id);
+    tracing::info!("Registered player {} with id {player_id}", player_name);
+    (StatusCode::CREATED, Json(JoinGameResponse { team_secret })).into_response()
+}
 
-    let Some(game) = state.game_services.get(&game_id) else {
-        return StatusCode::NOT_FOUND;
+/// Check the code given by `client` to join a private game, public games need no code.
+pub(super) async fn check_join_code(
+    state: &ApiState,
+    game_id: &GameId,
+    client: IpAddr,
+    code: Option<&str>,
+) -> Result<(), StatusCode> {
+    let Some(join_code) = state
+        .read()
+        .await
+        .game_services
+        .get(game_id)
+        .and_then(|game| game.join_code.clone())
+    else {
+        return Ok(());
     };
+    check_code_attempt(state, game_id, &join_code, client, code).await
+}
+
+/// Check a code submitted by a client against the code of a private game, with the lockout after
+/// too many wrong codes.
+pub(super) async fn check_code_attempt(
+    state: &ApiState,
+    game_id: &GameId,
+    join_code: &JoinCode,
+    client: IpAddr,
+    code: Option<&str>,
+) -> Result<(), StatusCode> {
+    let now = Utc::now();
+    let mut state = state.write().await;
+    if is_locked_out(&state, game_id, client, now) {
+        tracing::warn!("Too many wrong codes from {client} to join game {game_id:?}");
+        return Err(StatusCode::TOO_MANY_REQUESTS);
+    }
+    if !code.is_some_and(|code| join_code.matches(code)) {
+        record_wrong_attempt(&mut state, game_id, client, now);
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(())
+}
 
-    if *game.state_rx.borrow() != GameState::Open {
-        return StatusCode::UNPROCESSABLE_ENTITY;
+/// Whether `client` cannot try a code for `game_id`, because of its wrong attempts or because the
+/// game already tracks as many clients as it can.
+fn is_locked_out(state: &AppState, game_id: &GameId, client: IpAddr, now: DateTime<Utc>) -> bool {
+    match state.join_code_attempts.get(&(game_id.clone(), client)) {
+        Some(attempts) => attempts.is_locked(now),
+        None => {
+            state
+                .join_code_attempts
+                .iter()
+                .filter(|((game, _), attempts)| game == game_id && !attempts.is_expired(now))
+                .count()
+                >= MAX_TRACKED_CLIENTS_PER_GAME
+        }
     }
+}
+
+/// Count a wrong join code or team secret from `client`, pruning expired attempts. Wrong attempts
+/// are not cleared by a right code, so that a right join code does not clear wrong team secrets.
+fn record_wrong_attempt(
+    state: &mut AppState,
+    game_id: &GameId,
+    client: IpAddr,
+    now: DateTime<Utc>,
+) {
+    state
+        .join_code_attempts
+        .retain(|_, attempts| !attempts.is_expired(now));
+    state
+        .join_code_attempts
+        .entry((game_id.clone(), client))
+        .or_default()
+        .record_wrong_code(now);
+}
 
+/// Register a player to a game, along with their stack, returning their id in the game and the
+/// secret of the team they created, in team games. Players joining an existing team with its
+/// secret get the team's id.
+pub async fn register_player(
+    state: &mut AppState,
+    game_id: &GameId,
+    player_name: &PlayerName,
+    team_secret: Option<TeamSecret>,
+) -> Result<(PlayerId, Option<TeamSecret>), StatusCode> {
+    let Some(game) = state.game_services.get(game_id) else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    // Whether a started game still accepts players depends on its late join policy
     let (tx, rx) = oneshot::channel();
 
     let _ = game
         .tx
         .send(GameMessage::RegisterPlayer {
             name: player_name.clone(),
+            team_secret,
             tx_back: tx,
         })
         .await;
 
-    let (player_id, player_stack) = match rx.await {
-        Ok(RegisterPlayerResponse::Success { id, stack }) => (id, stack),
+    let (player_id, player_stack, team_secret) = match rx.await {
+        Ok(RegisterPlayerResponse::Success {
+            id,
+            stack,
+            team_secret,
+        }) => (id, stack, team_secret),
+        Ok(RegisterPlayerResponse::JoinedTeam { id }) => {
+            // The team's stack already exists, the new member shares it
+            tracing::info!("Player {} joined team with id {id}", player_name);
+            return Ok((id, None));
+        }
+        Ok(RegisterPlayerResponse::InvalidTeamSecret) => {
+            tracing::warn!("Wrong secret to join team {}", player_name);
+            return Err(StatusCode::FORBIDDEN);
+        }
         Ok(RegisterPlayerResponse::PlayerAlreadyExist) => {
             tracing::warn!("Player with name {} already exist", player_name);
-            return StatusCode::CONFLICT;
+            return Err(StatusCode::CONFLICT);
         }
         Ok(RegisterPlayerResponse::GameStarted) => {
             tracing::warn!("Cannot register a player to a running game");
-            return StatusCode::CONFLICT;
+            return Err(StatusCode::CONFLICT);
+        }
+        Ok(RegisterPlayerResponse::GameFull) => {
+            tracing::warn!("Cannot register a player to a full game");
+            return Err(StatusCode::CONFLICT);
         }
         Err(err) => {
             tracing::error!("Error while sending message to game instance: {err:?}");
-            return StatusCode::INTERNAL_SERVER_ERROR;
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
         }
     };
     if let Some(stack) = player_stack {
-        match state.stack_services.get_mut(&game_id) {
+        match state.stack_services.get_mut(game_id) {
             Some(game_stacks) => {
                 if game_stacks.get(&player_id).is_some() {
                     tracing::error!(
                         "A stack already exist for player {player_id:?} in game {game_id:?}"
                     );
-                    return StatusCode::INTERNAL_SERVER_ERROR;
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
                 }
                 game_stacks.insert(player_id.clone(), stack.clone());
             }
@@ -82,31 +326,38 @@ pub async fn join_game(
             }
         }
     }
-
-    add_game_cookies(&cookies, &player_id, &player_name, &game_id);
-    tracing::info!("Registered player {} with id {player_id}", player_name);
-    StatusCode::CREATED
+    Ok((player_id, team_secret))
 }
 
 #[cfg(test)]
 mod test_api_join_game {
     use crate::game::delivery_period::DeliveryPeriodId;
-    use crate::game::infra::stack_config::{GameStackConfig, GameStackFixedConfig};
+    use crate::game::infra::archive::Archives;
+    use crate::game::infra::stack_config::{GameStackConfig, GameStackFixedConfig, PlantConfig};
     use crate::game::{
-        GameContext, GameId, GameMessage, GameName, GameState, RegisterPlayerResponse,
+        GameContext, GameId, GameMessage, GameName, GameState, HostToken, JoinCode, PhaseDurations,
+        PlayerLimits, RegisterPlayerResponse, TeamSecret,
+    };
+    use crate::infra::api::join_game::{
+        JOIN_CODE_LOCKOUT, JoinCodeAttempts, JoinGame, JoinGameResponse,
+        MAX_TRACKED_CLIENTS_PER_GAME, MAX_WRONG_JOIN_CODES, client_address, join_game,
+        record_wrong_attempt,
     };
-    use crate::infra::api::join_game::{JoinGame, join_game};
     use crate::infra::api::{ApiState, state::AppState};
-    use crate::plants::StackService;
     use crate::plants::infra::{StackContext, StackState};
+    use crate::plants::{PlantDefinition, StackService};
     use crate::player::PlayerId;
-    use crate::utils::config::AppConfig;
-    use crate::utils::units::{Energy, EnergyCost, Power};
+    use crate::utils::config::{AppConfig, IpNetwork};
+    use crate::utils::units::{CarbonPrice, Energy, EnergyCost, Power};
     use axum::Router;
     use axum::body::Body;
+    use axum::extract::connect_info::MockConnectInfo;
     use axum::http::{self, Request, StatusCode};
     use axum::routing::post;
+    use chrono::Utc;
+    use http_body_util::BodyExt;
     use std::collections::HashMap;
+    use std::net::{IpAddr, SocketAddr};
     use std::sync::Arc;
     use tokio::sync::{RwLock, mpsc, watch};
     use tower::ServiceExt;
@@ -115,28 +366,51 @@ mod test_api_join_game {
     fn init_state() -> ApiState {
         let (tx, _) = mpsc::channel(16);
         let (cleanup_tx, _) = mpsc::channel(16);
+        let (archive_tx, _) = mpsc::channel(16);
         Arc::new(RwLock::new(AppState {
             game_services: HashMap::new(),
             market_services: HashMap::new(),
             stack_services: HashMap::new(),
             player_connections_repository: tx,
             cleanup_tx,
+            archives: Archives::new(None),
+            archive_tx,
+            tournaments: HashMap::new(),
+            join_code_attempts: HashMap::new(),
             config: AppConfig::default(),
         }))
     }
 
+    fn client() -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], 8080))
+    }
+
     fn stack_config() -> GameStackConfig {
         GameStackConfig::Fixed(GameStackFixedConfig {
-            battery_capacity: Energy::from(300),
-            consumers_forecasts: vec![],
-            consumers_forecasts_range: 2,
-            consumers_revenues: EnergyCost::from(50),
-            gas_capacity: Power::from(500),
-            gas_cost: EnergyCost::from(80),
-            nuclear_capacity: Power::from(1200),
-            nuclear_cost: EnergyCost::from(35),
-            renewable_forecasts: vec![],
-            renewable_forecasts_range: 2,
+            plants: vec![
+                PlantConfig::new(
+                    "battery",
+                    PlantDefinition::Battery {
+                        capacity: Energy::from(300),
+                    },
+                ),
+                PlantConfig::new(
+                    "gas",
+                    PlantDefinition::GasPlant {
+                        energy_cost: EnergyCost::from(80),
+                        capacity: Power::from(500),
+                    },
+                ),
+                PlantConfig::new(
+                    "nuclear",
+                    PlantDefinition::Nuclear {
+                        energy_cost: EnergyCost::from(35),
+                        capacity: Power::from(1200),
+                    },
+                ),
+            ],
+            carbon_price: CarbonPrice::default(),
+            weather: None,
         })
     }
 
@@ -149,8 +423,14 @@ mod test_api_join_game {
                 name: GameName::default(),
                 stack: stack_config(),
                 last_delivery_period: DeliveryPeriodId::from(3),
+                join_code: None,
+                host_token: HostToken::default(),
+                player_limits: PlayerLimits::default(),
+                durations: PhaseDurations::default(),
+                created_at: Utc::now(),
                 tx,
                 state_rx,
+                players_rx: watch::channel(Vec::new()).1,
             },
             rx,
         )
@@ -169,15 +449,18 @@ mod test_api_join_game {
         let app = Router::new()
             .route("/join", post(join_game))
             .layer(CookieManagerLayer::new())
+            .layer(MockConnectInfo(client()))
             .with_state(state);
 
         let request_body = JoinGame {
             game_id: game_id.to_string(),
             player_name: "TestPlayer".to_string(),
+            join_code: None,
+            team_secret: None,
         };
 
         tokio::spawn(async move {
-            if let Some(GameMessage::RegisterPlayer { name: _, tx_back }) = rx.recv().await {
+            if let Some(GameMessage::RegisterPlayer { tx_back, .. }) = rx.recv().await {
                 let player_id = PlayerId::default();
                 let (_, state_rx) = watch::channel(StackState::Open);
                 let (tx, _) = mpsc::channel(16);
@@ -187,6 +470,7 @@ mod test_api_join_game {
                     .send(RegisterPlayerResponse::Success {
                         id: player_id,
                         stack: Some(player_stack),
+                        team_secret: None,
                     })
                     .unwrap();
             }
@@ -214,6 +498,287 @@ mod test_api_join_game {
         assert_eq!(cookies.len(), 3);
     }
 
+    #[tokio::test]
+    async fn test_join_running_game_with_late_join() {
+        let state = init_state();
+        let game_id = GameId::default();
+        let (game, mut rx) = start_game(
+            game_id.clone(),
+            GameState::Running {
+                period: DeliveryPeriodId::from(2),
+                end_at: None,
+            },
+        );
+        state
+            .write()
+            .await
+            .game_services
+            .insert(game_id.clone(), game);
+
+        let app = Router::new()
+            .route("/join", post(join_game))
+            .layer(CookieManagerLayer::new())
+            .layer(MockConnectInfo(client()))
+            .with_state(state.clone());
+
+        let request_body = JoinGame {
+            game_id: game_id.to_string(),
+            player_name: "LatePlayer".to_string(),
+            join_code: None,
+            team_secret: None,
+        };
+
+        let player_id = PlayerId::default();
+        let late_player_id = player_id.clone();
+        tokio::spawn(async move {
+            if let Some(GameMessage::RegisterPlayer { tx_back, .. }) = rx.recv().await {
+                let (_, state_rx) = watch::channel(StackState::Closed);
+                let (tx, _) = mpsc::channel(16);
+                let service = StackService::new(tx);
+                tx_back
+                    .send(RegisterPlayerResponse::Success {
+                        id: late_player_id,
+                        stack: Some(StackContext { service, state_rx }),
+                        team_secret: None,
+                    })
+                    .unwrap();
+            }
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/join")
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(serde_json::to_string(&request_body).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert!(
+            state.read().await.stack_services[&game_id].contains_key(&player_id),
+            "The late player's stack should be registered"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_join_existing_team() {
+        let state = init_state();
+        let game_id = GameId::default();
+        let (game, mut rx) = start_game(game_id.clone(), GameState::Open);
+        state
+            .write()
+            .await
+            .game_services
+            .insert(game_id.clone(), game);
+
+        let app = Router::new()
+            .route("/join", post(join_game))
+            .layer(CookieManagerLayer::new())
+            .layer(MockConnectInfo(client()))
+            .with_state(state.clone());
+
+        let request_body = JoinGame {
+            game_id: game_id.to_string(),
+            player_name: "Team".to_string(),
+            join_code: None,
+            team_secret: Some("secret".to_string()),
+        };
+
+        let team_id = PlayerId::default();
+        let id = team_id.clone();
+        tokio::spawn(async move {
+            if let Some(GameMessage::RegisterPlayer {
+                team_secret,
+                tx_back,
+                ..
+            }) = rx.recv().await
+            {
+                assert_eq!(team_secret, Some(TeamSecret::from("secret")));
+                tx_back
+                    .send(RegisterPlayerResponse::JoinedTeam { id })
+                    .unwrap();
+            }
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/join")
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(serde_json::to_string(&request_body).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let cookies = response
+            .headers()
+            .get_all(http::header::SET_COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .collect::<Vec<_>>();
+        assert!(
+            cookies
+                .iter()
+                .any(|cookie| cookie.starts_with(&format!("player_id={team_id}"))),
+            "The member should play with the team's id"
+        );
+        assert!(
+            state.read().await.stack_services.is_empty(),
+            "No new stack should be created for a team member"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_team_creator_receives_the_team_secret() {
+        let state = init_state();
+        let game_id = GameId::default();
+        let (game, mut rx) = start_game(game_id.clone(), GameState::Open);
+        state
+            .write()
+            .await
+            .game_services
+            .insert(game_id.clone(), game);
+        let app = Router::new()
+            .route("/join", post(join_game))
+            .layer(CookieManagerLayer::new())
+            .layer(MockConnectInfo(client()))
+            .with_state(state.clone());
+        tokio::spawn(async move {
+            if let Some(GameMessage::RegisterPlayer { tx_back, .. }) = rx.recv().await {
+                let _ = tx_back.send(RegisterPlayerResponse::Success {
+                    id: PlayerId::default(),
+                    stack: None,
+                    team_secret: Some(TeamSecret::from("secret")),
+                });
+            }
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/join")
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        serde_json::to_string(&JoinGame {
+                            game_id: game_id.to_string(),
+                            player_name: "Team".to_string(),
+                            join_code: None,
+                            team_secret: None,
+                        })
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body: JoinGameResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body.team_secret, Some(TeamSecret::from("secret")));
+    }
+
+    #[tokio::test]
+    async fn test_join_team_with_wrong_secret() {
+        let state = init_state();
+        let game_id = GameId::default();
+        let (game, mut rx) = start_game(game_id.clone(), GameState::Open);
+        state
+            .write()
+            .await
+            .game_services
+            .insert(game_id.clone(), game);
+        let app = Router::new()
+            .route("/join", post(join_game))
+            .layer(CookieManagerLayer::new())
+            .layer(MockConnectInfo(client()))
+            .with_state(state.clone());
+        tokio::spawn(async move {
+            if let Some(GameMessage::RegisterPlayer { tx_back, .. }) = rx.recv().await {
+                let _ = tx_back.send(RegisterPlayerResponse::InvalidTeamSecret);
+            }
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/join")
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        serde_json::to_string(&JoinGame {
+                            game_id: game_id.to_string(),
+                            player_name: "Team".to_string(),
+                            join_code: None,
+                            team_secret: Some("wrong".to_string()),
+                        })
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert!(response.headers().get(http::header::SET_COOKIE).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_join_team_locked_after_too_many_wrong_secrets() {
+        let state = init_state();
+        let game_id = GameId::default();
+        let (game, mut rx) = start_game(game_id.clone(), GameState::Open);
+        state
+            .write()
+            .await
+            .game_services
+            .insert(game_id.clone(), game);
+        let app = Router::new()
+            .route("/join", post(join_game))
+            .layer(CookieManagerLayer::new())
+            .layer(MockConnectInfo(client()))
+            .with_state(state.clone());
+        tokio::spawn(async move {
+            while let Some(GameMessage::RegisterPlayer { tx_back, .. }) = rx.recv().await {
+                let _ = tx_back.send(RegisterPlayerResponse::InvalidTeamSecret);
+            }
+        });
+        let join_team = || {
+            app.clone().oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/join")
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        serde_json::to_string(&JoinGame {
+                            game_id: game_id.to_string(),
+                            player_name: "Team".to_string(),
+                            join_code: None,
+                            team_secret: Some("wrong".to_string()),
+                        })
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+        };
+
+        for _ in 0..MAX_WRONG_JOIN_CODES {
+            assert_eq!(join_team().await.unwrap().status(), StatusCode::FORBIDDEN);
+        }
+        assert_eq!(
+            join_team().await.unwrap().status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+    }
+
     #[tokio::test]
     async fn test_join_game_player_already_exists() {
         let state = init_state();
@@ -227,15 +792,18 @@ mod test_api_join_game {
         let app = Router::new()
             .route("/join", post(join_game))
             .layer(CookieManagerLayer::new())
+            .layer(MockConnectInfo(client()))
             .with_state(state);
 
         let request_body = JoinGame {
             game_id: game_id.to_string(),
             player_name: "TestPlayer".to_string(),
+            join_code: None,
+            team_secret: None,
         };
 
         tokio::spawn(async move {
-            if let Some(GameMessage::RegisterPlayer { name: _, tx_back }) = rx.recv().await {
+            if let Some(GameMessage::RegisterPlayer { tx_back, .. }) = rx.recv().await {
                 tx_back
                     .send(RegisterPlayerResponse::PlayerAlreadyExist)
                     .unwrap();
@@ -270,15 +838,18 @@ mod test_api_join_game {
         let app = Router::new()
             .route("/join", post(join_game))
             .layer(CookieManagerLayer::new())
+            .layer(MockConnectInfo(client()))
             .with_state(state);
 
         let request_body = JoinGame {
             game_id: game_id.to_string(),
             player_name: "TestPlayer".to_string(),
+            join_code: None,
+            team_secret: None,
         };
 
         tokio::spawn(async move {
-            if let Some(GameMessage::RegisterPlayer { name: _, tx_back }) = rx.recv().await {
+            if let Some(GameMessage::RegisterPlayer { tx_back, .. }) = rx.recv().await {
                 tx_back.send(RegisterPlayerResponse::GameStarted).unwrap();
             }
         });
@@ -305,11 +876,14 @@ mod test_api_join_game {
         let app = Router::new()
             .route("/join", post(join_game))
             .layer(CookieManagerLayer::new())
+            .layer(MockConnectInfo(client()))
             .with_state(state);
 
         let request_body = JoinGame {
             game_id: GameId::default().to_string(),
             player_name: "TestPlayer".to_string(),
+            join_code: None,
+            team_secret: None,
         };
 
         let response = app
@@ -326,4 +900,281 @@ mod test_api_join_game {
 
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
+
+    async fn join_private_game(
+        app: Router,
+        game_id: &GameId,
+        join_code: Option<&str>,
+    ) -> StatusCode {
+        let request_body = JoinGame {
+            game_id: game_id.to_string(),
+            player_name: "TestPlayer".to_string(),
+            join_code: join_code.map(str::to_string),
+            team_secret: None,
+        };
+        app.oneshot(
+            Request::builder()
+                .method(http::Method::POST)
+                .uri("/join")
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(serde_json::to_string(&request_body).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+        .status()
+    }
+
+    fn start_private_game(id: GameId) -> GameContext {
+        let (game, mut rx) = start_game(id, GameState::Open);
+        tokio::spawn(async move {
+            while let Some(GameMessage::RegisterPlayer { tx_back, .. }) = rx.recv().await {
+                let _ = tx_back.send(RegisterPlayerResponse::Success {
+                    id: PlayerId::default(),
+                    stack: None,
+                    team_secret: None,
+                });
+            }
+        });
+        GameContext {
+            join_code: Some(JoinCode::from("ABC123")),
+            ..game
+        }
+    }
+
+    #[tokio::test]
+    async fn test_join_private_game_with_code() {
+        let state = init_state();
+        let game_id = GameId::default();
+        let game = start_private_game(game_id.clone());
+        state
+            .write()
+            .await
+            .game_services
+            .insert(game_id.clone(), game);
+        let app = Router::new()
+            .route("/join", post(join_game))
+            .layer(CookieManagerLayer::new())
+            .layer(MockConnectInfo(client()))
+            .with_state(state);
+
+        assert_eq!(
+            join_private_game(app.clone(), &game_id, None).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            join_private_game(app, &game_id, Some(" abc123 ")).await,
+            StatusCode::CREATED
+        );
+    }
+
+    #[tokio::test]
+    async fn test_join_private_game_locked_after_too_many_wrong_codes() {
+        let state = init_state();
+        let game_id = GameId::default();
+        let game = start_private_game(game_id.clone());
+        state
+            .write()
+            .await
+            .game_services
+            .insert(game_id.clone(), game);
+        let app = Router::new()
+            .route("/join", post(join_game))
+            .layer(CookieManagerLayer::new())
+            .layer(MockConnectInfo(client()))
+            .with_state(state);
+
+        for _ in 0..MAX_WRONG_JOIN_CODES {
+            assert_eq!(
+                join_private_game(app.clone(), &game_id, Some("WRONG1")).await,
+                StatusCode::FORBIDDEN
+            );
+        }
+        assert_eq!(
+            join_private_game(app, &game_id, Some("ABC123")).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+    }
+
+    #[tokio::test]
+    async fn test_join_code_lockout_is_per_client() {
+        let state = init_state();
+        let game_id = GameId::default();
+        let game = start_private_game(game_id.clone());
+        state
+            .write()
+            .await
+            .game_services
+            .insert(game_id.clone(), game);
+        let app = Router::new()
+            .route("/join", post(join_game))
+            .layer(CookieManagerLayer::new())
+            .layer(MockConnectInfo(client()))
+            .with_state(state.clone());
+        let other_client_app = Router::new()
+            .route("/join", post(join_game))
+            .layer(CookieManagerLayer::new())
+            .layer(MockConnectInfo(SocketAddr::from(([10, 0, 0, 2], 8080))))
+            .with_state(state);
+
+        for _ in 0..MAX_WRONG_JOIN_CODES {
+            join_private_game(app.clone(), &game_id, Some("WRONG1")).await;
+        }
+        assert_eq!(
+            join_private_game(app, &game_id, Some("ABC123")).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(
+            join_private_game(other_client_app, &game_id, Some("ABC123")).await,
+            StatusCode::CREATED
+        );
+    }
+
+    #[test]
+    fn test_join_code_lockout_expires() {
+        let now = Utc::now();
+        let mut attempts = JoinCodeAttempts::default();
+        for _ in 0..MAX_WRONG_JOIN_CODES {
+            assert!(!attempts.is_locked(now));
+            attempts.record_wrong_code(now);
+        }
+
+        assert!(attempts.is_locked(now));
+        assert!(!attempts.is_locked(now + JOIN_CODE_LOCKOUT));
+    }
+
+    #[test]
+    fn test_wrong_attempts_expire() {
+        let now = Utc::now();
+        let mut attempts = JoinCodeAttempts::default();
+        attempts.record_wrong_code(now);
+
+        assert!(!attempts.is_expired(now));
+        assert!(attempts.is_expired(now + JOIN_CODE_LOCKOUT));
+    }
+
+    #[test]
+    fn test_client_address_only_forwarded_by_trusted_proxies() {
+        let proxies = [IpNetwork::parse("10.0.0.0/8").unwrap()];
+        let proxy = IpAddr::from([10, 0, 0, 2]);
+        let client = IpAddr::from([1, 2, 3, 4]);
+        let mut headers = http::HeaderMap::new();
+        headers.insert("x-forwarded-for", "6.6.6.6, 1.2.3.4".parse().unwrap());
+
+        assert_eq!(client_address(&proxies, proxy, &headers), client);
+        assert_eq!(
+            client_address(&proxies, client, &headers),
+            client,
+            "Clients cannot forge their address"
+        );
+        assert_eq!(
+            client_address(&proxies, proxy, &http::HeaderMap::new()),
+            proxy
+        );
+
+        headers.insert("x-forwarded-for", "1.2.3.4, 10.0.0.3".parse().unwrap());
+        assert_eq!(client_address(&proxies, proxy, &headers), client);
+        headers.insert("x-forwarded-for", "garbage".parse().unwrap());
+        assert_eq!(client_address(&proxies, proxy, &headers), proxy);
+    }
+
+    #[tokio::test]
+    async fn test_join_code_lockout_of_clients_behind_a_trusted_proxy() {
+        let state = init_state();
+        state.write().await.config.trusted_proxies = vec![IpNetwork::parse("127.0.0.1").unwrap()];
+        let game_id = GameId::default();
+        let game = start_private_game(game_id.clone());
+        state
+            .write()
+            .await
+            .game_services
+            .insert(game_id.clone(), game);
+        let app = Router::new()
+            .route("/join", post(join_game))
+            .layer(CookieManagerLayer::new())
+            .layer(MockConnectInfo(client()))
+            .with_state(state);
+        let join_from = |forwarded_for: &str, join_code: &str| {
+            let request_body = JoinGame {
+                game_id: game_id.to_string(),
+                player_name: "TestPlayer".to_string(),
+                join_code: Some(join_code.to_string()),
+                team_secret: None,
+            };
+            app.clone().oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/join")
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .header("x-forwarded-for", forwarded_for)
+                    .body(Body::from(serde_json::to_string(&request_body).unwrap()))
+                    .unwrap(),
+            )
+        };
+
+        for _ in 0..MAX_WRONG_JOIN_CODES {
+            join_from("1.2.3.4", "WRONG1").await.unwrap();
+        }
+        assert_eq!(
+            join_from("1.2.3.4", "ABC123").await.unwrap().status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(
+            join_from("5.6.7.8", "ABC123").await.unwrap().status(),
+            StatusCode::CREATED
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tracked_clients_capped_per_game() {
+        let state = init_state();
+        let game_id = GameId::default();
+        let game = start_private_game(game_id.clone());
+        {
+            let mut state = state.write().await;
+            state.game_services.insert(game_id.clone(), game);
+            for idx in 0..MAX_TRACKED_CLIENTS_PER_GAME {
+                let client = IpAddr::from([10, 0, (idx / 256) as u8, (idx % 256) as u8]);
+                record_wrong_attempt(&mut state, &game_id, client, Utc::now());
+            }
+        }
+        let app = Router::new()
+            .route("/join", post(join_game))
+            .layer(CookieManagerLayer::new())
+            .layer(MockConnectInfo(client()))
+            .with_state(state.clone());
+
+        assert_eq!(
+            join_private_game(app, &game_id, Some("ABC123")).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(
+            state.read().await.join_code_attempts.len(),
+            MAX_TRACKED_CLIENTS_PER_GAME
+        );
+    }
+
+    #[tokio::test]
+    async fn test_expired_attempts_pruned_when_recording_attempts() {
+        let state = init_state();
+        let mut state = state.write().await;
+        let now = Utc::now();
+        let other_game = GameId::default();
+        record_wrong_attempt(
+            &mut state,
+            &other_game,
+            client().ip(),
+            now - JOIN_CODE_LOCKOUT,
+        );
+
+        let game_id = GameId::default();
+        record_wrong_attempt(&mut state, &game_id, client().ip(), now);
+
+        assert_eq!(state.join_code_attempts.len(), 1);
+        assert!(
+            state
+                .join_code_attempts
+                .contains_key(&(game_id, client().ip()))
+        );
+    }
 }
@@ -1,15 +1,85 @@
-use axum::{Json, extract::State, response::IntoResponse};
+use std::time::Duration;
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::{game::GameState, player::GameStackConfigView};
+use crate::{
+    game::{GameContext, GameId, GameState, HostToken},
+    player::{GameStackConfigView, PlayerName},
+};
 
-use super::ApiState;
+use super::{
+    ApiState,
+    current_game::state_name,
+    join_game::{ClientAddress, check_join_code},
+};
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, PartialOrd)]
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
 struct GameView {
     id: String,
     name: String,
     stack: GameStackConfigView,
+    /// Open, Running or Ended
+    state: String,
+    /// Number of players, teams counting as a single player
+    players: usize,
+    /// Names of the players, given to whoever can see the game: anyone for public games, holders
+    /// of the host token or join code for private games
+    player_names: Vec<PlayerName>,
+    /// Players needed for the game to start
+    min_players: Option<usize>,
+    max_players: Option<usize>,
+    /// Whether a code is required to join the game
+    private: bool,
+    number_of_periods: usize,
+    /// Durations of the phases of the delivery periods, as when creating the game: the trading
+    /// phase, the post delivery phase and the trading phase of the first periods. Phases without
+    /// duration last until all players are ready
+    period_duration_seconds: Option<u64>,
+    post_delivery_duration_seconds: Option<u64>,
+    period_durations_schedule_seconds: Vec<u64>,
+    created_at: DateTime<Utc>,
+}
+
+impl From<&GameContext> for GameView {
+    fn from(game: &GameContext) -> Self {
+        let player_names: Vec<PlayerName> = game
+            .players_rx
+            .borrow()
+            .iter()
+            .map(|(_, name)| name.clone())
+            .collect();
+        GameView {
+            id: game.id.to_string(),
+            name: game.name.to_string(),
+            stack: (&game.stack).into(),
+            state: state_name(&game.state_rx.borrow()),
+            players: player_names.len(),
+            player_names,
+            min_players: game.player_limits.min,
+            max_players: game.player_limits.max,
+            private: game.join_code.is_some(),
+            number_of_periods: usize::from(game.last_delivery_period),
+            period_duration_seconds: game.durations.running.map(|duration| duration.as_secs()),
+            post_delivery_duration_seconds: game
+                .durations
+                .post_delivery()
+                .map(|duration| duration.as_secs()),
+            period_durations_schedule_seconds: game
+                .durations
+                .schedule
+                .iter()
+                .map(Duration::as_secs)
+                .collect(),
+            created_at: game.created_at,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -17,74 +87,202 @@ struct ListGamesResponse {
     games: Vec<GameView>,
 }
 
-pub async fn list_games(State(state): State<ApiState>) -> impl IntoResponse {
-    let state = state.read().await;
+#[derive(Debug, Default, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum GameStateFilter {
+    #[default]
+    Open,
+    /// Started games, including paused ones and games between two delivery periods
+    Running,
+    Ended,
+    All,
+}
 
-    Json(ListGamesResponse {
-        games: state
-            .game_services
-            .iter()
-            .filter_map(|(_, game)| {
-                if *game.state_rx.borrow() == GameState::Open {
-                    return Some(GameView {
-                        id: game.id.to_string(),
-                        name: game.name.to_string(),
-                        stack: (&game.stack).into(),
-                    });
-                }
-                None
+impl GameStateFilter {
+    fn matches(&self, state: &GameState) -> bool {
+        match self {
+            GameStateFilter::Open => *state == GameState::Open,
+            GameStateFilter::Running => matches!(
+                state,
+                GameState::Running { .. }
+                    | GameState::PostDelivery { .. }
+                    | GameState::Paused { .. }
+            ),
+            GameStateFilter::Ended => matches!(state, GameState::Ended(_)),
+            GameStateFilter::All => true,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ListGamesFilter {
+    #[serde(default)]
+    state: GameStateFilter,
+    /// Part of the game's name, case insensitive
+    name: Option<String>,
+    /// Only list games that can accept another player
+    #[serde(default)]
+    has_room: bool,
+}
+
+impl ListGamesFilter {
+    fn matches(&self, game: &GameView, state: &GameState) -> bool {
+        self.state.matches(state)
+            && self.name.as_ref().is_none_or(|name| {
+                game.name
+                    .to_lowercase()
+                    .contains(&name.trim().to_lowercase())
             })
-            .collect(),
-    })
+            && (!self.has_room || game.max_players.is_none_or(|max| game.players < max))
+    }
+}
+
+/// Public games, open ones by default, from the oldest to the newest. Private games are only
+/// joined with their code and never listed.
+pub async fn list_games(
+    State(state): State<ApiState>,
+    Query(filter): Query<ListGamesFilter>,
+) -> impl IntoResponse {
+    let state = state.read().await;
+
+    let mut games = state
+        .game_services
+        .values()
+        .filter(|game| game.join_code.is_none())
+        .map(|game| (GameView::from(game), game.state_rx.borrow().clone()))
+        .filter(|(view, game_state)| filter.matches(view, game_state))
+        .map(|(view, _)| view)
+        .collect::<Vec<_>>();
+    games.sort_by_key(|game| game.created_at);
+    Json(ListGamesResponse { games })
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct GameDetailsQuery {
+    /// Secret of the game's host
+    host_token: Option<String>,
+    /// Code of the game, for private games
+    join_code: Option<String>,
+}
+
+/// Details of a single game whatever its state, e.g. for spectators. Private games require their
+/// host token or join code.
+pub async fn game_details(
+    ClientAddress(client): ClientAddress,
+    State(state): State<ApiState>,
+    Path(game_id): Path<String>,
+    Query(query): Query<GameDetailsQuery>,
+) -> impl IntoResponse {
+    let Some(game_id) = GameId::parse(&game_id) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let (is_host, private) = match state.read().await.game_services.get(&game_id) {
+        Some(game) => (
+            query.host_token.as_deref().map(HostToken::from).as_ref() == Some(&game.host_token),
+            game.join_code.is_some(),
+        ),
+        None => return StatusCode::NOT_FOUND.into_response(),
+    };
+    // Wrong codes count towards the join code lockout, as when joining the game
+    if private
+        && !is_host
+        && let Err(status) =
+            check_join_code(&state, &game_id, client, query.join_code.as_deref()).await
+    {
+        return status.into_response();
+    }
+
+    let state = state.read().await;
+    let Some(game) = state.game_services.get(&game_id) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    Json(GameView::from(game)).into_response()
 }
 
 #[cfg(test)]
 mod test_api_list_games {
+    use std::net::IpAddr;
+
     use crate::{
         game::{
-            GameContext, GameId, GameName,
+            GameContext, GameId, GameName, HostToken, JoinCode, PhaseDurations, PlayerLimits,
             delivery_period::DeliveryPeriodId,
-            infra::stack_config::{GameStackConfig, GameStackFixedConfig},
+            infra::{
+                archive::Archives,
+                stack_config::{GameStackConfig, GameStackFixedConfig, PlantConfig},
+            },
         },
         infra::api::state::AppState,
+        plants::PlantDefinition,
+        player::{PlayerId, PlayerName},
         utils::{
             config::AppConfig,
-            units::{Energy, EnergyCost, Power},
+            units::{CarbonPrice, Energy, EnergyCost, Power},
         },
     };
 
     use super::*;
+    use crate::infra::api::tutorial::create_tutorial_game;
+    use axum::{
+        Router,
+        body::Body,
+        http::{self, Request, Uri},
+        routing::post,
+    };
+    use chrono::Utc;
     use http_body_util::BodyExt;
     use serde_json::{Value, json};
     use std::collections::HashMap;
     use std::sync::Arc;
+    use std::time::Duration;
     use tokio::sync::{RwLock, mpsc, watch};
+    use tower::ServiceExt;
+    use tower_cookies::CookieManagerLayer;
 
     fn init_state() -> ApiState {
         let (tx, _) = mpsc::channel(16);
         let (cleanup_tx, _) = mpsc::channel(16);
+        let (archive_tx, _) = mpsc::channel(16);
         Arc::new(RwLock::new(AppState {
             game_services: HashMap::new(),
             market_services: HashMap::new(),
             stack_services: HashMap::new(),
             player_connections_repository: tx,
             cleanup_tx,
+            archives: Archives::new(None),
+            archive_tx,
+            tournaments: HashMap::new(),
+            join_code_attempts: HashMap::new(),
             config: AppConfig::default(),
         }))
     }
 
     fn stack_config() -> GameStackConfig {
         GameStackConfig::Fixed(GameStackFixedConfig {
-            battery_capacity: Energy::from(300),
-            consumers_forecasts: vec![],
-            consumers_forecasts_range: 2,
-            consumers_revenues: EnergyCost::from(50),
-            gas_capacity: Power::from(500),
-            gas_cost: EnergyCost::from(80),
-            nuclear_capacity: Power::from(1200),
-            nuclear_cost: EnergyCost::from(35),
-            renewable_forecasts: vec![],
-            renewable_forecasts_range: 2,
+            plants: vec![
+                PlantConfig::new(
+                    "battery",
+                    PlantDefinition::Battery {
+                        capacity: Energy::from(300),
+                    },
+                ),
+                PlantConfig::new(
+                    "gas",
+                    PlantDefinition::GasPlant {
+                        energy_cost: EnergyCost::from(80),
+                        capacity: Power::from(500),
+                    },
+                ),
+                PlantConfig::new(
+                    "nuclear",
+                    PlantDefinition::Nuclear {
+                        energy_cost: EnergyCost::from(35),
+                        capacity: Power::from(1200),
+                    },
+                ),
+            ],
+            carbon_price: CarbonPrice::default(),
+            weather: None,
         })
     }
 
@@ -93,6 +291,17 @@ mod test_api_list_games {
         (&config).into()
     }
 
+    fn created_at() -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000, 0).unwrap()
+    }
+
+    fn start_private_game(id: GameId, name: GameName) -> GameContext {
+        GameContext {
+            join_code: Some(JoinCode::from("ABC123")),
+            ..start_game(id, name, GameState::Open)
+        }
+    }
+
     fn start_game(id: GameId, name: GameName, state: GameState) -> GameContext {
         let (tx, _) = mpsc::channel(16);
         let (_, state_rx) = watch::channel(state);
@@ -101,16 +310,84 @@ mod test_api_list_games {
             name,
             stack: stack_config(),
             last_delivery_period: DeliveryPeriodId::from(3),
+            join_code: None,
+            host_token: HostToken::default(),
+            player_limits: PlayerLimits::default(),
+            durations: PhaseDurations::default(),
+            created_at: created_at(),
             tx,
             state_rx,
+            players_rx: watch::channel(Vec::new()).1,
+        }
+    }
+
+    fn game_view(id: &str, state: &str) -> GameView {
+        GameView {
+            id: id.to_string(),
+            name: id.to_string(),
+            stack: stack_config_view(),
+            state: state.to_string(),
+            players: 0,
+            player_names: vec![],
+            min_players: None,
+            max_players: None,
+            private: false,
+            number_of_periods: 3,
+            period_duration_seconds: None,
+            post_delivery_duration_seconds: None,
+            period_durations_schedule_seconds: vec![],
+            created_at: created_at(),
+        }
+    }
+
+    async fn insert_games_in_each_state(state: &ApiState) {
+        for game_state in [
+            GameState::Open,
+            GameState::Running {
+                period: DeliveryPeriodId::from(0),
+                end_at: None,
+            },
+            GameState::PostDelivery {
+                period: DeliveryPeriodId::from(0),
+                end_at: None,
+            },
+            GameState::Ended(DeliveryPeriodId::from(0)),
+        ] {
+            let game_id = GameId::from(game_state.to_string());
+            let game_name = GameName::from(game_state.to_string());
+            let ctx = start_game(game_id.clone(), game_name, game_state);
+            state.write().await.game_services.insert(game_id, ctx);
         }
     }
 
+    async fn list(state: ApiState, query: &str) -> ListGamesResponse {
+        let uri: Uri = format!("/api/games?{query}").parse().unwrap();
+        let response = list_games(State(state), Query::try_from_uri(&uri).unwrap()).await;
+        let body = response
+            .into_response()
+            .into_body()
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    fn names(response: &ListGamesResponse) -> Vec<String> {
+        let mut names = response
+            .games
+            .iter()
+            .map(|game| game.name.clone())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
     #[tokio::test]
     async fn test_list_games_empty() {
         let state = init_state();
 
-        let response = list_games(State(state)).await;
+        let response = list_games(State(state), Query(ListGamesFilter::default())).await;
         let body = response
             .into_response()
             .into_body()
@@ -135,25 +412,9 @@ mod test_api_list_games {
             s.game_services.insert(game_id, ctx);
         }
 
-        let response = list_games(State(state)).await;
-        let body = response
-            .into_response()
-            .into_body()
-            .collect()
-            .await
-            .unwrap()
-            .to_bytes();
-        let body: ListGamesResponse = serde_json::from_slice(&body).unwrap();
-        assert!(body.games.contains(&GameView {
-            id: 0.to_string(),
-            name: 0.to_string(),
-            stack: stack_config_view(),
-        }));
-        assert!(body.games.contains(&GameView {
-            id: 1.to_string(),
-            name: 1.to_string(),
-            stack: stack_config_view(),
-        },));
+        let body = list(state, "").await;
+        assert!(body.games.contains(&game_view("0", "Open")));
+        assert!(body.games.contains(&game_view("1", "Open")));
     }
 
     #[tokio::test]
@@ -161,43 +422,295 @@ mod test_api_list_games {
         let state = init_state();
 
         // Add games for each variant of GameState
-        for game_state in [
-            GameState::Open,
-            GameState::Running {
-                period: DeliveryPeriodId::from(0),
-                end_at: None,
+        insert_games_in_each_state(&state).await;
+
+        let body = list(state, "").await;
+        assert_eq!(
+            body,
+            ListGamesResponse {
+                games: vec![game_view(&GameState::Open.to_string(), "Open")]
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_games_filter_by_state() {
+        let state = init_state();
+        insert_games_in_each_state(&state).await;
+        let running = GameState::Running {
+            period: DeliveryPeriodId::from(0),
+            end_at: None,
+        };
+        let post_delivery = GameState::PostDelivery {
+            period: DeliveryPeriodId::from(0),
+            end_at: None,
+        };
+        let ended = GameState::Ended(DeliveryPeriodId::from(0));
+
+        let paused = GameId::from("Paused");
+        let ctx = start_game(
+            paused.clone(),
+            GameName::from("Paused".to_string()),
+            GameState::Paused {
+                phase: Box::new(running.clone()),
+                remaining: None,
             },
-            GameState::PostDelivery {
-                period: DeliveryPeriodId::from(0),
+        );
+        state.write().await.game_services.insert(paused, ctx);
+
+        let mut expected = vec![
+            running.to_string(),
+            post_delivery.to_string(),
+            "Paused".to_string(),
+        ];
+        expected.sort();
+        assert_eq!(names(&list(state.clone(), "state=running").await), expected);
+        assert_eq!(
+            names(&list(state.clone(), "state=ended").await),
+            vec![ended.to_string()]
+        );
+        assert_eq!(list(state, "state=all").await.games.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_list_games_filter_by_name() {
+        let state = init_state();
+        for name in ["Friday lobby", "Monday game"] {
+            let game_id = GameId::from(name);
+            let ctx = start_game(
+                game_id.clone(),
+                GameName::from(name.to_string()),
+                GameState::Open,
+            );
+            state.write().await.game_services.insert(game_id, ctx);
+        }
+
+        assert_eq!(
+            names(&list(state.clone(), "name=LOBBY").await),
+            vec!["Friday lobby".to_string()]
+        );
+        assert!(list(state, "name=tuesday").await.games.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_games_filter_games_with_room() {
+        let state = init_state();
+        for (name, max) in [("full", Some(1)), ("room", Some(2)), ("unlimited", None)] {
+            let game_id = GameId::from(name);
+            let ctx = GameContext {
+                player_limits: PlayerLimits { min: None, max },
+                players_rx: watch::channel(vec![(PlayerId::default(), PlayerName::from("p1"))]).1,
+                ..start_game(
+                    game_id.clone(),
+                    GameName::from(name.to_string()),
+                    GameState::Open,
+                )
+            };
+            state.write().await.game_services.insert(game_id, ctx);
+        }
+
+        assert_eq!(list(state.clone(), "").await.games.len(), 3);
+        assert_eq!(
+            names(&list(state, "has_room=true").await),
+            vec!["room".to_string(), "unlimited".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_games_sorted_by_creation() {
+        let state = init_state();
+        for (name, offset) in [("newer", 10), ("older", 0)] {
+            let game_id = GameId::from(name);
+            let ctx = GameContext {
+                created_at: created_at() + chrono::Duration::seconds(offset),
+                ..start_game(
+                    game_id.clone(),
+                    GameName::from(name.to_string()),
+                    GameState::Open,
+                )
+            };
+            state.write().await.game_services.insert(game_id, ctx);
+        }
+
+        let games = list(state, "").await.games;
+        assert_eq!(games[0].name, "older");
+        assert_eq!(games[1].name, "newer");
+    }
+
+    #[tokio::test]
+    async fn test_list_games_hides_private_games() {
+        let state = init_state();
+        let game_id = GameId::from("private");
+        let ctx = start_private_game(game_id.clone(), GameName::from("private".to_string()));
+        state.write().await.game_services.insert(game_id, ctx);
+
+        assert!(list(state.clone(), "").await.games.is_empty());
+        assert!(list(state, "state=all").await.games.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_games_hides_tutorial_games() {
+        let state = init_state();
+        let app = Router::new()
+            .route("/tutorial", post(create_tutorial_game))
+            .layer(CookieManagerLayer::new())
+            .with_state(state.clone());
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/tutorial")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(state.read().await.game_services.len(), 1);
+
+        assert!(list(state.clone(), "state=running").await.games.is_empty());
+        assert!(list(state, "state=all").await.games.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_games_shows_players_and_limits() {
+        let state = init_state();
+        let game_id = GameId::from("lobby");
+        let ctx = GameContext {
+            player_limits: PlayerLimits {
+                min: Some(2),
+                max: Some(4),
+            },
+            durations: PhaseDurations {
+                running: Some(Duration::from_secs(180)),
+                post_delivery: Some(Duration::from_secs(30)),
+                schedule: vec![Duration::from_secs(300)],
+            },
+            players_rx: watch::channel(vec![
+                (PlayerId::default(), PlayerName::from("p1")),
+                (PlayerId::default(), PlayerName::from("p2")),
+            ])
+            .1,
+            ..start_game(
+                game_id.clone(),
+                GameName::from("lobby".to_string()),
+                GameState::Open,
+            )
+        };
+        state.write().await.game_services.insert(game_id, ctx);
+
+        assert_eq!(
+            list(state, "").await.games,
+            vec![GameView {
+                players: 2,
+                player_names: vec![PlayerName::from("p1"), PlayerName::from("p2")],
+                min_players: Some(2),
+                max_players: Some(4),
+                period_duration_seconds: Some(180),
+                post_delivery_duration_seconds: Some(30),
+                period_durations_schedule_seconds: vec![300],
+                ..game_view("lobby", "Open")
+            }]
+        );
+    }
+
+    async fn details(state: ApiState, game_id: &str, query: &str) -> axum::response::Response {
+        let uri: Uri = format!("/api/game/{game_id}?{query}").parse().unwrap();
+        game_details(
+            ClientAddress(IpAddr::from([127, 0, 0, 1])),
+            State(state),
+            Path(game_id.to_string()),
+            Query::try_from_uri(&uri).unwrap(),
+        )
+        .await
+        .into_response()
+    }
+
+    async fn details_view(response: axum::response::Response) -> GameView {
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_game_details_of_running_and_private_games() {
+        let state = init_state();
+        let running = GameId::from("running");
+        let ctx = start_game(
+            running.clone(),
+            GameName::from("running".to_string()),
+            GameState::Running {
+                period: DeliveryPeriodId::from(1),
                 end_at: None,
             },
-            GameState::Ended(DeliveryPeriodId::from(0)),
+        );
+        state.write().await.game_services.insert(running, ctx);
+        let private = GameId::from("private");
+        let ctx = start_private_game(private.clone(), GameName::from("private".to_string()));
+        state.write().await.game_services.insert(private, ctx);
+
+        let view = details_view(details(state.clone(), "running", "").await).await;
+        assert_eq!(view, game_view("running", "Running"));
+
+        for query in ["", "join_code=WRONG1", "host_token=not_the_token"] {
+            assert_eq!(
+                details(state.clone(), "private", query).await.status(),
+                StatusCode::FORBIDDEN
+            );
+        }
+        let response = details(state, "private", "join_code=abc123").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["private"], json!(true));
+        assert!(body.get("join_code").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_game_details_player_names_as_visible_as_the_game() {
+        let state = init_state();
+        let players = vec![(PlayerId::default(), PlayerName::from("p1"))];
+        let public = GameId::from("public");
+        let ctx = GameContext {
+            host_token: HostToken::from("token"),
+            players_rx: watch::channel(players.clone()).1,
+            ..start_game(
+                public.clone(),
+                GameName::from("public".to_string()),
+                GameState::Open,
+            )
+        };
+        state.write().await.game_services.insert(public, ctx);
+        let private = GameId::from("private");
+        let ctx = GameContext {
+            host_token: HostToken::from("token"),
+            players_rx: watch::channel(players).1,
+            ..start_private_game(private.clone(), GameName::from("private".to_string()))
+        };
+        state.write().await.game_services.insert(private, ctx);
+
+        assert_eq!(
+            list(state.clone(), "").await.games[0].player_names,
+            vec![PlayerName::from("p1")]
+        );
+        for (game, query) in [
+            ("public", ""),
+            ("public", "host_token=token"),
+            ("private", "host_token=token"),
+            ("private", "join_code=ABC123"),
         ] {
-            let game_id = GameId::from(game_state.to_string());
-            let game_name = GameName::from(game_state.to_string());
-            let ctx = start_game(game_id.clone(), game_name, game_state);
-            let mut s = state.write().await;
-            s.game_services.insert(game_id, ctx);
+            let view = details_view(details(state.clone(), game, query).await).await;
+            assert_eq!(view.player_names, vec![PlayerName::from("p1")]);
         }
+    }
+
+    #[tokio::test]
+    async fn test_game_details_unknown_game() {
+        let state = init_state();
 
-        let response = list_games(State(state)).await;
-        let body = response
-            .into_response()
-            .into_body()
-            .collect()
-            .await
-            .unwrap()
-            .to_bytes();
-        let body: ListGamesResponse = serde_json::from_slice(&body).unwrap();
         assert_eq!(
-            body,
-            ListGamesResponse {
-                games: vec![GameView {
-                    id: GameState::Open.to_string(),
-                    name: GameState::Open.to_string(),
-                    stack: stack_config_view()
-                },]
-            }
+            details(state, "unknown", "").await.status(),
+            StatusCode::NOT_FOUND
         );
     }
 }
@@ -1,37 +1,64 @@
 use axum::{
     Router,
     http::{
-        HeaderValue, Method,
+        HeaderName, HeaderValue, Method,
         header::{CONTENT_TYPE, COOKIE},
     },
     routing::{get, post},
 };
 use create_game::create_game;
 use current_game::current_game;
+use game_replay::game_replay;
+use game_results::game_results;
+use host_game::{HOST_TOKEN_HEADER, host_command};
 use join_game::join_game;
-use list_games::list_games;
+use list_games::{game_details, list_games};
 use state::ApiState;
+use tournament::{create_tournament, join_tournament, play_tournament_round, tournament_standings};
 use tower_cookies::CookieManagerLayer;
 use tower_http::{cors::CorsLayer, trace::TraceLayer};
 use tutorial::create_tutorial_game;
-use ws::handle_ws_connection;
+use ws::{handle_spectator_connection, handle_ws_connection};
 
 use crate::AppConfig;
 
 mod cookies;
 mod create_game;
 mod current_game;
+mod game_replay;
+mod game_results;
+mod host_game;
 mod join_game;
 mod list_games;
+pub mod scenario;
 pub mod state;
+pub mod tournament;
 mod tutorial;
 pub mod ws;
 
 pub fn build_router(state: ApiState, config: AppConfig) -> Router {
     Router::new()
         .route("/api/game", post(create_game).get(current_game))
+        .route("/api/game/{game_id}", get(game_details))
+        .route("/api/game/{game_id}/replay", get(game_replay))
+        .route("/api/game/{game_id}/results", get(game_results))
+        .route("/api/game/{game_id}/host", post(host_command))
+        .route(
+            "/api/game/{game_id}/spectate",
+            get(handle_spectator_connection),
+        )
         .route("/api/games", get(list_games))
         .route("/api/game/join", post(join_game))
+        .route("/api/tournament", post(create_tournament))
+        .route("/api/tournament/{tournament_id}", get(tournament_standings))
+        .route(
+            "/api/tournament/{tournament_id}/join",
+            post(join_tournament),
+        )
+        .route(
+            "/api/tournament/{tournament_id}/play",
+            post(play_tournament_round),
+        )
         .route("/api/tutorial", post(create_tutorial_game))
         .route("/api/ws", get(handle_ws_connection))
         .with_state(state)
@@ -39,7 +66,11 @@ pub fn build_router(state: ApiState, config: AppConfig) -> Router {
         .layer(CookieManagerLayer::new())
         .layer(
             CorsLayer::new()
-                .allow_headers([CONTENT_TYPE, COOKIE])
+                .allow_headers([
+                    CONTENT_TYPE,
+                    COOKIE,
+                    HeaderName::from_static(HOST_TOKEN_HEADER),
+                ])
                 .allow_origin(
                     config
                         .allow_origin
@@ -0,0 +1,386 @@
+use std::{fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    forecast::{
+        profile::LoadProfileError,
+        shock::{CreateShockModelError, ShockModel},
+        signal::CreateGlobalSignalError,
+    },
+    game::scores::ImbalancePrices,
+    market::bots::BotKind,
+    utils::is_valid_file_stem,
+};
+
+use super::create_game::GameStackConfigRequest;
+
+/// Current version of the scenario format, scenarios of other versions are rejected.
+pub const SCENARIO_VERSION: u32 = 1;
+
+/// Scenarios shipped with the server, available even if the scenarios directory is missing.
+const TUTORIAL_SCENARIO: &str = include_str!("../../../scenarios/tutorial.json");
+
+/// Longest phase of a delivery period, so that its deadline stays representable.
+pub const MAX_PHASE_DURATION_SECONDS: u64 = 24 * 3600;
+
+const BUNDLED_SCENARIOS: [(&str, &str); 1] = [("tutorial", TUTORIAL_SCENARIO)];
+
+fn default_bots() -> Vec<BotKind> {
+    vec![BotKind::ExtremeOrders]
+}
+
+/// Complete description of a game: its delivery periods, the players' stacks, and the market.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Scenario {
+    pub version: u32,
+    #[serde(default)]
+    pub description: String,
+    pub number_of_periods: usize,
+    /// Duration of each delivery period, periods only end once all players are ready if not set
+    #[serde(default)]
+    pub period_duration_seconds: Option<u64>,
+    /// Duration of the post delivery phase of each period, same as the delivery periods if not set
+    #[serde(default)]
+    pub post_delivery_duration_seconds: Option<u64>,
+    /// Durations of the first delivery periods, e.g. for a longer first period, the following
+    /// periods last `period_duration_seconds`
+    #[serde(default)]
+    pub period_durations_schedule_seconds: Vec<u64>,
+    pub stack: GameStackConfigRequest,
+    #[serde(default = "default_bots")]
+    pub bots: Vec<BotKind>,
+    #[serde(default)]
+    pub imbalance_prices: ImbalancePrices,
+    #[serde(default)]
+    pub rank_emissions: bool,
+    /// Let players join the game after it has started
+    #[serde(default)]
+    pub allow_late_join: bool,
+    /// Players joining with the same name share one portfolio as a team
+    #[serde(default)]
+    pub team_play: bool,
+    /// Hide the game from the games listing, players need its join code to join it
+    #[serde(default)]
+    pub private: bool,
+    /// Players needed to start the game, it starts as soon as its players are ready if not set
+    #[serde(default)]
+    pub min_players: Option<usize>,
+    /// Players accepted in the game, unlimited if not set
+    #[serde(default)]
+    pub max_players: Option<usize>,
+    /// Correlation of renewables and consumers between players, in [0, 1], fully decorrelated
+    /// if not set
+    #[serde(default)]
+    pub weather_correlation: Option<f64>,
+    #[serde(default)]
+    pub shocks: Vec<ShockModel>,
+    /// Seed of the game's randomness, random if not set
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Clone, thiserror::Error, PartialEq)]
+pub enum ScenarioError {
+    #[error("Invalid scenario name {0:?}")]
+    InvalidName(String),
+    #[error("Scenario {0:?} does not exist")]
+    NotFound(String),
+    #[error("Unable to read scenario: {0}")]
+    Io(String),
+    #[error("Unable to parse scenario: {0}")]
+    Parse(String),
+    #[error("Unsupported scenario version {0}, expected version {SCENARIO_VERSION}")]
+    UnsupportedVersion(u64),
+    #[error("A game must have at least one delivery period")]
+    NoDeliveryPeriod,
+    #[error(
+        "Delivery periods must last between one second and {MAX_PHASE_DURATION_SECONDS} seconds"
+    )]
+    InvalidPeriodDuration,
+    #[error("A game must accept at least one player, and its minimum of players")]
+    InvalidPlayerLimits,
+    #[error("Imbalance prices must not reward a production deficit")]
+    InvalidImbalancePrices,
+    #[error("Invalid weather correlation: {0}")]
+    Weather(#[from] CreateGlobalSignalError),
+    #[error("Invalid shock model: {0}")]
+    Shock(#[from] CreateShockModelError),
+    #[error("Invalid forecast profile: {0}")]
+    Profile(#[from] LoadProfileError),
+}
+
+impl Scenario {
+    /// Scenario of the tutorial, embedded in the binary so that it does not depend on the
+    /// server's scenarios directory.
+    pub fn tutorial() -> Result<Scenario, ScenarioError> {
+        Scenario::parse(TUTORIAL_SCENARIO)
+    }
+
+    /// Parse a JSON scenario, checking its version before its content so that scenarios written
+    /// for another version get a meaningful error.
+    pub fn parse(content: &str) -> Result<Scenario, ScenarioError> {
+        let value: serde_json::Value =
+            serde_json::from_str(content).map_err(|err| ScenarioError::Parse(err.to_string()))?;
+        let Some(version) = value.get("version").and_then(serde_json::Value::as_u64) else {
+            return Err(ScenarioError::Parse("missing version".to_string()));
+        };
+        if version != u64::from(SCENARIO_VERSION) {
+            return Err(ScenarioError::UnsupportedVersion(version));
+        }
+
+        let scenario: Scenario =
+            serde_json::from_value(value).map_err(|err| ScenarioError::Parse(err.to_string()))?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    /// Check the game parameters, the stack and its forecast profiles are checked when the game
+    /// is created.
+    pub fn validate(&self) -> Result<(), ScenarioError> {
+        if self.number_of_periods == 0 {
+            return Err(ScenarioError::NoDeliveryPeriod);
+        }
+        let valid_duration = |seconds: &u64| (1..=MAX_PHASE_DURATION_SECONDS).contains(seconds);
+        if !self.period_duration_seconds.iter().all(valid_duration)
+            || !self
+                .post_delivery_duration_seconds
+                .iter()
+                .all(valid_duration)
+            || !self
+                .period_durations_schedule_seconds
+                .iter()
+                .all(valid_duration)
+        {
+            return Err(ScenarioError::InvalidPeriodDuration);
+        }
+        if let Some(max) = self.max_players
+            && (max == 0 || self.min_players.is_some_and(|min| min > max))
+        {
+            return Err(ScenarioError::InvalidPlayerLimits);
+        }
+        if self.imbalance_prices.positive > self.imbalance_prices.negative {
+            return Err(ScenarioError::InvalidImbalancePrices);
+        }
+        if let Some(correlation) = self.weather_correlation
+            && !(0. ..=1.).contains(&correlation)
+        {
+            return Err(CreateGlobalSignalError::CorrelationOutOfRange.into());
+        }
+        for shock in &self.shocks {
+            shock.validate()?;
+        }
+        Ok(())
+    }
+}
+
+/// Server-side directory of scenario files, referenced by their name without extension. Bundled
+/// scenarios are used when no file of the same name exists.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScenarioDirectory {
+    path: PathBuf,
+}
+
+impl ScenarioDirectory {
+    pub fn new(path: impl Into<PathBuf>) -> ScenarioDirectory {
+        ScenarioDirectory { path: path.into() }
+    }
+
+    /// Load the scenario `name` from `<name>.json`.
+    pub fn load(&self, name: &str) -> Result<Scenario, ScenarioError> {
+        if !is_valid_file_stem(name) {
+            return Err(ScenarioError::InvalidName(name.to_string()));
+        }
+
+        let path = self.path.join(format!("{name}.json"));
+        if path.is_file() {
+            let content =
+                fs::read_to_string(&path).map_err(|err| ScenarioError::Io(err.to_string()))?;
+            return Scenario::parse(&content);
+        }
+        match BUNDLED_SCENARIOS
+            .iter()
+            .find(|(bundled, _)| *bundled == name)
+        {
+            Some((_, content)) => Scenario::parse(content),
+            None => Err(ScenarioError::NotFound(name.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        forecast::{profile::ProfileDirectory, signal::CreateGlobalSignalError},
+        game::{
+            infra::stack_config::{GameStackConfig, PlantConfig},
+            scores::ImbalancePrices,
+        },
+        market::bots::BotKind,
+        plants::PlantDefinition,
+        utils::{rng::seeded_rng, units::EnergyCost},
+    };
+
+    use super::{MAX_PHASE_DURATION_SECONDS, Scenario, ScenarioDirectory, ScenarioError};
+
+    fn scenario(content: &str) -> String {
+        format!(
+            r#"{{
+                "version": 1,
+                "number_of_periods": 3,
+                "stack": {{"Plants": {{"plants": [{{"name": "battery", "technology": "Battery", "capacity": 300}}]}}}}
+                {content}
+            }}"#
+        )
+    }
+
+    #[test]
+    fn test_parse_minimal_scenario() {
+        let scenario = Scenario::parse(&scenario("")).unwrap();
+
+        assert_eq!(scenario.number_of_periods, 3);
+        assert_eq!(scenario.period_duration_seconds, None);
+        assert_eq!(scenario.bots, vec![BotKind::ExtremeOrders]);
+        assert_eq!(scenario.imbalance_prices, ImbalancePrices::default());
+    }
+
+    #[test]
+    fn test_parse_scenario_with_imbalance_prices() {
+        let scenario = Scenario::parse(&scenario(
+            r#", "imbalance_prices": {"positive": 10, "negative": 150}, "bots": []"#,
+        ))
+        .unwrap();
+
+        assert_eq!(scenario.imbalance_prices.negative, EnergyCost::from(150));
+        assert!(scenario.bots.is_empty());
+    }
+
+    #[test]
+    fn test_parse_scenario_errors() {
+        assert_eq!(
+            Scenario::parse(r#"{"version": 2, "number_of_periods": 3}"#).unwrap_err(),
+            ScenarioError::UnsupportedVersion(2)
+        );
+        assert!(matches!(
+            Scenario::parse(r#"{"number_of_periods": 3}"#),
+            Err(ScenarioError::Parse(_))
+        ));
+        assert!(matches!(
+            Scenario::parse(r#"{"version": 1, "number_of_periods": 3}"#),
+            Err(ScenarioError::Parse(_))
+        ));
+        assert_eq!(
+            Scenario::parse(&scenario(r#", "weather_correlation": 1.5"#)).unwrap_err(),
+            ScenarioError::Weather(CreateGlobalSignalError::CorrelationOutOfRange)
+        );
+        assert_eq!(
+            Scenario::parse(&scenario(r#", "period_duration_seconds": 0"#)).unwrap_err(),
+            ScenarioError::InvalidPeriodDuration
+        );
+        assert_eq!(
+            Scenario::parse(&scenario(r#", "min_players": 4, "max_players": 2"#)).unwrap_err(),
+            ScenarioError::InvalidPlayerLimits
+        );
+        assert_eq!(
+            Scenario::parse(&scenario(r#", "max_players": 0"#)).unwrap_err(),
+            ScenarioError::InvalidPlayerLimits
+        );
+        assert_eq!(
+            Scenario::parse(&scenario(r#", "post_delivery_duration_seconds": 0"#)).unwrap_err(),
+            ScenarioError::InvalidPeriodDuration
+        );
+        assert_eq!(
+            Scenario::parse(&scenario(&format!(
+                r#", "period_duration_seconds": {}"#,
+                MAX_PHASE_DURATION_SECONDS + 1
+            )))
+            .unwrap_err(),
+            ScenarioError::InvalidPeriodDuration
+        );
+        assert_eq!(
+            Scenario::parse(&scenario(&format!(
+                r#", "period_durations_schedule_seconds": [{}]"#,
+                u64::MAX
+            )))
+            .unwrap_err(),
+            ScenarioError::InvalidPeriodDuration
+        );
+        assert_eq!(
+            Scenario::parse(&scenario(
+                r#", "period_durations_schedule_seconds": [120, 0]"#
+            ))
+            .unwrap_err(),
+            ScenarioError::InvalidPeriodDuration
+        );
+        assert_eq!(
+            Scenario::parse(&scenario(
+                r#", "imbalance_prices": {"positive": 100, "negative": 15}"#
+            ))
+            .unwrap_err(),
+            ScenarioError::InvalidImbalancePrices
+        );
+    }
+
+    #[test]
+    fn test_load_scenario_from_directory() {
+        let dir = std::env::temp_dir().join(format!("parcelec-scenarios-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("short.json"), scenario("")).unwrap();
+        let scenarios = ScenarioDirectory::new(&dir);
+
+        assert_eq!(scenarios.load("short").unwrap().number_of_periods, 3);
+        assert_eq!(
+            scenarios.load("long").unwrap_err(),
+            ScenarioError::NotFound("long".to_string())
+        );
+        assert_eq!(
+            scenarios.load("../short").unwrap_err(),
+            ScenarioError::InvalidName("../short".to_string())
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_tutorial_scenario_is_embedded() {
+        let scenario = Scenario::tutorial().unwrap();
+
+        assert!(scenario.validate().is_ok());
+        assert_eq!(scenario.number_of_periods, 4);
+    }
+
+    #[test]
+    fn test_bundled_tutorial_scenario() {
+        let scenario = ScenarioDirectory::new("does-not-exist")
+            .load("tutorial")
+            .unwrap();
+
+        assert_eq!(scenario.number_of_periods, 4);
+        assert_eq!(scenario.period_duration_seconds, None);
+        assert_eq!(
+            scenario.bots,
+            vec![BotKind::TutorialInitialOrders, BotKind::ExtremeOrders]
+        );
+        let Ok(GameStackConfig::Fixed(stack)) = scenario
+            .stack
+            .into_stack_config(&ProfileDirectory::new("does-not-exist"), &mut seeded_rng(0))
+        else {
+            unreachable!("Tutorial should have a fixed stack")
+        };
+        let Some(PlantConfig {
+            definition: PlantDefinition::Consumers { forecasts, .. },
+            ..
+        }) = stack.plants.iter().find(|plant| plant.name == "consumers")
+        else {
+            unreachable!("Tutorial should have consumers")
+        };
+        assert_eq!(
+            forecasts
+                .forecasts
+                .iter()
+                .map(|f| f.value)
+                .collect::<Vec<_>>(),
+            vec![-1000, -1200, -600, -1800]
+        );
+    }
+}
@@ -1,19 +1,33 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, net::IpAddr, sync::Arc};
 
-use tokio::sync::{RwLock, mpsc};
+use tokio::sync::{RwLock, mpsc, oneshot};
 use tokio_util::sync::CancellationToken;
 
 use crate::{
-    game::{GameContext, GameId},
+    game::infra::{
+        archive::{ArchiveStore, ArchivedGame, Archives},
+        persistence::GameStore,
+    },
+    game::{GameContext, GameId, GameMessage, GameState},
     market::{MarketContext, MarketService},
     plants::{StackService, infra::StackContext},
     player::{
         PlayerId,
         infra::{ConnectionRepositoryMessage, PlayerConnectionRepository},
     },
+    tournament::TournamentId,
     utils::config::AppConfig,
 };
 
+use super::{
+    create_game::restore_game,
+    join_game::JoinCodeAttempts,
+    tournament::{
+        TournamentContext, TournamentStore, abandon_tournament_round, end_tournament_round,
+        restore_tournaments,
+    },
+};
+
 pub type ApiState = Arc<RwLock<AppState>>;
 pub struct AppState {
     pub market_services: HashMap<GameId, MarketContext<MarketService>>,
@@ -21,6 +35,12 @@ pub struct AppState {
     pub stack_services: HashMap<GameId, HashMap<PlayerId, StackContext<StackService>>>,
     pub player_connections_repository: mpsc::Sender<ConnectionRepositoryMessage>,
     pub cleanup_tx: mpsc::Sender<GameId>,
+    /// Timelines of finished games, kept after their actors are terminated
+    pub archives: Archives,
+    pub archive_tx: mpsc::Sender<ArchivedGame>,
+    pub tournaments: HashMap<TournamentId, TournamentContext>,
+    /// Wrong codes submitted by each client to join each private game
+    pub join_code_attempts: HashMap<(GameId, IpAddr), JoinCodeAttempts>,
     pub config: AppConfig,
 }
 
@@ -29,31 +49,80 @@ impl AppState {
         self.market_services.remove(game_id);
         self.game_services.remove(game_id);
         self.stack_services.remove(game_id);
+        self.join_code_attempts
+            .retain(|(game, _), _| game != game_id);
     }
 }
 
 pub fn new_api_state(config: &AppConfig) -> ApiState {
     let connections = PlayerConnectionRepository::start();
     let (cleanup_tx, mut cleanup_rx) = mpsc::channel(128);
+    let (archive_tx, mut archive_rx) = mpsc::channel(128);
+    let archive_store = config.archive_dir.clone().map(ArchiveStore::new);
 
-    let state = Arc::new(RwLock::new(AppState {
+    let mut app_state = AppState {
         game_services: HashMap::new(),
         market_services: HashMap::new(),
         stack_services: HashMap::new(),
         player_connections_repository: connections,
         cleanup_tx,
+        archives: Archives::new(archive_store.clone()),
+        archive_tx,
+        tournaments: HashMap::new(),
+        join_code_attempts: HashMap::new(),
         config: config.clone(),
-    }));
+    };
+
+    let store = config.persistence_dir.clone().map(GameStore::new);
+    if let Some(store) = &store {
+        for snapshot in store.load_all() {
+            restore_game(&mut app_state, snapshot, store.clone());
+        }
+    }
+    // Tournaments are restored once the games of their rounds are
+    let stalled_rounds = match &config.persistence_dir {
+        Some(dir) => restore_tournaments(&mut app_state, &TournamentStore::new(dir)),
+        None => Vec::new(),
+    };
+    let state = Arc::new(RwLock::new(app_state));
+
+    let cloned_state = state.clone();
+    tokio::spawn(async move {
+        let mut state = cloned_state.write().await;
+        for game_id in stalled_rounds {
+            abandon_tournament_round(&mut state, &game_id).await;
+        }
+    });
 
     let cloned_state = state.clone();
     tokio::spawn(async move {
         while let Some(game_id) = cleanup_rx.recv().await {
             let mut state = cloned_state.write().await;
             state.remove_game(&game_id);
+            abandon_tournament_round(&mut state, &game_id).await;
+            if let Some(store) = &store {
+                store.remove(&game_id).await;
+            }
             tracing::info!("AppState cleaned for game {game_id:?}");
         }
     });
 
+    let cloned_state = state.clone();
+    tokio::spawn(async move {
+        while let Some(archived) = archive_rx.recv().await {
+            let id = archived.archive.id.clone();
+            if let Some(store) = &archive_store
+                && let Err(err) = store.save(&archived).await
+            {
+                tracing::warn!("Unable to save archive of game {id:?}: {err}");
+            }
+            tracing::info!("Game {id:?} archived");
+            let mut state = cloned_state.write().await;
+            end_tournament_round(&mut state, &archived.archive).await;
+            state.archives.insert(archived);
+        }
+    });
+
     state
 }
 
@@ -65,3 +134,143 @@ pub fn cleanup_state(game: GameId, token: CancellationToken, cleanup_tx: mpsc::S
         }
     });
 }
+
+/// Archive of a finished game, read from the store if it is no longer kept in memory.
+pub async fn get_archive(state: &ApiState, game_id: &GameId) -> Option<ArchivedGame> {
+    let store = {
+        let state = state.read().await;
+        if let Some(archived) = state.archives.cached(game_id) {
+            return Some(archived.clone());
+        }
+        state.archives.store()?.clone()
+    };
+    store.load(game_id).await.ok()
+}
+
+/// Archive the timeline of a game once it has ended.
+pub fn archive_game_on_end(game: GameContext, archive_tx: mpsc::Sender<ArchivedGame>) {
+    tokio::spawn(async move {
+        let mut state_rx = game.state_rx.clone();
+        while !matches!(*state_rx.borrow_and_update(), GameState::Ended(_)) {
+            if state_rx.changed().await.is_err() {
+                return;
+            }
+        }
+        let (tx_back, rx) = oneshot::channel();
+        if game
+            .tx
+            .send(GameMessage::GetArchive { tx_back })
+            .await
+            .is_err()
+        {
+            return;
+        }
+        if let Ok(Some(archive)) = rx.await {
+            let _ = archive_tx.send(archive).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use tokio::sync::{mpsc, watch};
+
+    use crate::{
+        game::{
+            GameContext, GameId, GameMessage, GameName, GameState, HostToken, PhaseDurations,
+            PlayerLimits,
+            delivery_period::DeliveryPeriodId,
+            infra::{
+                archive::{ArchivedGame, GameArchive},
+                stack_config::{GameStackConfig, GameStackFixedConfig},
+            },
+        },
+        utils::units::CarbonPrice,
+    };
+
+    use super::archive_game_on_end;
+
+    fn game_context() -> (
+        GameContext,
+        mpsc::Receiver<GameMessage>,
+        watch::Sender<GameState>,
+    ) {
+        let (tx, rx) = mpsc::channel(16);
+        let (state_tx, state_rx) = watch::channel(GameState::Open);
+        let game = GameContext {
+            id: GameId::default(),
+            name: GameName::default(),
+            stack: GameStackConfig::Fixed(GameStackFixedConfig {
+                plants: Vec::new(),
+                carbon_price: CarbonPrice::default(),
+                weather: None,
+            }),
+            last_delivery_period: DeliveryPeriodId::from(1),
+            join_code: None,
+            host_token: HostToken::default(),
+            player_limits: PlayerLimits::default(),
+            durations: PhaseDurations::default(),
+            created_at: Utc::now(),
+            tx,
+            state_rx,
+            players_rx: watch::channel(Vec::new()).1,
+        };
+        (game, rx, state_tx)
+    }
+
+    fn archived_game(id: GameId) -> ArchivedGame {
+        ArchivedGame {
+            archive: GameArchive {
+                id,
+                name: GameName::default(),
+                ended_at: Utc::now(),
+                players: Vec::new(),
+                periods: Vec::new(),
+                rankings: Vec::new(),
+                emissions_rankings: None,
+            },
+            host_token: HostToken::default(),
+            join_code: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_game_archived_once_ended() {
+        let (game, mut rx, state_tx) = game_context();
+        let archived = archived_game(game.id.clone());
+        let (archive_tx, mut archive_rx) = mpsc::channel(16);
+
+        archive_game_on_end(game, archive_tx);
+        state_tx
+            .send(GameState::Running {
+                period: DeliveryPeriodId::from(1),
+                end_at: None,
+            })
+            .unwrap();
+        tokio::task::yield_now().await;
+        assert!(rx.try_recv().is_err());
+
+        state_tx
+            .send(GameState::Ended(DeliveryPeriodId::from(1)))
+            .unwrap();
+        let Some(GameMessage::GetArchive { tx_back }) = rx.recv().await else {
+            unreachable!("Should have asked the game for its archive")
+        };
+        tx_back.send(Some(archived.clone())).unwrap();
+
+        assert_eq!(archive_rx.recv().await, Some(archived));
+    }
+
+    #[tokio::test]
+    async fn test_no_archive_when_game_terminated_before_ending() {
+        let (game, mut rx, state_tx) = game_context();
+        let (archive_tx, mut archive_rx) = mpsc::channel(16);
+
+        archive_game_on_end(game, archive_tx);
+        drop(state_tx);
+
+        assert_eq!(archive_rx.recv().await, None);
+        assert!(rx.try_recv().is_err());
+    }
+}
@@ -0,0 +1,744 @@
+use std::{
+    fs,
+    path::{Path as FsPath, PathBuf},
+};
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tower_cookies::Cookies;
+
+use crate::{
+    game::{GameId, GameName, HostToken, infra::archive::GameArchive},
+    player::PlayerName,
+    tournament::{
+        Tournament, TournamentId, TournamentPlayerToken, TournamentRound, TournamentScoring,
+        TournamentStanding,
+    },
+};
+
+use super::{
+    ApiState,
+    cookies::add_game_cookies,
+    create_game::{GameSetup, prepare_game, start_game},
+    join_game::register_player,
+    scenario::{ScenarioDirectory, ScenarioError},
+    state::AppState,
+};
+
+/// Tournament along with the prepared games of its rounds, the game of each round being started
+/// once the previous one has ended. All the games of a tournament share its host token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TournamentContext {
+    pub name: String,
+    pub tournament: Tournament,
+    pub rounds: Vec<GameSetup>,
+    pub host_token: HostToken,
+}
+
+/// Current version of the tournament snapshot format, snapshots of other versions are not
+/// restored.
+pub const TOURNAMENT_SNAPSHOT_VERSION: u32 = 1;
+
+/// Snapshot of a tournament, saved whenever it changes so that it can be restored after a server
+/// restart along with the games of its rounds.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TournamentSnapshot {
+    pub version: u32,
+    pub saved_at: DateTime<Utc>,
+    pub id: TournamentId,
+    pub context: TournamentContext,
+}
+
+#[derive(Debug, Deserialize)]
+struct SnapshotVersion {
+    version: u32,
+}
+
+#[derive(Debug, Clone, thiserror::Error, PartialEq)]
+pub enum TournamentStoreError {
+    #[error("Unable to access tournament snapshot: {0}")]
+    Io(String),
+    #[error("Unable to parse tournament snapshot: {0}")]
+    Parse(String),
+    #[error("Unsupported snapshot version {0}, expected version {TOURNAMENT_SNAPSHOT_VERSION}")]
+    UnsupportedVersion(u32),
+}
+
+/// Local directory holding a JSON snapshot per tournament, named after the tournament id, next to
+/// the snapshots of the games.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TournamentStore {
+    path: PathBuf,
+}
+
+impl TournamentStore {
+    pub fn new(persistence_dir: impl Into<PathBuf>) -> TournamentStore {
+        TournamentStore {
+            path: persistence_dir.into().join("tournaments"),
+        }
+    }
+
+    fn snapshot_path(&self, tournament: &TournamentId) -> PathBuf {
+        self.path.join(format!("{tournament}.json"))
+    }
+
+    /// Save the snapshot of a tournament, replacing the previous one. The snapshot is written to
+    /// a temporary file first so that a crash while saving never leaves a truncated snapshot.
+    pub async fn save(&self, snapshot: &TournamentSnapshot) -> Result<(), TournamentStoreError> {
+        let content = serde_json::to_vec(snapshot)
+            .map_err(|err| TournamentStoreError::Parse(err.to_string()))?;
+        let path = self.snapshot_path(&snapshot.id);
+        let tmp_path = path.with_extension("json.tmp");
+
+        tokio::fs::create_dir_all(&self.path)
+            .await
+            .map_err(|err| TournamentStoreError::Io(err.to_string()))?;
+        tokio::fs::write(&tmp_path, content)
+            .await
+            .map_err(|err| TournamentStoreError::Io(err.to_string()))?;
+        tokio::fs::rename(&tmp_path, &path)
+            .await
+            .map_err(|err| TournamentStoreError::Io(err.to_string()))
+    }
+
+    /// Load a snapshot, checking its version before its content.
+    pub fn load(path: &FsPath) -> Result<TournamentSnapshot, TournamentStoreError> {
+        let content =
+            fs::read_to_string(path).map_err(|err| TournamentStoreError::Io(err.to_string()))?;
+        let snapshot: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|err| TournamentStoreError::Parse(err.to_string()))?;
+        let SnapshotVersion { version } = SnapshotVersion::deserialize(&snapshot)
+            .map_err(|err| TournamentStoreError::Parse(err.to_string()))?;
+        if version != TOURNAMENT_SNAPSHOT_VERSION {
+            return Err(TournamentStoreError::UnsupportedVersion(version));
+        }
+        serde_json::from_value(snapshot).map_err(|err| TournamentStoreError::Parse(err.to_string()))
+    }
+
+    /// Load the snapshots of all the tournaments of the store, invalid snapshots are skipped.
+    pub fn load_all(&self) -> Vec<TournamentSnapshot> {
+        let Ok(entries) = fs::read_dir(&self.path) else {
+            return Vec::new();
+        };
+        entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| match TournamentStore::load(&path) {
+                Ok(snapshot) => Some(snapshot),
+                Err(err) => {
+                    tracing::warn!("Tournament snapshot {path:?} not restored: {err}");
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// Snapshot the tournament, if tournaments are persisted.
+async fn save_tournament(state: &AppState, tournament_id: &TournamentId) {
+    let (Some(dir), Some(context)) = (
+        state.config.persistence_dir.as_ref(),
+        state.tournaments.get(tournament_id),
+    ) else {
+        return;
+    };
+    let snapshot = TournamentSnapshot {
+        version: TOURNAMENT_SNAPSHOT_VERSION,
+        saved_at: Utc::now(),
+        id: tournament_id.clone(),
+        context: context.clone(),
+    };
+    if let Err(err) = TournamentStore::new(dir).save(&snapshot).await {
+        tracing::warn!("Unable to save tournament {tournament_id:?}: {err}");
+    }
+}
+
+/// Restore the tournaments of the store, once the games of their rounds have been restored.
+/// Returns the games of the current rounds which are gone, whose rounds are to be abandoned.
+pub fn restore_tournaments(state: &mut AppState, store: &TournamentStore) -> Vec<GameId> {
+    let mut stalled_rounds = Vec::new();
+    for snapshot in store.load_all() {
+        if let Some(game) = snapshot.context.tournament.current_game()
+            && !state.game_services.contains_key(game)
+        {
+            stalled_rounds.push(game.clone());
+        }
+        state.tournaments.insert(snapshot.id, snapshot.context);
+    }
+    stalled_rounds
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewTournamentRequest {
+    name: String,
+    /// Scenario of each round, from the server's scenarios directory
+    rounds: Vec<String>,
+    #[serde(default)]
+    scoring: TournamentScoring,
+}
+
+#[derive(Debug, Serialize)]
+struct NewTournamentSuccess {
+    tournament_id: TournamentId,
+    /// Secret to send host commands to the games of the tournament, only given to its creator
+    host_token: HostToken,
+}
+
+/// Create a tournament and start the game of its first round.
+pub async fn create_tournament(
+    State(state): State<ApiState>,
+    Json(request): Json<NewTournamentRequest>,
+) -> impl IntoResponse {
+    if request.name.is_empty() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    let (scenarios_dir, profiles_dir) = {
+        let state = state.read().await;
+        (
+            ScenarioDirectory::new(state.config.scenarios_dir.clone()),
+            state.config.profiles_dir.clone(),
+        )
+    };
+    let rounds = match request
+        .rounds
+        .iter()
+        .map(|scenario| {
+            scenarios_dir
+                .load(scenario)
+                .and_then(|scenario| prepare_game(scenario, profiles_dir.clone()))
+        })
+        .collect::<Result<Vec<GameSetup>, ScenarioError>>()
+    {
+        Ok(rounds) => rounds,
+        Err(ScenarioError::NotFound(_)) => return StatusCode::NOT_FOUND.into_response(),
+        Err(err) => {
+            tracing::warn!("Invalid tournament scenario: {err}");
+            return StatusCode::BAD_REQUEST.into_response();
+        }
+    };
+    let tournament = match Tournament::new(rounds.len(), request.scoring) {
+        Ok(tournament) => tournament,
+        Err(err) => {
+            tracing::warn!("Invalid tournament {:?}: {err}", request.name);
+            return StatusCode::BAD_REQUEST.into_response();
+        }
+    };
+
+    let tournament_id = TournamentId::default();
+    let host_token = HostToken::default();
+    let mut state = state.write().await;
+    state.tournaments.insert(
+        tournament_id.clone(),
+        TournamentContext {
+            name: request.name,
+            tournament,
+            rounds,
+            host_token: host_token.clone(),
+        },
+    );
+    start_next_round(&mut state, &tournament_id);
+    save_tournament(&state, &tournament_id).await;
+
+    (
+        StatusCode::CREATED,
+        Json(NewTournamentSuccess {
+            tournament_id,
+            host_token,
+        }),
+    )
+        .into_response()
+}
+
+/// Start the game of the next round of a tournament, if any round is left to play. The game is
+/// private and its code is never shared, so that players can only join it through the tournament.
+fn start_next_round(state: &mut AppState, tournament_id: &TournamentId) {
+    let Some(context) = state.tournaments.get(tournament_id) else {
+        return;
+    };
+    let round = context.tournament.rounds().len();
+    let Some(setup) = context.rounds.get(round).cloned().map(GameSetup::private) else {
+        return;
+    };
+    let game_name = GameName::from(format!("{} #{}", context.name, round + 1));
+    let host_token = context.host_token.clone();
+
+    let (game_id, _) = start_game(state, game_name, setup, host_token);
+    if let Some(context) = state.tournaments.get_mut(tournament_id)
+        && let Err(err) = context.tournament.start_round(game_id)
+    {
+        tracing::error!("Unable to start round of tournament {tournament_id:?}: {err}");
+    }
+}
+
+/// Record the results of a tournament's round once its game is archived, and start the next
+/// round.
+pub async fn end_tournament_round(state: &mut AppState, archive: &GameArchive) {
+    let Some(tournament_id) = state
+        .tournaments
+        .iter()
+        .find(|(_, context)| context.tournament.current_game() == Some(&archive.id))
+        .map(|(tournament_id, _)| tournament_id.clone())
+    else {
+        return;
+    };
+    let game_players = state
+        .game_services
+        .get(&archive.id)
+        .map(|game| game.players_rx.borrow().clone())
+        .unwrap_or_default();
+    let Some(context) = state.tournaments.get_mut(&tournament_id) else {
+        return;
+    };
+    if let Err(err) = context
+        .tournament
+        .end_round(&archive.id, &archive.rankings, &game_players)
+    {
+        tracing::error!("Unable to end round of tournament {tournament_id:?}: {err}");
+        return;
+    }
+    start_next_round(state, &tournament_id);
+    save_tournament(state, &tournament_id).await;
+}
+
+/// Abandon the round of a tournament whose game is removed before it ended, e.g. once the game's
+/// lifetime is over, and start the next round so that the tournament does not stall.
+pub async fn abandon_tournament_round(state: &mut AppState, game: &GameId) {
+    let Some((tournament_id, context)) = state
+        .tournaments
+        .iter_mut()
+        .find(|(_, context)| context.tournament.current_game() == Some(game))
+    else {
+        return;
+    };
+    let tournament_id = tournament_id.clone();
+    if let Err(err) = context.tournament.abandon_round(game) {
+        tracing::error!("Unable to abandon round of tournament {tournament_id:?}: {err}");
+        return;
+    }
+    tracing::warn!("Round game {game:?} of tournament {tournament_id:?} abandoned");
+    start_next_round(state, &tournament_id);
+    save_tournament(state, &tournament_id).await;
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JoinTournament {
+    player_name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JoinTournamentSuccess {
+    /// Secret identifying the player from one round to the next
+    player_token: TournamentPlayerToken,
+}
+
+pub async fn join_tournament(
+    State(state): State<ApiState>,
+    Path(tournament_id): Path<String>,
+    Json(input): Json<JoinTournament>,
+) -> impl IntoResponse {
+    let (Some(tournament_id), Some(player_name)) = (
+        TournamentId::parse(&tournament_id),
+        PlayerName::parse(&input.player_name),
+    ) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let mut state = state.write().await;
+    let Some(context) = state.tournaments.get_mut(&tournament_id) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match context.tournament.register_player(player_name) {
+        Ok(player_token) => {
+            save_tournament(&state, &tournament_id).await;
+            (
+                StatusCode::CREATED,
+                Json(JoinTournamentSuccess { player_token }),
+            )
+                .into_response()
+        }
+        Err(err) => {
+            tracing::warn!("Unable to join tournament {tournament_id:?}: {err}");
+            StatusCode::CONFLICT.into_response()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PlayTournamentRound {
+    player_token: TournamentPlayerToken,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PlayTournamentRoundSuccess {
+    game_id: GameId,
+}
+
+/// Join the game of the current round of a tournament, with the same name as in the previous
+/// rounds. Players already registered to the round's game get their game cookies back.
+pub async fn play_tournament_round(
+    cookies: Cookies,
+    State(state): State<ApiState>,
+    Path(tournament_id): Path<String>,
+    Json(input): Json<PlayTournamentRound>,
+) -> impl IntoResponse {
+    let Some(tournament_id) = TournamentId::parse(&tournament_id) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let mut state = state.write().await;
+    let Some(context) = state.tournaments.get(&tournament_id) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let tournament = &context.tournament;
+    let player_name = match tournament.player(&input.player_token) {
+        Ok(player) => player.name.clone(),
+        Err(_) => return StatusCode::UNAUTHORIZED.into_response(),
+    };
+    let Some(game_id) = tournament.current_game().cloned() else {
+        return StatusCode::CONFLICT.into_response();
+    };
+
+    let player_id = match tournament
+        .player_id_in_current_game(&input.player_token)
+        .cloned()
+    {
+        Some(player_id) => player_id,
+        None => {
+            let player_id = match register_player(&mut state, &game_id, &player_name, None).await {
+                Ok((player_id, _)) => player_id,
+                Err(status) => return status.into_response(),
+            };
+            if let Some(context) = state.tournaments.get_mut(&tournament_id) {
+                let _ = context.tournament.record_player_joined_game(
+                    &input.player_token,
+                    game_id.clone(),
+                    player_id.clone(),
+                );
+            }
+            save_tournament(&state, &tournament_id).await;
+            player_id
+        }
+    };
+
+    add_game_cookies(&cookies, &player_id, &player_name, &game_id);
+    (StatusCode::OK, Json(PlayTournamentRoundSuccess { game_id })).into_response()
+}
+
+#[derive(Debug, Serialize)]
+struct TournamentView<'a> {
+    name: &'a str,
+    number_of_rounds: usize,
+    rounds: &'a [TournamentRound],
+    standings: Vec<TournamentStanding>,
+}
+
+/// Rounds of a tournament and its leaderboard over the rounds played so far.
+pub async fn tournament_standings(
+    State(state): State<ApiState>,
+    Path(tournament_id): Path<String>,
+) -> impl IntoResponse {
+    let Some(tournament_id) = TournamentId::parse(&tournament_id) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let state = state.read().await;
+    let Some(context) = state.tournaments.get(&tournament_id) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    Json(TournamentView {
+        name: &context.name,
+        number_of_rounds: context.tournament.number_of_rounds(),
+        rounds: context.tournament.rounds(),
+        standings: context.tournament.standings(),
+    })
+    .into_response()
+}
+
+#[cfg(test)]
+mod test_api_tournament {
+    use std::{collections::HashMap, sync::Arc};
+
+    use axum::{
+        Router,
+        body::Body,
+        http::{self, Request, StatusCode},
+        routing::{get, post},
+    };
+    use chrono::Utc;
+    use http_body_util::BodyExt;
+    use serde_json::{Value, json};
+    use tokio::sync::{RwLock, mpsc};
+    use tower::ServiceExt;
+    use tower_cookies::CookieManagerLayer;
+
+    use crate::{
+        game::{
+            GameName,
+            infra::archive::{Archives, GameArchive},
+        },
+        infra::api::{ApiState, state::AppState},
+        player::{PlayerName, PlayerResultView},
+        tournament::TournamentId,
+        utils::{config::AppConfig, units::Money},
+    };
+
+    use super::{
+        TournamentStore, abandon_tournament_round, create_tournament, end_tournament_round,
+        join_tournament, play_tournament_round, restore_tournaments, tournament_standings,
+    };
+
+    fn init_state() -> ApiState {
+        init_state_with_config(AppConfig::default())
+    }
+
+    fn init_state_with_config(config: AppConfig) -> ApiState {
+        let (tx, _) = mpsc::channel(16);
+        let (cleanup_tx, _) = mpsc::channel(16);
+        let (archive_tx, _) = mpsc::channel(16);
+        Arc::new(RwLock::new(AppState {
+            game_services: HashMap::new(),
+            market_services: HashMap::new(),
+            stack_services: HashMap::new(),
+            player_connections_repository: tx,
+            cleanup_tx,
+            archives: Archives::new(None),
+            archive_tx,
+            tournaments: HashMap::new(),
+            join_code_attempts: HashMap::new(),
+            config,
+        }))
+    }
+
+    fn build_app(state: ApiState) -> Router {
+        Router::new()
+            .route("/tournament", post(create_tournament))
+            .route("/tournament/{tournament_id}", get(tournament_standings))
+            .route("/tournament/{tournament_id}/join", post(join_tournament))
+            .route(
+                "/tournament/{tournament_id}/play",
+                post(play_tournament_round),
+            )
+            .layer(CookieManagerLayer::new())
+            .with_state(state)
+    }
+
+    async fn send(app: Router, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+        let request = match body {
+            Some(body) => Request::builder()
+                .method(http::Method::POST)
+                .uri(uri)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+            None => Request::builder().uri(uri).body(Body::empty()).unwrap(),
+        };
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    #[tokio::test]
+    async fn test_create_tournament_unknown_scenario() {
+        let (status, _) = send(
+            build_app(init_state()),
+            "/tournament",
+            Some(json!({"name": "league", "rounds": ["tutorial", "missing"]})),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_create_tournament_without_round() {
+        let (status, _) = send(
+            build_app(init_state()),
+            "/tournament",
+            Some(json!({"name": "league", "rounds": []})),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_tournament_rounds_are_chained() {
+        let state = init_state();
+        let app = build_app(state.clone());
+
+        let (status, body) = send(
+            app.clone(),
+            "/tournament",
+            Some(json!({
+                "name": "league",
+                "rounds": ["tutorial", "tutorial"],
+                "scoring": {"RankPoints": [10, 5]}
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let tournament_id = TournamentId::from(body["tournament_id"].as_str().unwrap());
+
+        let (status, body) = send(
+            app.clone(),
+            &format!("/tournament/{tournament_id}/join"),
+            Some(json!({"player_name": "toto"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let player_token = body["player_token"].clone();
+
+        // Players join the game of the current round with their tournament token
+        let (status, body) = send(
+            app.clone(),
+            &format!("/tournament/{tournament_id}/play"),
+            Some(json!({"player_token": player_token})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let first_game = body["game_id"].as_str().unwrap().to_string();
+        let (status, _) = send(
+            app.clone(),
+            &format!("/tournament/{tournament_id}/play"),
+            Some(json!({"player_token": player_token})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "Players can get back to their game");
+        assert!(
+            state.read().await.game_services[&first_game.as_str().into()]
+                .join_code
+                .is_some(),
+            "Round games can only be joined through the tournament"
+        );
+
+        let archive = GameArchive {
+            id: first_game.as_str().into(),
+            name: GameName::from("league #1".to_string()),
+            ended_at: Utc::now(),
+            players: vec![PlayerName::from("toto")],
+            periods: Vec::new(),
+            rankings: vec![PlayerResultView {
+                player: PlayerName::from("toto"),
+                rank: 1,
+                score: Money::from(100),
+            }],
+            emissions_rankings: None,
+        };
+        end_tournament_round(&mut *state.write().await, &archive).await;
+
+        let (status, body) = send(app.clone(), &format!("/tournament/{tournament_id}"), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["rounds"].as_array().unwrap().len(), 2);
+        assert_eq!(body["standings"][0]["player"], "toto");
+        assert_eq!(body["standings"][0]["score"], 10);
+
+        let (status, body) = send(
+            app,
+            &format!("/tournament/{tournament_id}/play"),
+            Some(json!({"player_token": player_token})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_ne!(body["game_id"].as_str().unwrap(), first_game);
+    }
+
+    #[tokio::test]
+    async fn test_play_tournament_round_unknown_player() {
+        let state = init_state();
+        let app = build_app(state.clone());
+        let (_, body) = send(
+            app.clone(),
+            "/tournament",
+            Some(json!({"name": "league", "rounds": ["tutorial"]})),
+        )
+        .await;
+        let tournament_id = body["tournament_id"].as_str().unwrap().to_string();
+
+        let (status, _) = send(
+            app,
+            &format!("/tournament/{tournament_id}/play"),
+            Some(json!({"player_token": "unknown"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_round_starts_the_next_one() {
+        let state = init_state();
+        let app = build_app(state.clone());
+        let (_, body) = send(
+            app,
+            "/tournament",
+            Some(json!({"name": "league", "rounds": ["tutorial", "tutorial"]})),
+        )
+        .await;
+        let tournament_id = TournamentId::from(body["tournament_id"].as_str().unwrap());
+        let first_game = state.read().await.tournaments[&tournament_id]
+            .tournament
+            .current_game()
+            .cloned()
+            .unwrap();
+
+        let mut state = state.write().await;
+        state.remove_game(&first_game);
+        abandon_tournament_round(&mut state, &first_game).await;
+
+        let tournament = &state.tournaments[&tournament_id].tournament;
+        assert_eq!(tournament.rounds().len(), 2);
+        assert_eq!(tournament.rounds()[0].results, Some(Vec::new()));
+        let second_game = tournament.current_game().unwrap();
+        assert_ne!(*second_game, first_game);
+        assert!(state.game_services.contains_key(second_game));
+    }
+
+    #[tokio::test]
+    async fn test_tournament_restored_from_its_snapshot() {
+        let dir = std::env::temp_dir().join(format!("parcelec-games-{}", uuid::Uuid::new_v4()));
+        let config = AppConfig {
+            persistence_dir: Some(dir.clone()),
+            ..AppConfig::default()
+        };
+        let state = init_state_with_config(config.clone());
+        let app = build_app(state.clone());
+        let (_, body) = send(
+            app.clone(),
+            "/tournament",
+            Some(json!({"name": "league", "rounds": ["tutorial"]})),
+        )
+        .await;
+        let tournament_id = TournamentId::from(body["tournament_id"].as_str().unwrap());
+        let (status, _) = send(
+            app,
+            &format!("/tournament/{tournament_id}/join"),
+            Some(json!({"player_name": "toto"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        // The round's game is not restored along with the tournament, its round is abandoned
+        let restored = init_state_with_config(config);
+        let mut restored = restored.write().await;
+        let stalled_rounds = restore_tournaments(&mut restored, &TournamentStore::new(&dir));
+        let context = &restored.tournaments[&tournament_id];
+        assert_eq!(context.name, "league");
+        assert_eq!(
+            context.tournament.standings()[0].player,
+            PlayerName::from("toto")
+        );
+        assert_eq!(
+            stalled_rounds.first(),
+            context.tournament.current_game(),
+            "The round's game is gone"
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
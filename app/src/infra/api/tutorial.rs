@@ -1,4 +1,4 @@
-use std::{collections::HashMap, time::Duration as StdDuration};
+use std::collections::HashMap;
 
 use axum::{extract::State, http::StatusCode, response::IntoResponse};
 use tokio::sync::oneshot;
@@ -6,21 +6,13 @@ use tokio::sync::oneshot;
 use tower_cookies::Cookies;
 
 use crate::{
-    forecast::ForecastValue,
-    game::{
-        GameActor, GameId, GameMessage, GameName, RegisterPlayerResponse,
-        infra::{
-            actor::GameActorConfig,
-            stack_config::{GameStackConfig, GameStackFixedConfig},
-        },
-    },
-    infra::api::{cookies::add_game_cookies, state::cleanup_state},
-    market::{MarketActor, bots::start_bots_tutorial},
-    player::{PlayerName, infra::PlayerConnectionsService},
-    utils::{
-        program_actors_termination,
-        units::{Energy, EnergyCost, Power},
+    game::{GameMessage, GameName, HostToken, RegisterPlayerResponse},
+    infra::api::{
+        cookies::add_game_cookies,
+        create_game::{GameSetup, prepare_game, start_game},
+        scenario::Scenario,
     },
+    player::PlayerName,
 };
 
 use super::ApiState;
@@ -29,56 +21,22 @@ pub async fn create_tutorial_game(
     cookies: Cookies,
     State(state): State<ApiState>,
 ) -> impl IntoResponse {
-    let mut state = state.write().await;
-    let game_id = GameId::default();
     let player_name = PlayerName::random();
     let game_name = GameName::from(format!("tutorial-{}", player_name));
-    let cancellation_token = program_actors_termination(StdDuration::from_secs(3600 * 24));
-    cleanup_state(
-        game_id.clone(),
-        cancellation_token.clone(),
-        state.cleanup_tx.clone(),
-    );
-    let connections_service =
-        PlayerConnectionsService::new(state.player_connections_repository.clone());
-    let market_context = MarketActor::start(
-        &game_id,
-        connections_service.clone(),
-        cancellation_token.clone(),
-    );
-    let game_config = GameActorConfig {
-        id: game_id.clone(),
-        name: game_name,
-        number_of_delivery_periods: 4,
-        delivery_period_duration: None,
-        stack_config: game_stack_config(),
+    let profiles_dir = state.read().await.config.profiles_dir.clone();
+    // Tutorial games are private, so that they are neither listed nor joined by other players
+    let setup = match Scenario::tutorial()
+        .and_then(|scenario| prepare_game(scenario, profiles_dir))
+        .map(GameSetup::private)
+    {
+        Ok(setup) => setup,
+        Err(err) => {
+            tracing::error!("Unable to prepare tutorial game: {err}");
+            return StatusCode::INTERNAL_SERVER_ERROR;
+        }
     };
-    let game_context = GameActor::start(
-        game_config,
-        connections_service.clone(),
-        market_context.clone(),
-        cancellation_token.clone(),
-    );
-
-    state
-        .market_services
-        .insert(game_id.clone(), market_context.clone());
-    state
-        .game_services
-        .insert(game_id.clone(), game_context.clone());
-
-    // Start the bots
-    let cloned_game_context = game_context.clone();
-    let cloned_market_context = market_context.clone();
-    let cloned_cancellation_token = cancellation_token.clone();
-    tokio::spawn(async move {
-        start_bots_tutorial(
-            cloned_game_context,
-            cloned_market_context,
-            cloned_cancellation_token,
-        )
-        .await;
-    });
+    let mut state = state.write().await;
+    let (game_id, game_context) = start_game(&mut state, game_name, setup, HostToken::default());
 
     // Register a player for this game
     let (tx_back, rx) = oneshot::channel();
@@ -86,12 +44,14 @@ pub async fn create_tutorial_game(
         .tx
         .send(GameMessage::RegisterPlayer {
             name: player_name.clone(),
+            team_secret: None,
             tx_back,
         })
         .await;
     let Ok(RegisterPlayerResponse::Success {
         id: player_id,
         stack: Some(stack),
+        ..
     }) = rx.await
     else {
         tracing::error!("Unable to register tutorial player");
@@ -125,52 +85,3 @@ pub async fn create_tutorial_game(
     tracing::info!("Tutorial game created");
     StatusCode::CREATED
 }
-
-fn game_stack_config() -> GameStackConfig {
-    GameStackConfig::Fixed(GameStackFixedConfig {
-        consumers_revenues: EnergyCost::from(56),
-        gas_cost: EnergyCost::from(80),
-        nuclear_cost: EnergyCost::from(35),
-        gas_capacity: Power::from(500),
-        nuclear_capacity: Power::from(1000),
-        battery_capacity: Energy::from(300),
-        consumers_forecasts: vec![
-            ForecastValue {
-                value: -1000,
-                deviation: 25,
-            },
-            ForecastValue {
-                value: -1200,
-                deviation: 50,
-            },
-            ForecastValue {
-                value: -600,
-                deviation: 75,
-            },
-            ForecastValue {
-                value: -1800,
-                deviation: 100,
-            },
-        ],
-        consumers_forecasts_range: 3,
-        renewable_forecasts: vec![
-            ForecastValue {
-                value: 250,
-                deviation: 25,
-            },
-            ForecastValue {
-                value: 150,
-                deviation: 50,
-            },
-            ForecastValue {
-                value: 300,
-                deviation: 75,
-            },
-            ForecastValue {
-                value: 100,
-                deviation: 75,
-            },
-        ],
-        renewable_forecasts_range: 3,
-    })
-}
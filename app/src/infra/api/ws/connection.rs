@@ -19,13 +19,13 @@ use crate::{
     game::{
         GameContext, GameId, GameMessage, GameState, GetPreviousScoresResult,
         delivery_period::DeliveryPeriodId,
-        infra::stack_config::GameStackPerPlayerPlayerConfig,
+        infra::stack_config::{GameStackPerPlayerPlayerConfig, PlayerInvestmentConfig},
         scores::{PlayerDetailedScore, PlayerScore},
     },
     infra::api::state::ApiState,
     market::{Direction, Market, MarketContext, order_book::OrderRequest as MarketOrderRequest},
     plants::{
-        GetSnapshotError, Stack,
+        GetSnapshotError, PlantId, Stack,
         infra::{ProgramPlant, StackContext},
     },
     player::{
@@ -68,6 +68,8 @@ enum WebSocketIncomingMessage {
     ConnectionReady,
     PlayerIsReady,
     RegisterPlayerStackConfig(PlayerStackConfigRequest),
+    InvestInPlant(PlayerInvestmentConfig),
+    RetirePlant { plant_id: PlantId },
     OrderRequest(OrderRequest),
     DeleteOrder { order_id: String },
     ProgramPlant(ProgramPlant),
@@ -115,21 +117,9 @@ pub async fn start_player_connection<MS: Market, PS: Stack>(
     let connection_id = Uuid::new_v4().to_string();
     let (tx, rx) = channel::<PlayerMessage>(16);
 
-    let Some(Ok(Message::Text(msg))) = ws.recv().await else {
-        tracing::error!("Haven't received Message::Text for connection readines, closing WS");
-        let _ = ws.close().await;
-        return Err(PlayerConnectionError::ClientNotReady);
-    };
-    match serde_json::from_str::<WebSocketIncomingMessage>(&msg) {
-        Ok(WebSocketIncomingMessage::ConnectionReady) => {}
-        _ => {
-            tracing::error!("First message is not a ConnectionReady, closing WS");
-            let _ = ws.close().await;
-            return Err(PlayerConnectionError::FirstMessageReceivedNotConnectionReady);
-        }
-    }
+    wait_for_connection_ready(&mut ws).await?;
 
-    register_connection(tx, &connection_id, &context).await;
+    register_connection(tx.clone(), &connection_id, &context).await;
 
     send_player_name(&mut ws, &context).await?;
     send_game_duration(&mut ws, &context).await?;
@@ -149,6 +139,7 @@ pub async fn start_player_connection<MS: Market, PS: Stack>(
         context.market.service.clone(),
         context.game_id.clone(),
         context.player_id.clone(),
+        tx,
         state,
     ));
     tokio::select! {
@@ -159,6 +150,26 @@ pub async fn start_player_connection<MS: Market, PS: Stack>(
     Ok(())
 }
 
+/// Wait for the client to signal that it is ready to receive messages, closing the connection if
+/// its first message is not a `ConnectionReady`.
+pub(super) async fn wait_for_connection_ready(
+    ws: &mut WebSocket,
+) -> Result<(), PlayerConnectionError> {
+    let Some(Ok(Message::Text(msg))) = ws.recv().await else {
+        tracing::error!("Haven't received Message::Text for connection readines, closing WS");
+        let _ = ws.close().await;
+        return Err(PlayerConnectionError::ClientNotReady);
+    };
+    match serde_json::from_str::<WebSocketIncomingMessage>(&msg) {
+        Ok(WebSocketIncomingMessage::ConnectionReady) => Ok(()),
+        _ => {
+            tracing::error!("First message is not a ConnectionReady, closing WS");
+            let _ = ws.close().await;
+            Err(PlayerConnectionError::FirstMessageReceivedNotConnectionReady)
+        }
+    }
+}
+
 async fn send_initial_trades_and_obs<MS: Market, PS: Stack>(
     ws: &mut WebSocket,
     context: &PlayerConnectionContext<MS, PS>,
@@ -300,9 +311,13 @@ async fn send_previous_scores<MS: Market, PS: Stack>(
             )
             .await?;
         }
-        GetPreviousScoresResult::PlayersRanking { scores } => {
+        GetPreviousScoresResult::PlayersRanking { scores, emissions } => {
             ws.send(
-                serde_json::to_string(&PlayerMessage::GameResults { rankings: scores })?.into(),
+                serde_json::to_string(&PlayerMessage::GameResults {
+                    rankings: scores,
+                    emissions_rankings: emissions,
+                })?
+                .into(),
             )
             .await?;
         }
@@ -352,6 +367,7 @@ async fn process_ws_messages<MS: Market>(
     market: MS,
     game_id: GameId,
     player_id: PlayerId,
+    connection_tx: Sender<PlayerMessage>,
     state: ApiState,
 ) {
     while let Some(Ok(Message::Text(msg))) = stream.next().await {
@@ -386,6 +402,42 @@ async fn process_ws_messages<MS: Market>(
                     };
                 }
             }
+            Ok(WebSocketIncomingMessage::InvestInPlant(investment)) => {
+                let (tx_back, rx) = oneshot::channel();
+                let _ = game_tx
+                    .send(GameMessage::InvestInPlant {
+                        player: player_id.clone(),
+                        investment,
+                        tx_back,
+                    })
+                    .await;
+                if let Ok(Err(err)) = rx.await {
+                    tracing::warn!("Investment of player {player_id} rejected: {err}");
+                    let _ = connection_tx
+                        .send(PlayerMessage::PlantInvestmentRejected {
+                            reason: err.to_string(),
+                        })
+                        .await;
+                }
+            }
+            Ok(WebSocketIncomingMessage::RetirePlant { plant_id }) => {
+                let (tx_back, rx) = oneshot::channel();
+                let _ = game_tx
+                    .send(GameMessage::RetirePlant {
+                        player: player_id.clone(),
+                        plant: plant_id,
+                        tx_back,
+                    })
+                    .await;
+                if let Ok(Err(err)) = rx.await {
+                    tracing::warn!("Player {player_id} could not retire plant: {err}");
+                    let _ = connection_tx
+                        .send(PlayerMessage::PlantInvestmentRejected {
+                            reason: err.to_string(),
+                        })
+                        .await;
+                }
+            }
             Ok(WebSocketIncomingMessage::OrderRequest(request)) => {
                 let order_request = MarketOrderRequest {
                     direction: request.direction,
@@ -417,7 +469,7 @@ async fn process_ws_messages<MS: Market>(
     }
 }
 
-async fn process_internal_messages(
+pub(super) async fn process_internal_messages(
     mut sink: SplitSink<WebSocket, Message>,
     mut rx: Receiver<PlayerMessage>,
     mut game_state: watch::Receiver<GameState>,
@@ -429,15 +481,22 @@ async fn process_internal_messages(
     }
 
     loop {
-        let msg = select! {
-            Some(msg) = rx.recv() => serde_json::to_string(&msg),
+        let (msg, kicked) = select! {
+            Some(msg) = rx.recv() => {
+                (serde_json::to_string(&msg), matches!(msg, PlayerMessage::Kicked))
+            }
             Ok(()) = game_state.changed() => {
-                serde_json::to_string(&game_state.borrow_and_update().clone())
+                (serde_json::to_string(&game_state.borrow_and_update().clone()), false)
             }
         };
         if send_msg(&mut sink, msg).await.is_err() {
             return;
         }
+        // A kicked player's connection is closed once they have been notified
+        if kicked {
+            let _ = sink.close().await;
+            return;
+        }
     }
 }
 
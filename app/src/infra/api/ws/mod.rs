@@ -16,6 +16,9 @@ use crate::{
 use super::ApiState;
 
 pub mod connection;
+pub mod spectator;
+
+pub use spectator::handle_spectator_connection;
 
 pub async fn handle_ws_connection(
     ws: WebSocketUpgrade,
@@ -32,6 +35,16 @@ pub async fn handle_ws_connection(
         invalidate_game_cookies(&cookies);
         return StatusCode::UNAUTHORIZED.into_response();
     };
+    // Kicked players keep their cookies, only registered players are let in
+    let is_registered = game_context
+        .players_rx
+        .borrow()
+        .iter()
+        .any(|(id, _)| *id == player_id);
+    if !is_registered {
+        invalidate_game_cookies(&cookies);
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
     // Not necessarly created yet ?
     let Some(market_context) = state.market_services.get(&game_id) else {
         invalidate_game_cookies(&cookies);
@@ -71,7 +84,17 @@ async fn handle_socket<MS: Market, PS: Stack>(
 #[cfg(test)]
 mod tests {
     use crate::{
-        infra::api::{build_router, state::AppState},
+        game::{
+            GameContext, GameId, GameMessage, GameName, HostCommand, HostToken,
+            RegisterPlayerResponse, infra::archive::Archives,
+        },
+        infra::api::{
+            build_router,
+            create_game::{prepare_game, start_game},
+            scenario::Scenario,
+            state::{ApiState, AppState},
+        },
+        player::PlayerName,
         utils::config::AppConfig,
     };
     use axum::http::{Request, StatusCode};
@@ -81,27 +104,41 @@ mod tests {
         net::{Ipv4Addr, SocketAddr},
         sync::Arc,
     };
-    use tokio::sync::{RwLock, mpsc};
+    use tokio::sync::{RwLock, mpsc, oneshot};
     use tokio_tungstenite::tungstenite::{Error, client::IntoClientRequest};
 
     async fn build_server() -> SocketAddr {
+        serve(build_state()).await
+    }
+
+    fn build_state() -> ApiState {
         let (tx_conn, _) = mpsc::channel(1024);
         let (cleanup_tx, _) = mpsc::channel(1024);
+        let (archive_tx, _) = mpsc::channel(1024);
         let config = AppConfig::default();
-        let state = Arc::new(RwLock::new(AppState {
+        Arc::new(RwLock::new(AppState {
             player_connections_repository: tx_conn,
             market_services: HashMap::new(),
             game_services: HashMap::new(),
             stack_services: HashMap::new(),
             cleanup_tx,
-            config: config.clone(),
-        }));
+            archives: Archives::new(None),
+            archive_tx,
+            tournaments: HashMap::new(),
+            join_code_attempts: HashMap::new(),
+            config,
+        }))
+    }
+
+    async fn serve(state: ApiState) -> SocketAddr {
+        let config = state.read().await.config.clone();
         let listener = tokio::net::TcpListener::bind(SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)))
             .await
             .unwrap();
         let addr = listener.local_addr().unwrap();
 
-        tokio::spawn(axum::serve(listener, build_router(state, config)).into_future());
+        let app = build_router(state, config).into_make_service_with_connect_info::<SocketAddr>();
+        tokio::spawn(axum::serve(listener, app).into_future());
         addr
     }
 
@@ -224,4 +261,107 @@ mod tests {
             _ => unreachable!(),
         }
     }
+
+    #[tokio::test]
+    async fn test_reject_spectator_connection_if_game_not_found() {
+        let addr = build_server().await;
+        let ws_request =
+            format!("ws://{addr}/api/game/970b9e72-89be-4ba2-978a-4be4eb53ff51/spectate")
+                .into_client_request()
+                .unwrap();
+
+        let res = tokio_tungstenite::connect_async(ws_request)
+            .await
+            .expect_err("Should have rejected the WS connection");
+        match res {
+            Error::Http(resp) => {
+                assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    async fn start_tutorial_game(state: &ApiState, token: HostToken) -> (GameId, GameContext) {
+        let setup = prepare_game(Scenario::tutorial().unwrap(), "profiles").unwrap();
+        start_game(
+            &mut *state.write().await,
+            GameName::from("game".to_string()),
+            setup,
+            token,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_reject_spectator_connection_without_host_token() {
+        let state = build_state();
+        let (game_id, _) = start_tutorial_game(&state, HostToken::from("token")).await;
+        let addr = serve(state).await;
+
+        for query in ["", "?host_token=not_the_token"] {
+            let ws_request = format!("ws://{addr}/api/game/{game_id}/spectate{query}")
+                .into_client_request()
+                .unwrap();
+            let res = tokio_tungstenite::connect_async(ws_request)
+                .await
+                .expect_err("Should have rejected the WS connection");
+            match res {
+                Error::Http(resp) => {
+                    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        let ws_request = format!("ws://{addr}/api/game/{game_id}/spectate?host_token=token")
+            .into_client_request()
+            .unwrap();
+        assert!(tokio_tungstenite::connect_async(ws_request).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_reject_ws_connection_of_kicked_player() {
+        let state = build_state();
+        let token = HostToken::default();
+        let (game_id, game) = start_tutorial_game(&state, token.clone()).await;
+        let (tx_back, rx) = oneshot::channel();
+        let _ = game
+            .tx
+            .send(GameMessage::RegisterPlayer {
+                name: PlayerName::from("kicked"),
+                team_secret: None,
+                tx_back,
+            })
+            .await;
+        let Ok(RegisterPlayerResponse::Success { id, .. }) = rx.await else {
+            unreachable!("Should have registered the player");
+        };
+        let (tx_back, rx) = oneshot::channel();
+        let _ = game
+            .tx
+            .send(GameMessage::HostCommand {
+                token,
+                command: HostCommand::KickPlayer(PlayerName::from("kicked")),
+                tx_back,
+            })
+            .await;
+        assert!(rx.await.unwrap().is_ok());
+        let addr = serve(state).await;
+
+        let mut ws_request = format!("ws://{addr}/api/ws").into_client_request().unwrap();
+        ws_request.headers_mut().insert(
+            "Cookie",
+            format!("player_id={id}; game_id={game_id}; player_name=kicked")
+                .parse()
+                .unwrap(),
+        );
+        let res = tokio_tungstenite::connect_async(ws_request)
+            .await
+            .expect_err("Should have rejected the WS connection");
+        match res {
+            Error::Http(resp) => {
+                assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+            }
+            _ => unreachable!(),
+        }
+    }
 }
@@ -0,0 +1,172 @@
+use axum::{
+    extract::{
+        Path, Query, State, WebSocketUpgrade,
+        ws::{Message, WebSocket},
+    },
+    http::StatusCode,
+    response::IntoResponse,
+};
+use futures_util::StreamExt;
+use serde::Deserialize;
+use tokio::sync::{
+    mpsc::{self, channel},
+    oneshot,
+};
+
+use crate::{
+    game::{GameContext, GameId, GameMessage, HostToken},
+    market::{Market, MarketContext},
+    player::{
+        PlayerId, PlayerMessage,
+        infra::{ConnectionRepositoryMessage, PlayerConnection},
+    },
+};
+
+use super::{
+    super::ApiState,
+    connection::{PlayerConnectionError, process_internal_messages, wait_for_connection_ready},
+};
+
+#[derive(Debug, Clone)]
+pub struct SpectatorConnectionContext<MS: Market> {
+    pub game_id: GameId,
+    /// Id of the connection, never registered to the game nor to its market
+    pub spectator_id: PlayerId,
+    pub connections_repository: mpsc::Sender<ConnectionRepositoryMessage>,
+    pub game: GameContext,
+    pub market: MarketContext<MS>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SpectatorQuery {
+    /// Secret of the game's host, the only one allowed to spectate the game
+    host_token: Option<String>,
+}
+
+/// Read-only connection to a game, e.g. for an instructor's dashboard, receiving the game state,
+/// the players' readiness, the public order book and the scores and positions of all players.
+/// Spectators are not players, hence never waited for before moving to the next phase of the game.
+pub async fn handle_spectator_connection(
+    ws: WebSocketUpgrade,
+    State(state): State<ApiState>,
+    Path(game_id): Path<String>,
+    Query(query): Query<SpectatorQuery>,
+) -> impl IntoResponse {
+    let Some(game_id) = GameId::parse(&game_id) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let state = state.read().await;
+    let (Some(game), Some(market)) = (
+        state.game_services.get(&game_id),
+        state.market_services.get(&game_id),
+    ) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let host_token = query.host_token.as_deref().map(HostToken::from);
+    if host_token.as_ref() != Some(&game.host_token) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    let context = SpectatorConnectionContext {
+        game_id: game_id.clone(),
+        spectator_id: PlayerId::default(),
+        connections_repository: state.player_connections_repository.clone(),
+        game: game.clone(),
+        market: market.clone(),
+    };
+
+    ws.on_upgrade(move |socket| async move {
+        tokio::spawn(async move {
+            if let Err(err) = start_spectator_connection(socket, context).await {
+                tracing::error!("Spectator connection ended: {err:?}");
+            };
+        });
+    })
+}
+
+pub async fn start_spectator_connection<MS: Market>(
+    mut ws: WebSocket,
+    context: SpectatorConnectionContext<MS>,
+) -> Result<(), PlayerConnectionError> {
+    let (tx, rx) = channel::<PlayerMessage>(16);
+
+    wait_for_connection_ready(&mut ws).await?;
+
+    let _ = context
+        .connections_repository
+        .send(ConnectionRepositoryMessage::RegisterSpectatorConnection(
+            context.game_id.clone(),
+            PlayerConnection {
+                id: context.spectator_id.to_string(),
+                player_id: context.spectator_id.clone(),
+                tx,
+            },
+        ))
+        .await;
+
+    for message in initial_messages(&context).await? {
+        ws.send(serde_json::to_string(&message)?.into()).await?;
+    }
+
+    let (sink, mut stream) = ws.split();
+    let sink_handle = tokio::spawn(process_internal_messages(sink, rx, context.game.state_rx));
+    // Spectators cannot act on the game, their messages are ignored until they disconnect
+    let stream_handle = tokio::spawn(async move {
+        while let Some(Ok(message)) = stream.next().await {
+            if let Message::Close(_) = message {
+                break;
+            }
+        }
+    });
+    tokio::select! {
+        _ = sink_handle => {},
+        _ = stream_handle => {}
+    }
+    tracing::info!("Spectator {:?} disconnected.", context.spectator_id);
+    Ok(())
+}
+
+async fn initial_messages<MS: Market>(
+    context: &SpectatorConnectionContext<MS>,
+) -> Result<Vec<PlayerMessage>, PlayerConnectionError> {
+    let obs = context.market.service.get_public_order_book().await;
+
+    let (tx_back, rx) = oneshot::channel();
+    context
+        .game
+        .tx
+        .send(GameMessage::GetReadiness { tx_back })
+        .await
+        .map_err(|_| PlayerConnectionError::InternalConnectionError)?;
+    let readiness = rx
+        .await
+        .map_err(|_| PlayerConnectionError::InternalConnectionError)?;
+
+    let (tx_back, rx) = oneshot::channel();
+    context
+        .game
+        .tx
+        .send(GameMessage::GetResults { tx_back })
+        .await
+        .map_err(|_| PlayerConnectionError::InternalConnectionError)?;
+    let results = rx
+        .await
+        .map_err(|_| PlayerConnectionError::InternalConnectionError)?;
+
+    Ok(vec![
+        PlayerMessage::GameDuration {
+            last_period: context.game.last_delivery_period,
+        },
+        PlayerMessage::StackConfig {
+            config: (&context.game.stack).into(),
+        },
+        PlayerMessage::OrderBookSnapshot {
+            bids: obs.bids,
+            offers: obs.offers,
+        },
+        PlayerMessage::ReadinessStatus { readiness },
+        PlayerMessage::PlayersScores {
+            scores: results.periods,
+        },
+    ])
+}
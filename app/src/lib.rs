@@ -5,6 +5,7 @@ pub mod infra;
 pub mod market;
 pub mod plants;
 pub mod player;
+pub mod tournament;
 pub mod utils;
 
 pub use infra::api::{build_router, state::new_api_state};
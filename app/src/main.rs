@@ -30,7 +30,10 @@ async fn start_server() {
     let state = new_api_state(&config);
     let app = build_router(state, config);
 
-    axum::serve(listener, app.into_make_service())
-        .await
-        .unwrap();
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await
+    .unwrap();
 }
@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use rand::random_range;
+use rand::Rng;
 use tokio::{
     sync::mpsc::{Receiver, channel},
     time::sleep,
@@ -11,13 +11,17 @@ use crate::{
     constants,
     market::{Direction, Market, MarketContext, MarketState, order_book::OrderRequest},
     player::{PlayerId, PlayerMessage},
-    utils::units::{Energy, EnergyCost},
+    utils::{
+        rng::GameRng,
+        units::{Energy, EnergyCost},
+    },
 };
 
 pub struct ExtremeOrdersBot<MS: Market> {
     bot_id: PlayerId,
     market: MarketContext<MS>,
     cancellation_token: CancellationToken,
+    rng: GameRng,
     _rx: Receiver<PlayerMessage>,
 }
 
@@ -25,20 +29,22 @@ impl<MS: Market> ExtremeOrdersBot<MS> {
     fn new(
         market: MarketContext<MS>,
         cancellation_token: CancellationToken,
+        mut rng: GameRng,
     ) -> ExtremeOrdersBot<MS> {
-        let bot_id = PlayerId::default();
+        let bot_id = PlayerId::from_rng(&mut rng);
         let (_, rx) = channel(16);
 
         ExtremeOrdersBot {
             bot_id,
             market,
             cancellation_token,
+            rng,
             _rx: rx,
         }
     }
 
-    pub fn start(market: MarketContext<MS>, cancellation_token: CancellationToken) {
-        let mut bot = ExtremeOrdersBot::new(market, cancellation_token);
+    pub fn start(market: MarketContext<MS>, cancellation_token: CancellationToken, rng: GameRng) {
+        let mut bot = ExtremeOrdersBot::new(market, cancellation_token, rng);
 
         tokio::spawn(async move {
             bot.run().await;
@@ -77,7 +83,7 @@ impl<MS: Market> ExtremeOrdersBot<MS> {
 
     async fn add_orders(&mut self) {
         self.wait_for_market_to_open().await;
-        sleep(Duration::from_secs_f32(random_range(2.0..10.0))).await;
+        sleep(Duration::from_secs_f32(self.rng.random_range(2.0..10.0))).await;
 
         self.market
             .service
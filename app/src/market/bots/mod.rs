@@ -1,27 +1,46 @@
 use extreme_orders::ExtremeOrdersBot;
+use serde::{Deserialize, Serialize};
 use tokio_util::sync::CancellationToken;
 use tutorial_initial_orders::TutorialInitialOrdersBot;
 
 use crate::{
     game::GameContext,
     market::{Market, MarketContext},
+    utils::rng::{GameRng, derived_rng},
 };
 
 pub mod extreme_orders;
 pub mod tutorial_initial_orders;
 
-pub async fn start_bots<MS: Market>(
-    market: MarketContext<MS>,
-    cancellation_token: CancellationToken,
-) {
-    ExtremeOrdersBot::start(market.clone(), cancellation_token);
+/// Bots that can take part in a game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BotKind {
+    /// Always provides extreme buy and sell offers, so that players can always trade
+    ExtremeOrders,
+    /// Posts the orders of the tutorial's first delivery periods
+    TutorialInitialOrders,
 }
 
-pub async fn start_bots_tutorial<MS: Market>(
+/// Start the bots of a game, their ids and timings being drawn from `rng`.
+pub async fn start_bots<MS: Market>(
+    bots: &[BotKind],
     game: GameContext,
     market: MarketContext<MS>,
     cancellation_token: CancellationToken,
+    mut rng: GameRng,
 ) {
-    TutorialInitialOrdersBot::start(game, market.clone(), cancellation_token.clone());
-    ExtremeOrdersBot::start(market.clone(), cancellation_token);
+    for bot in bots {
+        let bot_rng = derived_rng(&mut rng);
+        match bot {
+            BotKind::ExtremeOrders => {
+                ExtremeOrdersBot::start(market.clone(), cancellation_token.clone(), bot_rng)
+            }
+            BotKind::TutorialInitialOrders => TutorialInitialOrdersBot::start(
+                game.clone(),
+                market.clone(),
+                cancellation_token.clone(),
+                bot_rng,
+            ),
+        }
+    }
 }
@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use rand::random_range;
+use rand::Rng;
 use tokio::{
     sync::mpsc::{Receiver, channel},
     time::sleep,
@@ -11,7 +11,10 @@ use crate::{
     game::{GameContext, GameState, delivery_period::DeliveryPeriodId},
     market::{Direction, Market, MarketContext, MarketState, order_book::OrderRequest},
     player::{PlayerId, PlayerMessage},
-    utils::units::{Energy, EnergyCost},
+    utils::{
+        rng::GameRng,
+        units::{Energy, EnergyCost},
+    },
 };
 
 pub struct TutorialInitialOrdersBot<MS: Market> {
@@ -19,6 +22,7 @@ pub struct TutorialInitialOrdersBot<MS: Market> {
     market: MarketContext<MS>,
     game: GameContext,
     cancellation_token: CancellationToken,
+    rng: GameRng,
     _rx: Receiver<PlayerMessage>,
 }
 
@@ -27,8 +31,9 @@ impl<MS: Market> TutorialInitialOrdersBot<MS> {
         game: GameContext,
         market: MarketContext<MS>,
         cancellation_token: CancellationToken,
+        mut rng: GameRng,
     ) -> TutorialInitialOrdersBot<MS> {
-        let bot_id = PlayerId::default();
+        let bot_id = PlayerId::from_rng(&mut rng);
         let (_, rx) = channel(16);
 
         TutorialInitialOrdersBot {
@@ -36,6 +41,7 @@ impl<MS: Market> TutorialInitialOrdersBot<MS> {
             game,
             market,
             cancellation_token,
+            rng,
             _rx: rx,
         }
     }
@@ -44,8 +50,9 @@ impl<MS: Market> TutorialInitialOrdersBot<MS> {
         game: GameContext,
         market: MarketContext<MS>,
         cancellation_token: CancellationToken,
+        rng: GameRng,
     ) {
-        let mut bot = TutorialInitialOrdersBot::new(game, market, cancellation_token);
+        let mut bot = TutorialInitialOrdersBot::new(game, market, cancellation_token, rng);
 
         tokio::spawn(async move {
             bot.run().await;
@@ -89,7 +96,7 @@ impl<MS: Market> TutorialInitialOrdersBot<MS> {
             return;
         };
 
-        sleep(Duration::from_secs_f32(random_range(2.0..10.0))).await;
+        sleep(Duration::from_secs_f32(self.rng.random_range(2.0..10.0))).await;
 
         if period == DeliveryPeriodId::from(1) {
             self.market
@@ -1,14 +1,19 @@
 use std::{collections::HashMap, fmt::Debug};
 
 use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
 use tokio::sync::{mpsc, oneshot, watch};
 use tokio_util::sync::CancellationToken;
 
 use crate::{
-    game::{GameId, delivery_period::DeliveryPeriodId},
+    game::{
+        GameId,
+        delivery_period::DeliveryPeriodId,
+        infra::event_log::{GameLog, GameLogEvent},
+    },
     market::{
         MarketContext, MarketState, OBS, OrderRepr,
-        order_book::{OrderBook, OrderRequest, Trade, TradeLeg},
+        order_book::{Order, OrderBook, OrderRequest, Trade, TradeLeg},
     },
     player::{PlayerConnections, PlayerId, PlayerMessage},
 };
@@ -21,6 +26,9 @@ pub enum MarketMessage {
         player_id: PlayerId,
         tx_back: oneshot::Sender<(Vec<TradeLeg>, OBS)>,
     },
+    GetPublicOrderBook {
+        tx_back: oneshot::Sender<OBS>,
+    },
     OpenMarket(DeliveryPeriodId),
     CloseMarket {
         period_id: DeliveryPeriodId,
@@ -30,6 +38,22 @@ pub enum MarketMessage {
     OrderDeletionRequest {
         order_id: String,
     },
+    GetPersistedState {
+        tx_back: oneshot::Sender<PersistedMarket>,
+    },
+    SetPaused(bool),
+    RemovePlayer(PlayerId),
+}
+
+/// State of a market saved between two delivery periods, i.e. with an empty order book, to
+/// restore it after a server restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistedMarket {
+    pub delivery_period: DeliveryPeriodId,
+    pub players: Vec<PlayerId>,
+    pub past_trades: HashMap<DeliveryPeriodId, Vec<Trade>>,
+    #[serde(default)]
+    pub closing_orders: HashMap<DeliveryPeriodId, Vec<Order>>,
 }
 
 pub struct MarketActor<PC: PlayerConnections> {
@@ -37,13 +61,20 @@ pub struct MarketActor<PC: PlayerConnections> {
     delivery_period: DeliveryPeriodId,
     state: MarketState,
     state_sender: watch::Sender<MarketState>,
+    /// Whether the game is paused by its host, freezing the order book
+    paused: bool,
     rx: mpsc::Receiver<MarketMessage>,
     tx: mpsc::Sender<MarketMessage>,
     order_book: OrderBook,
     players: Vec<PlayerId>,
+    /// Players kicked from the game, whose orders are ignored
+    removed_players: Vec<PlayerId>,
     players_connections: PC,
     past_trades: HashMap<DeliveryPeriodId, Vec<Trade>>,
+    /// Orders left unmatched in the order book when the market closed
+    closing_orders: HashMap<DeliveryPeriodId, Vec<Order>>,
     cancellation_token: CancellationToken,
+    log: Option<GameLog>,
 }
 
 impl<PC: PlayerConnections> MarketActor<PC> {
@@ -62,19 +93,24 @@ impl<PC: PlayerConnections> MarketActor<PC> {
             state,
             delivery_period,
             state_sender: state_tx,
+            paused: false,
             rx,
             tx,
             players: Vec::new(),
+            removed_players: Vec::new(),
             players_connections,
             order_book: OrderBook::new(),
             past_trades: HashMap::new(),
+            closing_orders: HashMap::new(),
             cancellation_token,
+            log: None,
         }
     }
 
     pub fn start(
         game_id: &GameId,
         players_connections: PC,
+        log: Option<GameLog>,
         cancellation_token: CancellationToken,
     ) -> MarketContext<MarketService> {
         let mut market = MarketActor::new(
@@ -84,6 +120,34 @@ impl<PC: PlayerConnections> MarketActor<PC> {
             players_connections,
             cancellation_token,
         );
+        market.log = log;
+        let context = market.get_context();
+
+        tokio::spawn(async move {
+            market.process().await;
+        });
+        context
+    }
+
+    /// Start a closed market from its persisted state, e.g. after a server restart.
+    pub fn restore(
+        game_id: &GameId,
+        persisted: PersistedMarket,
+        players_connections: PC,
+        log: Option<GameLog>,
+        cancellation_token: CancellationToken,
+    ) -> MarketContext<MarketService> {
+        let mut market = MarketActor::new(
+            game_id.clone(),
+            MarketState::Closed,
+            persisted.delivery_period,
+            players_connections,
+            cancellation_token,
+        );
+        market.log = log;
+        market.players = persisted.players;
+        market.past_trades = persisted.past_trades;
+        market.closing_orders = persisted.closing_orders;
         let context = market.get_context();
 
         tokio::spawn(async move {
@@ -117,16 +181,56 @@ impl<PC: PlayerConnections> MarketActor<PC> {
     async fn process_message(&mut self, message: MarketMessage) {
         match (&self.state, message) {
             (_, MarketMessage::GetMarketSnapshot { player_id, tx_back }) => {
-                if !self.players.contains(&player_id) {
+                if !self.players.contains(&player_id) && !self.removed_players.contains(&player_id)
+                {
                     self.players.push(player_id.clone());
                 }
-                let _ = tx_back.send((self.player_trades(&player_id), self.player_obs(&player_id)));
+                let _ = tx_back.send((self.player_trades(&player_id), self.obs(Some(&player_id))));
+            }
+            (_, MarketMessage::GetPublicOrderBook { tx_back }) => {
+                let _ = tx_back.send(self.obs(None));
+            }
+            (_, MarketMessage::GetPersistedState { tx_back }) => {
+                let _ = tx_back.send(PersistedMarket {
+                    delivery_period: self.delivery_period,
+                    players: self.players.clone(),
+                    past_trades: self.past_trades.clone(),
+                    closing_orders: self.closing_orders.clone(),
+                });
+            }
+            (_, MarketMessage::SetPaused(paused)) => {
+                self.paused = paused;
+            }
+            (_, MarketMessage::RemovePlayer(player_id)) => {
+                self.players.retain(|player| *player != player_id);
+                self.order_book.remove_player_orders(&player_id);
+                self.removed_players.push(player_id);
+                self.send_order_book_snapshot_to_all().await;
+            }
+            (MarketState::Open, MarketMessage::OrderRequest(request))
+                if self.removed_players.contains(&request.owner) =>
+            {
+                tracing::warn!(
+                    "Order request from removed player {:?} ignored",
+                    request.owner
+                );
+            }
+            (MarketState::Open, MarketMessage::OrderRequest(_))
+            | (MarketState::Open, MarketMessage::OrderDeletionRequest { order_id: _ })
+                if self.paused =>
+            {
+                tracing::warn!(
+                    "Market paused, cannot process new order request, or deletion request"
+                );
             }
             (MarketState::Open, MarketMessage::OrderRequest(request)) => {
                 self.process_order_request(request).await
             }
             (MarketState::Open, MarketMessage::OrderDeletionRequest { order_id }) => {
                 tracing::info!("Order deletion request for order: {order_id:?}");
+                self.record(GameLogEvent::OrderDeleted {
+                    order_id: order_id.clone(),
+                });
                 self.order_book.remove_offer(order_id);
                 self.send_order_book_snapshot_to_all().await;
             }
@@ -171,9 +275,18 @@ impl<PC: PlayerConnections> MarketActor<PC> {
             "Closing market for period"
         );
 
-        // Drain trades from order book and store them
+        // Drain trades from order book and store them, along with the unmatched orders
+        let snapshot = self.order_book.snapshot();
+        let closing_orders = snapshot
+            .bids
+            .iter()
+            .map(|bid| bid.0.clone())
+            .chain(snapshot.offers.iter().map(|offer| offer.0.clone()))
+            .collect();
+        self.closing_orders.insert(period_id, closing_orders);
         let trades = self.order_book.drain();
         self.past_trades.insert(period_id, trades.clone());
+        self.record(GameLogEvent::MarketClosed { period: period_id });
 
         // Update market state
         self.state = MarketState::Closed;
@@ -200,6 +313,12 @@ impl<PC: PlayerConnections> MarketActor<PC> {
         self.send_empty_trade_list_to_all().await;
     }
 
+    fn record(&self, event: GameLogEvent) {
+        if let Some(log) = &self.log {
+            log.record(event);
+        }
+    }
+
     async fn send_order_book_snapshot_to_all(&self) {
         join_all(
             self.players
@@ -208,6 +327,16 @@ impl<PC: PlayerConnections> MarketActor<PC> {
                 .collect::<Vec<_>>(),
         )
         .await;
+        let obs = self.obs(None);
+        self.players_connections
+            .send_to_spectators(
+                &self.game_id,
+                PlayerMessage::OrderBookSnapshot {
+                    bids: obs.bids,
+                    offers: obs.offers,
+                },
+            )
+            .await;
     }
 
     async fn send_empty_trade_list_to_all(&self) {
@@ -219,24 +348,25 @@ impl<PC: PlayerConnections> MarketActor<PC> {
             .await;
     }
 
-    fn player_obs(&self, player_id: &PlayerId) -> OBS {
+    /// Order book as seen by a player, or by spectators if no player is given.
+    fn obs(&self, player_id: Option<&PlayerId>) -> OBS {
         let snapshot = self.order_book.snapshot();
         OBS {
             bids: snapshot
                 .bids
                 .iter()
-                .map(|bid| OrderRepr::from_bid(bid, Some(player_id)))
+                .map(|bid| OrderRepr::from_bid(bid, player_id))
                 .collect(),
             offers: snapshot
                 .offers
                 .iter()
-                .map(|offer| OrderRepr::from_offer(offer, Some(player_id)))
+                .map(|offer| OrderRepr::from_offer(offer, player_id))
                 .collect(),
         }
     }
 
     async fn send_order_book_snapshot_to_player(&self, player_id: &PlayerId) {
-        let obs = self.player_obs(player_id);
+        let obs = self.obs(Some(player_id));
         let message = PlayerMessage::OrderBookSnapshot {
             bids: obs.bids,
             offers: obs.offers,
@@ -273,8 +403,17 @@ impl<PC: PlayerConnections> MarketActor<PC> {
 
     #[tracing::instrument(name = "ActorMarket::process_order_request", skip(self))]
     async fn process_order_request(&mut self, request: OrderRequest) {
-        let trades = self.order_book.register_order_request(request);
+        let order = Order::from(request);
+        self.record(GameLogEvent::OrderPlaced {
+            order: order.clone(),
+        });
+        let trades = self.order_book.register_order(order);
         tracing::info!("New trades: {trades:?}");
+        for trade in trades.iter() {
+            self.record(GameLogEvent::TradeExecuted {
+                trade: trade.clone(),
+            });
+        }
 
         // Update all players with new order book state
         self.send_order_book_snapshot_to_all().await;
@@ -295,7 +434,11 @@ mod tests {
     use tokio_util::sync::CancellationToken;
 
     use crate::{
-        game::{GameId, delivery_period::DeliveryPeriodId},
+        game::{
+            GameId,
+            delivery_period::DeliveryPeriodId,
+            infra::event_log::{GameLog, GameLogEntry, GameLogEvent},
+        },
         market::{Direction, MarketState, order_book::OrderRequest},
         player::{PlayerConnections, PlayerId},
         utils::units::{Energy, EnergyCost},
@@ -333,6 +476,7 @@ mod tests {
         async fn send_to_player(&self, _game: &GameId, player: &PlayerId, message: PlayerMessage) {
             let _ = self.tx_send_to_player.send((player.clone(), message)).await;
         }
+        async fn send_to_spectators(&self, _game: &GameId, _message: PlayerMessage) {}
     }
 
     fn start_market_actor(
@@ -396,6 +540,54 @@ mod tests {
         assert!(bids.first().unwrap().owned);
     }
 
+    #[tokio::test]
+    async fn test_orders_and_trades_are_logged() {
+        let (conn_tx, ..) = MockPlayerConnections::new();
+        let (log, mut log_rx) = GameLog::channel();
+        let mut market = MarketActor::new(
+            GameId::default(),
+            MarketState::Open,
+            DeliveryPeriodId::from(0),
+            conn_tx,
+            CancellationToken::new(),
+        );
+        market.log = Some(log);
+        let tx = market.tx.clone();
+        tokio::spawn(async move {
+            market.process().await;
+        });
+        let (buyer, seller) = (PlayerId::from("buyer"), PlayerId::from("seller"));
+
+        for (direction, owner) in [(Direction::Buy, &buyer), (Direction::Sell, &seller)] {
+            tx.send(MarketMessage::OrderRequest(OrderRequest {
+                direction,
+                price: EnergyCost::from(50),
+                volume: Energy::from(10),
+                owner: owner.clone(),
+            }))
+            .await
+            .unwrap();
+        }
+
+        let Some(GameLogEntry {
+            event: GameLogEvent::OrderPlaced { order },
+            ..
+        }) = log_rx.recv().await
+        else {
+            unreachable!("Should have logged the bid")
+        };
+        assert_eq!(order.owner, buyer);
+        assert!(matches!(
+            log_rx.recv().await.map(|entry| entry.event),
+            Some(GameLogEvent::OrderPlaced { order }) if order.owner == seller
+        ));
+        assert!(matches!(
+            log_rx.recv().await.map(|entry| entry.event),
+            Some(GameLogEvent::TradeExecuted { trade })
+                if trade.buyer == buyer && trade.seller == seller
+        ));
+    }
+
     #[tokio::test]
     async fn test_process_delete_order() {
         let game_id = GameId::default();
@@ -529,6 +721,114 @@ mod tests {
         };
     }
 
+    #[tokio::test]
+    async fn test_paused_market_does_not_process_order_request() {
+        let game_id = GameId::default();
+        let (conn_tx, mut conn_rx, ..) = MockPlayerConnections::new();
+        let token = CancellationToken::new();
+        let mut market = MarketActor::new(
+            game_id,
+            MarketState::Open,
+            DeliveryPeriodId::from(1),
+            conn_tx,
+            token,
+        );
+        let tx = market.tx.clone();
+        tokio::spawn(async move {
+            market.process().await;
+        });
+        let player_id = register_player(tx.clone()).await;
+        let order = OrderRequest {
+            direction: Direction::Buy,
+            volume: Energy::from(10),
+            price: EnergyCost::from(50),
+            owner: player_id.to_owned(),
+        };
+
+        // Pause the market and send an OrderRequest
+        let _ = tx.send(MarketMessage::SetPaused(true)).await;
+        let _ = tx.send(MarketMessage::OrderRequest(order.clone())).await;
+
+        // We should not receive an order book snapshot
+        tokio::select! {
+        _ = conn_rx.recv() => {
+            unreachable!("Should not have received a message");
+        }
+        _ = tokio::time::sleep(Duration::from_micros(1)) => {}
+        };
+
+        // Once resumed, the order is processed
+        let _ = tx.send(MarketMessage::SetPaused(false)).await;
+        let _ = tx.send(MarketMessage::OrderRequest(order)).await;
+        let Some((_, PlayerMessage::OrderBookSnapshot { .. })) = conn_rx.recv().await else {
+            unreachable!("Should have received an order book snapshot");
+        };
+    }
+
+    #[tokio::test]
+    async fn test_removed_player_loses_their_orders_and_cannot_trade() {
+        let game_id = GameId::default();
+        let (conn_tx, mut conn_rx, ..) = MockPlayerConnections::new();
+        let (tx, _) = start_market_actor(&game_id, conn_tx);
+        let removed = register_player(tx.clone()).await;
+        let other = register_player(tx.clone()).await;
+        let order = OrderRequest {
+            direction: Direction::Buy,
+            volume: Energy::from(10),
+            price: EnergyCost::from(50),
+            owner: removed.clone(),
+        };
+        let _ = tx.send(MarketMessage::OrderRequest(order.clone())).await;
+        for _ in 0..2 {
+            let _ = conn_rx.recv().await;
+        }
+
+        let _ = tx.send(MarketMessage::RemovePlayer(removed.clone())).await;
+
+        // Only the remaining player is notified, and the removed player's bid is gone
+        let Some((target, PlayerMessage::OrderBookSnapshot { bids, .. })) = conn_rx.recv().await
+        else {
+            unreachable!("Should have received an order book snapshot");
+        };
+        assert_eq!(target, other);
+        assert!(bids.is_empty());
+
+        // Further orders from the removed player are ignored
+        let _ = tx.send(MarketMessage::OrderRequest(order)).await;
+        tokio::select! {
+        _ = conn_rx.recv() => {
+            unreachable!("Should not have received a message");
+        }
+        _ = tokio::time::sleep(Duration::from_micros(1)) => {}
+        };
+    }
+
+    #[tokio::test]
+    async fn test_public_order_book_does_not_register_caller() {
+        let game_id = GameId::default();
+        let (conn_tx, ..) = MockPlayerConnections::new();
+        let (tx, _) = start_market_actor(&game_id, conn_tx);
+        let player_id = register_player(tx.clone()).await;
+        let _ = tx
+            .send(MarketMessage::OrderRequest(OrderRequest {
+                direction: Direction::Buy,
+                volume: Energy::from(10),
+                price: EnergyCost::from(50),
+                owner: player_id.clone(),
+            }))
+            .await;
+
+        let (tx_back, rx) = oneshot::channel();
+        let _ = tx.send(MarketMessage::GetPublicOrderBook { tx_back }).await;
+        let obs = rx.await.unwrap();
+        assert_eq!(obs.bids.len(), 1);
+        assert!(!obs.bids[0].owned);
+
+        let (tx_back, rx) = oneshot::channel();
+        let _ = tx.send(MarketMessage::GetPersistedState { tx_back }).await;
+        assert_eq!(rx.await.unwrap().players, vec![player_id]);
+    }
+
     #[tokio::test]
     async fn test_close_market_and_reopen() {
         let game_id = GameId::default();
@@ -1,5 +1,5 @@
 pub mod actor;
 pub mod service;
 
-pub use actor::{MarketActor, MarketMessage};
+pub use actor::{MarketActor, MarketMessage, PersistedMarket};
 pub use service::MarketService;
@@ -6,7 +6,7 @@ use tokio::sync::{mpsc, oneshot};
 use crate::{
     game::delivery_period::DeliveryPeriodId,
     market::{
-        Market, OBS,
+        Market, OBS, PersistedMarket,
         order_book::{OrderRequest, Trade, TradeLeg},
     },
     player::PlayerId,
@@ -66,6 +66,20 @@ impl Market for MarketService {
         ))
     }
 
+    async fn get_public_order_book(&self) -> OBS {
+        let (tx, rx) = oneshot::channel();
+
+        let _ = self
+            .tx
+            .send(MarketMessage::GetPublicOrderBook { tx_back: tx })
+            .await;
+
+        rx.await.unwrap_or(OBS {
+            bids: Vec::new(),
+            offers: Vec::new(),
+        })
+    }
+
     async fn new_order(&self, request: OrderRequest) {
         let _ = self.tx.send(MarketMessage::OrderRequest(request)).await;
     }
@@ -76,6 +90,24 @@ impl Market for MarketService {
             .send(MarketMessage::OrderDeletionRequest { order_id })
             .await;
     }
+
+    async fn set_paused(&self, paused: bool) {
+        let _ = self.tx.send(MarketMessage::SetPaused(paused)).await;
+    }
+
+    async fn remove_player(&self, player: PlayerId) {
+        let _ = self.tx.send(MarketMessage::RemovePlayer(player)).await;
+    }
+
+    async fn get_persisted_state(&self) -> Option<PersistedMarket> {
+        let (tx_back, rx) = oneshot::channel();
+        let _ = self
+            .tx
+            .send(MarketMessage::GetPersistedState { tx_back })
+            .await;
+
+        rx.await.ok()
+    }
 }
 
 #[cfg(test)]
@@ -95,10 +127,17 @@ mockall::mock! {
             player: PlayerId,
         ) -> impl Future<Output = (Vec<TradeLeg>, OBS)> + Send;
 
+        fn get_public_order_book(&self) -> impl Future<Output = OBS> + Send;
+
         fn new_order(&self, request: OrderRequest) -> impl Future<Output = ()> + Send;
 
         fn delete_order(&self, order_id: String) -> impl Future<Output = ()> + Send;
 
+        fn set_paused(&self, paused: bool) -> impl Future<Output = ()> + Send;
+
+        fn remove_player(&self, player: PlayerId) -> impl Future<Output = ()> + Send;
+
+        fn get_persisted_state(&self) -> impl Future<Output = Option<PersistedMarket>> + Send;
     }
 
     impl Clone for MarketService {
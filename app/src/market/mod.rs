@@ -16,7 +16,7 @@ pub mod bots;
 pub mod infra;
 pub mod order_book;
 
-pub use infra::{MarketActor, MarketMessage, MarketService};
+pub use infra::{MarketActor, MarketMessage, MarketService, PersistedMarket};
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub enum Direction {
@@ -47,6 +47,10 @@ pub trait Market: Clone + Send + Sync + 'static {
         player: PlayerId,
     ) -> impl Future<Output = (Vec<TradeLeg>, OBS)> + Send;
 
+    /// Get the order book without owned orders, e.g. for spectators. Unlike
+    /// [Market::get_market_snapshot], the caller is not registered to the market.
+    fn get_public_order_book(&self) -> impl Future<Output = OBS> + Send;
+
     /// Post a new order for the current delivery period. If the market is closed the request is
     /// ignored.
     fn new_order(&self, request: OrderRequest) -> impl Future<Output = ()> + Send;
@@ -54,6 +58,17 @@ pub trait Market: Clone + Send + Sync + 'static {
     /// Delete an order from the market. Silently fails if the order does not exist or if the market
     /// is closed.
     fn delete_order(&self, order_id: String) -> impl Future<Output = ()> + Send;
+
+    /// Pause or resume the market, new orders and order deletions are ignored while the market is
+    /// paused.
+    fn set_paused(&self, paused: bool) -> impl Future<Output = ()> + Send;
+
+    /// Remove a player from the market along with their orders, their new orders being ignored.
+    fn remove_player(&self, player: PlayerId) -> impl Future<Output = ()> + Send;
+
+    /// Get the state of the market to persist between two delivery periods, `None` if the market
+    /// is no longer running.
+    fn get_persisted_state(&self) -> impl Future<Output = Option<PersistedMarket>> + Send;
 }
 
 #[derive(Debug, Clone, Serialize, PartialEq)]
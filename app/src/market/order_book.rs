@@ -11,7 +11,7 @@ use crate::{
 
 use super::Direction;
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Trade {
     pub buyer: PlayerId,
     pub seller: PlayerId,
@@ -65,7 +65,7 @@ pub struct OrderRequest {
     pub owner: PlayerId,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Order {
     pub id: String,
     pub direction: Direction,
@@ -190,7 +190,11 @@ impl OrderBook {
     }
 
     pub fn register_order_request(&mut self, order_request: OrderRequest) -> Vec<Trade> {
-        let order = Order::from(order_request);
+        self.register_order(Order::from(order_request))
+    }
+
+    /// Insert an order with a known id and timestamp, and return the trades it matched.
+    pub fn register_order(&mut self, order: Order) -> Vec<Trade> {
         let trades = match order.direction {
             Direction::Buy => self.insert_bid(order),
             Direction::Sell => self.insert_offer(order),
@@ -206,6 +210,12 @@ impl OrderBook {
         self.offers.retain(|offer| offer.0.id != order_id);
     }
 
+    /// Remove all the orders of a player, e.g. once they have been kicked from the game.
+    pub fn remove_player_orders(&mut self, player: &PlayerId) {
+        self.bids.retain(|bid| bid.0.owner != *player);
+        self.offers.retain(|offer| offer.0.owner != *player);
+    }
+
     pub fn snapshot(&self) -> OrderBookSnapshot<'_> {
         OrderBookSnapshot {
             bids: self.bids.as_slice(),
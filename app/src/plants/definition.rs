@@ -0,0 +1,320 @@
+use std::convert::Infallible;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    forecast::{ForecastValue, signal::WeatherSignal},
+    game::delivery_period::DeliveryPeriodId,
+    utils::{
+        rng::{GameRng, derived_rng},
+        units::{CarbonPrice, Energy, EnergyCost, Power},
+    },
+};
+
+use super::{
+    PlantCategory, PowerPlant,
+    technologies::{
+        battery::Battery, consumers::Consumers, gas_plant::GasPlant, nuclear::NuclearPlant,
+        renewable::RenewablePlant,
+    },
+};
+
+/// Technologies of power plants along with their parameters, the single list of technologies
+/// from which plant definitions, game requests and players' views are derived. They only differ by
+/// the forecasts `F` of renewable plants and consumers.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, PartialOrd)]
+#[serde(tag = "technology")]
+pub enum PlantTechnology<F> {
+    Battery {
+        capacity: Energy,
+    },
+    GasPlant {
+        energy_cost: EnergyCost,
+        capacity: Power,
+    },
+    Nuclear {
+        energy_cost: EnergyCost,
+        capacity: Power,
+    },
+    RenewablePlant {
+        #[serde(flatten)]
+        forecasts: F,
+        forecasts_range: usize,
+    },
+    Consumers {
+        revenues: EnergyCost,
+        #[serde(flatten)]
+        forecasts: F,
+        forecasts_range: usize,
+    },
+}
+
+/// Explicit forecasts of a plant.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, PartialOrd)]
+pub struct PlantForecasts {
+    pub forecasts: Vec<ForecastValue>,
+}
+
+impl FromIterator<ForecastValue> for PlantForecasts {
+    fn from_iter<T: IntoIterator<Item = ForecastValue>>(iter: T) -> Self {
+        PlantForecasts {
+            forecasts: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl From<Vec<ForecastValue>> for PlantForecasts {
+    fn from(forecasts: Vec<ForecastValue>) -> Self {
+        PlantForecasts { forecasts }
+    }
+}
+
+/// Serialisable definition of a power plant, i.e. its technology and parameters. This is the
+/// single entrypoint to create plants, from a game configuration or a JSON file.
+pub type PlantDefinition = PlantTechnology<PlantForecasts>;
+
+impl<F> PlantTechnology<F> {
+    /// Same technology and parameters, with forecasts converted by `convert` from the plant's
+    /// forecasts and their range.
+    pub fn try_map_forecasts<G, E>(
+        self,
+        convert: impl FnOnce(F, usize) -> Result<G, E>,
+    ) -> Result<PlantTechnology<G>, E> {
+        Ok(match self {
+            PlantTechnology::Battery { capacity } => PlantTechnology::Battery { capacity },
+            PlantTechnology::GasPlant {
+                energy_cost,
+                capacity,
+            } => PlantTechnology::GasPlant {
+                energy_cost,
+                capacity,
+            },
+            PlantTechnology::Nuclear {
+                energy_cost,
+                capacity,
+            } => PlantTechnology::Nuclear {
+                energy_cost,
+                capacity,
+            },
+            PlantTechnology::RenewablePlant {
+                forecasts,
+                forecasts_range,
+            } => PlantTechnology::RenewablePlant {
+                forecasts: convert(forecasts, forecasts_range)?,
+                forecasts_range,
+            },
+            PlantTechnology::Consumers {
+                revenues,
+                forecasts,
+                forecasts_range,
+            } => PlantTechnology::Consumers {
+                revenues,
+                forecasts: convert(forecasts, forecasts_range)?,
+                forecasts_range,
+            },
+        })
+    }
+
+    /// Same as [PlantTechnology::try_map_forecasts] for a conversion that cannot fail.
+    pub fn map_forecasts<G>(self, convert: impl FnOnce(F, usize) -> G) -> PlantTechnology<G> {
+        let Ok(technology) = self.try_map_forecasts(|forecasts, forecasts_range| {
+            Ok::<_, Infallible>(convert(forecasts, forecasts_range))
+        });
+        technology
+    }
+
+    pub fn category(&self) -> PlantCategory {
+        match self {
+            PlantTechnology::Battery { .. } => PlantCategory::Battery,
+            PlantTechnology::GasPlant { .. } => PlantCategory::GasPlant,
+            PlantTechnology::Nuclear { .. } => PlantCategory::Nuclear,
+            PlantTechnology::RenewablePlant { .. } => PlantCategory::RenewablePlant,
+            PlantTechnology::Consumers { .. } => PlantCategory::Consumers,
+        }
+    }
+}
+
+/// Game-wide parameters of the plants built from definitions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlantEnvironment {
+    pub carbon_price: CarbonPrice,
+    pub weather: Option<WeatherSignal>,
+}
+
+impl PlantDefinition {
+    /// Create the plant described by the definition, plants based on forecasts draw their
+    /// realisations from a generator derived from `rng`.
+    pub fn build(
+        &self,
+        environment: &PlantEnvironment,
+        rng: &mut GameRng,
+    ) -> Box<dyn PowerPlant + Send + Sync> {
+        self.build_starting_at(environment, DeliveryPeriodId::from(1), rng)
+    }
+
+    /// Create the plant described by the definition, for a plant whose first delivery period is
+    /// `first_period` (e.g. a plant built during the game).
+    pub fn build_starting_at(
+        &self,
+        environment: &PlantEnvironment,
+        first_period: DeliveryPeriodId,
+        rng: &mut GameRng,
+    ) -> Box<dyn PowerPlant + Send + Sync> {
+        let carbon_price = environment.carbon_price;
+        let weather = environment.weather.as_ref();
+        match self {
+            PlantDefinition::Battery { capacity } => {
+                Box::new(Battery::new(*capacity, Energy::from(0)))
+            }
+            PlantDefinition::GasPlant {
+                energy_cost,
+                capacity,
+            } => Box::new(GasPlant::new(*energy_cost, *capacity).with_carbon_price(carbon_price)),
+            PlantDefinition::Nuclear {
+                energy_cost,
+                capacity,
+            } => {
+                Box::new(NuclearPlant::new(*capacity, *energy_cost).with_carbon_price(carbon_price))
+            }
+            PlantDefinition::RenewablePlant {
+                forecasts,
+                forecasts_range,
+            } => {
+                let plant = RenewablePlant::starting_at(
+                    forecasts.forecasts.clone(),
+                    *forecasts_range,
+                    first_period,
+                    derived_rng(rng),
+                );
+                match weather {
+                    Some(weather) => Box::new(plant.with_signal(weather.renewable.clone())),
+                    None => Box::new(plant),
+                }
+            }
+            PlantDefinition::Consumers {
+                revenues,
+                forecasts,
+                forecasts_range,
+            } => {
+                let consumers = Consumers::starting_at(
+                    *revenues,
+                    forecasts.forecasts.clone(),
+                    *forecasts_range,
+                    first_period,
+                    derived_rng(rng),
+                );
+                match weather {
+                    Some(weather) => Box::new(consumers.with_signal(weather.consumers.clone())),
+                    None => Box::new(consumers),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        forecast::ForecastValue,
+        plants::PowerPlantPublicRepr,
+        utils::{
+            rng::seeded_rng,
+            units::{CarbonPrice, Energy, EnergyCost, Power},
+        },
+    };
+
+    use super::{PlantDefinition, PlantEnvironment};
+
+    fn definitions() -> Vec<PlantDefinition> {
+        vec![
+            PlantDefinition::Battery {
+                capacity: Energy::from(100),
+            },
+            PlantDefinition::GasPlant {
+                energy_cost: EnergyCost::from(80),
+                capacity: Power::from(500),
+            },
+            PlantDefinition::Nuclear {
+                energy_cost: EnergyCost::from(35),
+                capacity: Power::from(1000),
+            },
+            PlantDefinition::RenewablePlant {
+                forecasts: vec![ForecastValue {
+                    value: 100,
+                    deviation: 0,
+                }]
+                .into(),
+                forecasts_range: 2,
+            },
+            PlantDefinition::Consumers {
+                revenues: EnergyCost::from(50),
+                forecasts: vec![ForecastValue {
+                    value: -100,
+                    deviation: 0,
+                }]
+                .into(),
+                forecasts_range: 2,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_built_plant_category_matches_definition() {
+        for definition in definitions() {
+            let plant = definition.build(&PlantEnvironment::default(), &mut seeded_rng(0));
+            assert_eq!(plant.category(), definition.category());
+        }
+    }
+
+    #[test]
+    fn test_build_gas_plant_with_carbon_price() {
+        let definition = PlantDefinition::GasPlant {
+            energy_cost: EnergyCost::from(80),
+            capacity: Power::from(500),
+        };
+
+        let PowerPlantPublicRepr::GasPlant(plant) = definition
+            .build(
+                &PlantEnvironment {
+                    carbon_price: CarbonPrice::from(20),
+                    weather: None,
+                },
+                &mut seeded_rng(0),
+            )
+            .current_state()
+        else {
+            unreachable!("Should be a gas plant")
+        };
+        assert_eq!(plant.settings.max_setpoint(), Power::from(500));
+        assert_eq!(plant.settings.carbon_price(), CarbonPrice::from(20));
+    }
+
+    #[test]
+    fn test_same_seed_builds_identical_plants() {
+        for definition in definitions() {
+            let mut first = definition.build(&PlantEnvironment::default(), &mut seeded_rng(7));
+            let mut second = definition.build(&PlantEnvironment::default(), &mut seeded_rng(7));
+            for _ in 0..4 {
+                assert_eq!(first.get_forecast(), second.get_forecast());
+                assert_eq!(first.dispatch(), second.dispatch());
+            }
+        }
+    }
+
+    #[test]
+    fn test_definitions_from_json() {
+        let definitions: Vec<PlantDefinition> = serde_json::from_str(
+            r#"[
+                {"technology": "Battery", "capacity": 100},
+                {"technology": "Nuclear", "energy_cost": 35, "capacity": 1000},
+                {"technology": "Consumers", "revenues": 50, "forecasts": [{"value": -100, "deviation": 0}], "forecasts_range": 2}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(definitions.len(), 3);
+        assert_eq!(definitions[0], self::definitions()[0]);
+        assert_eq!(definitions[1], self::definitions()[2]);
+        assert_eq!(definitions[2], self::definitions()[4]);
+    }
+}
@@ -1,4 +1,7 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    mem,
+};
 
 use serde::{Deserialize, Serialize, ser::SerializeStruct};
 use tokio::sync::{
@@ -8,10 +11,16 @@ use tokio::sync::{
 use tokio_util::sync::CancellationToken;
 
 use crate::{
-    forecast::Forecast,
-    game::{GameId, delivery_period::DeliveryPeriodId},
+    forecast::{Forecast, ForecastValue, shock::ForecastShock},
+    game::{
+        GameId,
+        delivery_period::DeliveryPeriodId,
+        infra::event_log::{GameLog, GameLogEvent},
+    },
     plants::{
-        PlantId, PlantOutput, PowerPlantPublicRepr, Stack, StackDispatchResults, StackPlants,
+        PlantId, PlantOutput, PlantPublicRepr, Stack, StackDispatchResults, StackPlants,
+        investment::{PlantInvestment, PlantLifecycleError},
+        persistence::PersistedStackPlants,
     },
     player::{PlayerConnections, PlayerId, PlayerMessage},
     utils::units::Power,
@@ -33,11 +42,49 @@ pub enum StackMessage {
         tx_back: oneshot::Sender<StackDispatchResults>,
     },
     ProgramSetpoint(ProgramPlant),
-    GetSnapshot(oneshot::Sender<HashMap<PlantId, PowerPlantPublicRepr>>),
+    GetSnapshot(oneshot::Sender<HashMap<PlantId, PlantPublicRepr>>),
     GetForecasts(oneshot::Sender<HashMap<PlantId, Option<Vec<Forecast>>>>),
     GetHistory(oneshot::Sender<HashMap<PlantId, Vec<PlantOutput>>>),
+    Invest {
+        investment: PlantInvestment,
+        tx_back: oneshot::Sender<Result<PlantId, PlantLifecycleError>>,
+    },
+    RetirePlant {
+        plant_id: PlantId,
+        tx_back: oneshot::Sender<Result<(), PlantLifecycleError>>,
+    },
+    ApplyShock {
+        shock: ForecastShock,
+        tx_back: oneshot::Sender<bool>,
+    },
+    GetPersistedState(oneshot::Sender<PersistedStack>),
+    SetPaused(bool),
+}
+
+/// State of a stack saved between two delivery periods, to restore it after a server restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistedStack {
+    pub delivery_period: DeliveryPeriodId,
+    pub plants: PersistedStackPlants,
+    #[serde(default)]
+    pub timeline: StackTimeline,
+    #[serde(default)]
+    pub next_forecasts: HashMap<PlantId, Forecast>,
+}
+
+/// What a plant was expected to do during a delivery period, and what it actually did.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlantPeriod {
+    pub name: String,
+    /// Last forecast of the plant for the period, i.e. the one shown to the player during the
+    /// previous period, for plants based on forecasts
+    pub forecast: Option<ForecastValue>,
+    pub output: PlantOutput,
 }
 
+/// Plants' forecasts and realised outputs of each delivery period dispatched by a stack.
+pub type StackTimeline = BTreeMap<DeliveryPeriodId, HashMap<PlantId, PlantPeriod>>;
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum StackState {
     Open,
@@ -73,6 +120,8 @@ pub struct StackActor<PC: PlayerConnections> {
     game: GameId,
     state: StackState,
     state_sender: watch::Sender<StackState>,
+    /// Whether the game is paused by its host, freezing the plants' setpoints
+    paused: bool,
     delivery_period: DeliveryPeriodId,
     player: PlayerId,
     plants: StackPlants,
@@ -80,7 +129,11 @@ pub struct StackActor<PC: PlayerConnections> {
     rx: Receiver<StackMessage>,
     players_connections: PC,
     past_results: HashMap<DeliveryPeriodId, StackDispatchResults>,
+    timeline: StackTimeline,
+    /// Last forecasts of the next delivery period, kept to be compared with its realised values
+    next_forecasts: HashMap<PlantId, Forecast>,
     cancellation_token: CancellationToken,
+    log: Option<GameLog>,
 }
 
 impl<PC: PlayerConnections> StackActor<PC> {
@@ -100,14 +153,48 @@ impl<PC: PlayerConnections> StackActor<PC> {
             game,
             state: initial_state,
             state_sender: state_tx,
+            paused: false,
             delivery_period,
             player,
             plants,
             players_connections,
             past_results: HashMap::new(),
+            timeline: StackTimeline::new(),
+            next_forecasts: HashMap::new(),
             tx,
             rx,
             cancellation_token,
+            log: None,
+        }
+    }
+
+    /// Record the events of the stack in the game's log.
+    pub fn with_log(mut self, log: Option<GameLog>) -> StackActor<PC> {
+        self.log = log;
+        self
+    }
+
+    /// Start the stack paused, for players joining a game paused by its host.
+    pub fn with_paused(mut self, paused: bool) -> StackActor<PC> {
+        self.paused = paused;
+        self
+    }
+
+    /// Timeline of the periods dispatched before the stack was restored, along with the forecasts
+    /// of the next period.
+    pub fn with_timeline(
+        mut self,
+        timeline: StackTimeline,
+        next_forecasts: HashMap<PlantId, Forecast>,
+    ) -> StackActor<PC> {
+        self.timeline = timeline;
+        self.next_forecasts = next_forecasts;
+        self
+    }
+
+    fn record(&self, event: GameLogEvent) {
+        if let Some(log) = &self.log {
+            log.record(event);
         }
     }
 
@@ -119,6 +206,10 @@ impl<PC: PlayerConnections> StackActor<PC> {
     }
 
     pub async fn run(&mut self) {
+        self.record(GameLogEvent::StackCreated {
+            player: self.player.clone(),
+            plants: self.plants.persist(),
+        });
         loop {
             tokio::select! {
                 Some(message) = self.rx.recv() => {
@@ -146,6 +237,45 @@ impl<PC: PlayerConnections> StackActor<PC> {
             (_, GetHistory(tx_back)) => {
                 let _ = tx_back.send(self.plants.history());
             }
+            (_, GetPersistedState(tx_back)) => {
+                let _ = tx_back.send(PersistedStack {
+                    delivery_period: self.delivery_period,
+                    plants: self.plants.persist(),
+                    timeline: self.timeline.clone(),
+                    next_forecasts: self.next_forecasts.clone(),
+                });
+            }
+            (_, ApplyShock { shock, tx_back }) => {
+                let affected = self.plants.apply_shock(&shock);
+                let _ = tx_back.send(affected);
+                if affected {
+                    self.record(GameLogEvent::ShockApplied {
+                        player: self.player.clone(),
+                        shock,
+                    });
+                    tracing::info!("Stack of player {:?} hit by shock {shock:?}", self.player);
+                    self.players_connections
+                        .send_to_player(
+                            &self.game,
+                            &self.player,
+                            PlayerMessage::ForecastShock { shock },
+                        )
+                        .await;
+                    self.send_stack_snapshot().await;
+                }
+            }
+            (_, SetPaused(paused)) => {
+                self.paused = paused;
+            }
+            (
+                Open,
+                ProgramSetpoint(ProgramPlant {
+                    plant_id,
+                    setpoint: _,
+                }),
+            ) if self.paused => {
+                tracing::warn!("Trying to program plant {plant_id:?} but game is paused.");
+            }
             (Open, ProgramSetpoint(ProgramPlant { plant_id, setpoint })) => {
                 self.program_plant_setpoint(plant_id, setpoint).await;
             }
@@ -180,6 +310,45 @@ impl<PC: PlayerConnections> StackActor<PC> {
                     let _ = tx_back.send(outputs.clone());
                 }
             }
+            (
+                Closed,
+                Invest {
+                    investment,
+                    tx_back,
+                },
+            ) => {
+                let plant_id = self.plants.invest(investment.clone(), self.delivery_period);
+                self.record(GameLogEvent::PlantInvested {
+                    player: self.player.clone(),
+                    plant: plant_id.clone(),
+                    investment,
+                    last_period: self.delivery_period,
+                });
+                tracing::info!("Player {:?} invested in plant {plant_id}", self.player);
+                let _ = tx_back.send(Ok(plant_id));
+                self.send_stack_snapshot().await;
+                self.send_stack_forecasts().await;
+            }
+            (Closed, RetirePlant { plant_id, tx_back }) => {
+                let result = self.plants.retire_plant(&plant_id);
+                let retired = result.is_ok();
+                let _ = tx_back.send(result);
+                if retired {
+                    self.record(GameLogEvent::PlantRetired {
+                        player: self.player.clone(),
+                        plant: plant_id.clone(),
+                    });
+                    tracing::info!("Player {:?} retired plant {plant_id}", self.player);
+                    self.send_stack_snapshot().await;
+                    self.send_stack_forecasts().await;
+                }
+            }
+            (Open, Invest { tx_back, .. }) => {
+                let _ = tx_back.send(Err(PlantLifecycleError::StackIsOpen));
+            }
+            (Open, RetirePlant { tx_back, .. }) => {
+                let _ = tx_back.send(Err(PlantLifecycleError::StackIsOpen));
+            }
         }
     }
 
@@ -229,8 +398,46 @@ impl<PC: PlayerConnections> StackActor<PC> {
         // Update state
         self.state = StackState::Closed;
 
-        // Dispatch plants and collect their outputs
+        // Dispatch plants and collect their outputs, along with what was forecast for them
+        let snapshot = self.plants.snapshot();
+        let forecasts = mem::take(&mut self.next_forecasts);
+        self.next_forecasts = self
+            .plants
+            .forecasts()
+            .into_iter()
+            .filter_map(|(plant_id, forecasts)| {
+                let forecast = forecasts?
+                    .into_iter()
+                    .find(|forecast| forecast.period == period_id.next())?;
+                Some((plant_id, forecast))
+            })
+            .collect();
         let dispatch_results = self.plants.dispatch_plants();
+        self.timeline.insert(
+            period_id,
+            dispatch_results
+                .plants_outputs()
+                .iter()
+                .map(|(plant_id, output)| {
+                    let period = PlantPeriod {
+                        name: snapshot
+                            .get(plant_id)
+                            .map(|plant| plant.name.clone())
+                            .unwrap_or_default(),
+                        forecast: forecasts
+                            .get(plant_id)
+                            .filter(|forecast| forecast.period == period_id)
+                            .map(|forecast| forecast.value),
+                        output: *output,
+                    };
+                    (plant_id.clone(), period)
+                })
+                .collect(),
+        );
+        self.record(GameLogEvent::StackClosed {
+            player: self.player.clone(),
+            period: period_id,
+        });
 
         // Store results for future reference
         self.past_results
@@ -264,6 +471,11 @@ impl<PC: PlayerConnections> StackActor<PC> {
     async fn program_plant_setpoint(&mut self, plant_id: PlantId, setpoint: Power) {
         if let Some(PlantOutput { cost, .. }) = self.plants.program_setpoint(&plant_id, setpoint) {
             tracing::info!("Programmed setpoint {setpoint:?} for plant {plant_id} (cost: {cost}");
+            self.record(GameLogEvent::SetpointProgrammed {
+                player: self.player.clone(),
+                plant: plant_id,
+                setpoint,
+            });
             self.send_stack_snapshot().await;
         };
     }
@@ -283,7 +495,7 @@ mod tests_stack {
         forecast::ForecastValue,
         game::{GameId, delivery_period::DeliveryPeriodId},
         plants::{
-            PlantId, PowerPlant, PowerPlantPublicRepr, StackPlants,
+            PlantId, PlantPublicRepr, PowerPlant, PowerPlantPublicRepr, StackPlants,
             infra::ProgramPlant,
             technologies::{
                 battery::Battery, consumers::Consumers, gas_plant::GasPlant, nuclear::NuclearPlant,
@@ -291,10 +503,13 @@ mod tests_stack {
             },
         },
         player::{PlayerConnections, PlayerId, PlayerMessage},
-        utils::units::{Energy, EnergyCost, NO_POWER, Power},
+        utils::{
+            rng::seeded_rng,
+            units::{Energy, EnergyCost, NO_POWER, Power},
+        },
     };
 
-    use super::{StackActor, StackMessage, StackState};
+    use super::{PlantPeriod, StackActor, StackMessage, StackState};
 
     #[derive(Debug, Clone)]
     struct MockedPlayerConnections {
@@ -314,6 +529,7 @@ mod tests_stack {
         async fn send_to_all_players(&self, _game: &GameId, message: PlayerMessage) -> () {
             let _ = self.tx.send(message).await;
         }
+        async fn send_to_spectators(&self, _game: &GameId, _message: PlayerMessage) {}
     }
 
     fn start_stack() -> (
@@ -347,15 +563,15 @@ mod tests_stack {
         move || {
             let mut map: HashMap<PlantId, Box<dyn PowerPlant + Send + Sync>> = HashMap::new();
             map.insert(
-                PlantId::default(),
+                PlantId::from_rng(&mut rand::rng()),
                 Box::new(Battery::new(Energy::from(300), Energy::from(0))),
             );
             map.insert(
-                PlantId::default(),
+                PlantId::from_rng(&mut rand::rng()),
                 Box::new(GasPlant::new(EnergyCost::from(80), Power::from(500))),
             );
             map.insert(
-                PlantId::default(),
+                PlantId::from_rng(&mut rand::rng()),
                 Box::new(RenewablePlant::new(
                     vec![
                         ForecastValue {
@@ -376,10 +592,11 @@ mod tests_stack {
                         },
                     ],
                     3,
+                    seeded_rng(0),
                 )),
             );
             map.insert(
-                PlantId::default(),
+                PlantId::from_rng(&mut rand::rng()),
                 Box::new(Consumers::new(
                     EnergyCost::from(56),
                     vec![
@@ -401,10 +618,11 @@ mod tests_stack {
                         },
                     ],
                     3,
+                    seeded_rng(0),
                 )),
             );
             map.insert(
-                PlantId::default(),
+                PlantId::from_rng(&mut rand::rng()),
                 Box::new(NuclearPlant::new(Power::from(1000), EnergyCost::from(35))),
             );
             StackPlants::new(map)
@@ -425,7 +643,7 @@ mod tests_stack {
 
     async fn get_stack_snashot(
         stack_tx: Sender<StackMessage>,
-    ) -> HashMap<PlantId, PowerPlantPublicRepr> {
+    ) -> HashMap<PlantId, PlantPublicRepr> {
         let (tx, rx) = oneshot::channel();
         let _ = stack_tx.send(StackMessage::GetSnapshot(tx)).await;
 
@@ -506,13 +724,90 @@ mod tests_stack {
         _ = tokio::time::sleep(Duration::from_micros(1)) => {}
         };
     }
+
+    #[tokio::test]
+    async fn test_no_dispatch_when_stack_paused() {
+        let (_, tx, _, mut conn_rx) = start_stack();
+        let plants = get_stack_snashot(tx.clone()).await;
+        let Some(plant_id) = plants.keys().next() else {
+            unreachable!("Stack should contain at least one power plant");
+        };
+
+        // Pause the stack and try to send a dispatch command
+        let _ = tx.send(StackMessage::SetPaused(true)).await;
+        let _ = tx
+            .send(StackMessage::ProgramSetpoint(ProgramPlant {
+                plant_id: plant_id.to_owned(),
+                setpoint: 100.into(),
+            }))
+            .await;
+
+        // Should not receive a new stack snapshot
+        tokio::select! {
+        _ = conn_rx.recv() => {
+            unreachable!("Should not have received a message");
+        }
+        _ = tokio::time::sleep(Duration::from_micros(1)) => {}
+        };
+
+        // Once resumed, the plant can be programmed
+        let _ = tx.send(StackMessage::SetPaused(false)).await;
+        let _ = tx
+            .send(StackMessage::ProgramSetpoint(ProgramPlant {
+                plant_id: plant_id.to_owned(),
+                setpoint: 100.into(),
+            }))
+            .await;
+        let Some(PlayerMessage::StackSnapshot { plants: _ }) = conn_rx.recv().await else {
+            unreachable!("Should have received a snapshot of the player's stack");
+        };
+    }
+
+    #[tokio::test]
+    async fn test_no_dispatch_when_stack_started_paused() {
+        let (connections, mut conn_rx) = MockedPlayerConnections::new();
+        let mut stack = StackActor::new(
+            GameId::default(),
+            PlayerId::default(),
+            default_stack_plants_builder()(),
+            StackState::Open,
+            DeliveryPeriodId::from(0),
+            connections,
+            CancellationToken::new(),
+        )
+        .with_paused(true);
+        let tx = stack.tx.clone();
+        tokio::spawn(async move {
+            stack.run().await;
+        });
+        let plants = get_stack_snashot(tx.clone()).await;
+        let Some(plant_id) = plants.keys().next() else {
+            unreachable!("Stack should contain at least one power plant");
+        };
+
+        let _ = tx
+            .send(StackMessage::ProgramSetpoint(ProgramPlant {
+                plant_id: plant_id.to_owned(),
+                setpoint: 100.into(),
+            }))
+            .await;
+
+        // Should not receive a new stack snapshot
+        tokio::select! {
+        _ = conn_rx.recv() => {
+            unreachable!("Should not have received a message");
+        }
+        _ = tokio::time::sleep(Duration::from_micros(1)) => {}
+        };
+    }
+
     #[tokio::test]
     async fn test_receive_plants_outputs_when_closing_stack() {
         let (_, tx, ..) = start_stack();
 
         let plants = get_stack_snashot(tx.clone()).await;
         let plants_balance = plants.values().fold(NO_POWER, |acc, plant| {
-            acc + match plant {
+            acc + match plant.state {
                 PowerPlantPublicRepr::Battery(batt) => batt.output.setpoint,
                 PowerPlantPublicRepr::Consumers(cons) => cons.output.setpoint,
                 PowerPlantPublicRepr::GasPlant(plant) => plant.output.setpoint,
@@ -537,7 +832,7 @@ mod tests_stack {
         assert_eq!(
             plants_balance,
             plants.values().fold(NO_POWER, |acc, plant| {
-                acc + match plant {
+                acc + match plant.state {
                     PowerPlantPublicRepr::Battery(batt) => batt.output.setpoint,
                     PowerPlantPublicRepr::Consumers(cons) => cons.output.setpoint,
                     PowerPlantPublicRepr::GasPlant(plant) => plant.output.setpoint,
@@ -785,6 +1080,74 @@ mod tests_stack {
         };
     }
 
+    #[tokio::test]
+    async fn test_closing_the_stack_should_record_forecasts_and_outputs() {
+        let (connections, _) = MockedPlayerConnections::new();
+        let forecast = ForecastValue {
+            value: 250,
+            deviation: 0,
+        };
+        let mut plants = StackPlants::default();
+        let plant_id = plants.add_plant(
+            "solar".to_string(),
+            Box::new(RenewablePlant::new(
+                vec![forecast, forecast],
+                2,
+                seeded_rng(0),
+            )),
+            &mut rand::rng(),
+        );
+        let mut stack = StackActor::new(
+            GameId::default(),
+            PlayerId::default(),
+            plants,
+            StackState::Closed,
+            DeliveryPeriodId::from(0),
+            connections,
+            CancellationToken::new(),
+        );
+        let tx = stack.tx.clone();
+        tokio::spawn(async move {
+            stack.run().await;
+        });
+
+        let mut outputs = Vec::new();
+        for period in [1, 2] {
+            let _ = tx
+                .send(StackMessage::OpenStack(DeliveryPeriodId::from(period - 1)))
+                .await;
+            let (tx_back, rx_back) = oneshot::channel();
+            let _ = tx
+                .send(StackMessage::CloseStack {
+                    tx_back,
+                    period_id: DeliveryPeriodId::from(period),
+                })
+                .await;
+            outputs.push(rx_back.await.unwrap().plants_outputs()[&plant_id]);
+        }
+        let (tx_back, rx_back) = oneshot::channel();
+        let _ = tx.send(StackMessage::GetPersistedState(tx_back)).await;
+        let persisted = rx_back.await.unwrap();
+
+        // No forecast is shown for the first period, only for the next ones
+        assert_eq!(
+            persisted.timeline[&DeliveryPeriodId::from(1)][&plant_id],
+            PlantPeriod {
+                name: "solar".to_string(),
+                forecast: None,
+                output: outputs[0],
+            }
+        );
+        assert_eq!(
+            persisted.timeline[&DeliveryPeriodId::from(2)][&plant_id],
+            PlantPeriod {
+                name: "solar".to_string(),
+                forecast: Some(forecast),
+                output: outputs[1],
+            }
+        );
+    }
+
     #[tokio::test]
     async fn test_terminate_actor() {
         let (connections, _) = MockedPlayerConnections::new();
@@ -1,5 +1,7 @@
 pub mod actor;
 pub mod service;
 
-pub use actor::{ProgramPlant, StackActor, StackContext, StackState};
+pub use actor::{
+    PersistedStack, PlantPeriod, ProgramPlant, StackActor, StackContext, StackState, StackTimeline,
+};
 pub use service::StackService;
@@ -5,16 +5,20 @@ use std::future::Future;
 use tokio::sync::{mpsc, oneshot};
 
 use crate::{
-    forecast::Forecast,
+    forecast::{Forecast, shock::ForecastShock},
     game::delivery_period::DeliveryPeriodId,
     plants::{
-        CloseStackError, GetSnapshotError, PlantId, PlantOutput, PowerPlantPublicRepr, Stack,
+        CloseStackError, GetSnapshotError, PlantId, PlantOutput, PlantPublicRepr, Stack,
         StackDispatchResults,
+        investment::{PlantInvestment, PlantLifecycleError},
     },
     utils::units::Power,
 };
 
-use super::{ProgramPlant, actor::StackMessage};
+use super::{
+    ProgramPlant,
+    actor::{PersistedStack, StackMessage},
+};
 
 /// Wrap the connection to the underlying [`crate::plants::infra::StackActor`] and implement the
 /// [`Stack`] API to interact with it.
@@ -27,6 +31,19 @@ impl StackService {
     pub fn new(tx: mpsc::Sender<StackMessage>) -> StackService {
         StackService { tx }
     }
+
+    /// Get the state of the stack to persist, `None` if the stack actor is no longer running.
+    pub async fn get_persisted_state(&self) -> Option<PersistedStack> {
+        let (tx_back, rx) = oneshot::channel();
+        let _ = self.tx.send(StackMessage::GetPersistedState(tx_back)).await;
+
+        rx.await.ok()
+    }
+
+    /// Pause or resume the stack, plants cannot be programmed while the stack is paused.
+    pub async fn set_paused(&self, paused: bool) {
+        let _ = self.tx.send(StackMessage::SetPaused(paused)).await;
+    }
 }
 
 impl Stack for StackService {
@@ -50,9 +67,7 @@ impl Stack for StackService {
         rx.await.map_err(|_| CloseStackError(delivery_period))
     }
 
-    async fn get_snapshot(
-        &self,
-    ) -> Result<HashMap<PlantId, PowerPlantPublicRepr>, GetSnapshotError> {
+    async fn get_snapshot(&self) -> Result<HashMap<PlantId, PlantPublicRepr>, GetSnapshotError> {
         let (tx_back, rx) = oneshot::channel();
         let _ = self.tx.send(StackMessage::GetSnapshot(tx_back)).await;
 
@@ -84,6 +99,44 @@ impl Stack for StackService {
 
         rx.await.unwrap_or(HashMap::new())
     }
+
+    async fn invest(&self, investment: PlantInvestment) -> Result<PlantId, PlantLifecycleError> {
+        let (tx_back, rx) = oneshot::channel();
+        let _ = self
+            .tx
+            .send(StackMessage::Invest {
+                investment,
+                tx_back,
+            })
+            .await;
+
+        rx.await
+            .unwrap_or(Err(PlantLifecycleError::StackUnavailable))
+    }
+
+    async fn retire_plant(&self, plant: PlantId) -> Result<(), PlantLifecycleError> {
+        let (tx_back, rx) = oneshot::channel();
+        let _ = self
+            .tx
+            .send(StackMessage::RetirePlant {
+                plant_id: plant,
+                tx_back,
+            })
+            .await;
+
+        rx.await
+            .unwrap_or(Err(PlantLifecycleError::StackUnavailable))
+    }
+
+    async fn apply_shock(&self, shock: ForecastShock) -> bool {
+        let (tx_back, rx) = oneshot::channel();
+        let _ = self
+            .tx
+            .send(StackMessage::ApplyShock { shock, tx_back })
+            .await;
+
+        rx.await.unwrap_or(false)
+    }
 }
 
 #[cfg(test)]
@@ -102,11 +155,23 @@ mockall::mock! {
 
         fn get_snapshot(
             &self,
-        ) -> impl Future<Output = Result<HashMap<PlantId, PowerPlantPublicRepr>, GetSnapshotError>> + Send;
+        ) -> impl Future<Output = Result<HashMap<PlantId, PlantPublicRepr>, GetSnapshotError>> + Send;
 
         fn get_forecasts(&self) -> impl Future<Output = HashMap<PlantId, Option<Vec<Forecast>>>> + Send;
 
         fn get_history(&self) -> impl Future<Output = HashMap<PlantId, Vec<PlantOutput>>> + Send;
+
+        fn invest(
+            &self,
+            investment: PlantInvestment,
+        ) -> impl Future<Output = Result<PlantId, PlantLifecycleError>> + Send;
+
+        fn retire_plant(
+            &self,
+            plant: PlantId,
+        ) -> impl Future<Output = Result<(), PlantLifecycleError>> + Send;
+
+        fn apply_shock(&self, shock: ForecastShock) -> impl Future<Output = bool> + Send;
     }
 
     impl Clone for StackService {
@@ -218,7 +283,7 @@ mod tests {
                 unreachable!()
             };
             let _ = tx_back.send(HashMap::from([(
-                PlantId::default(),
+                PlantId::from_rng(&mut rand::rng()),
                 Some(vec![Forecast {
                     period: DeliveryPeriodId::from(1),
                     value: ForecastValue {
@@ -250,7 +315,9 @@ mod tests {
         let (tx, _) = mpsc::channel(128);
         let service = StackService::new(tx);
 
-        let _ = service.program_setpoint(PlantId::default(), 0.into()).await;
+        let _ = service
+            .program_setpoint(PlantId::from_rng(&mut rand::rng()), 0.into())
+            .await;
     }
 
     #[tokio::test]
@@ -263,7 +330,7 @@ mod tests {
                 unreachable!()
             };
             let _ = tx_back.send(HashMap::from([(
-                PlantId::default(),
+                PlantId::from_rng(&mut rand::rng()),
                 vec![PlantOutput {
                     cost: Money::from(12),
                     setpoint: Power::from(100),
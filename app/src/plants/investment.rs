@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+
+use crate::utils::units::Money;
+
+use super::{PlantDefinition, PlantEnvironment};
+
+/// Lifecycle status of a plant of a stack.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(tag = "status")]
+pub enum PlantStatus {
+    /// The plant can be programmed and is dispatched.
+    #[default]
+    Operating,
+    /// The plant has been invested in, and will be operating in `periods_left` delivery periods.
+    UnderConstruction { periods_left: usize },
+    /// The plant has been decommissioned and will never produce again.
+    Retired,
+}
+
+/// Capital expenditure of a plant, paid in instalments over a number of delivery periods.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct CapexSchedule {
+    remaining: Money,
+    payments_left: usize,
+}
+
+impl CapexSchedule {
+    pub fn new(total: Money, payments: usize) -> CapexSchedule {
+        CapexSchedule {
+            remaining: total,
+            payments_left: payments,
+        }
+    }
+
+    pub fn remaining(&self) -> Money {
+        self.remaining
+    }
+
+    pub fn payments_left(&self) -> usize {
+        self.payments_left
+    }
+
+    /// Amount to pay for the current delivery period. Instalments are as even as possible and sum
+    /// up exactly to the total capex.
+    pub fn next_payment(&mut self) -> Money {
+        if self.payments_left == 0 {
+            return Money::from(0);
+        }
+        let payment = self.remaining / i32::try_from(self.payments_left).unwrap_or(i32::MAX);
+        self.remaining = self.remaining - payment;
+        self.payments_left -= 1;
+        payment
+    }
+}
+
+/// Investment in a new plant for a stack, decided by its player between two delivery periods.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlantInvestment {
+    pub name: String,
+    pub definition: PlantDefinition,
+    pub environment: PlantEnvironment,
+    /// Seed of the plant's forecasts and realisations, drawn from the game's generator
+    pub seed: u64,
+    /// Number of delivery periods before the plant is operating
+    pub build_delay: usize,
+    pub capex: CapexSchedule,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum PlantLifecycleError {
+    #[error("Plants cannot be built or retired while the stack is open")]
+    StackIsOpen,
+    #[error("Plant does not exist")]
+    PlantDoesNotExist,
+    #[error("Plant is already retired")]
+    PlantAlreadyRetired,
+    #[error("Only production plants can be retired")]
+    PlantCannotBeRetired,
+    #[error("Stack is unavailable")]
+    StackUnavailable,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::units::Money;
+
+    use super::CapexSchedule;
+
+    #[test]
+    fn test_capex_payments_sum_up_to_total() {
+        let mut capex = CapexSchedule::new(Money::from(1000), 3);
+
+        let payments = (0..3).map(|_| capex.next_payment()).collect::<Vec<_>>();
+
+        assert_eq!(
+            payments,
+            vec![Money::from(333), Money::from(333), Money::from(334)]
+        );
+        assert_eq!(capex.remaining(), Money::from(0));
+        assert_eq!(capex.payments_left(), 0);
+    }
+
+    #[test]
+    fn test_no_payment_once_capex_is_paid() {
+        let mut capex = CapexSchedule::new(Money::from(100), 1);
+
+        assert_eq!(capex.next_payment(), Money::from(100));
+        assert_eq!(capex.next_payment(), Money::from(0));
+    }
+}
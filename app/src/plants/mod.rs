@@ -1,25 +1,38 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{self, Display};
 use std::future::Future;
 use std::ops::Add;
 
 use derive_more::{AsRef, Display, From};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use technologies::battery::BatteryPublicRepr;
 use technologies::consumers::ConsumersPublicRepr;
 use technologies::gas_plant::GasPlantPublicRepr;
 use technologies::nuclear::NuclearPublicRepr;
 use technologies::renewable::RenewablePlantPublicRepr;
-use uuid::Uuid;
+use uuid::Builder;
 
+pub mod definition;
 pub mod infra;
+pub mod investment;
+pub mod persistence;
+pub mod registry;
 pub mod technologies;
 
+pub use definition::{PlantDefinition, PlantEnvironment, PlantForecasts, PlantTechnology};
 pub use infra::StackService;
+use investment::{CapexSchedule, PlantInvestment, PlantLifecycleError, PlantStatus};
+use persistence::PersistedPlant;
+pub use registry::PlantCategory;
+use registry::{STACK_LINES, lines};
 
-use crate::forecast::Forecast;
+use crate::forecast::{Forecast, shock::ForecastShock};
 use crate::game::delivery_period::DeliveryPeriodId;
-use crate::utils::units::{Energy, Money, Power, TIMESTEP};
+use crate::utils::rng::seeded_rng;
+use crate::utils::units::{
+    EmissionFactor, Emissions, Energy, Money, NO_EMISSIONS, NO_POWER, Power, TIMESTEP,
+};
 
 #[derive(Debug)]
 #[allow(dead_code)]
@@ -57,7 +70,7 @@ pub trait Stack: Clone + Send + Sync + 'static {
     /// Get a snapshot of the stack's power plants current setpoint and cost.
     fn get_snapshot(
         &self,
-    ) -> impl Future<Output = Result<HashMap<PlantId, PowerPlantPublicRepr>, GetSnapshotError>> + Send;
+    ) -> impl Future<Output = Result<HashMap<PlantId, PlantPublicRepr>, GetSnapshotError>> + Send;
 
     /// Get a forecast, if available, for each plant of the stack, for the next delivery period.
     fn get_forecasts(&self)
@@ -65,6 +78,23 @@ pub trait Stack: Clone + Send + Sync + 'static {
 
     /// Get an output history for each plant of the stack.
     fn get_history(&self) -> impl Future<Output = HashMap<PlantId, Vec<PlantOutput>>> + Send;
+
+    /// Add a new plant to the stack, operating once its build delay is elapsed. Plants can only be
+    /// built while the stack is closed.
+    fn invest(
+        &self,
+        investment: PlantInvestment,
+    ) -> impl Future<Output = Result<PlantId, PlantLifecycleError>> + Send;
+
+    /// Decommission a plant of the stack. Plants can only be retired while the stack is closed.
+    fn retire_plant(
+        &self,
+        plant: PlantId,
+    ) -> impl Future<Output = Result<(), PlantLifecycleError>> + Send;
+
+    /// Apply a forecast shock to the plants of the stack, the player is notified if any plant was
+    /// affected. Return whether the stack was affected.
+    fn apply_shock(&self, shock: ForecastShock) -> impl Future<Output = bool> + Send;
 }
 
 #[derive(Debug, Serialize, Clone, Copy)]
@@ -77,7 +107,19 @@ pub enum PowerPlantPublicRepr {
     Nuclear(NuclearPublicRepr),
 }
 
-#[derive(Debug, PartialEq, Serialize, Clone, Copy)]
+/// Public representation of a plant of a stack, along with its display name and lifecycle.
+#[derive(Debug, Serialize, Clone)]
+pub struct PlantPublicRepr {
+    pub name: String,
+    #[serde(flatten)]
+    pub state: PowerPlantPublicRepr,
+    #[serde(flatten)]
+    pub status: PlantStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capex: Option<CapexSchedule>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Copy)]
 pub struct PlantOutput {
     pub setpoint: Power,
     pub cost: Money,
@@ -100,6 +142,18 @@ pub trait PowerPlant {
     fn get_history(&self) -> Vec<PlantOutput>;
 
     fn category(&self) -> PlantCategory;
+
+    /// CO2 emitted by the plant for each unit of energy it produces
+    fn emission_factor(&self) -> EmissionFactor;
+
+    /// Shift the output of the current delivery period by `magnitude` (relative change), out of
+    /// its forecasted range. Return whether the plant is subject to forecast shocks.
+    fn apply_shock(&mut self, _magnitude: f64) -> bool {
+        false
+    }
+
+    /// Serialisable copy of the plant's complete state, see [`PersistedPlant`].
+    fn persist(&self) -> PersistedPlant;
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, From, Display, AsRef)]
@@ -107,50 +161,197 @@ pub trait PowerPlant {
 #[from(&str, String)]
 pub struct PlantId(String);
 
-impl Default for PlantId {
-    fn default() -> Self {
-        PlantId(Uuid::new_v4().to_string())
+impl PlantId {
+    /// Generate an id from the given generator, e.g. the game's one so that plants have the same
+    /// ids when the game is replayed.
+    pub fn from_rng(rng: &mut impl Rng) -> Self {
+        PlantId(
+            Builder::from_random_bytes(rng.random())
+                .into_uuid()
+                .to_string(),
+        )
     }
 }
 
-pub enum PlantCategory {
-    Battery,
-    GasPlant,
-    RenewablePlant,
-    Consumers,
-    Nuclear,
+#[derive(Default)]
+pub struct StackPlants {
+    plants: HashMap<PlantId, Box<dyn PowerPlant + Send + Sync>>,
+    names: HashMap<PlantId, String>,
+    statuses: HashMap<PlantId, PlantStatus>,
+    capex: HashMap<PlantId, CapexSchedule>,
 }
 
-pub struct StackPlants(HashMap<PlantId, Box<dyn PowerPlant + Send + Sync>>);
-
 impl StackPlants {
     pub fn new(plants: HashMap<PlantId, Box<dyn PowerPlant + Send + Sync>>) -> StackPlants {
-        Self(plants)
+        Self {
+            plants,
+            ..Default::default()
+        }
+    }
+
+    /// Add a plant to the stack under the given display name, and return its id drawn from `rng`.
+    pub fn add_plant(
+        &mut self,
+        name: String,
+        plant: Box<dyn PowerPlant + Send + Sync>,
+        rng: &mut impl Rng,
+    ) -> PlantId {
+        let plant_id = PlantId::from_rng(rng);
+        self.insert_plant(plant_id.clone(), name, plant);
+        plant_id
+    }
+
+    fn insert_plant(
+        &mut self,
+        plant_id: PlantId,
+        name: String,
+        plant: Box<dyn PowerPlant + Send + Sync>,
+    ) {
+        self.plants.insert(plant_id.clone(), plant);
+        self.names.insert(plant_id, name);
+    }
+
+    /// Add a plant whose capex is paid in instalments over the following delivery periods.
+    pub fn add_plant_with_capex(
+        &mut self,
+        name: String,
+        plant: Box<dyn PowerPlant + Send + Sync>,
+        capex: CapexSchedule,
+        rng: &mut impl Rng,
+    ) -> PlantId {
+        let plant_id = self.add_plant(name, plant, rng);
+        self.capex.insert(plant_id.clone(), capex);
+        plant_id
+    }
+
+    /// Build the plant the player invested in, it will start operating once its build delay is
+    /// elapsed, `last_period` being the last delivery period dispatched by the stack. Its id is
+    /// derived from the investment's seed.
+    pub fn invest(
+        &mut self,
+        investment: PlantInvestment,
+        last_period: DeliveryPeriodId,
+    ) -> PlantId {
+        let plant_id = PlantId::from_rng(&mut seeded_rng(investment.seed));
+        self.invest_as(plant_id.clone(), investment, last_period);
+        plant_id
+    }
+
+    /// Same as [`StackPlants::invest`] for a plant whose id is already known, e.g. when replaying
+    /// a game from its log.
+    pub fn invest_as(
+        &mut self,
+        plant_id: PlantId,
+        investment: PlantInvestment,
+        last_period: DeliveryPeriodId,
+    ) {
+        let first_period =
+            DeliveryPeriodId::from(usize::from(last_period) + investment.build_delay + 1);
+        let plant = investment.definition.build_starting_at(
+            &investment.environment,
+            first_period,
+            &mut seeded_rng(investment.seed),
+        );
+        self.insert_plant(plant_id.clone(), investment.name, plant);
+        self.capex.insert(plant_id.clone(), investment.capex);
+        if investment.build_delay > 0 {
+            self.statuses.insert(
+                plant_id,
+                PlantStatus::UnderConstruction {
+                    periods_left: investment.build_delay,
+                },
+            );
+        }
     }
 
-    pub fn snapshot(&self) -> HashMap<PlantId, PowerPlantPublicRepr> {
-        self.0
+    /// Decommission a plant, it will no longer produce but its remaining capex is still due.
+    pub fn retire_plant(&mut self, plant_id: &PlantId) -> Result<(), PlantLifecycleError> {
+        let Some(plant) = self.plants.get(plant_id) else {
+            return Err(PlantLifecycleError::PlantDoesNotExist);
+        };
+        if plant.category() == PlantCategory::Consumers {
+            return Err(PlantLifecycleError::PlantCannotBeRetired);
+        }
+        if self.status(plant_id) == PlantStatus::Retired {
+            return Err(PlantLifecycleError::PlantAlreadyRetired);
+        }
+        if let Some(plant) = self.plants.get_mut(plant_id) {
+            plant.program_setpoint(NO_POWER);
+        }
+        self.statuses.insert(plant_id.clone(), PlantStatus::Retired);
+        Ok(())
+    }
+
+    fn status(&self, plant_id: &PlantId) -> PlantStatus {
+        self.statuses.get(plant_id).copied().unwrap_or_default()
+    }
+
+    /// Display name of a plant, defaults to the plant's category when no name was given.
+    fn name(&self, plant_id: &PlantId, plant: &(dyn PowerPlant + Send + Sync)) -> String {
+        self.names
+            .get(plant_id)
+            .cloned()
+            .unwrap_or_else(|| plant.category().to_string())
+    }
+
+    pub fn snapshot(&self) -> HashMap<PlantId, PlantPublicRepr> {
+        self.plants
             .iter()
-            .map(|(plant_id, plant)| (plant_id.to_owned(), plant.current_state()))
+            .map(|(plant_id, plant)| {
+                (
+                    plant_id.to_owned(),
+                    PlantPublicRepr {
+                        name: self.name(plant_id, plant.as_ref()),
+                        state: plant.current_state(),
+                        status: self.status(plant_id),
+                        capex: self.capex.get(plant_id).copied(),
+                    },
+                )
+            })
             .collect()
     }
 
     pub fn forecasts(&self) -> HashMap<PlantId, Option<Vec<Forecast>>> {
-        self.0
+        self.plants
             .iter()
-            .map(|(plant_id, plant)| (plant_id.to_owned(), plant.get_forecast()))
+            .map(|(plant_id, plant)| {
+                let forecast = match self.status(plant_id) {
+                    PlantStatus::Retired => None,
+                    _ => plant.get_forecast(),
+                };
+                (plant_id.to_owned(), forecast)
+            })
             .collect()
     }
 
+    /// Apply a forecast shock to the operating plants of the shocked technology, and return whether
+    /// any plant of the stack was affected.
+    pub fn apply_shock(&mut self, shock: &ForecastShock) -> bool {
+        let mut affected = false;
+        for (plant_id, plant) in self.plants.iter_mut() {
+            let operating = self
+                .statuses
+                .get(plant_id)
+                .is_none_or(|status| *status == PlantStatus::Operating);
+            if operating && plant.category() == shock.technology {
+                affected |= plant.apply_shock(shock.magnitude);
+            }
+        }
+        affected
+    }
+
     pub fn history(&self) -> HashMap<PlantId, Vec<PlantOutput>> {
-        self.0
+        self.plants
             .iter()
             .map(|(plant_id, plant)| (plant_id.to_owned(), plant.get_history()))
             .collect()
     }
 
     pub fn program_setpoint(&mut self, plant_id: &PlantId, setpoint: Power) -> Option<PlantOutput> {
-        if let Some(plant) = self.0.get_mut(plant_id) {
+        if self.status(plant_id) != PlantStatus::Operating {
+            return None;
+        }
+        if let Some(plant) = self.plants.get_mut(plant_id) {
             return Some(plant.program_setpoint(setpoint));
         };
         None
@@ -160,31 +361,52 @@ impl StackPlants {
         let mut outputs = HashMap::new();
         let mut state = StackAggregatedState::empty();
 
-        for (id, plant) in self.0.iter_mut() {
-            let output = plant.dispatch();
-            outputs.insert(id.clone(), output);
-
-            match plant.category() {
-                PlantCategory::Consumers => {
-                    state.consumers = state.consumers + output;
-                }
-                PlantCategory::GasPlant => {
-                    state.gas = state.gas + output;
-                }
-                PlantCategory::Nuclear => {
-                    state.nuclear = state.nuclear + output;
-                }
-                PlantCategory::RenewablePlant => {
-                    state.renewables = state.renewables + output;
-                }
-                PlantCategory::Battery if output.setpoint > Power::from(0) => {
-                    state.battery_discharge = state.battery_discharge + output;
+        for (id, plant) in self.plants.iter_mut() {
+            let status = self.statuses.get(id).copied().unwrap_or_default();
+            let output = match status {
+                PlantStatus::Operating => plant.dispatch(),
+                PlantStatus::UnderConstruction { periods_left } => {
+                    let status = match periods_left {
+                        0 | 1 => PlantStatus::Operating,
+                        _ => PlantStatus::UnderConstruction {
+                            periods_left: periods_left - 1,
+                        },
+                    };
+                    self.statuses.insert(id.clone(), status);
+                    PlantOutput {
+                        setpoint: NO_POWER,
+                        cost: Money::from(0),
+                    }
                 }
-                PlantCategory::Battery if output.setpoint < Power::from(0) => {
-                    state.battery_charge = state.battery_charge + output;
-                }
-                _ => {}
+                PlantStatus::Retired => PlantOutput {
+                    setpoint: NO_POWER,
+                    cost: Money::from(0),
+                },
+            };
+            state.emissions = state.emissions
+                + output.setpoint.max(Power::from(0)) * TIMESTEP * plant.emission_factor();
+            if let Some(line) = plant.category().stack_line(&output) {
+                state.add_output(line, output);
             }
+
+            // Capex is accounted in the plant's cost but reported on its own line
+            let capex = PlantOutput {
+                setpoint: NO_POWER,
+                cost: Money::from(0)
+                    - self
+                        .capex
+                        .get_mut(id)
+                        .map(CapexSchedule::next_payment)
+                        .unwrap_or_default(),
+            };
+            state.add_output(lines::CAPEX, capex);
+            outputs.insert(
+                id.clone(),
+                PlantOutput {
+                    setpoint: output.setpoint,
+                    cost: output.cost + capex.cost,
+                },
+            );
         }
 
         StackDispatchResults::new(outputs, state)
@@ -237,79 +459,58 @@ impl Add<PlantOutput> for Output {
     }
 }
 
+/// Plants outputs of a stack summed per line of the registry, see [`registry::STACK_LINES`].
 #[derive(Debug, Clone)]
 pub struct StackAggregatedState {
-    consumers: Output,
-    renewables: Output,
-    gas: Output,
-    nuclear: Output,
-    battery_discharge: Output,
-    battery_charge: Output,
+    lines: BTreeMap<String, Output>,
+    emissions: Emissions,
 }
 
 impl StackAggregatedState {
-    pub fn new(
-        consumers: Output,
-        renewables: Output,
-        gas: Output,
-        nuclear: Output,
-        battery_discharge: Output,
-        battery_charge: Output,
-    ) -> Self {
-        Self {
-            consumers,
-            renewables,
-            gas,
-            nuclear,
-            battery_discharge,
-            battery_charge,
-        }
-    }
-
     pub fn empty() -> Self {
         Self {
-            consumers: Output::empty(),
-            renewables: Output::empty(),
-            gas: Output::empty(),
-            nuclear: Output::empty(),
-            battery_discharge: Output::empty(),
-            battery_charge: Output::empty(),
+            lines: STACK_LINES
+                .iter()
+                .map(|line| (line.to_string(), Output::empty()))
+                .collect(),
+            emissions: NO_EMISSIONS,
         }
     }
 
-    pub fn consumers(&self) -> &Output {
-        &self.consumers
+    pub fn with_line(mut self, line: &str, output: Output) -> Self {
+        self.lines.insert(line.to_string(), output);
+        self
     }
-    pub fn renewables(&self) -> &Output {
-        &self.renewables
+
+    pub fn with_emissions(mut self, emissions: Emissions) -> Self {
+        self.emissions = emissions;
+        self
     }
-    pub fn gas(&self) -> &Output {
-        &self.gas
+
+    fn add_output(&mut self, line: &str, output: PlantOutput) {
+        let total = self.lines.remove(line).unwrap_or_else(Output::empty) + output;
+        self.lines.insert(line.to_string(), total);
     }
-    pub fn nuclear(&self) -> &Output {
-        &self.nuclear
+
+    /// Aggregated output of a line, empty if no plant contributed to it.
+    pub fn line(&self, line: &str) -> Output {
+        self.lines.get(line).cloned().unwrap_or_else(Output::empty)
     }
-    pub fn battery_discharge(&self) -> &Output {
-        &self.battery_discharge
+    pub fn lines(&self) -> impl Iterator<Item = (&String, &Output)> {
+        self.lines.iter()
     }
-    pub fn battery_charge(&self) -> &Output {
-        &self.battery_charge
+    pub fn emissions(&self) -> Emissions {
+        self.emissions
     }
     pub fn position(&self) -> Energy {
-        self.consumers.volume
-            + self.renewables.volume
-            + self.gas.volume
-            + self.nuclear.volume
-            + self.battery_discharge.volume
-            + self.battery_charge.volume
+        self.lines
+            .values()
+            .fold(Energy::from(0), |total, output| total + output.volume)
     }
     pub fn pnl(&self) -> Money {
-        self.consumers.money
-            + self.renewables.money
-            + self.gas.money
-            + self.nuclear.money
-            + self.battery_discharge.money
-            + self.battery_charge.money
+        self.lines
+            .values()
+            .fold(Money::from(0), |total, output| total + output.money)
     }
 }
 
@@ -338,6 +539,9 @@ impl StackDispatchResults {
     pub fn pnl(&self) -> Money {
         self.aggregated_state.pnl()
     }
+    pub fn emissions(&self) -> Emissions {
+        self.aggregated_state.emissions()
+    }
 }
 
 impl Default for StackDispatchResults {
@@ -353,14 +557,23 @@ impl Default for StackDispatchResults {
 mod test {
     use std::collections::HashMap;
 
-    use crate::forecast::ForecastValue;
+    use crate::forecast::{ForecastValue, shock::ForecastShock};
+    use crate::game::delivery_period::DeliveryPeriodId;
+    use crate::plants::investment::{
+        CapexSchedule, PlantInvestment, PlantLifecycleError, PlantStatus,
+    };
+    use crate::plants::registry::lines;
     use crate::plants::technologies::battery::Battery;
     use crate::plants::technologies::consumers::Consumers;
     use crate::plants::technologies::gas_plant::GasPlant;
     use crate::plants::technologies::nuclear::NuclearPlant;
     use crate::plants::technologies::renewable::RenewablePlant;
-    use crate::plants::{PlantId, StackDispatchResults, StackPlants};
-    use crate::utils::units::{Energy, EnergyCost, Money, Power};
+    use crate::plants::{
+        PlantCategory, PlantDefinition, PlantEnvironment, PlantId, PowerPlantPublicRepr,
+        StackDispatchResults, StackPlants,
+    };
+    use crate::utils::rng::seeded_rng;
+    use crate::utils::units::{CarbonPrice, Emissions, Energy, EnergyCost, Money, Power};
 
     fn make_stack() -> (StackPlants, PlantId) {
         let id = PlantId::from("plant-a");
@@ -392,6 +605,48 @@ mod test {
         assert!(snapshot.contains_key(&id));
     }
 
+    #[test]
+    fn test_snapshot_default_name_is_plant_category() {
+        let (stack, id) = make_stack();
+        let snapshot = stack.snapshot();
+        assert_eq!(snapshot[&id].name, "GasPlant");
+    }
+
+    #[test]
+    fn test_serialize_plant_public_repr_flattens_plant_state() {
+        let mut stack = StackPlants::default();
+        let id = stack.add_plant(
+            "gas-a".to_string(),
+            Box::new(GasPlant::new(EnergyCost::from(10), Power::from(100))),
+            &mut rand::rng(),
+        );
+
+        let json = serde_json::to_value(&stack.snapshot()[&id]).unwrap();
+        assert_eq!(json["name"], "gas-a");
+        assert_eq!(json["type"], "GasPlant");
+        assert_eq!(json["settings"]["max_setpoint"], 100);
+    }
+
+    #[test]
+    fn test_add_plant_with_name() {
+        let mut stack = StackPlants::default();
+        let id_a = stack.add_plant(
+            "gas-a".to_string(),
+            Box::new(GasPlant::new(EnergyCost::from(10), Power::from(100))),
+            &mut rand::rng(),
+        );
+        let id_b = stack.add_plant(
+            "gas-b".to_string(),
+            Box::new(GasPlant::new(EnergyCost::from(20), Power::from(100))),
+            &mut rand::rng(),
+        );
+
+        let snapshot = stack.snapshot();
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot[&id_a].name, "gas-a");
+        assert_eq!(snapshot[&id_b].name, "gas-b");
+    }
+
     #[test]
     fn test_forecasts_returns_none_for_gas_plant() {
         let (stack, id) = make_stack();
@@ -464,8 +719,55 @@ mod test {
         stack.program_setpoint(&id, Power::from(60));
         let result = stack.dispatch_plants();
         let state = result.aggregated_state();
-        assert_eq!(state.gas().volume(), &Energy::from(60));
-        assert_eq!(state.gas().money(), &Money::from(-600));
+        assert_eq!(state.line(lines::GAS).volume(), &Energy::from(60));
+        assert_eq!(state.line(lines::GAS).money(), &Money::from(-600));
+    }
+
+    #[test]
+    fn test_dispatch_tracks_emissions() {
+        let gas_id = PlantId::from("gas-a");
+        let nuclear_id = PlantId::from("nuclear-a");
+        let mut plants: HashMap<PlantId, Box<dyn crate::plants::PowerPlant + Send + Sync>> =
+            HashMap::new();
+        plants.insert(
+            gas_id.clone(),
+            Box::new(GasPlant::new(EnergyCost::from(10), Power::from(100))),
+        );
+        plants.insert(
+            nuclear_id.clone(),
+            Box::new(NuclearPlant::new(Power::from(100), EnergyCost::from(5))),
+        );
+        plants.insert(
+            PlantId::from("renewable-a"),
+            Box::new(RenewablePlant::new(
+                vec![ForecastValue {
+                    value: 50,
+                    deviation: 0,
+                }],
+                3,
+                seeded_rng(0),
+            )),
+        );
+        let mut stack = StackPlants::new(plants);
+        stack.program_setpoint(&gas_id, Power::from(60));
+        stack.program_setpoint(&nuclear_id, Power::from(100));
+        let result = stack.dispatch_plants();
+        assert_eq!(result.emissions(), Emissions::from(60 * 400 + 100 * 12));
+    }
+
+    #[test]
+    fn test_dispatch_includes_carbon_cost_in_plants_costs() {
+        let plant = GasPlant::new(EnergyCost::from(10), Power::from(100))
+            .with_carbon_price(CarbonPrice::from(100));
+        let (mut stack, id) = make_single_plant_stack(plant, "gas-a");
+        stack.program_setpoint(&id, Power::from(60));
+        let result = stack.dispatch_plants();
+        let state = result.aggregated_state();
+        assert_eq!(
+            state.line(lines::GAS).money(),
+            &Money::from(-60 * (10 + 40))
+        );
+        assert_eq!(state.emissions(), Emissions::from(60 * 400));
     }
 
     #[test]
@@ -475,8 +777,8 @@ mod test {
         stack.program_setpoint(&id, Power::from(80));
         let result = stack.dispatch_plants();
         let state = result.aggregated_state();
-        assert_eq!(state.nuclear().volume(), &Energy::from(80));
-        assert_eq!(state.nuclear().money(), &Money::from(-400));
+        assert_eq!(state.line(lines::NUCLEAR).volume(), &Energy::from(80));
+        assert_eq!(state.line(lines::NUCLEAR).money(), &Money::from(-400));
     }
 
     #[test]
@@ -488,12 +790,13 @@ mod test {
                 deviation: 0,
             }],
             3,
+            seeded_rng(0),
         );
         let (mut stack, _) = make_single_plant_stack(plant, "consumers-a");
         let result = stack.dispatch_plants();
         let state = result.aggregated_state();
-        assert_eq!(state.consumers().volume(), &Energy::from(-100));
-        assert_eq!(state.consumers().money(), &Money::from(5000));
+        assert_eq!(state.line(lines::CONSUMERS).volume(), &Energy::from(-100));
+        assert_eq!(state.line(lines::CONSUMERS).money(), &Money::from(5000));
     }
 
     #[test]
@@ -504,12 +807,13 @@ mod test {
                 deviation: 0,
             }],
             3,
+            seeded_rng(0),
         );
         let (mut stack, _) = make_single_plant_stack(plant, "renewable-a");
         let result = stack.dispatch_plants();
         let state = result.aggregated_state();
-        assert_eq!(state.renewables().volume(), &Energy::from(75));
-        assert_eq!(state.renewables().money(), &Money::from(0));
+        assert_eq!(state.line(lines::RENEWABLES).volume(), &Energy::from(75));
+        assert_eq!(state.line(lines::RENEWABLES).money(), &Money::from(0));
     }
 
     #[test]
@@ -519,8 +823,11 @@ mod test {
         stack.program_setpoint(&id, Power::from(50));
         let result = stack.dispatch_plants();
         let state = result.aggregated_state();
-        assert_eq!(state.battery_discharge().volume(), &Energy::from(50));
-        assert_eq!(state.battery_charge().volume(), &Energy::from(0));
+        assert_eq!(
+            state.line(lines::BATTERY_DISCHARGE).volume(),
+            &Energy::from(50)
+        );
+        assert_eq!(state.line(lines::BATTERY_CHARGE).volume(), &Energy::from(0));
     }
 
     #[test]
@@ -530,8 +837,14 @@ mod test {
         stack.program_setpoint(&id, Power::from(-30));
         let result = stack.dispatch_plants();
         let state = result.aggregated_state();
-        assert_eq!(state.battery_charge().volume(), &Energy::from(-30));
-        assert_eq!(state.battery_discharge().volume(), &Energy::from(0));
+        assert_eq!(
+            state.line(lines::BATTERY_CHARGE).volume(),
+            &Energy::from(-30)
+        );
+        assert_eq!(
+            state.line(lines::BATTERY_DISCHARGE).volume(),
+            &Energy::from(0)
+        );
     }
 
     #[test]
@@ -540,8 +853,11 @@ mod test {
         let (mut stack, _) = make_single_plant_stack(plant, "battery-a");
         let result = stack.dispatch_plants();
         let state = result.aggregated_state();
-        assert_eq!(state.battery_discharge().volume(), &Energy::from(0));
-        assert_eq!(state.battery_charge().volume(), &Energy::from(0));
+        assert_eq!(
+            state.line(lines::BATTERY_DISCHARGE).volume(),
+            &Energy::from(0)
+        );
+        assert_eq!(state.line(lines::BATTERY_CHARGE).volume(), &Energy::from(0));
     }
 
     #[test]
@@ -563,8 +879,11 @@ mod test {
         stack.program_setpoint(&id_b, Power::from(60));
         let result = stack.dispatch_plants();
         let state = result.aggregated_state();
-        assert_eq!(state.gas().volume(), &Energy::from(100));
-        assert_eq!(state.gas().money(), &Money::from(-(40 * 10 + 60 * 20)));
+        assert_eq!(state.line(lines::GAS).volume(), &Energy::from(100));
+        assert_eq!(
+            state.line(lines::GAS).money(),
+            &Money::from(-(40 * 10 + 60 * 20))
+        );
     }
 
     #[test]
@@ -586,8 +905,8 @@ mod test {
         stack.program_setpoint(&id_b, Power::from(70));
         let result = stack.dispatch_plants();
         let state = result.aggregated_state();
-        assert_eq!(state.nuclear().volume(), &Energy::from(120));
-        assert_eq!(state.nuclear().money(), &Money::from(-120 * 5));
+        assert_eq!(state.line(lines::NUCLEAR).volume(), &Energy::from(120));
+        assert_eq!(state.line(lines::NUCLEAR).money(), &Money::from(-120 * 5));
     }
 
     #[test]
@@ -605,6 +924,7 @@ mod test {
                     deviation: 0,
                 }],
                 3,
+                seeded_rng(0),
             )),
         );
         plants.insert(
@@ -616,13 +936,14 @@ mod test {
                     deviation: 0,
                 }],
                 3,
+                seeded_rng(0),
             )),
         );
         let mut stack = StackPlants::new(plants);
         let result = stack.dispatch_plants();
         let state = result.aggregated_state();
-        assert_eq!(state.consumers().volume(), &Energy::from(-175));
-        assert_eq!(state.consumers().money(), &Money::from(175 * 50));
+        assert_eq!(state.line(lines::CONSUMERS).volume(), &Energy::from(-175));
+        assert_eq!(state.line(lines::CONSUMERS).money(), &Money::from(175 * 50));
     }
 
     #[test]
@@ -639,6 +960,7 @@ mod test {
                     deviation: 0,
                 }],
                 3,
+                seeded_rng(0),
             )),
         );
         plants.insert(
@@ -649,13 +971,14 @@ mod test {
                     deviation: 0,
                 }],
                 3,
+                seeded_rng(0),
             )),
         );
         let mut stack = StackPlants::new(plants);
         let result = stack.dispatch_plants();
         let state = result.aggregated_state();
-        assert_eq!(state.renewables().volume(), &Energy::from(75));
-        assert_eq!(state.renewables().money(), &Money::from(0));
+        assert_eq!(state.line(lines::RENEWABLES).volume(), &Energy::from(75));
+        assert_eq!(state.line(lines::RENEWABLES).money(), &Money::from(0));
     }
 
     #[test]
@@ -677,8 +1000,11 @@ mod test {
         stack.program_setpoint(&id_b, Power::from(40));
         let result = stack.dispatch_plants();
         let state = result.aggregated_state();
-        assert_eq!(state.battery_discharge().volume(), &Energy::from(70));
-        assert_eq!(state.battery_charge().volume(), &Energy::from(0));
+        assert_eq!(
+            state.line(lines::BATTERY_DISCHARGE).volume(),
+            &Energy::from(70)
+        );
+        assert_eq!(state.line(lines::BATTERY_CHARGE).volume(), &Energy::from(0));
     }
 
     #[test]
@@ -700,8 +1026,14 @@ mod test {
         stack.program_setpoint(&id_b, Power::from(-50));
         let result = stack.dispatch_plants();
         let state = result.aggregated_state();
-        assert_eq!(state.battery_charge().volume(), &Energy::from(-70));
-        assert_eq!(state.battery_discharge().volume(), &Energy::from(0));
+        assert_eq!(
+            state.line(lines::BATTERY_CHARGE).volume(),
+            &Energy::from(-70)
+        );
+        assert_eq!(
+            state.line(lines::BATTERY_DISCHARGE).volume(),
+            &Energy::from(0)
+        );
     }
 
     #[test]
@@ -723,8 +1055,14 @@ mod test {
         stack.program_setpoint(&id_b, Power::from(-30));
         let result = stack.dispatch_plants();
         let state = result.aggregated_state();
-        assert_eq!(state.battery_discharge().volume(), &Energy::from(40));
-        assert_eq!(state.battery_charge().volume(), &Energy::from(-30));
+        assert_eq!(
+            state.line(lines::BATTERY_DISCHARGE).volume(),
+            &Energy::from(40)
+        );
+        assert_eq!(
+            state.line(lines::BATTERY_CHARGE).volume(),
+            &Energy::from(-30)
+        );
     }
 
     #[test]
@@ -815,6 +1153,7 @@ mod test {
                     deviation: 0,
                 }],
                 3,
+                seeded_rng(0),
             )),
         );
         plants.insert(
@@ -827,4 +1166,198 @@ mod test {
         let result = stack.dispatch_plants();
         assert_eq!(result.pnl(), Money::from(-60 * 10));
     }
+
+    #[test]
+    fn test_shock_only_affects_plants_of_shocked_technology() {
+        let mut stack = StackPlants::default();
+        let renewable = stack.add_plant(
+            "renewable".to_string(),
+            Box::new(RenewablePlant::new(
+                vec![ForecastValue {
+                    value: 400,
+                    deviation: 0,
+                }],
+                1,
+                seeded_rng(0),
+            )),
+            &mut rand::rng(),
+        );
+        let gas = stack.add_plant(
+            "gas".to_string(),
+            Box::new(GasPlant::new(EnergyCost::from(50), Power::from(300))),
+            &mut rand::rng(),
+        );
+        let shock = ForecastShock {
+            period: DeliveryPeriodId::from(1),
+            technology: PlantCategory::RenewablePlant,
+            magnitude: -0.5,
+        };
+
+        assert!(stack.apply_shock(&shock));
+
+        let snapshot = stack.snapshot();
+        let PowerPlantPublicRepr::RenewablePlant(plant) = snapshot[&renewable].state else {
+            unreachable!("Should be a renewable plant")
+        };
+        assert_eq!(plant.output.setpoint, Power::from(200));
+        let PowerPlantPublicRepr::GasPlant(plant) = snapshot[&gas].state else {
+            unreachable!("Should be a gas plant")
+        };
+        assert_eq!(plant.output.setpoint, Power::from(0));
+        assert!(!stack.apply_shock(&ForecastShock {
+            technology: PlantCategory::Consumers,
+            ..shock
+        }));
+    }
+
+    fn gas_investment(build_delay: usize) -> PlantInvestment {
+        PlantInvestment {
+            name: "new gas".to_string(),
+            definition: PlantDefinition::GasPlant {
+                energy_cost: EnergyCost::from(10),
+                capacity: Power::from(100),
+            },
+            environment: PlantEnvironment::default(),
+            seed: 0,
+            build_delay,
+            capex: CapexSchedule::new(Money::from(900), 3),
+        }
+    }
+
+    #[test]
+    fn test_invested_plant_id_is_derived_from_its_seed() {
+        let mut first = StackPlants::default();
+        let mut second = StackPlants::default();
+
+        assert_eq!(
+            first.invest(gas_investment(0), DeliveryPeriodId::from(1)),
+            second.invest(gas_investment(0), DeliveryPeriodId::from(1))
+        );
+    }
+
+    #[test]
+    fn test_invested_plant_cannot_be_programmed_while_under_construction() {
+        let mut stack = StackPlants::default();
+        let id = stack.invest(gas_investment(1), DeliveryPeriodId::from(1));
+
+        assert_eq!(
+            stack.snapshot()[&id].status,
+            PlantStatus::UnderConstruction { periods_left: 1 }
+        );
+        assert!(stack.program_setpoint(&id, Power::from(50)).is_none());
+
+        let result = stack.dispatch_plants();
+        assert_eq!(result.plants_outputs()[&id].setpoint, Power::from(0));
+        assert_eq!(stack.snapshot()[&id].status, PlantStatus::Operating);
+        assert!(stack.program_setpoint(&id, Power::from(50)).is_some());
+    }
+
+    #[test]
+    fn test_invested_plant_without_build_delay_is_operating() {
+        let mut stack = StackPlants::default();
+        let id = stack.invest(gas_investment(0), DeliveryPeriodId::from(1));
+
+        assert_eq!(stack.snapshot()[&id].status, PlantStatus::Operating);
+        assert!(stack.program_setpoint(&id, Power::from(50)).is_some());
+    }
+
+    #[test]
+    fn test_dispatch_charges_capex_on_its_own_line() {
+        let mut stack = StackPlants::default();
+        let id = stack.invest(gas_investment(0), DeliveryPeriodId::from(1));
+        stack.program_setpoint(&id, Power::from(50));
+
+        let result = stack.dispatch_plants();
+
+        assert_eq!(result.plants_outputs()[&id].cost, Money::from(-500 - 300));
+        let state = result.aggregated_state();
+        assert_eq!(state.line(lines::GAS).money(), &Money::from(-500));
+        assert_eq!(state.line(lines::CAPEX).money(), &Money::from(-300));
+        assert_eq!(state.line(lines::CAPEX).volume(), &Energy::from(0));
+        assert_eq!(result.pnl(), Money::from(-800));
+    }
+
+    #[test]
+    fn test_invested_renewable_plant_forecasts_start_when_operating() {
+        let mut stack = StackPlants::default();
+        let id = stack.invest(
+            PlantInvestment {
+                definition: PlantDefinition::RenewablePlant {
+                    forecasts: vec![ForecastValue {
+                        value: 50,
+                        deviation: 0,
+                    }]
+                    .into(),
+                    forecasts_range: 2,
+                },
+                ..gas_investment(1)
+            },
+            DeliveryPeriodId::from(2),
+        );
+
+        let forecasts = stack.forecasts()[&id].clone().unwrap();
+        assert_eq!(forecasts[0].period, DeliveryPeriodId::from(5));
+    }
+
+    #[test]
+    fn test_retired_plant_no_longer_produces() {
+        let (mut stack, id) = make_stack();
+        stack.program_setpoint(&id, Power::from(60));
+
+        assert_eq!(stack.retire_plant(&id), Ok(()));
+
+        assert_eq!(stack.snapshot()[&id].status, PlantStatus::Retired);
+        assert!(stack.program_setpoint(&id, Power::from(60)).is_none());
+        assert!(stack.forecasts()[&id].is_none());
+        let result = stack.dispatch_plants();
+        assert_eq!(result.plants_outputs()[&id].setpoint, Power::from(0));
+        assert_eq!(result.pnl(), Money::from(0));
+    }
+
+    #[test]
+    fn test_retire_plant_errors() {
+        let (mut stack, id) = make_stack();
+
+        assert_eq!(
+            stack.retire_plant(&PlantId::from("unknown")),
+            Err(PlantLifecycleError::PlantDoesNotExist)
+        );
+        assert_eq!(stack.retire_plant(&id), Ok(()));
+        assert_eq!(
+            stack.retire_plant(&id),
+            Err(PlantLifecycleError::PlantAlreadyRetired)
+        );
+    }
+
+    #[test]
+    fn test_consumers_cannot_be_retired() {
+        let mut stack = StackPlants::default();
+        let id = stack.add_plant(
+            "consumers".to_string(),
+            Box::new(Consumers::new(
+                EnergyCost::from(80),
+                vec![],
+                2,
+                seeded_rng(0),
+            )),
+            &mut rand::rng(),
+        );
+
+        assert_eq!(
+            stack.retire_plant(&id),
+            Err(PlantLifecycleError::PlantCannotBeRetired)
+        );
+        assert_eq!(stack.snapshot()[&id].status, PlantStatus::Operating);
+    }
+
+    #[test]
+    fn test_retired_plant_still_pays_its_capex() {
+        let mut stack = StackPlants::default();
+        let id = stack.invest(gas_investment(0), DeliveryPeriodId::from(1));
+        let _ = stack.retire_plant(&id);
+
+        let result = stack.dispatch_plants();
+
+        assert_eq!(result.pnl(), Money::from(-300));
+    }
 }
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    PlantId, PowerPlant, StackPlants,
+    investment::{CapexSchedule, PlantStatus},
+    technologies::{
+        battery::Battery, consumers::Consumers, gas_plant::GasPlant, nuclear::NuclearPlant,
+        renewable::RenewablePlant,
+    },
+};
+
+/// Complete state of a plant, including its history and the state of its random generator, so
+/// that a restored plant behaves exactly as the original one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum PersistedPlant {
+    Battery(Battery),
+    GasPlant(GasPlant),
+    Nuclear(NuclearPlant),
+    RenewablePlant(RenewablePlant),
+    Consumers(Consumers),
+}
+
+impl PersistedPlant {
+    pub fn into_plant(self) -> Box<dyn PowerPlant + Send + Sync> {
+        match self {
+            PersistedPlant::Battery(plant) => Box::new(plant),
+            PersistedPlant::GasPlant(plant) => Box::new(plant),
+            PersistedPlant::Nuclear(plant) => Box::new(plant),
+            PersistedPlant::RenewablePlant(plant) => Box::new(plant),
+            PersistedPlant::Consumers(plant) => Box::new(plant),
+        }
+    }
+}
+
+/// Serialisable state of the plants of a stack.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PersistedStackPlants {
+    plants: HashMap<PlantId, PersistedPlant>,
+    names: HashMap<PlantId, String>,
+    statuses: HashMap<PlantId, PlantStatus>,
+    capex: HashMap<PlantId, CapexSchedule>,
+}
+
+impl StackPlants {
+    pub fn persist(&self) -> PersistedStackPlants {
+        PersistedStackPlants {
+            plants: self
+                .plants
+                .iter()
+                .map(|(plant_id, plant)| (plant_id.clone(), plant.persist()))
+                .collect(),
+            names: self.names.clone(),
+            statuses: self.statuses.clone(),
+            capex: self.capex.clone(),
+        }
+    }
+
+    /// Rebuild the plants of a stack, keeping their ids so that players' clients stay in sync.
+    pub fn restore(persisted: PersistedStackPlants) -> StackPlants {
+        StackPlants {
+            plants: persisted
+                .plants
+                .into_iter()
+                .map(|(plant_id, plant)| (plant_id, plant.into_plant()))
+                .collect(),
+            names: persisted.names,
+            statuses: persisted.statuses,
+            capex: persisted.capex,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        forecast::ForecastValue,
+        plants::{
+            PlantDefinition, PlantEnvironment, StackPlants, investment::CapexSchedule,
+            technologies::nuclear::NuclearPlant,
+        },
+        utils::{
+            rng::seeded_rng,
+            units::{EnergyCost, Money, Power},
+        },
+    };
+
+    use super::PersistedStackPlants;
+
+    fn round_trip(plants: &StackPlants) -> StackPlants {
+        let persisted: PersistedStackPlants =
+            serde_json::from_str(&serde_json::to_string(&plants.persist()).unwrap()).unwrap();
+        StackPlants::restore(persisted)
+    }
+
+    #[test]
+    fn test_restored_stack_dispatches_like_the_original() {
+        let mut rng = seeded_rng(3);
+        let mut plants = StackPlants::default();
+        let consumers = PlantDefinition::Consumers {
+            revenues: EnergyCost::from(60),
+            forecasts: vec![ForecastValue {
+                value: -1000,
+                deviation: 200,
+            }]
+            .into(),
+            forecasts_range: 3,
+        };
+        plants.add_plant(
+            "consumers".to_string(),
+            consumers.build(&PlantEnvironment::default(), &mut rng),
+            &mut rng,
+        );
+        plants.add_plant_with_capex(
+            "nuclear".to_string(),
+            Box::new(NuclearPlant::new(Power::from(1000), EnergyCost::from(35))),
+            CapexSchedule::new(Money::from(1000), 4),
+            &mut rng,
+        );
+        plants.dispatch_plants();
+
+        let mut restored = round_trip(&plants);
+
+        assert_eq!(restored.forecasts(), plants.forecasts());
+        assert_eq!(restored.history(), plants.history());
+        for _ in 0..3 {
+            let expected = plants.dispatch_plants();
+            let actual = restored.dispatch_plants();
+            assert_eq!(actual.plants_outputs(), expected.plants_outputs());
+            assert_eq!(actual.pnl(), expected.pnl());
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::units::NO_POWER;
+
+use super::PlantOutput;
+
+/// Lines of a stack's aggregated state, plants outputs are summed per line and reported as such in
+/// players' detailed scores.
+pub mod lines {
+    pub const CONSUMERS: &str = "consumers";
+    pub const RENEWABLES: &str = "renewables";
+    pub const GAS: &str = "gas";
+    pub const NUCLEAR: &str = "nuclear";
+    pub const BATTERY_DISCHARGE: &str = "battery_discharge";
+    pub const BATTERY_CHARGE: &str = "battery_charge";
+    /// Capital expenditure of the plants invested in during the game
+    pub const CAPEX: &str = "capex";
+}
+
+/// All the lines of a stack's aggregated state, in display order.
+pub const STACK_LINES: [&str; 7] = [
+    lines::CONSUMERS,
+    lines::RENEWABLES,
+    lines::GAS,
+    lines::NUCLEAR,
+    lines::BATTERY_DISCHARGE,
+    lines::BATTERY_CHARGE,
+    lines::CAPEX,
+];
+
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PlantCategory {
+    Battery,
+    GasPlant,
+    RenewablePlant,
+    Consumers,
+    Nuclear,
+}
+
+impl PlantCategory {
+    /// Line of the stack's aggregated state a plant output is accounted in, if any.
+    pub fn stack_line(&self, output: &PlantOutput) -> Option<&'static str> {
+        match self {
+            PlantCategory::Consumers => Some(lines::CONSUMERS),
+            PlantCategory::RenewablePlant => Some(lines::RENEWABLES),
+            PlantCategory::GasPlant => Some(lines::GAS),
+            PlantCategory::Nuclear => Some(lines::NUCLEAR),
+            PlantCategory::Battery if output.setpoint > NO_POWER => Some(lines::BATTERY_DISCHARGE),
+            PlantCategory::Battery if output.setpoint < NO_POWER => Some(lines::BATTERY_CHARGE),
+            PlantCategory::Battery => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        plants::{PlantOutput, registry::lines},
+        utils::units::{Money, Power},
+    };
+
+    use super::{PlantCategory, STACK_LINES};
+
+    fn output(setpoint: i32) -> PlantOutput {
+        PlantOutput {
+            setpoint: Power::from(setpoint),
+            cost: Money::from(0),
+        }
+    }
+
+    #[test]
+    fn test_every_category_line_is_registered() {
+        for category in [
+            PlantCategory::Consumers,
+            PlantCategory::RenewablePlant,
+            PlantCategory::GasPlant,
+            PlantCategory::Nuclear,
+            PlantCategory::Battery,
+        ] {
+            for setpoint in [-10, 10] {
+                if let Some(line) = category.stack_line(&output(setpoint)) {
+                    assert!(STACK_LINES.contains(&line));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_battery_line_depends_on_setpoint_sign() {
+        let battery = PlantCategory::Battery;
+        assert_eq!(
+            battery.stack_line(&output(10)),
+            Some(lines::BATTERY_DISCHARGE)
+        );
+        assert_eq!(
+            battery.stack_line(&output(-10)),
+            Some(lines::BATTERY_CHARGE)
+        );
+        assert_eq!(battery.stack_line(&output(0)), None);
+    }
+}
@@ -1,18 +1,20 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     forecast::Forecast,
-    plants::{PlantOutput, PowerPlant, PowerPlantPublicRepr},
-    utils::units::{Energy, Money, Power, TIMESTEP},
+    plants::{PlantOutput, PowerPlant, PowerPlantPublicRepr, persistence::PersistedPlant},
+    utils::units::{EmissionFactor, Energy, Money, Power, TIMESTEP},
 };
 
 /// Store energy accros delivery periods
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Battery {
     settings: BatterySettings,
     charge: Energy,
     setpoint: Power,
     history: Vec<PlantOutput>,
 }
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BatterySettings {
     max_charge: Energy,
 }
@@ -96,6 +98,14 @@ impl PowerPlant for Battery {
     fn category(&self) -> crate::plants::PlantCategory {
         crate::plants::PlantCategory::Battery
     }
+
+    fn emission_factor(&self) -> EmissionFactor {
+        EmissionFactor::default()
+    }
+
+    fn persist(&self) -> PersistedPlant {
+        PersistedPlant::Battery(self.clone())
+    }
 }
 
 #[cfg(test)]
@@ -1,9 +1,18 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    forecast::{Forecast, ForecastValue},
-    plants::{PlantOutput, PowerPlant, PowerPlantPublicRepr, technologies::ForecastsBasedPlant},
-    utils::units::{EnergyCost, GENERATOR_CONVENTION_TO_MONEY, Money, Power, TIMESTEP},
+    forecast::{Forecast, ForecastValue, signal::GlobalSignal},
+    game::delivery_period::DeliveryPeriodId,
+    plants::{
+        PlantOutput, PowerPlant, PowerPlantPublicRepr, persistence::PersistedPlant,
+        technologies::ForecastsBasedPlant,
+    },
+    utils::{
+        rng::GameRng,
+        units::{
+            EmissionFactor, EnergyCost, GENERATOR_CONVENTION_TO_MONEY, Money, Power, TIMESTEP,
+        },
+    },
 };
 
 #[derive(Debug, Serialize, Clone, Copy)]
@@ -11,6 +20,7 @@ pub struct ConsumersPublicRepr {
     pub output: PlantOutput,
     pub revenue: EnergyCost,
 }
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Consumers {
     price_per_mwh: EnergyCost,
     state: ForecastsBasedPlant,
@@ -22,8 +32,26 @@ impl Consumers {
         price_per_mwh: EnergyCost,
         forecasts: Vec<ForecastValue>,
         forecasts_range: usize,
+        rng: GameRng,
     ) -> Consumers {
-        let state = ForecastsBasedPlant::new(forecasts, forecasts_range);
+        Consumers::starting_at(
+            price_per_mwh,
+            forecasts,
+            forecasts_range,
+            DeliveryPeriodId::from(1),
+            rng,
+        )
+    }
+
+    /// Create consumers from `first_period` onwards, see [`ForecastsBasedPlant::starting_at`].
+    pub fn starting_at(
+        price_per_mwh: EnergyCost,
+        forecasts: Vec<ForecastValue>,
+        forecasts_range: usize,
+        first_period: DeliveryPeriodId,
+        rng: GameRng,
+    ) -> Consumers {
+        let state = ForecastsBasedPlant::starting_at(forecasts, forecasts_range, first_period, rng);
 
         Consumers {
             price_per_mwh,
@@ -32,6 +60,12 @@ impl Consumers {
         }
     }
 
+    /// Correlate the consumption with the other stacks of the game, see [`GlobalSignal`].
+    pub fn with_signal(mut self, signal: GlobalSignal) -> Consumers {
+        self.state = self.state.with_signal(signal);
+        self
+    }
+
     fn cost(&self) -> Money {
         self.state.setpoint() * TIMESTEP * self.price_per_mwh * GENERATOR_CONVENTION_TO_MONEY
     }
@@ -80,6 +114,19 @@ impl PowerPlant for Consumers {
     fn category(&self) -> crate::plants::PlantCategory {
         crate::plants::PlantCategory::Consumers
     }
+
+    fn emission_factor(&self) -> EmissionFactor {
+        EmissionFactor::default()
+    }
+
+    fn apply_shock(&mut self, magnitude: f64) -> bool {
+        self.state.apply_shock(magnitude);
+        true
+    }
+
+    fn persist(&self) -> PersistedPlant {
+        PersistedPlant::Consumers(self.clone())
+    }
 }
 
 #[cfg(test)]
@@ -89,7 +136,10 @@ mod tests {
         forecast::ForecastValue,
         game::delivery_period::DeliveryPeriodId,
         plants::PowerPlant,
-        utils::units::{EnergyCost, Power},
+        utils::{
+            rng::seeded_rng,
+            units::{EnergyCost, Power},
+        },
     };
 
     use super::Consumers;
@@ -115,7 +165,7 @@ mod tests {
     fn test_consumers() {
         let energy_cost = EnergyCost::from(75);
         let forecasts = get_forecasts();
-        let mut consumers = Consumers::new(energy_cost, forecasts, 2);
+        let mut consumers = Consumers::new(energy_cost, forecasts, 2, seeded_rng(0));
 
         // Consumers cannot be programed
         let initial_setpoint = consumers.state.setpoint();
@@ -147,7 +197,7 @@ mod tests {
     fn test_consumers_forecasts_periods() {
         let energy_cost = EnergyCost::from(75);
         let forecsts = get_forecasts();
-        let mut consumers = Consumers::new(energy_cost, forecsts, 2);
+        let mut consumers = Consumers::new(energy_cost, forecsts, 2, seeded_rng(0));
 
         let forecasts = consumers.get_forecast().unwrap();
         assert_eq!(
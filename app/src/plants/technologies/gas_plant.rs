@@ -1,22 +1,29 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    constants::GAS_EMISSION_FACTOR,
     forecast::Forecast,
-    plants::{PlantOutput, PowerPlant, PowerPlantPublicRepr},
-    utils::units::{EnergyCost, GENERATOR_CONVENTION_TO_MONEY, Money, NO_POWER, Power, TIMESTEP},
+    plants::{PlantOutput, PowerPlant, PowerPlantPublicRepr, persistence::PersistedPlant},
+    utils::units::{
+        CarbonPrice, EmissionFactor, EnergyCost, GENERATOR_CONVENTION_TO_MONEY, Money, NO_POWER,
+        Power, TIMESTEP,
+    },
 };
 
 /// Plant with no dynamic constraints.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GasPlant {
     settings: GasPlantSettings,
     setpoint: Power,
     history: Vec<PlantOutput>,
 }
 
-#[derive(Debug, Serialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 pub struct GasPlantSettings {
     energy_cost: EnergyCost,
     max_setpoint: Power,
+    emission_factor: EmissionFactor,
+    carbon_price: CarbonPrice,
 }
 
 impl GasPlantSettings {
@@ -27,6 +34,19 @@ impl GasPlantSettings {
     pub fn max_setpoint(&self) -> Power {
         self.max_setpoint
     }
+
+    pub fn emission_factor(&self) -> EmissionFactor {
+        self.emission_factor
+    }
+
+    pub fn carbon_price(&self) -> CarbonPrice {
+        self.carbon_price
+    }
+
+    /// Cost of producing a unit of energy, including the cost of the emitted CO2.
+    pub fn total_energy_cost(&self) -> EnergyCost {
+        self.energy_cost + self.emission_factor * self.carbon_price
+    }
 }
 
 impl GasPlant {
@@ -35,14 +55,22 @@ impl GasPlant {
             settings: GasPlantSettings {
                 energy_cost,
                 max_setpoint,
+                emission_factor: EmissionFactor::from(GAS_EMISSION_FACTOR),
+                carbon_price: CarbonPrice::default(),
             },
             setpoint: Power::from(0),
             history: Vec::new(),
         }
     }
 
+    /// Set the price paid for each tonne of CO2 emitted by the plant.
+    pub fn with_carbon_price(mut self, carbon_price: CarbonPrice) -> GasPlant {
+        self.settings.carbon_price = carbon_price;
+        self
+    }
+
     fn cost(&self) -> Money {
-        self.setpoint * TIMESTEP * self.settings.energy_cost * GENERATOR_CONVENTION_TO_MONEY
+        self.setpoint * TIMESTEP * self.settings.total_energy_cost() * GENERATOR_CONVENTION_TO_MONEY
     }
 }
 
@@ -91,13 +119,21 @@ impl PowerPlant for GasPlant {
     fn category(&self) -> crate::plants::PlantCategory {
         crate::plants::PlantCategory::GasPlant
     }
+
+    fn emission_factor(&self) -> EmissionFactor {
+        self.settings.emission_factor
+    }
+
+    fn persist(&self) -> PersistedPlant {
+        PersistedPlant::GasPlant(self.clone())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
         plants::{PlantOutput, PowerPlant, technologies::gas_plant::GasPlant},
-        utils::units::{EnergyCost, Money, Power},
+        utils::units::{CarbonPrice, EmissionFactor, EnergyCost, Money, Power},
     };
 
     #[test]
@@ -167,4 +203,20 @@ mod tests {
         let plant = GasPlant::new(EnergyCost::from(70), Power::from(1000));
         assert!(plant.get_forecast().is_none());
     }
+
+    #[test]
+    fn test_gas_plant_carbon_price_adds_to_cost() {
+        let mut plant = GasPlant::new(EnergyCost::from(70), Power::from(1000))
+            .with_carbon_price(CarbonPrice::from(80));
+
+        assert_eq!(plant.emission_factor(), EmissionFactor::from(400));
+        // 70 €/MWh + 400 kgCO2/MWh * 80 €/tCO2 = 102 €/MWh
+        assert_eq!(
+            plant.program_setpoint(Power::from(100)),
+            PlantOutput {
+                setpoint: Power::from(100),
+                cost: Money::from(-100 * 102)
+            }
+        );
+    }
 }
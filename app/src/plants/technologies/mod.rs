@@ -1,7 +1,15 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
 use crate::{
-    forecast::{Forecast, ForecastValue},
+    constants,
+    forecast::{
+        Forecast, ForecastValue, revision::ForecastRevisions, round_to_nearest,
+        signal::GlobalSignal,
+    },
     game::delivery_period::DeliveryPeriodId,
-    utils::units::Power,
+    utils::{rng::GameRng, units::Power},
 };
 
 pub mod battery;
@@ -10,21 +18,49 @@ pub mod gas_plant;
 pub mod nuclear;
 pub mod renewable;
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ForecastsBasedPlant {
     period: usize,
     base_forecasts: Vec<ForecastValue>,
     forecasts_range: usize,
+    /// Forecasts and realised values of the current and next delivery periods, generated when a
+    /// period enters the forecasts range
+    revisions: BTreeMap<usize, ForecastRevisions>,
+    /// Signal shared with the other stacks of the game the realisations are correlated with
+    signal: Option<GlobalSignal>,
+    rng: GameRng,
 
     setpoint: Power,
     forecasts: Vec<Forecast>,
 }
 
 impl ForecastsBasedPlant {
-    pub fn new(base_forecasts: Vec<ForecastValue>, forecasts_range: usize) -> Self {
+    pub fn new(base_forecasts: Vec<ForecastValue>, forecasts_range: usize, rng: GameRng) -> Self {
+        Self::starting_at(
+            base_forecasts,
+            forecasts_range,
+            DeliveryPeriodId::from(1),
+            rng,
+        )
+    }
+
+    /// Create a plant whose first delivery period is `first_period`, e.g. a plant built during the
+    /// game, so that its setpoint and forecasts are aligned with the game's delivery periods.
+    /// Forecasts and realisations are drawn from `rng`, e.g. one derived from the game's seed so
+    /// that the plant behaves identically when the game is replayed.
+    pub fn starting_at(
+        base_forecasts: Vec<ForecastValue>,
+        forecasts_range: usize,
+        first_period: DeliveryPeriodId,
+        rng: GameRng,
+    ) -> Self {
         let mut res = Self {
-            period: 0,
+            period: usize::from(first_period.previous()),
             forecasts_range,
             base_forecasts,
+            revisions: BTreeMap::new(),
+            signal: None,
+            rng,
             setpoint: Power::from(0),
             forecasts: vec![],
         };
@@ -32,6 +68,14 @@ impl ForecastsBasedPlant {
         res
     }
 
+    /// Correlate the forecasts and realisations of the plant with a game-wide signal.
+    pub fn with_signal(mut self, signal: GlobalSignal) -> Self {
+        self.signal = Some(signal);
+        self.revisions.clear();
+        self.refresh();
+        self
+    }
+
     pub fn setpoint(&self) -> Power {
         self.setpoint
     }
@@ -42,42 +86,80 @@ impl ForecastsBasedPlant {
 
     pub fn dispatch(&mut self) {
         self.period += 1;
+        self.refresh();
+    }
+
+    fn refresh(&mut self) {
+        self.revisions = self.revisions.split_off(&self.period);
+        for period in self.period..=self.period + self.forecasts_range {
+            if !self.revisions.contains_key(&period) {
+                let revisions = self.generate_revisions(period);
+                self.revisions.insert(period, revisions);
+            }
+        }
 
         self.setpoint = self.compute_setpoint();
 
         self.forecasts = self.compute_forecasts();
     }
 
-    fn compute_setpoint(&self) -> Power {
+    fn generate_revisions(&mut self, period: usize) -> ForecastRevisions {
+        let base = self.base_forecast(period);
+        let horizon = period - self.period;
+        match &self.signal {
+            Some(signal) => ForecastRevisions::generate_towards(
+                base,
+                horizon,
+                signal.position(period, &mut self.rng),
+            ),
+            None => ForecastRevisions::generate(base, horizon, &mut self.rng),
+        }
+    }
+
+    /// Shift the realised value of the current delivery period by `magnitude` (relative change),
+    /// regardless of the forecasts made for it.
+    pub fn apply_shock(&mut self, magnitude: f64) {
+        let shocked = f64::from(i32::from(self.setpoint)) * (1. + magnitude);
+        self.setpoint = Power::from(round_to_nearest(
+            shocked.round() as i32,
+            constants::SETPOINT_BASE_VALUE,
+        ));
+    }
+
+    /// Furthest forecast of a delivery period, nothing is produced nor consumed after the end of
+    /// the base forecasts.
+    fn base_forecast(&self, period: usize) -> ForecastValue {
         self.base_forecasts
-            .get(self.period - 1)
-            .unwrap_or(&ForecastValue::default())
-            .forecast()
+            .get(period - 1)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn compute_setpoint(&self) -> Power {
+        self.revisions
+            .get(&self.period)
+            .map(ForecastRevisions::realised)
+            .unwrap_or_default()
     }
 
     fn compute_forecasts(&self) -> Vec<Forecast> {
-        let mut forecasts = vec![];
-        for idx in 1..=self.forecasts_range {
-            let forecast = if self.base_forecasts.is_empty() {
-                ForecastValue::default()
-            } else {
-                *self
-                    .base_forecasts
-                    .get((self.period - 1 + idx) % self.base_forecasts.len())
-                    .unwrap()
-            };
-            forecasts.push(Forecast {
+        (1..=self.forecasts_range)
+            .map(|idx| Forecast {
                 period: DeliveryPeriodId::from(self.period + idx),
-                value: forecast,
-            });
-        }
-        forecasts
+                value: self
+                    .revisions
+                    .get(&(self.period + idx))
+                    .map(|revisions| revisions.at(idx))
+                    .unwrap_or_default(),
+            })
+            .collect()
     }
 }
 
 #[cfg(test)]
 mod test_forecasts_based_plant {
     use super::*;
+    use crate::utils::rng::seeded_rng;
 
     fn test_forecasts() -> Vec<ForecastValue> {
         vec![
@@ -103,7 +185,7 @@ mod test_forecasts_based_plant {
     #[test]
     fn test_new_plant() {
         let forecasts_range = 2;
-        let plant = ForecastsBasedPlant::new(test_forecasts(), forecasts_range);
+        let plant = ForecastsBasedPlant::new(test_forecasts(), forecasts_range, seeded_rng(0));
 
         assert!((75..=125).contains(&plant.setpoint.into()));
         assert_eq!(plant.forecasts().len(), forecasts_range);
@@ -118,7 +200,7 @@ mod test_forecasts_based_plant {
     #[test]
     fn test_plant_dispatch() {
         let forecasts_range = 2;
-        let mut plant = ForecastsBasedPlant::new(test_forecasts(), forecasts_range);
+        let mut plant = ForecastsBasedPlant::new(test_forecasts(), forecasts_range, seeded_rng(0));
 
         plant.dispatch();
 
@@ -135,7 +217,7 @@ mod test_forecasts_based_plant {
     #[test]
     fn test_plant_dispatch_overflow_base_forecasts_length() {
         let forecasts_range = 2;
-        let mut plant = ForecastsBasedPlant::new(test_forecasts(), forecasts_range);
+        let mut plant = ForecastsBasedPlant::new(test_forecasts(), forecasts_range, seeded_rng(0));
 
         plant.dispatch();
         plant.dispatch();
@@ -147,13 +229,16 @@ mod test_forecasts_based_plant {
             vec![DeliveryPeriodId::from(4), DeliveryPeriodId::from(5),]
         );
         assert!((1950..=2050).contains(&plant.forecasts.first().unwrap().value.value));
-        assert!((75..=125).contains(&plant.forecasts.get(1).unwrap().value.value));
+        assert_eq!(
+            plant.forecasts.get(1).unwrap().value,
+            ForecastValue::default()
+        );
     }
 
     #[test]
     fn test_forecast_range_greater_than_base_forecasts_length() {
         let forecasts_range = test_forecasts().len() + 1;
-        let mut plant = ForecastsBasedPlant::new(test_forecasts(), forecasts_range);
+        let mut plant = ForecastsBasedPlant::new(test_forecasts(), forecasts_range, seeded_rng(0));
 
         assert_eq!(plant.forecasts().len(), forecasts_range);
 
@@ -164,7 +249,7 @@ mod test_forecasts_based_plant {
     #[test]
     fn test_base_forecasts_empty() {
         let forecasts_range = 2;
-        let mut plant = ForecastsBasedPlant::new(vec![], forecasts_range);
+        let mut plant = ForecastsBasedPlant::new(vec![], forecasts_range, seeded_rng(0));
 
         assert_eq!(plant.setpoint(), Power::from(0));
         assert_eq!(plant.forecasts().len(), forecasts_range);
@@ -181,4 +266,93 @@ mod test_forecasts_based_plant {
             ForecastValue::default()
         );
     }
+
+    #[test]
+    fn test_forecasts_of_a_period_converge_until_delivery() {
+        let forecasts_range = 3;
+        let mut plant = ForecastsBasedPlant::new(test_forecasts(), forecasts_range, seeded_rng(0));
+        let target = DeliveryPeriodId::from(4);
+
+        let mut previous: Option<ForecastValue> = None;
+        for _ in 0..forecasts_range {
+            let forecast = plant
+                .forecasts()
+                .iter()
+                .find(|f| f.period == target)
+                .unwrap()
+                .value;
+            if let Some(previous) = previous {
+                assert!(forecast.deviation <= previous.deviation);
+                assert!(forecast.included_in(&previous));
+            }
+            previous = Some(forecast);
+            plant.dispatch();
+        }
+
+        let last = previous.unwrap();
+        let setpoint: i32 = plant.setpoint().into();
+        assert!((last.lower_range()..=last.upper_range()).contains(&setpoint));
+    }
+
+    #[test]
+    fn test_plants_with_fully_correlated_signal_have_identical_realisations() {
+        let signal = GlobalSignal::generate(4, 1., &mut rand::rng()).unwrap();
+        let mut first = ForecastsBasedPlant::new(test_forecasts(), 2, seeded_rng(0))
+            .with_signal(signal.clone());
+        let mut second =
+            ForecastsBasedPlant::new(test_forecasts(), 2, seeded_rng(0)).with_signal(signal);
+
+        for _ in 0..4 {
+            assert_eq!(first.setpoint(), second.setpoint());
+            assert_eq!(first.forecasts(), second.forecasts());
+            first.dispatch();
+            second.dispatch();
+        }
+    }
+
+    #[test]
+    fn test_shock_moves_setpoint_out_of_forecasted_range() {
+        let mut plant = ForecastsBasedPlant::new(
+            vec![ForecastValue {
+                value: 400,
+                deviation: 0,
+            }],
+            1,
+            seeded_rng(0),
+        );
+
+        plant.apply_shock(-0.5);
+
+        assert_eq!(plant.setpoint(), Power::from(200));
+    }
+
+    #[test]
+    fn test_plants_with_same_seed_have_identical_realisations() {
+        let mut first = ForecastsBasedPlant::new(test_forecasts(), 2, seeded_rng(3));
+        let mut second = ForecastsBasedPlant::new(test_forecasts(), 2, seeded_rng(3));
+
+        for _ in 0..6 {
+            assert_eq!(first.setpoint(), second.setpoint());
+            assert_eq!(first.forecasts(), second.forecasts());
+            first.dispatch();
+            second.dispatch();
+        }
+    }
+
+    #[test]
+    fn test_plant_starting_at_later_period() {
+        let forecasts_range = 2;
+        let plant = ForecastsBasedPlant::starting_at(
+            test_forecasts(),
+            forecasts_range,
+            DeliveryPeriodId::from(3),
+            seeded_rng(0),
+        );
+
+        assert!((950..=1050).contains(&plant.setpoint.into()));
+        assert_eq!(
+            plant.forecasts.iter().map(|f| f.period).collect::<Vec<_>>(),
+            vec![DeliveryPeriodId::from(4), DeliveryPeriodId::from(5),]
+        );
+    }
 }
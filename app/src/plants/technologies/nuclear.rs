@@ -1,9 +1,13 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    constants::NUCLEAR_EMISSION_FACTOR,
     forecast::Forecast,
-    plants::{PlantOutput, PowerPlant, PowerPlantPublicRepr},
-    utils::units::{EnergyCost, GENERATOR_CONVENTION_TO_MONEY, Money, NO_POWER, Power, TIMESTEP},
+    plants::{PlantOutput, PowerPlant, PowerPlantPublicRepr, persistence::PersistedPlant},
+    utils::units::{
+        CarbonPrice, EmissionFactor, EnergyCost, GENERATOR_CONVENTION_TO_MONEY, Money, NO_POWER,
+        Power, TIMESTEP,
+    },
 };
 
 #[derive(Debug, Serialize, Clone, Copy, PartialEq)]
@@ -12,10 +16,13 @@ pub struct NuclearPublicRepr {
     pub max_setpoint: Power,
     pub previous_setpoint: Power,
     pub energy_cost: EnergyCost,
+    pub emission_factor: EmissionFactor,
+    pub carbon_price: CarbonPrice,
     pub locked: bool,
     pub touched: bool,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NuclearPlant {
     setpoint: Power,
     previous_setpoint: Power,
@@ -23,9 +30,17 @@ pub struct NuclearPlant {
     touched: bool,
     locked: bool,
     energy_cost: EnergyCost,
+    /// Snapshots taken before the emission factor was stored default to the nuclear one
+    #[serde(default = "nuclear_emission_factor")]
+    emission_factor: EmissionFactor,
+    carbon_price: CarbonPrice,
     history: Vec<PlantOutput>,
 }
 
+fn nuclear_emission_factor() -> EmissionFactor {
+    EmissionFactor::from(NUCLEAR_EMISSION_FACTOR)
+}
+
 impl NuclearPlant {
     pub fn new(max_setpoint: Power, energy_cost: EnergyCost) -> NuclearPlant {
         NuclearPlant {
@@ -33,14 +48,23 @@ impl NuclearPlant {
             previous_setpoint: Power::from(0),
             max_setpoint,
             energy_cost,
+            emission_factor: nuclear_emission_factor(),
+            carbon_price: CarbonPrice::default(),
             touched: false,
             locked: false,
             history: Vec::new(),
         }
     }
 
+    /// Set the price paid for each tonne of CO2 emitted by the plant.
+    pub fn with_carbon_price(mut self, carbon_price: CarbonPrice) -> NuclearPlant {
+        self.carbon_price = carbon_price;
+        self
+    }
+
     fn cost(&self) -> Money {
-        self.setpoint * TIMESTEP * self.energy_cost * GENERATOR_CONVENTION_TO_MONEY
+        let energy_cost = self.energy_cost + self.emission_factor * self.carbon_price;
+        self.setpoint * TIMESTEP * energy_cost * GENERATOR_CONVENTION_TO_MONEY
     }
 }
 
@@ -54,6 +78,8 @@ impl PowerPlant for NuclearPlant {
             max_setpoint: self.max_setpoint,
             previous_setpoint: self.previous_setpoint,
             energy_cost: self.energy_cost,
+            emission_factor: self.emission_factor,
+            carbon_price: self.carbon_price,
             locked: self.locked,
             touched: self.touched,
         })
@@ -93,6 +119,14 @@ impl PowerPlant for NuclearPlant {
     fn category(&self) -> crate::plants::PlantCategory {
         crate::plants::PlantCategory::Nuclear
     }
+
+    fn emission_factor(&self) -> EmissionFactor {
+        self.emission_factor
+    }
+
+    fn persist(&self) -> PersistedPlant {
+        PersistedPlant::Nuclear(self.clone())
+    }
 }
 
 #[cfg(test)]
@@ -102,7 +136,7 @@ mod test {
         plants::{
             PlantOutput, PowerPlant, PowerPlantPublicRepr, technologies::nuclear::NuclearPlant,
         },
-        utils::units::{EnergyCost, Money, Power},
+        utils::units::{CarbonPrice, EmissionFactor, EnergyCost, Money, Power},
     };
 
     use super::NuclearPublicRepr;
@@ -236,6 +270,8 @@ mod test {
                 max_setpoint: Power::from(1200),
                 previous_setpoint: Power::from(0),
                 energy_cost: EnergyCost::from(35),
+                emission_factor: EmissionFactor::from(12),
+                carbon_price: CarbonPrice::from(0),
                 locked: false,
                 touched: false
             }
@@ -254,9 +290,31 @@ mod test {
                 max_setpoint: Power::from(1200),
                 previous_setpoint: Power::from(600),
                 energy_cost: EnergyCost::from(35),
+                emission_factor: EmissionFactor::from(12),
+                carbon_price: CarbonPrice::from(0),
                 locked: true,
                 touched: false
             }
         );
     }
+
+    #[test]
+    fn nuclear_persisted_without_emission_factor() {
+        let plant = NuclearPlant::new(Power::from(1000), EnergyCost::from(35));
+        let mut persisted = serde_json::to_value(&plant).unwrap();
+        persisted.as_object_mut().unwrap().remove("emission_factor");
+
+        let restored: NuclearPlant = serde_json::from_value(persisted).unwrap();
+        assert_eq!(restored.emission_factor(), EmissionFactor::from(12));
+    }
+
+    #[test]
+    fn nuclear_carbon_price_adds_to_cost() {
+        let mut plant = NuclearPlant::new(Power::from(1000), EnergyCost::from(35))
+            .with_carbon_price(CarbonPrice::from(500));
+
+        // 35 €/MWh + 12 kgCO2/MWh * 500 €/tCO2 = 41 €/MWh
+        let output = plant.program_setpoint(Power::from(100));
+        assert_eq!(output.cost, Money::from(-100 * 41));
+    }
 }
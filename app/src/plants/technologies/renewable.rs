@@ -1,23 +1,45 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    forecast::{Forecast, ForecastValue},
-    plants::{PlantOutput, PowerPlant, PowerPlantPublicRepr, technologies::ForecastsBasedPlant},
-    utils::units::{Money, Power},
+    forecast::{Forecast, ForecastValue, signal::GlobalSignal},
+    game::delivery_period::DeliveryPeriodId,
+    plants::{
+        PlantOutput, PowerPlant, PowerPlantPublicRepr, persistence::PersistedPlant,
+        technologies::ForecastsBasedPlant,
+    },
+    utils::{
+        rng::GameRng,
+        units::{EmissionFactor, Money, Power},
+    },
 };
 
 #[derive(Debug, Serialize, Clone, Copy)]
 pub struct RenewablePlantPublicRepr {
     pub output: PlantOutput,
 }
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RenewablePlant {
     state: ForecastsBasedPlant,
     history: Vec<PlantOutput>,
 }
 
 impl RenewablePlant {
-    pub fn new(forecasts: Vec<ForecastValue>, forecasts_range: usize) -> RenewablePlant {
-        let plant = ForecastsBasedPlant::new(forecasts, forecasts_range);
+    pub fn new(
+        forecasts: Vec<ForecastValue>,
+        forecasts_range: usize,
+        rng: GameRng,
+    ) -> RenewablePlant {
+        RenewablePlant::starting_at(forecasts, forecasts_range, DeliveryPeriodId::from(1), rng)
+    }
+
+    /// Create a plant producing from `first_period` onwards, see [`ForecastsBasedPlant::starting_at`].
+    pub fn starting_at(
+        forecasts: Vec<ForecastValue>,
+        forecasts_range: usize,
+        first_period: DeliveryPeriodId,
+        rng: GameRng,
+    ) -> RenewablePlant {
+        let plant = ForecastsBasedPlant::starting_at(forecasts, forecasts_range, first_period, rng);
         let history = Vec::new();
 
         RenewablePlant {
@@ -26,6 +48,12 @@ impl RenewablePlant {
         }
     }
 
+    /// Correlate the production with the other stacks of the game, see [`GlobalSignal`].
+    pub fn with_signal(mut self, signal: GlobalSignal) -> RenewablePlant {
+        self.state = self.state.with_signal(signal);
+        self
+    }
+
     fn cost(&self) -> Money {
         Money::from(0)
     }
@@ -71,6 +99,19 @@ impl PowerPlant for RenewablePlant {
     fn category(&self) -> crate::plants::PlantCategory {
         crate::plants::PlantCategory::RenewablePlant
     }
+
+    fn emission_factor(&self) -> EmissionFactor {
+        EmissionFactor::default()
+    }
+
+    fn apply_shock(&mut self, magnitude: f64) -> bool {
+        self.state.apply_shock(magnitude);
+        true
+    }
+
+    fn persist(&self) -> PersistedPlant {
+        PersistedPlant::RenewablePlant(self.clone())
+    }
 }
 
 #[cfg(test)]
@@ -79,7 +120,10 @@ mod tests {
         forecast::ForecastValue,
         game::delivery_period::DeliveryPeriodId,
         plants::{PlantOutput, PowerPlant},
-        utils::units::{Money, Power},
+        utils::{
+            rng::seeded_rng,
+            units::{Money, Power},
+        },
     };
 
     use super::RenewablePlant;
@@ -103,7 +147,7 @@ mod tests {
 
     #[test]
     fn test_renewable_plant() {
-        let mut plant = RenewablePlant::new(get_forecasts(), 2);
+        let mut plant = RenewablePlant::new(get_forecasts(), 2, seeded_rng(0));
 
         // Initial history is empty
         assert!(plant.get_history().is_empty());
@@ -126,7 +170,7 @@ mod tests {
 
     #[test]
     fn test_renewable_forecasts_periods() {
-        let mut plant = RenewablePlant::new(get_forecasts(), 2);
+        let mut plant = RenewablePlant::new(get_forecasts(), 2, seeded_rng(0));
 
         let forecasts = plant.get_forecast().unwrap();
         assert_eq!(
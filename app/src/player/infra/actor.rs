@@ -26,11 +26,16 @@ impl Debug for PlayerConnection {
 #[derive(Debug)]
 pub enum ConnectionRepositoryMessage {
     RegisterConnection(GameId, PlayerConnection),
+    /// Register a read-only connection, receiving the messages sent to all players and to
+    /// spectators, along with the ones sent to its own id
+    RegisterSpectatorConnection(GameId, PlayerConnection),
     SendToPlayer(GameId, PlayerId, PlayerMessage),
     SendToAllPlayers(GameId, PlayerMessage),
+    SendToSpectators(GameId, PlayerMessage),
 }
 pub struct PlayerConnectionRepository {
     players_connections: HashMap<GameId, Vec<PlayerConnection>>,
+    spectators_connections: HashMap<GameId, Vec<PlayerConnection>>,
     rx: mpsc::Receiver<ConnectionRepositoryMessage>,
 }
 
@@ -40,6 +45,7 @@ impl PlayerConnectionRepository {
 
         let mut repo = PlayerConnectionRepository {
             players_connections: HashMap::new(),
+            spectators_connections: HashMap::new(),
             rx,
         };
         tokio::spawn(async move { repo.run().await });
@@ -60,22 +66,50 @@ impl PlayerConnectionRepository {
                         }
                     }
                 }
+                ConnectionRepositoryMessage::RegisterSpectatorConnection(game_id, new_conn) => {
+                    tracing::info!("Registering spectator {:?}", new_conn.player_id);
+                    self.spectators_connections
+                        .entry(game_id)
+                        .or_default()
+                        .push(new_conn);
+                }
                 ConnectionRepositoryMessage::SendToAllPlayers(game_id, message) => {
-                    join_all(self.players_connections.get(&game_id).into_iter().flat_map(
-                        |connections| connections.iter().map(|conn| conn.tx.send(message.clone())),
-                    ))
+                    join_all(
+                        self.players_connections
+                            .get(&game_id)
+                            .into_iter()
+                            .chain(self.spectators_connections.get(&game_id))
+                            .flat_map(|connections| {
+                                connections.iter().map(|conn| conn.tx.send(message.clone()))
+                            }),
+                    )
                     .await;
                 }
                 ConnectionRepositoryMessage::SendToPlayer(game_id, player_id, message) => {
                     tracing::debug!("{player_id:?}: proxying msg {message:?}");
-                    join_all(self.players_connections.get(&game_id).into_iter().flat_map(
-                        |connections| {
-                            connections
-                                .iter()
-                                .filter(|conn| conn.player_id == player_id)
-                                .map(|conn| conn.tx.send(message.clone()))
-                        },
-                    ))
+                    join_all(
+                        self.players_connections
+                            .get(&game_id)
+                            .into_iter()
+                            .chain(self.spectators_connections.get(&game_id))
+                            .flat_map(|connections| {
+                                connections
+                                    .iter()
+                                    .filter(|conn| conn.player_id == player_id)
+                                    .map(|conn| conn.tx.send(message.clone()))
+                            }),
+                    )
+                    .await;
+                }
+                ConnectionRepositoryMessage::SendToSpectators(game_id, message) => {
+                    join_all(
+                        self.spectators_connections
+                            .get(&game_id)
+                            .into_iter()
+                            .flat_map(|connections| {
+                                connections.iter().map(|conn| conn.tx.send(message.clone()))
+                            }),
+                    )
                     .await;
                 }
             }
@@ -84,10 +118,13 @@ impl PlayerConnectionRepository {
     }
 
     fn clean_dropped_connections(&mut self) {
-        let _ = self
+        for connections in self
             .players_connections
             .values_mut()
-            .map(|connections| connections.retain(|conn| !conn.tx.is_closed()));
+            .chain(self.spectators_connections.values_mut())
+        {
+            connections.retain(|conn| !conn.tx.is_closed());
+        }
     }
 }
 
@@ -305,4 +342,50 @@ mod test {
             unreachable!("Should have received a message");
         };
     }
+
+    #[tokio::test]
+    async fn test_spectators_receive_public_messages_only() {
+        let repository = PlayerConnectionRepository::start();
+        let game_id = GameId::from("game_id");
+
+        let (_, mut player_rx) = register_connection(&game_id, repository.clone()).await;
+        let (tx, mut spectator_rx) = mpsc::channel(16);
+        let _ = repository
+            .send(ConnectionRepositoryMessage::RegisterSpectatorConnection(
+                game_id.clone(),
+                PlayerConnection {
+                    id: Uuid::new_v4().to_string(),
+                    player_id: PlayerId::default(),
+                    tx,
+                },
+            ))
+            .await;
+
+        let _ = repository
+            .send(ConnectionRepositoryMessage::SendToSpectators(
+                game_id.clone(),
+                PlayerMessage::PlayersScores { scores: Vec::new() },
+            ))
+            .await;
+        let _ = repository
+            .send(ConnectionRepositoryMessage::SendToAllPlayers(
+                game_id,
+                PlayerMessage::OrderBookSnapshot {
+                    bids: Vec::new(),
+                    offers: Vec::new(),
+                },
+            ))
+            .await;
+
+        let Some(PlayerMessage::PlayersScores { .. }) = spectator_rx.recv().await else {
+            unreachable!("Spectator should have received the players' scores");
+        };
+        let Some(PlayerMessage::OrderBookSnapshot { .. }) = spectator_rx.recv().await else {
+            unreachable!("Spectator should have received the message sent to all players");
+        };
+        // Players only receive the message sent to all players
+        let Some(PlayerMessage::OrderBookSnapshot { .. }) = player_rx.recv().await else {
+            unreachable!("Player should have received the message sent to all players");
+        };
+    }
 }
@@ -39,4 +39,14 @@ impl PlayerConnections for PlayerConnectionsService {
             ))
             .await;
     }
+
+    async fn send_to_spectators(&self, game: &GameId, message: PlayerMessage) {
+        let _ = self
+            .tx
+            .send(ConnectionRepositoryMessage::SendToSpectators(
+                game.clone(),
+                message,
+            ))
+            .await;
+    }
 }
@@ -2,22 +2,25 @@ use std::collections::HashMap;
 
 use derive_more::{AsRef, Display, From, Into};
 use petname::petname;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
+use uuid::{Builder, Uuid};
 
 use crate::{
-    forecast::Forecast,
+    forecast::{Forecast, shock::ForecastShock},
     game::{
         GameId,
         delivery_period::DeliveryPeriodId,
+        infra::results::PlayerPeriodResults,
         infra::stack_config::{
-            GameStackConfig, GameStackFixedConfig, GameStackPerPlayerBaseConfig,
+            CapacityCosts, GameStackConfig, GameStackFixedConfig, GameStackPerPlayerBaseConfig,
+            InvestmentConfig, PlantConfig,
         },
         scores::{PlayerDetailedScore, PlayerScore},
     },
     market::{OrderRepr, order_book::TradeLeg},
-    plants::{PlantId, PlantOutput, PowerPlantPublicRepr},
-    utils::units::{Energy, EnergyCost, Money, Power},
+    plants::{PlantId, PlantOutput, PlantPublicRepr, PlantTechnology},
+    utils::units::{CarbonPrice, Emissions, Energy, EnergyCost, Money, Power},
 };
 
 pub mod infra;
@@ -34,7 +37,18 @@ impl Default for PlayerId {
     }
 }
 
-#[derive(Debug, Serialize, From, Display, Into, PartialEq, Eq, Clone, Hash)]
+impl PlayerId {
+    /// Generate an id from the given generator, e.g. for bots of a seeded game.
+    pub fn from_rng(rng: &mut impl Rng) -> Self {
+        PlayerId(
+            Builder::from_random_bytes(rng.random())
+                .into_uuid()
+                .to_string(),
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, From, Display, Into, PartialEq, Eq, Clone, Hash)]
 #[from(String, &str)]
 pub struct PlayerName(String);
 
@@ -51,13 +65,20 @@ impl PlayerName {
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Serialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct PlayerResultView {
     pub player: PlayerName,
     pub rank: usize,
     pub score: Money,
 }
 
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct PlayerEmissionsResultView {
+    pub player: PlayerName,
+    pub rank: usize,
+    pub emissions: Emissions,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, PartialOrd)]
 #[serde(tag = "type")]
 pub enum GameStackConfigView {
@@ -74,29 +95,44 @@ impl From<&GameStackConfig> for GameStackConfigView {
     }
 }
 
+/// Forecasts are not part of the plants parameters shared with players, they are sent separately
+/// once the game started.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, PartialOrd)]
+pub struct HiddenForecasts {}
+
+/// Parameters of a plant shared with players.
+pub type PlantDefinitionView = PlantTechnology<HiddenForecasts>;
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, PartialOrd)]
+pub struct PlantConfigView {
+    pub name: String,
+    #[serde(flatten)]
+    pub technology: PlantDefinitionView,
+}
+
+impl From<&PlantConfig> for PlantConfigView {
+    fn from(value: &PlantConfig) -> Self {
+        Self {
+            name: value.name.clone(),
+            technology: value
+                .definition
+                .clone()
+                .map_forecasts(|_, _| HiddenForecasts {}),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, PartialOrd)]
 pub struct GameStackFixedConfigView {
-    pub gas_cost: EnergyCost,
-    pub nuclear_cost: EnergyCost,
-    pub consumers_revenues: EnergyCost,
-    pub gas_capacity: Power,
-    pub nuclear_capacity: Power,
-    pub battery_capacity: Energy,
-    pub consumers_forecasts_range: usize,
-    pub renewable_forecasts_range: usize,
+    pub plants: Vec<PlantConfigView>,
+    pub carbon_price: CarbonPrice,
 }
 
 impl From<&GameStackFixedConfig> for GameStackFixedConfigView {
     fn from(value: &GameStackFixedConfig) -> Self {
         Self {
-            gas_cost: value.gas_cost,
-            gas_capacity: value.gas_capacity,
-            nuclear_cost: value.nuclear_cost,
-            nuclear_capacity: value.nuclear_capacity,
-            battery_capacity: value.battery_capacity,
-            consumers_forecasts_range: value.consumers_forecasts_range,
-            consumers_revenues: value.consumers_revenues,
-            renewable_forecasts_range: value.renewable_forecasts_range,
+            plants: value.plants.iter().map(PlantConfigView::from).collect(),
+            carbon_price: value.carbon_price,
         }
     }
 }
@@ -113,6 +149,10 @@ pub struct GameStackPerPlayerBaseConfigView {
     pub consumers_forecasts_range: usize,
     pub renewable_max_capacity: Power,
     pub renewable_forecasts_range: usize,
+    pub carbon_price: CarbonPrice,
+    pub capacity_costs: CapacityCosts,
+    pub budget: Option<Money>,
+    pub investment: Option<InvestmentConfig>,
 }
 
 impl From<&GameStackPerPlayerBaseConfig> for GameStackPerPlayerBaseConfigView {
@@ -128,6 +168,10 @@ impl From<&GameStackPerPlayerBaseConfig> for GameStackPerPlayerBaseConfigView {
             consumers_capacity: value.consumers_capacity,
             renewable_forecasts_range: value.renewable_forecasts_range,
             renewable_max_capacity: value.renewable_max_capacity,
+            carbon_price: value.carbon_price,
+            capacity_costs: value.capacity_costs.clone(),
+            budget: value.budget,
+            investment: value.investment.clone(),
         }
     }
 }
@@ -146,8 +190,15 @@ pub enum PlayerMessage {
     StackConfig {
         config: GameStackConfigView,
     },
+    StackConfigRejected {
+        reason: String,
+    },
+    /// An investment in a plant, or its retirement, was refused
+    PlantInvestmentRejected {
+        reason: String,
+    },
     StackSnapshot {
-        plants: Option<HashMap<PlantId, PowerPlantPublicRepr>>,
+        plants: Option<HashMap<PlantId, PlantPublicRepr>>,
     },
     StackForecasts {
         forecasts: HashMap<PlantId, Option<Vec<Forecast>>>,
@@ -155,13 +206,21 @@ pub enum PlayerMessage {
     StackHistory {
         history: HashMap<PlantId, Vec<PlantOutput>>,
     },
+    /// Realised values of the current delivery period were shifted out of their forecasts
+    ForecastShock {
+        shock: ForecastShock,
+    },
     DeliveryPeriodResults {
         delivery_period: DeliveryPeriodId,
         score: PlayerScore,
         detailed_score: Option<PlayerDetailedScore>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        shocks: Vec<ForecastShock>,
     },
     GameResults {
         rankings: Vec<PlayerResultView>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        emissions_rankings: Option<Vec<PlayerEmissionsResultView>>,
     },
     ReadinessStatus {
         readiness: HashMap<PlayerName, bool>,
@@ -172,6 +231,13 @@ pub enum PlayerMessage {
     GameDuration {
         last_period: DeliveryPeriodId,
     },
+    /// The player was removed from the game by its host
+    Kicked,
+    /// Scores of every player for delivery periods, sent to spectators only. The detailed score of
+    /// a player is their position: the volumes produced, consumed and traded on each line.
+    PlayersScores {
+        scores: Vec<PlayerPeriodResults>,
+    },
 }
 
 pub trait PlayerConnections: Clone + Send + Sync + 'static {
@@ -189,11 +255,23 @@ pub trait PlayerConnections: Clone + Send + Sync + 'static {
         game: &GameId,
         message: PlayerMessage,
     ) -> impl Future<Output = ()> + Send;
+
+    /// Send a message to the spectators' connections of a given game only, e.g. for data private
+    /// to each player.
+    fn send_to_spectators(
+        &self,
+        game: &GameId,
+        message: PlayerMessage,
+    ) -> impl Future<Output = ()> + Send;
 }
 
 #[cfg(test)]
 mod test {
-    use crate::player::PlayerId;
+    use crate::{
+        game::infra::stack_config::PlantConfig,
+        plants::PlantDefinition,
+        player::{PlantConfigView, PlayerId},
+    };
 
     #[test]
     fn test_player_id_from_into_string() {
@@ -207,4 +285,26 @@ mod test {
     fn test_player_id_as_ref() {
         assert_eq!(PlayerId::from("toto").as_ref(), "toto");
     }
+
+    #[test]
+    fn test_plant_config_view_hides_forecasts() {
+        let config = PlantConfig::new(
+            "consumers",
+            PlantDefinition::Consumers {
+                revenues: 80.into(),
+                forecasts: vec![].into(),
+                forecasts_range: 2,
+            },
+        );
+
+        assert_eq!(
+            serde_json::to_value(PlantConfigView::from(&config)).unwrap(),
+            serde_json::json!({
+                "name": "consumers",
+                "technology": "Consumers",
+                "revenues": 80,
+                "forecasts_range": 2,
+            })
+        );
+    }
 }
@@ -0,0 +1,543 @@
+use std::collections::HashMap;
+
+use derive_more::{AsRef, Display, From};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    game::GameId,
+    player::{PlayerId, PlayerName, PlayerResultView},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Display, From, AsRef, Serialize, Deserialize)]
+#[serde(transparent)]
+#[from(String, &str)]
+#[as_ref(str)]
+pub struct TournamentId(String);
+impl TournamentId {
+    pub fn parse(value: &str) -> Option<TournamentId> {
+        if value.is_empty() {
+            return None;
+        }
+        Some(TournamentId(value.to_string()))
+    }
+}
+
+impl Default for TournamentId {
+    fn default() -> Self {
+        TournamentId(Uuid::new_v4().to_string())
+    }
+}
+
+/// Secret handed to a player joining a tournament, identifying them from one game to the next.
+#[derive(Debug, Clone, PartialEq, Eq, Display, From, Serialize, Deserialize)]
+#[serde(transparent)]
+#[from(String, &str)]
+pub struct TournamentPlayerToken(String);
+
+impl Default for TournamentPlayerToken {
+    fn default() -> Self {
+        TournamentPlayerToken(Uuid::new_v4().to_string())
+    }
+}
+
+/// How the results of the games of a tournament add up in its leaderboard.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum TournamentScoring {
+    /// Players are ranked on the sum of their PnL over the games
+    #[default]
+    PnlSum,
+    /// Players earn the points of their rank in each game, e.g. `[10, 6, 3]`, ranks beyond the
+    /// list earn no point
+    RankPoints(Vec<i32>),
+}
+
+impl TournamentScoring {
+    fn score(&self, result: &PlayerResultView) -> i32 {
+        match self {
+            TournamentScoring::PnlSum => result.score.into(),
+            TournamentScoring::RankPoints(points) => result
+                .rank
+                .checked_sub(1)
+                .and_then(|index| points.get(index))
+                .copied()
+                .unwrap_or(0),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum TournamentError {
+    #[error("A tournament must have at least one round")]
+    NoRound,
+    #[error("A player with this name already joined the tournament")]
+    NameAlreadyExists,
+    #[error("The tournament is over")]
+    TournamentOver,
+    #[error("The current round has not ended yet")]
+    RoundInProgress,
+    #[error("No round of the tournament is being played")]
+    NoRoundInProgress,
+    #[error("Unknown tournament player")]
+    PlayerNotFound,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TournamentPlayer {
+    pub name: PlayerName,
+    token: TournamentPlayerToken,
+    /// Id of the player in each game of the tournament they played
+    games: HashMap<GameId, PlayerId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TournamentRound {
+    pub game: GameId,
+    /// Final rankings of the round's game, once it has ended
+    pub results: Option<Vec<PlayerResultView>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TournamentStanding {
+    pub player: PlayerName,
+    pub rank: usize,
+    pub score: i32,
+    pub rounds_played: usize,
+}
+
+/// Series of games played in a row by the same group of players, whose results add up in a
+/// leaderboard. Players are identified in the games' rankings by the id they got when joining each
+/// round's game.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tournament {
+    number_of_rounds: usize,
+    scoring: TournamentScoring,
+    players: Vec<TournamentPlayer>,
+    rounds: Vec<TournamentRound>,
+}
+
+impl Tournament {
+    pub fn new(
+        number_of_rounds: usize,
+        scoring: TournamentScoring,
+    ) -> Result<Tournament, TournamentError> {
+        if number_of_rounds == 0 {
+            return Err(TournamentError::NoRound);
+        }
+        Ok(Tournament {
+            number_of_rounds,
+            scoring,
+            players: Vec::new(),
+            rounds: Vec::new(),
+        })
+    }
+
+    pub fn number_of_rounds(&self) -> usize {
+        self.number_of_rounds
+    }
+
+    pub fn rounds(&self) -> &[TournamentRound] {
+        &self.rounds
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.rounds.len() == self.number_of_rounds
+            && self.rounds.iter().all(|round| round.results.is_some())
+    }
+
+    /// Game of the round being played, if any.
+    pub fn current_game(&self) -> Option<&GameId> {
+        self.rounds
+            .last()
+            .filter(|round| round.results.is_none())
+            .map(|round| &round.game)
+    }
+
+    pub fn register_player(
+        &mut self,
+        name: PlayerName,
+    ) -> Result<TournamentPlayerToken, TournamentError> {
+        if self.is_over() {
+            return Err(TournamentError::TournamentOver);
+        }
+        if self.players.iter().any(|player| player.name == name) {
+            return Err(TournamentError::NameAlreadyExists);
+        }
+        let token = TournamentPlayerToken::default();
+        self.players.push(TournamentPlayer {
+            name,
+            token: token.clone(),
+            games: HashMap::new(),
+        });
+        Ok(token)
+    }
+
+    pub fn player(
+        &self,
+        token: &TournamentPlayerToken,
+    ) -> Result<&TournamentPlayer, TournamentError> {
+        self.players
+            .iter()
+            .find(|player| player.token == *token)
+            .ok_or(TournamentError::PlayerNotFound)
+    }
+
+    /// Id of the player in the current round's game, if they already joined it.
+    pub fn player_id_in_current_game(&self, token: &TournamentPlayerToken) -> Option<&PlayerId> {
+        let game = self.current_game()?;
+        self.player(token).ok()?.games.get(game)
+    }
+
+    pub fn record_player_joined_game(
+        &mut self,
+        token: &TournamentPlayerToken,
+        game: GameId,
+        id: PlayerId,
+    ) -> Result<(), TournamentError> {
+        let player = self
+            .players
+            .iter_mut()
+            .find(|player| player.token == *token)
+            .ok_or(TournamentError::PlayerNotFound)?;
+        player.games.insert(game, id);
+        Ok(())
+    }
+
+    pub fn start_round(&mut self, game: GameId) -> Result<(), TournamentError> {
+        if self.rounds.len() >= self.number_of_rounds {
+            return Err(TournamentError::TournamentOver);
+        }
+        if self.current_game().is_some() {
+            return Err(TournamentError::RoundInProgress);
+        }
+        self.rounds.push(TournamentRound {
+            game,
+            results: None,
+        });
+        Ok(())
+    }
+
+    /// Record the final rankings of the current round's game, given the ids and names of the game's
+    /// players. Only the tournament's players who joined the game through the tournament are kept.
+    pub fn end_round(
+        &mut self,
+        game: &GameId,
+        rankings: &[PlayerResultView],
+        game_players: &[(PlayerId, PlayerName)],
+    ) -> Result<(), TournamentError> {
+        if self.current_game() != Some(game) {
+            return Err(TournamentError::NoRoundInProgress);
+        }
+        let results = rankings
+            .iter()
+            .filter_map(|result| {
+                let player = self.players.iter().find(|player| {
+                    player
+                        .games
+                        .get(game)
+                        .and_then(|id| game_players.iter().find(|(player_id, _)| player_id == id))
+                        .is_some_and(|(_, name)| *name == result.player)
+                })?;
+                Some(PlayerResultView {
+                    player: player.name.clone(),
+                    ..result.clone()
+                })
+            })
+            .collect();
+        if let Some(round) = self.rounds.last_mut() {
+            round.results = Some(results);
+        }
+        Ok(())
+    }
+
+    /// Close the current round without results, e.g. when its game was removed before it ended,
+    /// so that the tournament moves on to its next round. No player scores in an abandoned round.
+    pub fn abandon_round(&mut self, game: &GameId) -> Result<(), TournamentError> {
+        self.end_round(game, &[], &[])
+    }
+
+    /// Leaderboard of the tournament over the rounds played so far, players with the same score
+    /// sharing the same rank.
+    pub fn standings(&self) -> Vec<TournamentStanding> {
+        let mut standings: Vec<TournamentStanding> = self
+            .players
+            .iter()
+            .map(|player| {
+                let results: Vec<&PlayerResultView> = self
+                    .rounds
+                    .iter()
+                    .filter_map(|round| round.results.as_ref())
+                    .filter_map(|results| results.iter().find(|r| r.player == player.name))
+                    .collect();
+                TournamentStanding {
+                    player: player.name.clone(),
+                    rank: 0,
+                    score: results
+                        .iter()
+                        .map(|result| self.scoring.score(result))
+                        .sum(),
+                    rounds_played: results.len(),
+                }
+            })
+            .collect();
+        standings.sort_by_key(|standing| std::cmp::Reverse(standing.score));
+        let mut previous: Option<(i32, usize)> = None;
+        for (idx, standing) in standings.iter_mut().enumerate() {
+            standing.rank = match previous {
+                Some((score, rank)) if score == standing.score => rank,
+                _ => idx + 1,
+            };
+            previous = Some((standing.score, standing.rank));
+        }
+        standings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        game::GameId,
+        player::{PlayerId, PlayerName, PlayerResultView},
+        utils::units::Money,
+    };
+
+    use super::{Tournament, TournamentError, TournamentScoring};
+
+    fn result(player: &str, rank: usize, score: i32) -> PlayerResultView {
+        PlayerResultView {
+            player: PlayerName::from(player),
+            rank,
+            score: Money::from(score),
+        }
+    }
+
+    /// Play a round joined by all the tournament's players.
+    fn play_round(tournament: &mut Tournament, rankings: &[PlayerResultView]) {
+        let game = GameId::default();
+        tournament.start_round(game.clone()).unwrap();
+        let game_players: Vec<(PlayerId, PlayerName)> = tournament
+            .players
+            .iter_mut()
+            .map(|player| {
+                let id = PlayerId::default();
+                player.games.insert(game.clone(), id.clone());
+                (id, player.name.clone())
+            })
+            .collect();
+        tournament
+            .end_round(&game, rankings, &game_players)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_tournament_needs_a_round() {
+        assert_eq!(
+            Tournament::new(0, TournamentScoring::PnlSum).unwrap_err(),
+            TournamentError::NoRound
+        );
+    }
+
+    #[test]
+    fn test_register_player_name_already_exists() {
+        let mut tournament = Tournament::new(2, TournamentScoring::PnlSum).unwrap();
+        tournament.register_player(PlayerName::from("p1")).unwrap();
+
+        assert_eq!(
+            tournament.register_player(PlayerName::from("p1")),
+            Err(TournamentError::NameAlreadyExists)
+        );
+    }
+
+    #[test]
+    fn test_rounds_are_played_one_at_a_time() {
+        let mut tournament = Tournament::new(2, TournamentScoring::PnlSum).unwrap();
+        let first = GameId::default();
+        tournament.start_round(first.clone()).unwrap();
+        assert_eq!(tournament.current_game(), Some(&first));
+        assert_eq!(
+            tournament.start_round(GameId::default()),
+            Err(TournamentError::RoundInProgress)
+        );
+        assert_eq!(
+            tournament.end_round(&GameId::default(), &[], &[]),
+            Err(TournamentError::NoRoundInProgress)
+        );
+
+        tournament.end_round(&first, &[], &[]).unwrap();
+        assert_eq!(tournament.current_game(), None);
+        assert!(!tournament.is_over());
+
+        play_round(&mut tournament, &[]);
+        assert!(tournament.is_over());
+        assert_eq!(
+            tournament.start_round(GameId::default()),
+            Err(TournamentError::TournamentOver)
+        );
+    }
+
+    #[test]
+    fn test_player_keeps_its_identity_between_games() {
+        let mut tournament = Tournament::new(2, TournamentScoring::PnlSum).unwrap();
+        let token = tournament.register_player(PlayerName::from("p1")).unwrap();
+        let game = GameId::default();
+        tournament.start_round(game.clone()).unwrap();
+        assert_eq!(tournament.player_id_in_current_game(&token), None);
+
+        let id = PlayerId::default();
+        tournament
+            .record_player_joined_game(&token, game.clone(), id.clone())
+            .unwrap();
+        assert_eq!(tournament.player_id_in_current_game(&token), Some(&id));
+
+        tournament.end_round(&game, &[], &[]).unwrap();
+        tournament.start_round(GameId::default()).unwrap();
+        assert_eq!(tournament.player_id_in_current_game(&token), None);
+        assert_eq!(
+            tournament.player(&token).unwrap().name,
+            PlayerName::from("p1")
+        );
+    }
+
+    #[test]
+    fn test_standings_sum_of_pnl() {
+        let mut tournament = Tournament::new(2, TournamentScoring::PnlSum).unwrap();
+        tournament.register_player(PlayerName::from("p1")).unwrap();
+        tournament.register_player(PlayerName::from("p2")).unwrap();
+
+        play_round(
+            &mut tournament,
+            &[
+                result("p1", 1, 100),
+                result("bot", 2, 50),
+                result("p2", 3, 10),
+            ],
+        );
+        play_round(
+            &mut tournament,
+            &[result("p2", 1, 200), result("p1", 2, 20)],
+        );
+
+        let standings = tournament.standings();
+        assert_eq!(standings.len(), 2);
+        assert_eq!(standings[0].player, PlayerName::from("p2"));
+        assert_eq!(standings[0].score, 210);
+        assert_eq!(standings[0].rank, 1);
+        assert_eq!(standings[1].player, PlayerName::from("p1"));
+        assert_eq!(standings[1].score, 120);
+        assert_eq!(standings[1].rounds_played, 2);
+    }
+
+    #[test]
+    fn test_tied_players_share_their_standing() {
+        let mut tournament = Tournament::new(1, TournamentScoring::PnlSum).unwrap();
+        for player in ["p1", "p2", "p3"] {
+            tournament
+                .register_player(PlayerName::from(player))
+                .unwrap();
+        }
+
+        play_round(
+            &mut tournament,
+            &[
+                result("p2", 1, 100),
+                result("p1", 1, 100),
+                result("p3", 3, 10),
+            ],
+        );
+
+        let ranks: Vec<(String, usize)> = tournament
+            .standings()
+            .iter()
+            .map(|s| (s.player.to_string(), s.rank))
+            .collect();
+        assert_eq!(
+            ranks,
+            vec![
+                ("p1".to_string(), 1),
+                ("p2".to_string(), 1),
+                ("p3".to_string(), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_abandoned_round_moves_the_tournament_on() {
+        let mut tournament = Tournament::new(2, TournamentScoring::PnlSum).unwrap();
+        tournament.register_player(PlayerName::from("p1")).unwrap();
+        let game = GameId::default();
+        tournament.start_round(game.clone()).unwrap();
+
+        tournament.abandon_round(&game).unwrap();
+
+        assert_eq!(tournament.current_game(), None);
+        assert_eq!(tournament.rounds()[0].results, Some(Vec::new()));
+        assert_eq!(tournament.standings()[0].rounds_played, 0);
+        tournament.start_round(GameId::default()).unwrap();
+    }
+
+    #[test]
+    fn test_results_are_attributed_by_player_id() {
+        let mut tournament = Tournament::new(1, TournamentScoring::PnlSum).unwrap();
+        let token = tournament.register_player(PlayerName::from("p1")).unwrap();
+        tournament.register_player(PlayerName::from("p2")).unwrap();
+        let game = GameId::default();
+        tournament.start_round(game.clone()).unwrap();
+        let id = PlayerId::default();
+        tournament
+            .record_player_joined_game(&token, game.clone(), id.clone())
+            .unwrap();
+
+        // p2 did not join the round's game, another player took their name
+        tournament
+            .end_round(
+                &game,
+                &[result("p2", 1, 100), result("p1", 2, 50)],
+                &[
+                    (PlayerId::default(), PlayerName::from("p2")),
+                    (id, PlayerName::from("p1")),
+                ],
+            )
+            .unwrap();
+
+        assert_eq!(
+            tournament.rounds()[0].results,
+            Some(vec![result("p1", 2, 50)])
+        );
+    }
+
+    #[test]
+    fn test_standings_rank_points() {
+        let mut tournament =
+            Tournament::new(2, TournamentScoring::RankPoints(vec![10, 6])).unwrap();
+        tournament.register_player(PlayerName::from("p1")).unwrap();
+        tournament.register_player(PlayerName::from("p2")).unwrap();
+        tournament.register_player(PlayerName::from("p3")).unwrap();
+
+        play_round(
+            &mut tournament,
+            &[
+                result("p1", 1, 100),
+                result("p2", 2, 90),
+                result("p3", 3, 80),
+            ],
+        );
+        play_round(
+            &mut tournament,
+            &[result("p2", 1, 100), result("p3", 2, 90)],
+        );
+
+        let standings = tournament.standings();
+        let scores: Vec<(String, i32, usize)> = standings
+            .iter()
+            .map(|s| (s.player.to_string(), s.score, s.rounds_played))
+            .collect();
+        assert_eq!(
+            scores,
+            vec![
+                ("p2".to_string(), 16, 2),
+                ("p1".to_string(), 10, 1),
+                ("p3".to_string(), 6, 2),
+            ]
+        );
+    }
+}
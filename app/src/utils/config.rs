@@ -1,9 +1,61 @@
-use std::env;
+use std::{env, net::IpAddr, path::PathBuf};
+
+/// Network given as an address and a prefix length, e.g. `10.0.0.0/8`, a single address being a
+/// network of its own.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IpNetwork {
+    address: IpAddr,
+    prefix: u32,
+}
+
+impl IpNetwork {
+    pub fn parse(value: &str) -> Option<IpNetwork> {
+        let (address, prefix) = match value.trim().split_once('/') {
+            Some((address, prefix)) => (address.parse().ok()?, Some(prefix.parse().ok()?)),
+            None => (value.trim().parse().ok()?, None),
+        };
+        let bits = match address {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        let prefix = prefix.unwrap_or(bits);
+        (prefix <= bits).then_some(IpNetwork { address, prefix })
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let mask = |bits: u32| u128::MAX.checked_shl(bits - self.prefix).unwrap_or(0);
+        match (self.address, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                (u128::from(network.to_bits()) ^ u128::from(ip.to_bits())) & mask(32) == 0
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                (network.to_bits() ^ ip.to_bits()) & mask(128) == 0
+            }
+            _ => false,
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     pub allow_origin: String,
     pub port: u16,
+    /// Directory of the forecast profiles that games can reference by name
+    pub profiles_dir: PathBuf,
+    /// Directory of the scenarios that games can be created from, in addition to bundled ones
+    pub scenarios_dir: PathBuf,
+    /// Directory where games and tournaments are snapshotted to be restored after a restart, they
+    /// are only kept in memory if not set
+    pub persistence_dir: Option<PathBuf>,
+    /// Directory where the events of each game are logged for audit and replay, games are not
+    /// logged if not set
+    pub event_log_dir: Option<PathBuf>,
+    /// Directory where the timelines of finished games are archived, only the most recent archives
+    /// are kept, in memory, if not set
+    pub archive_dir: Option<PathBuf>,
+    /// Networks of the reverse proxies, e.g. Traefik, whose `X-Forwarded-For` header gives the
+    /// address of clients, the header of other peers is ignored
+    pub trusted_proxies: Vec<IpNetwork>,
 }
 
 impl AppConfig {
@@ -14,6 +66,22 @@ impl AppConfig {
                 .unwrap_or("9002".to_owned())
                 .parse::<u16>()
                 .unwrap_or(9002),
+            profiles_dir: PathBuf::from(env::var("PROFILES_DIR").unwrap_or("profiles".to_owned())),
+            scenarios_dir: PathBuf::from(
+                env::var("SCENARIOS_DIR").unwrap_or("scenarios".to_owned()),
+            ),
+            persistence_dir: env::var("PERSISTENCE_DIR").ok().map(PathBuf::from),
+            event_log_dir: env::var("EVENT_LOG_DIR").ok().map(PathBuf::from),
+            archive_dir: env::var("ARCHIVE_DIR").ok().map(PathBuf::from),
+            trusted_proxies: env::var("TRUSTED_PROXIES")
+                .unwrap_or_default()
+                .split(',')
+                .filter(|network| !network.trim().is_empty())
+                .map(|network| {
+                    IpNetwork::parse(network)
+                        .unwrap_or_else(|| panic!("Invalid trusted proxy network {network:?}"))
+                })
+                .collect(),
         }
     }
 }
@@ -24,6 +92,53 @@ impl Default for AppConfig {
         AppConfig {
             allow_origin: String::from("http://127.0.0.1:5173"),
             port: 9003,
+            profiles_dir: PathBuf::from("profiles"),
+            scenarios_dir: PathBuf::from("scenarios"),
+            persistence_dir: None,
+            event_log_dir: None,
+            archive_dir: None,
+            trusted_proxies: Vec::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use super::IpNetwork;
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_ip_network_contains() {
+        let network = IpNetwork::parse("10.0.0.0/8").unwrap();
+        assert!(network.contains(ip("10.0.3.4")));
+        assert!(!network.contains(ip("11.0.0.1")));
+        assert!(!network.contains(ip("::ffff:10.0.0.1")));
+
+        let single = IpNetwork::parse("192.168.1.2").unwrap();
+        assert!(single.contains(ip("192.168.1.2")));
+        assert!(!single.contains(ip("192.168.1.3")));
+
+        assert!(
+            IpNetwork::parse("0.0.0.0/0")
+                .unwrap()
+                .contains(ip("1.2.3.4"))
+        );
+        assert!(
+            IpNetwork::parse("fd00::/8")
+                .unwrap()
+                .contains(ip("fd12::1"))
+        );
+    }
+
+    #[test]
+    fn test_invalid_ip_network() {
+        assert_eq!(IpNetwork::parse("10.0.0.0/33"), None);
+        assert_eq!(IpNetwork::parse("traefik"), None);
+        assert_eq!(IpNetwork::parse("10.0.0.0/"), None);
+    }
+}
@@ -3,6 +3,7 @@ use std::time::Duration;
 use tokio_util::sync::CancellationToken;
 
 pub mod config;
+pub mod rng;
 pub mod units;
 
 pub fn program_actors_termination(duration_before_cleaning: Duration) -> CancellationToken {
@@ -17,11 +18,20 @@ pub fn program_actors_termination(duration_before_cleaning: Duration) -> Cancell
     cloned_token
 }
 
+/// Whether `name` can safely reference a server-side file, i.e. it is not empty and only made of
+/// alphanumeric characters, `-` and `_`.
+pub fn is_valid_file_stem(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
 #[cfg(test)]
 mod test {
     use std::time::Duration;
 
-    use crate::utils::program_actors_termination;
+    use crate::utils::{is_valid_file_stem, program_actors_termination};
 
     #[tokio::test]
     async fn test_cleanup() {
@@ -31,4 +41,12 @@ mod test {
         tokio::time::sleep(Duration::from_millis(25)).await;
         assert!(token.is_cancelled());
     }
+
+    #[test]
+    fn test_valid_file_stem() {
+        assert!(is_valid_file_stem("winter_load-2"));
+        assert!(!is_valid_file_stem(""));
+        assert!(!is_valid_file_stem("../secret"));
+        assert!(!is_valid_file_stem("load.csv"));
+    }
 }
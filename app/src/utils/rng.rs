@@ -0,0 +1,52 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+
+/// Random number generator of a game, seeded so that a game can be replayed identically. Its
+/// state can be serialised, so that a restored game keeps drawing the same values.
+pub type GameRng = ChaCha12Rng;
+
+pub fn seeded_rng(seed: u64) -> GameRng {
+    GameRng::seed_from_u64(seed)
+}
+
+/// Independent generator derived from `rng`, e.g. one per plant so that plants realisations do not
+/// depend on the order in which they are dispatched.
+pub fn derived_rng(rng: &mut GameRng) -> GameRng {
+    GameRng::from_rng(rng)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::{derived_rng, seeded_rng};
+
+    #[test]
+    fn test_same_seed_same_values() {
+        let mut first = seeded_rng(42);
+        let mut second = seeded_rng(42);
+
+        for _ in 0..10 {
+            assert_eq!(first.random::<u64>(), second.random::<u64>());
+        }
+    }
+
+    #[test]
+    fn test_derived_rng_reproducible() {
+        let mut first = derived_rng(&mut seeded_rng(42));
+        let mut second = derived_rng(&mut seeded_rng(42));
+
+        assert_eq!(first.random::<u64>(), second.random::<u64>());
+    }
+
+    #[test]
+    fn test_serialised_rng_resumes_sequence() {
+        let mut rng = seeded_rng(42);
+        rng.random::<u64>();
+
+        let mut restored: super::GameRng =
+            serde_json::from_str(&serde_json::to_string(&rng).unwrap()).unwrap();
+
+        assert_eq!(rng.random::<u64>(), restored.random::<u64>());
+    }
+}
@@ -42,6 +42,11 @@ impl Power {
     pub fn abs(&self) -> Self {
         Self(self.0.abs())
     }
+
+    /// Cost of building this capacity, `None` on overflow.
+    pub fn checked_mul(self, rhs: CapacityCost) -> Option<Money> {
+        self.0.checked_mul(rhs.0).map(Money)
+    }
 }
 
 pub const NO_POWER: Power = Power(0);
@@ -149,10 +154,87 @@ pub const GENERATOR_CONVENTION_TO_MONEY: i32 = -1;
 )]
 pub struct Money(i32);
 
+impl Money {
+    /// Sum of two amounts, `None` on overflow.
+    pub fn checked_add(self, rhs: Money) -> Option<Money> {
+        self.0.checked_add(rhs.0).map(Money)
+    }
+}
+
+/// Represent an amount of CO2 emissions (in kgCO2).
+#[derive(
+    Debug,
+    Display,
+    From,
+    Into,
+    PartialEq,
+    PartialOrd,
+    Ord,
+    Eq,
+    Add,
+    Sub,
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Default,
+)]
+pub struct Emissions(i32);
+
+pub const NO_EMISSIONS: Emissions = Emissions(0);
+
+/// Represent the CO2 emitted when producing a unit of energy (in kgCO2/MWh).
+#[derive(
+    Debug, From, Into, PartialEq, PartialOrd, Ord, Eq, Serialize, Deserialize, Clone, Copy, Default,
+)]
+pub struct EmissionFactor(i32);
+
+impl Mul<EmissionFactor> for Energy {
+    type Output = Emissions;
+
+    fn mul(self, rhs: EmissionFactor) -> Self::Output {
+        Emissions(self.0 * rhs.0)
+    }
+}
+
+/// Represent the price to pay for emitting CO2 (in €/tCO2).
+#[derive(
+    Debug, From, Into, PartialEq, PartialOrd, Ord, Eq, Serialize, Deserialize, Clone, Copy, Default,
+)]
+pub struct CarbonPrice(i32);
+
+const KG_PER_TONNE: i32 = 1000;
+
+/// The carbon cost of producing a unit of energy, i.e. (kgCO2/MWh) * (€/tCO2) -> €/MWh, rounded
+/// to the nearest euro so that low emitting plants still pay for their emissions.
+impl Mul<CarbonPrice> for EmissionFactor {
+    type Output = EnergyCost;
+
+    fn mul(self, rhs: CarbonPrice) -> Self::Output {
+        EnergyCost((f64::from(self.0 * rhs.0) / f64::from(KG_PER_TONNE)).round() as i32)
+    }
+}
+
+/// Represent the cost of building a unit of capacity (in €/MW, or €/MWh for storage).
+#[derive(
+    Debug, From, Into, PartialEq, PartialOrd, Ord, Eq, Serialize, Deserialize, Clone, Copy, Default,
+)]
+pub struct CapacityCost(i32);
+
+impl Energy {
+    /// Cost of building this storage capacity, `None` on overflow.
+    pub fn checked_mul(self, rhs: CapacityCost) -> Option<Money> {
+        self.0.checked_mul(rhs.0).map(Money)
+    }
+}
+
 #[cfg(test)]
 mod test {
 
-    use super::{Energy, EnergyCost, Money, Power, Time};
+    use super::{
+        CapacityCost, CarbonPrice, EmissionFactor, Emissions, Energy, EnergyCost, Money, Power,
+        Time,
+    };
 
     #[test]
     fn test_multiply_power_by_time_into_energy() {
@@ -167,4 +249,33 @@ mod test {
         assert_eq!(Energy(10) * EnergyCost(-10), Money(-100));
         assert_eq!(Energy(-10) * EnergyCost(-10), Money(100));
     }
+
+    #[test]
+    fn test_multiply_energy_by_emission_factor_into_emissions() {
+        assert_eq!(Energy(10) * EmissionFactor(400), Emissions(4000));
+        assert_eq!(Energy(0) * EmissionFactor(400), Emissions(0));
+    }
+
+    #[test]
+    fn test_multiply_emission_factor_by_carbon_price_into_energy_cost() {
+        assert_eq!(EmissionFactor(400) * CarbonPrice(80), EnergyCost(32));
+        assert_eq!(EmissionFactor(12) * CarbonPrice(80), EnergyCost(1));
+        assert_eq!(EmissionFactor(12) * CarbonPrice(200), EnergyCost(2));
+        assert_eq!(EmissionFactor(12) * CarbonPrice(30), EnergyCost(0));
+        assert_eq!(EmissionFactor(0) * CarbonPrice(80), EnergyCost(0));
+    }
+
+    #[test]
+    fn test_multiply_capacity_by_capacity_cost_into_money() {
+        assert_eq!(Power(100).checked_mul(CapacityCost(50)), Some(Money(5000)));
+        assert_eq!(Energy(100).checked_mul(CapacityCost(20)), Some(Money(2000)));
+        assert_eq!(Power(i32::MAX).checked_mul(CapacityCost(2)), None);
+        assert_eq!(Energy(i32::MAX).checked_mul(CapacityCost(2)), None);
+    }
+
+    #[test]
+    fn test_checked_add_money() {
+        assert_eq!(Money(10).checked_add(Money(-5)), Some(Money(5)));
+        assert_eq!(Money(i32::MAX).checked_add(Money(1)), None);
+    }
 }
@@ -40,6 +40,12 @@ pub async fn init() -> (String, ServerTaskHandle) {
         let config = AppConfig {
             port: addr.port(),
             allow_origin: addr.to_string(),
+            profiles_dir: "profiles".into(),
+            scenarios_dir: "scenarios".into(),
+            persistence_dir: None,
+            event_log_dir: None,
+            archive_dir: None,
+            trusted_proxies: Vec::new(),
         };
         let state = new_api_state(&config);
         let app = parcelec_app::build_router(state, config);
@@ -49,7 +55,11 @@ pub async fn init() -> (String, ServerTaskHandle) {
         let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
         let assigned_addr = listener.local_addr().unwrap();
         tx.send(assigned_addr).unwrap();
-        axum::serve(listener, app.into_make_service()).await
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+        )
+        .await
     });
 
     let assigned_addr = tokio::time::timeout(TESTRUN_SETUP_TIMEOUT, rx)
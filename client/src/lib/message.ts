@@ -62,21 +62,32 @@ const PlayerDetailedScore = z.object({
   nuclear: z.object({ volume: z.number(), pnl: z.number() }),
   battery_discharge: z.object({ volume: z.number(), pnl: z.number() }),
   battery_charge: z.object({ volume: z.number(), pnl: z.number() }),
+  capex: z
+    .object({ volume: z.number(), pnl: z.number() })
+    .default({ volume: 0, pnl: 0 }),
   market_bought: z.object({ volume: z.number(), pnl: z.number() }),
   market_sold: z.object({ volume: z.number(), pnl: z.number() }),
   imbalance: z.object({ volume: z.number(), pnl: z.number() }),
 });
 
+const ForecastShockSchema = z.object({
+  period: z.number(),
+  technology: z.enum([
+    "Battery",
+    "GasPlant",
+    "RenewablePlant",
+    "Consumers",
+    "Nuclear",
+  ]),
+  magnitude: z.number(),
+});
+
 const FixedStackConfigSchema = z.object({
   type: z.literal("Fixed"),
-  gas_cost: z.number(),
-  nuclear_cost: z.number(),
-  consumers_revenues: z.number(),
-  gas_capacity: z.number(),
-  nuclear_capacity: z.number(),
-  battery_capacity: z.number(),
-  consumers_forecasts_range: z.number(),
-  renewable_forecasts_range: z.number(),
+  plants: z.array(
+    z.object({ name: z.string(), technology: z.string() }).passthrough(),
+  ),
+  carbon_price: z.number(),
 });
 
 const PerPlayerStackConfigSchema = z.object({
@@ -128,6 +139,10 @@ const WSMessageSchema = z.discriminatedUnion("type", [
     type: z.literal("StackConfig"),
     config: StackConfigSchema,
   }),
+  z.object({
+    type: z.literal("PlantInvestmentRejected"),
+    reason: z.string(),
+  }),
   z.object({
     type: z.literal("StackSnapshot"),
     plants: z
@@ -178,12 +193,19 @@ const WSMessageSchema = z.discriminatedUnion("type", [
     state: z.enum(["Open", "Running", "PostDelivery", "Ended"]),
     delivery_period: z.number(),
     end_at: z.string(),
+    paused: z.boolean().default(false),
+    remaining_seconds: z.number().nullable().optional(),
+  }),
+  z.object({
+    type: z.literal("ForecastShock"),
+    shock: ForecastShockSchema,
   }),
   z.object({
     type: z.literal("DeliveryPeriodResults"),
     delivery_period: z.number(),
     score: PlayerScore,
     detailed_score: PlayerDetailedScore,
+    shocks: z.array(ForecastShockSchema).default([]),
   }),
   z.object({
     type: z.literal("PlayerScores"),
@@ -194,6 +216,17 @@ const WSMessageSchema = z.discriminatedUnion("type", [
       .record(z.coerce.number(), PlayerDetailedScore)
       .transform((rec) => new Map(Object.entries(rec))),
   }),
+  z.object({
+    type: z.literal("PlayersScores"),
+    scores: z.array(
+      z.object({
+        player: z.string(),
+        period: z.number(),
+        score: PlayerScore,
+        detailed_score: PlayerDetailedScore.nullable(),
+      }),
+    ),
+  }),
   z.object({
     type: z.literal("GameResults"),
     rankings: z.array(
@@ -218,6 +251,9 @@ const WSMessageSchema = z.discriminatedUnion("type", [
     type: z.literal("GameDuration"),
     last_period: z.number(),
   }),
+  z.object({
+    type: z.literal("Kicked"),
+  }),
 ]);
 
 type WSMessage = z.infer<typeof WSMessageSchema>;
@@ -227,6 +263,7 @@ export type OrderBook = Omit<
 >;
 export type Trade = Omit<Extract<WSMessage, { type: "NewTrade" }>, "type">;
 export type OrderBookEntry = z.infer<typeof OrderBookEntrySchema>;
+export type ForecastShock = z.infer<typeof ForecastShockSchema>;
 export type StackConfig = Omit<
   Extract<WSMessage, { type: "StackConfig" }>,
   "type"
@@ -280,6 +317,10 @@ export type PlayerScores = Pick<
   Extract<WSMessage, { type: "PlayerScores" }>,
   "scores"
 >["scores"];
+export type PlayersScores = Pick<
+  Extract<WSMessage, { type: "PlayersScores" }>,
+  "scores"
+>["scores"];
 export type GameResults = Pick<
   Extract<WSMessage, { type: "GameResults" }>,
   "rankings"